@@ -3,15 +3,33 @@
 //! This module defines the standard Molt commands.
 
 #[cfg(feature = "dict")]
-use crate::dict::{dict_new, dict_path_insert, dict_path_remove, list_to_dict};
+use crate::dict::{dict_new, dict_path_get, dict_path_insert, dict_path_remove, list_to_dict};
+#[cfg(any(feature = "dict", feature = "namespaces"))]
+use crate::eval::ControlFunc;
+use crate::eval::{EvalFrame, Step};
 use crate::interp::Interp;
-use crate::types::*;
+#[cfg(feature = "info")]
+use crate::interp::FrameKind;
+use crate::parser::SubstFlags;
+#[cfg(feature = "namespaces")]
+use crate::namespace;
+#[cfg(feature = "regex")]
+use crate::regex::{Captures, Regex};
 #[cfg(feature = "string-command")]
+use crate::tokenizer::Tokenizer;
+use crate::types::*;
 use crate::util;
 use crate::*;
 
+use core::cmp::Ordering;
 use core::fmt::Write as _;
+#[cfg(feature = "coroutines")]
+use alloc::boxed::Box;
+#[cfg(feature = "dict")]
+use alloc::rc::Rc;
 use alloc::string::String;
+#[cfg(any(feature = "regex", feature = "traces"))]
+use alloc::vec;
 use alloc::vec::Vec;
 
 /// # append *varName* ?*value* ...?
@@ -37,6 +55,32 @@ pub fn cmd_append(interp: &mut Interp, argv: &[Value]) -> MoltOptResult {
     interp.set_var_return(&argv[1], new_string.into()).map(Some)
 }
 
+/// # apply *lambdaExpr* ?*arg* ...?
+///
+/// Calls an anonymous function.  The *lambdaExpr* is a list of two elements, a parameter
+/// list and a body, as for `proc`; the arguments are bound to the parameters, and the
+/// body is evaluated in its own local scope.  The parsed lambda is cached in the
+/// *lambdaExpr* value, so calling the same lambda repeatedly is efficient.
+///
+/// With the `namespaces` feature, an optional third element names the namespace in which
+/// the body is evaluated, relative to the global namespace.
+///
+/// ## TCL Liens
+///
+/// * Without the `namespaces` feature, the optional third element of *lambdaExpr* is ignored.
+pub(crate) fn cmd_apply(interp: &mut Interp, argv: &[Value]) -> Result<Step, Exception> {
+    check_args(1, argv, 2, 0, "lambdaExpr ?arg ...?")?;
+
+    let lambda = argv[1].as_lambda()?;
+
+    #[cfg(feature = "namespaces")]
+    if !interp.namespace_exists(&lambda.namespace) {
+        return molt_err!("namespace \"{}\" not found", namespace::display(&lambda.namespace));
+    }
+
+    Ok(interp.call_proc(&lambda, true, argv))
+}
+
 /// # array *subcommand* ?*arg*...?
 pub fn cmd_array(interp: &mut Interp, argv: &[Value]) -> MoltOptResult {
     interp.call_subcommand(argv, 1, &ARRAY_SUBCOMMANDS)
@@ -57,18 +101,53 @@ pub fn cmd_array_exists(interp: &mut Interp, argv: &[Value]) -> MoltOptResult {
     molt_opt_ok!(Value::from(interp.array_exists(argv[2].as_str())))
 }
 
-/// # array names arrayName
-/// TODO: Add glob matching as a feature, and support standard TCL options.
+/// # array names arrayName ?*mode*? ?*pattern*?
+///
+/// Returns the names of the array's elements.  If *pattern* is given, returns only the
+/// names that match it; *mode* may be `-exact`, `-glob`, or `-regexp`, and defaults to
+/// `-glob`.
 pub fn cmd_array_names(interp: &mut Interp, argv: &[Value]) -> MoltOptResult {
-    check_args(2, argv, 3, 3, "arrayName")?;
-    molt_opt_ok!(Value::from(interp.array_names(argv[2].as_str())))
+    check_args(2, argv, 3, 5, "arrayName ?mode? ?pattern?")?;
+
+    let names = interp.array_names(argv[2].as_str());
+
+    let names = match argv.len() {
+        3 => names,
+        4 => glob_filter(names, Some(&argv[3])),
+        _ => match argv[3].as_str() {
+            "-exact" => names.into_iter().filter(|name| name == &argv[4]).collect(),
+            "-glob" => glob_filter(names, Some(&argv[4])),
+            #[cfg(feature = "regex")]
+            "-regexp" => {
+                let regex = Regex::new(argv[4].as_str(), false)?;
+                names.into_iter().filter(|name| regex.is_match(name.as_str())).collect()
+            }
+            mode => return molt_err!("bad option \"{}\": must be -exact, -glob, or -regexp", mode),
+        },
+    };
+
+    molt_opt_ok!(Value::from(names))
 }
 
-/// # array get arrayname
-/// TODO: Add glob matching as a feature, and support standard TCL options.
+/// # array get arrayname ?*pattern*?
+///
+/// Returns a flat list of the array's element names and values.  If *pattern* is given,
+/// returns only the elements whose names match it.
 pub fn cmd_array_get(interp: &mut Interp, argv: &[Value]) -> MoltOptResult {
-    check_args(2, argv, 3, 3, "arrayName")?;
-    molt_opt_ok!(Value::from(interp.array_get(argv[2].as_str())))
+    check_args(2, argv, 3, 4, "arrayName ?pattern?")?;
+
+    let mut list = interp.array_get(argv[2].as_str());
+
+    if let Some(pattern) = argv.get(3) {
+        list = list
+            .chunks(2)
+            .filter(|kv| util::glob_match(pattern.as_str(), kv[0].as_str(), false))
+            .flatten()
+            .cloned()
+            .collect();
+    }
+
+    molt_opt_ok!(Value::from(list))
 }
 
 /// # array set arrayName list
@@ -146,13 +225,20 @@ pub fn cmd_break(_interp: &mut Interp, argv: &[Value]) -> MoltOptResult {
 /// Executes a script, returning the result code.  If the resultVarName is given, the result
 /// of executing the script is returned in it.  The result code is returned as an integer,
 /// 0=Ok, 1=Error, 2=Return, 3=Break, 4=Continue.
-pub fn cmd_catch(interp: &mut Interp, argv: &[Value]) -> MoltOptResult {
+pub(crate) fn cmd_catch(interp: &mut Interp, argv: &[Value]) -> Result<Step, Exception> {
     check_args(1, argv, 2, 4, "script ?resultVarName? ?optionsVarName?")?;
 
     // If the script called `return x`, should get Return, -level 1, -code Okay here
-    let result = interp.eval_value(&argv[1]);
+    interp.push_eval_frame(EvalFrame::Catch {
+        argv: argv.to_vec(),
+    });
+    Ok(interp.push_body(&argv[1]))
+}
 
-    let (code, value) = match &result {
+/// Converts the result of the script evaluated by `catch` into the result of the `catch`
+/// command, setting its variables.
+pub(crate) fn catch_result(interp: &mut Interp, argv: &[Value], result: &MoltResult) -> MoltResult {
+    let (code, value) = match result {
         Ok(val) => (0, val.clone()),
         Err(exception) => match exception.code() {
             ResultCode::Okay => unreachable!(), // Should not be reachable here.
@@ -170,12 +256,21 @@ pub fn cmd_catch(interp: &mut Interp, argv: &[Value]) -> MoltOptResult {
 
     if argv.len() == 4 {
         #[cfg(feature = "dict")]
-        interp.set_var(&argv[3], interp.return_options(&result))?;
+        interp.set_var(&argv[3], interp.return_options(result))?;
         #[cfg(not(feature = "dict"))]
         unimplemented!("return options");
     }
 
-    Ok(Some(Value::from(code)))
+    Ok(Value::from(code))
+}
+
+/// # concat ?*arg* ...?
+///
+/// Joins the arguments into a single string, separated by spaces, after trimming leading and
+/// trailing whitespace from each.  Empty arguments are ignored.  If the arguments are all
+/// lists, the result is a list containing all of their elements.
+pub fn cmd_concat(_interp: &mut Interp, argv: &[Value]) -> MoltOptResult {
+    molt_opt_ok!(list::concat_values(&argv[1..]))
 }
 
 /// # continue
@@ -187,30 +282,86 @@ pub fn cmd_continue(_interp: &mut Interp, argv: &[Value]) -> MoltOptResult {
     Err(Exception::molt_continue())
 }
 
+/// # coroutine *name* *command* ?*arg* ...?
+///
+/// Creates a coroutine: a command called *name* that runs the *command* with the
+/// arguments, at the global level, until it calls `yield`.  The `coroutine` command
+/// returns the value passed to `yield`.  Calling *name* resumes the coroutine where it
+/// left off, with `yield` returning the argument passed to *name*, if any, until it
+/// yields again; calling *name* returns the value passed to `yield`.  When the *command*
+/// returns, its result is the result of the call, and *name* is deleted.
+///
+/// A coroutine can yield within procedures, lambdas, command substitutions, expressions,
+/// and control structures, but not within native commands that evaluate scripts
+/// themselves, such as `time`, `lsort -command`, and trace callbacks; there, as in standard
+/// TCL, `yield` returns the error "cannot yield: C stack busy".  See also
+/// `Interp::resume_coroutine`.
+///
+/// ## TCL Liens
+///
+/// * The *command* is resolved in the global namespace.
+/// * A coroutine that is deleted while it is suspended is discarded without unwinding.
+#[cfg(feature = "coroutines")]
+pub fn cmd_coroutine(interp: &mut Interp, argv: &[Value]) -> MoltOptResult {
+    check_args(1, argv, 3, 0, "name cmd ?arg ...?")?;
+
+    interp.create_coroutine(argv[1].as_str(), &argv[2..]).map(Some)
+}
+
 /// # dict *subcommand* ?*arg*...?
 #[cfg(feature = "dict")]
-pub fn cmd_dict(interp: &mut Interp, argv: &[Value]) -> MoltOptResult {
-    interp.call_subcommand(argv, 1, &DICT_SUBCOMMANDS)
+pub(crate) fn cmd_dict(interp: &mut Interp, argv: &[Value]) -> Result<Step, Exception> {
+    call_control_subcommand(interp, argv, &DICT_SUBCOMMANDS)
 }
 
 #[cfg(feature = "dict")]
-static DICT_SUBCOMMANDS: [Subcommand; 9] = [
-    Subcommand("create", cmd_dict_new),
-    Subcommand("exists", cmd_dict_exists),
-    Subcommand("get", cmd_dict_get),
-    Subcommand("keys", cmd_dict_keys),
-    Subcommand("remove", cmd_dict_remove),
-    Subcommand("set", cmd_dict_set),
-    Subcommand("size", cmd_dict_size),
-    Subcommand("unset", cmd_dict_unset),
-    Subcommand("values", cmd_dict_values),
+static DICT_SUBCOMMANDS: [(&str, SubcommandFunc); 20] = [
+    ("append", SubcommandFunc::Native(cmd_dict_append)),
+    ("create", SubcommandFunc::Native(cmd_dict_new)),
+    ("exists", SubcommandFunc::Native(cmd_dict_exists)),
+    ("filter", SubcommandFunc::Control(cmd_dict_filter)),
+    ("for", SubcommandFunc::Control(cmd_dict_for)),
+    ("get", SubcommandFunc::Native(cmd_dict_get)),
+    ("getdef", SubcommandFunc::Native(cmd_dict_getdef)),
+    ("incr", SubcommandFunc::Native(cmd_dict_incr)),
+    ("keys", SubcommandFunc::Native(cmd_dict_keys)),
+    ("lappend", SubcommandFunc::Native(cmd_dict_lappend)),
+    ("map", SubcommandFunc::Control(cmd_dict_map)),
+    ("merge", SubcommandFunc::Native(cmd_dict_merge)),
+    ("remove", SubcommandFunc::Native(cmd_dict_remove)),
+    ("replace", SubcommandFunc::Native(cmd_dict_replace)),
+    ("set", SubcommandFunc::Native(cmd_dict_set)),
+    ("size", SubcommandFunc::Native(cmd_dict_size)),
+    ("unset", SubcommandFunc::Native(cmd_dict_unset)),
+    ("update", SubcommandFunc::Control(cmd_dict_update)),
+    ("values", SubcommandFunc::Native(cmd_dict_values)),
+    ("with", SubcommandFunc::Control(cmd_dict_with)),
 ];
 
+/// # dict append *dictVarName* *key* ?*string* ...?
+///
+/// Appends the strings to the value of the key in the dictionary variable, which need not
+/// exist, and returns the new dictionary.
+#[cfg(feature = "dict")]
+fn cmd_dict_append(interp: &mut Interp, argv: &[Value]) -> MoltOptResult {
+    check_args(2, argv, 4, 0, "dictVarName key ?value ...?")?;
+
+    update_dict_entry(interp, argv, |old| {
+        let mut new_string: String = old.map(|val| val.to_string()).unwrap_or_default();
+
+        for item in &argv[4..] {
+            new_string.push_str(item.as_str());
+        }
+
+        molt_ok!(new_string)
+    })
+}
+
 /// # dict create ?key value ...?
 #[cfg(feature = "dict")]
 fn cmd_dict_new(_: &mut Interp, argv: &[Value]) -> MoltOptResult {
     // FIRST, we need an even number of arguments.
-    if argv.len() % 2 != 0 {
+    if !argv.len().is_multiple_of(2) {
         return molt_err!(
             "wrong # args: should be \"{} {}\"",
             Value::from(&argv[0..2]).to_string(),
@@ -249,27 +400,122 @@ fn cmd_dict_exists(_: &mut Interp, argv: &[Value]) -> MoltOptResult {
     molt_opt_ok!(true)
 }
 
+/// # dict filter *dictionary* *filterType* ?*arg* ...?
+///
+/// Returns the entries of the dictionary that match the filter:
+///
+/// * `dict filter` *dictionary* `key` ?*globPattern* ...? matches the entries whose keys
+///   match any of the patterns.
+/// * `dict filter` *dictionary* `value` ?*globPattern* ...? matches the entries whose
+///   values match any of the patterns.
+/// * `dict filter` *dictionary* `script` {*keyVarName* *valueVarName*} *filterScript*
+///   assigns each entry's key and value to the variables, and matches the entry if the
+///   script returns true.  `break` ends the filtering, and `continue` excludes the entry.
+#[cfg(feature = "dict")]
+fn cmd_dict_filter(interp: &mut Interp, argv: &[Value]) -> Result<Step, Exception> {
+    check_args(2, argv, 4, 0, "dictionary filterType ?arg ...?")?;
+
+    let mut filtered = dict_new();
+
+    match argv[3].as_str() {
+        filter_type @ ("key" | "value") => {
+            let dict = argv[2].as_dict()?;
+
+            for (key, value) in dict.iter() {
+                let item = if filter_type == "key" { key } else { value };
+
+                if argv[4..].iter().any(|pat| util::glob_match(pat.as_str(), item.as_str(), false)) {
+                    filtered.insert(key.clone(), value.clone());
+                }
+            }
+        }
+        "script" => {
+            if argv.len() != 6 {
+                return molt_err!(
+                    "wrong # args: should be \"{} {} dictionary script {{keyVarName valueVarName}} filterScript\"",
+                    argv[0], argv[1]);
+            }
+
+            let state = DictLoop::new(&argv[4], &argv[2], &argv[5], DictLoopKind::Filter(filtered))?;
+            return dict_loop_step(interp, state);
+        }
+        other => {
+            return molt_err!("bad filterType \"{}\": must be key, script, or value", other);
+        }
+    }
+
+    Ok(Step::Deliver(molt_ok!(filtered)))
+}
+
+/// # dict for {*keyVarName* *valueVarName*} *dictionary* *body*
+///
+/// Assigns each entry's key and value to the variables in turn, and evaluates the body.
+/// `break` and `continue` work as they do in `foreach`.  Returns the empty string.
+#[cfg(feature = "dict")]
+fn cmd_dict_for(interp: &mut Interp, argv: &[Value]) -> Result<Step, Exception> {
+    check_args(2, argv, 5, 5, "{keyVarName valueVarName} dictionary script")?;
+
+    let state = DictLoop::new(&argv[2], &argv[3], &argv[4], DictLoopKind::For)?;
+    dict_loop_step(interp, state)
+}
+
 /// # dict get *dictionary* ?*key* ...?
 #[cfg(feature = "dict")]
 fn cmd_dict_get(_: &mut Interp, argv: &[Value]) -> MoltOptResult {
     check_args(2, argv, 3, 0, "dictionary ?key ...?")?;
 
+    dict_path_get(&argv[2], &argv[3..]).map(Some)
+}
+
+/// # dict getdef *dictionary* ?*key* ...? *key* *default*
+///
+/// Returns the value at the end of the path of keys through the (possibly nested)
+/// dictionary, as for `dict get`, or the *default* if any of the keys doesn't exist.
+#[cfg(feature = "dict")]
+fn cmd_dict_getdef(_: &mut Interp, argv: &[Value]) -> MoltOptResult {
+    check_args(2, argv, 5, 0, "dictionary ?key ...? key default")?;
+
     let mut value: Value = argv[2].clone();
-    let indices = &argv[3..];
 
-    for index in indices {
+    for key in &argv[3..argv.len() - 1] {
         let dict = value.as_dict()?;
 
-        if let Some(val) = dict.get(index) {
+        if let Some(val) = dict.get(key) {
             value = val.clone();
         } else {
-            return molt_err!("key \"{}\" not known in dictionary", index);
+            return molt_opt_ok!(argv[argv.len() - 1].clone());
         }
     }
 
     molt_opt_ok!(value)
 }
 
+/// # dict incr *dictVarName* *key* ?*increment*?
+///
+/// Adds the *increment*, which defaults to 1, to the integer value of the key in the
+/// dictionary variable, which need not exist, and returns the new dictionary.  A missing
+/// key is treated as 0.
+#[cfg(feature = "dict")]
+fn cmd_dict_incr(interp: &mut Interp, argv: &[Value]) -> MoltOptResult {
+    check_args(2, argv, 4, 5, "dictVarName key ?increment?")?;
+
+    let increment: MoltInt = if argv.len() == 5 {
+        argv[4].as_int()?
+    } else {
+        1
+    };
+
+    update_dict_entry(interp, argv, |old| {
+        let old_value = match old {
+            Some(val) => val.as_int()?,
+            None => 0,
+        };
+
+        // Overflow wraps, as in Tcl.
+        molt_ok!(old_value.wrapping_add(increment))
+    })
+}
+
 /// # dict keys *dictionary*
 /// TODO: Add filtering when we have glob matching.
 #[cfg(feature = "dict")]
@@ -281,6 +527,57 @@ fn cmd_dict_keys(_: &mut Interp, argv: &[Value]) -> MoltOptResult {
     molt_opt_ok!(keys)
 }
 
+/// # dict lappend *dictVarName* *key* ?*value* ...?
+///
+/// Appends the values to the list value of the key in the dictionary variable, which
+/// need not exist, and returns the new dictionary.
+#[cfg(feature = "dict")]
+fn cmd_dict_lappend(interp: &mut Interp, argv: &[Value]) -> MoltOptResult {
+    check_args(2, argv, 4, 0, "dictVarName key ?value ...?")?;
+
+    update_dict_entry(interp, argv, |old| {
+        let mut list: MoltList = match old {
+            Some(val) => val.to_list()?,
+            None => Vec::new(),
+        };
+
+        list.extend_from_slice(&argv[4..]);
+        molt_ok!(list)
+    })
+}
+
+/// # dict map {*keyVarName* *valueVarName*} *dictionary* *body*
+///
+/// Assigns each entry's key and value to the variables in turn, and evaluates the body,
+/// returning a dictionary of the keys and the body's results.  `break` ends the mapping,
+/// returning the entries so far, and `continue` omits the entry.
+#[cfg(feature = "dict")]
+fn cmd_dict_map(interp: &mut Interp, argv: &[Value]) -> Result<Step, Exception> {
+    check_args(2, argv, 5, 5, "{keyVarName valueVarName} dictionary script")?;
+
+    let state = DictLoop::new(&argv[2], &argv[3], &argv[4], DictLoopKind::Map(dict_new()))?;
+    dict_loop_step(interp, state)
+}
+
+/// # dict merge ?*dictionary* ...?
+///
+/// Returns a dictionary containing the entries of all of the dictionaries.  When a key
+/// appears in more than one dictionary, the value from the last one wins.
+#[cfg(feature = "dict")]
+fn cmd_dict_merge(_: &mut Interp, argv: &[Value]) -> MoltOptResult {
+    check_args(2, argv, 2, 0, "?dictionary ...?")?;
+
+    let mut merged = dict_new();
+
+    for dict_val in &argv[2..] {
+        for (key, value) in dict_val.as_dict()?.iter() {
+            merged.insert(key.clone(), value.clone());
+        }
+    }
+
+    molt_opt_ok!(merged)
+}
+
 /// # dict remove *dictionary* ?*key* ...?
 #[cfg(feature = "dict")]
 fn cmd_dict_remove(_: &mut Interp, argv: &[Value]) -> MoltOptResult {
@@ -299,6 +596,32 @@ fn cmd_dict_remove(_: &mut Interp, argv: &[Value]) -> MoltOptResult {
     molt_opt_ok!(dict)
 }
 
+/// # dict replace *dictionary* ?*key* *value* ...?
+///
+/// Returns a copy of the dictionary with the keys set to the values.
+#[cfg(feature = "dict")]
+fn cmd_dict_replace(_: &mut Interp, argv: &[Value]) -> MoltOptResult {
+    check_args(2, argv, 3, 0, "dictionary ?key value ...?")?;
+
+    // FIRST, we need key/value pairs.
+    if argv.len().is_multiple_of(2) {
+        return molt_err!(
+            "wrong # args: should be \"{} {}\"",
+            Value::from(&argv[0..2]).to_string(),
+            "dictionary ?key value ...?"
+        );
+    }
+
+    // NEXT, get and clone the dictionary, so we can modify it.
+    let mut dict = (*argv[2].as_dict()?).clone();
+
+    for pair in argv[3..].chunks(2) {
+        dict.insert(pair[0].clone(), pair[1].clone());
+    }
+
+    molt_opt_ok!(dict)
+}
+
 /// # dict set *dictVarName* *key* ?*key* ...? *value*
 #[cfg(feature = "dict")]
 fn cmd_dict_set(interp: &mut Interp, argv: &[Value]) -> MoltOptResult {
@@ -339,6 +662,67 @@ fn cmd_dict_unset(interp: &mut Interp, argv: &[Value]) -> MoltOptResult {
     }
 }
 
+/// # dict update *dictVarName* *key* *varName* ?*key* *varName* ...? *body*
+///
+/// Assigns the values of the keys in the dictionary variable to the corresponding
+/// variables, unsetting the variables for missing keys, and evaluates the body.  Then
+/// copies the variables back into the dictionary, removing the keys whose variables were
+/// unset, and returns the body's result.  The dictionary is updated even if the body
+/// returns an error, unless the dictionary variable itself has been unset.
+#[cfg(feature = "dict")]
+fn cmd_dict_update(interp: &mut Interp, argv: &[Value]) -> Result<Step, Exception> {
+    let argsig = "dictVarName key varName ?key varName ...? script";
+    check_args(2, argv, 6, 0, argsig)?;
+
+    // The key/varName arguments must come in pairs.
+    if !argv.len().is_multiple_of(2) {
+        return molt_err!(
+            "wrong # args: should be \"{} {}\"",
+            Value::from(&argv[0..2]).to_string(),
+            argsig
+        );
+    }
+
+    let pairs = &argv[3..argv.len() - 1];
+
+    // FIRST, copy the values into the variables.
+    let dict_val = interp.var(&argv[2])?;
+    let dict = dict_val.as_dict()?;
+
+    for pair in pairs.chunks(2) {
+        match dict.get(&pair[0]) {
+            Some(value) => interp.set_var(&pair[1], value.clone())?,
+            None => interp.unset_var(&pair[1]),
+        }
+    }
+
+    // NEXT, evaluate the body.
+    interp.push_eval_frame(EvalFrame::DictUpdate {
+        argv: argv.to_vec(),
+    });
+    Ok(interp.push_body(&argv[argv.len() - 1]))
+}
+
+/// Finishes a `dict update` command once its body is done: copies the variables back into
+/// the dictionary, and returns the body's result.
+#[cfg(feature = "dict")]
+pub(crate) fn dict_update_result(interp: &mut Interp, argv: &[Value], result: MoltResult) -> MoltResult {
+    let pairs = &argv[3..argv.len() - 1];
+
+    if let Ok(mut dict_val) = interp.var(&argv[2]) {
+        for pair in pairs.chunks(2) {
+            dict_val = match interp.var(&pair[1]) {
+                Ok(value) => dict_path_insert(&dict_val, &pair[..1], &value)?,
+                Err(_) => dict_path_remove(&dict_val, &pair[..1])?,
+            };
+        }
+
+        interp.set_var(&argv[2], dict_val)?;
+    }
+
+    result
+}
+
 /// # dict values *dictionary*
 /// TODO: Add filtering when we have glob matching.
 #[cfg(feature = "dict")]
@@ -350,6 +734,190 @@ fn cmd_dict_values(_: &mut Interp, argv: &[Value]) -> MoltOptResult {
     molt_opt_ok!(values)
 }
 
+/// # dict with *dictVarName* ?*key* ...? *body*
+///
+/// Assigns the entries of the dictionary in the variable, or of the nested dictionary at
+/// the end of the path of keys, to variables named after the keys, and evaluates the
+/// body.  Then copies the variables back into the dictionary, removing the keys whose
+/// variables were unset, and returns the body's result.  As with `dict update`, the
+/// dictionary is updated even if the body returns an error.
+///
+/// ## TCL Liens
+///
+/// * Variables created by the body are not added to the dictionary, even if they are
+///   named after keys added to the dictionary by the body.
+#[cfg(feature = "dict")]
+fn cmd_dict_with(interp: &mut Interp, argv: &[Value]) -> Result<Step, Exception> {
+    check_args(2, argv, 4, 0, "dictVarName ?key ...? script")?;
+
+    let path = &argv[3..argv.len() - 1];
+
+    // FIRST, copy the entries into the variables.
+    let inner = dict_path_get(&interp.var(&argv[2])?, path)?;
+    let keys: MoltList = inner.as_dict()?.keys().cloned().collect();
+
+    for (key, value) in inner.as_dict()?.iter() {
+        interp.set_var(key, value.clone())?;
+    }
+
+    // NEXT, evaluate the body.
+    interp.push_eval_frame(EvalFrame::DictWith {
+        argv: argv.to_vec(),
+        keys,
+    });
+    Ok(interp.push_body(&argv[argv.len() - 1]))
+}
+
+/// Finishes a `dict with` command once its body is done: copies the variables named after
+/// the keys back into the dictionary, and returns the body's result.
+#[cfg(feature = "dict")]
+pub(crate) fn dict_with_result(
+    interp: &mut Interp,
+    argv: &[Value],
+    keys: &[Value],
+    result: MoltResult,
+) -> MoltResult {
+    let path = &argv[3..argv.len() - 1];
+
+    if let Ok(dict_val) = interp.var(&argv[2]) {
+        let mut inner = dict_path_get(&dict_val, path)?;
+
+        for key in keys {
+            let key_path = core::slice::from_ref(key);
+
+            inner = match interp.var(key) {
+                Ok(value) => dict_path_insert(&inner, key_path, &value)?,
+                Err(_) => dict_path_remove(&inner, key_path)?,
+            };
+        }
+
+        if path.is_empty() {
+            interp.set_var(&argv[2], inner)?;
+        } else {
+            interp.set_var(&argv[2], dict_path_insert(&dict_val, path, &inner)?)?;
+        }
+    }
+
+    result
+}
+
+/// Updates the value of the key in the dictionary variable, which need not exist, by
+/// passing the old value, if any, to the `update` function, and returns the new
+/// dictionary.  This is used by `dict append`, `dict incr`, and `dict lappend`, whose
+/// arguments are the variable name and the key.
+#[cfg(feature = "dict")]
+fn update_dict_entry(
+    interp: &mut Interp,
+    argv: &[Value],
+    update: impl FnOnce(Option<&Value>) -> MoltResult,
+) -> MoltOptResult {
+    let dict_val = interp.var(&argv[2]).unwrap_or_else(|_| Value::from(dict_new()));
+    let new_value = update(dict_val.as_dict()?.get(&argv[3]))?;
+
+    interp.set_var_return(&argv[2], dict_path_insert(&dict_val, &argv[3..4], &new_value)?).map(Some)
+}
+
+/// The state of a `dict for`, `dict map`, or `dict filter` loop: assigns each entry's key
+/// and value to the two variables in turn, and evaluates the body.  `break` ends the loop,
+/// and `continue` skips the entry.
+#[cfg(feature = "dict")]
+pub(crate) struct DictLoop {
+    vars: MoltList,
+
+    // The dictionary is shared, so the body can't modify it.
+    dict: Rc<MoltDict>,
+    index: usize,
+    body: Value,
+    kind: DictLoopKind,
+}
+
+/// What a dictionary loop makes of the results of its body.
+#[cfg(feature = "dict")]
+enum DictLoopKind {
+    /// `dict for`: nothing.
+    For,
+
+    /// `dict map`: the dictionary of the keys and the body's results.
+    Map(MoltDict),
+
+    /// `dict filter`: the dictionary of the entries for which the body returns true.
+    Filter(MoltDict),
+}
+
+#[cfg(feature = "dict")]
+impl DictLoop {
+    fn new(var_list: &Value, dict_val: &Value, body: &Value, kind: DictLoopKind) -> Result<Self, Exception> {
+        let vars = var_list.to_list()?;
+
+        if vars.len() != 2 {
+            return molt_err!("must have exactly two variable names");
+        }
+
+        Ok(Self {
+            vars,
+            dict: dict_val.as_dict()?,
+            index: 0,
+            body: body.clone(),
+            kind,
+        })
+    }
+}
+
+/// Starts the next iteration of a dictionary loop, or returns its result if it's done.
+#[cfg(feature = "dict")]
+fn dict_loop_step(interp: &mut Interp, state: DictLoop) -> Result<Step, Exception> {
+    let (key, value) = match state.dict.get_index(state.index) {
+        Some(entry) => entry,
+        None => {
+            return Ok(Step::Deliver(match state.kind {
+                DictLoopKind::For => molt_ok!(),
+                DictLoopKind::Map(dict) | DictLoopKind::Filter(dict) => molt_ok!(dict),
+            }))
+        }
+    };
+
+    interp.set_var(&state.vars[0], key.clone())?;
+    interp.set_var(&state.vars[1], value.clone())?;
+
+    let body = state.body.clone();
+    interp.push_eval_frame(EvalFrame::DictLoop(state));
+    Ok(interp.push_body(&body))
+}
+
+/// Handles the result of a dictionary loop's body, and goes on to the next entry.
+#[cfg(feature = "dict")]
+pub(crate) fn dict_loop_result(
+    interp: &mut Interp,
+    mut state: DictLoop,
+    result: MoltResult,
+) -> Result<Step, Exception> {
+    match result {
+        Ok(result) => {
+            let (key, value) = state.dict.get_index(state.index).unwrap();
+
+            match &mut state.kind {
+                DictLoopKind::For => (),
+                DictLoopKind::Map(mapped) => {
+                    mapped.insert(key.clone(), result);
+                }
+                DictLoopKind::Filter(filtered) => {
+                    if result.as_bool()? {
+                        filtered.insert(key.clone(), value.clone());
+                    }
+                }
+            }
+        }
+        Err(exception) => match exception.code() {
+            ResultCode::Break => state.index = state.dict.len(),
+            ResultCode::Continue => (),
+            _ => return Err(exception),
+        },
+    }
+
+    state.index += 1;
+    dict_loop_step(interp, state)
+}
+
 /// error *message*
 ///
 /// Returns an error with the given message.
@@ -363,6 +931,22 @@ pub fn cmd_error(_interp: &mut Interp, argv: &[Value]) -> MoltOptResult {
     molt_err!(argv[1].clone())
 }
 
+/// # eval *arg* ?*arg* ...?
+///
+/// Concatenates the arguments as for `concat` and evaluates the result as a script in the
+/// current scope.  A single argument is evaluated as is, so that its parsed form is reused
+/// when the same value is evaluated repeatedly.
+pub(crate) fn cmd_eval(interp: &mut Interp, argv: &[Value]) -> Result<Step, Exception> {
+    check_args(1, argv, 2, 0, "arg ?arg ...?")?;
+
+    if argv.len() == 2 {
+        Ok(interp.push_body(&argv[1]))
+    } else {
+        let script = Value::from(list::concat_values(&argv[1..]));
+        Ok(interp.push_body(&script))
+    }
+}
+
 /// # exit ?*returnCode*?
 ///
 /// Terminates the application by calling `std::process::exit()`.
@@ -389,108 +973,264 @@ pub fn cmd_exit(_interp: &mut Interp, argv: &[Value]) -> MoltOptResult {
 ///
 /// See the Molt Book.
 #[cfg(feature = "expr")]
-pub fn cmd_expr(interp: &mut Interp, argv: &[Value]) -> MoltOptResult {
+pub(crate) fn cmd_expr(interp: &mut Interp, argv: &[Value]) -> Result<Step, Exception> {
     check_args(1, argv, 2, 2, "expr")?;
 
-    interp.expr(&argv[1]).map(Some)
+    Ok(interp.push_expr(&argv[1]))
 }
 
 /// # for *start* *test* *next* *command*
 ///
 /// A standard "for" loop.  start, next, and command are scripts; test is an expression
 ///
-pub fn cmd_for(interp: &mut Interp, argv: &[Value]) -> MoltOptResult {
+pub(crate) fn cmd_for(interp: &mut Interp, argv: &[Value]) -> Result<Step, Exception> {
     check_args(1, argv, 5, 5, "start test next command")?;
 
-    let start = &argv[1];
-    let test = &argv[2];
-    let next = &argv[3];
-    let command = &argv[4];
+    interp.push_eval_frame(EvalFrame::For(ForLoop {
+        test: argv[2].clone(),
+        next: argv[3].clone(),
+        body: argv[4].clone(),
+        phase: ForPhase::Start,
+    }));
+    Ok(interp.push_body(&argv[1]))
+}
+
+/// The state of a `for` loop.
+pub(crate) struct ForLoop {
+    test: Value,
+    next: Value,
+    body: Value,
 
-    // Start
-    interp.eval_value(start)?;
+    /// The script or expression whose result the loop is waiting for.
+    phase: ForPhase,
+}
 
-    while interp.expr_bool(test)? {
-        let result = interp.eval_value(command);
+enum ForPhase {
+    Start,
+    Test,
+    Body,
+    Next,
+}
 
-        if let Err(exception) = result {
-            match exception.code() {
-                ResultCode::Break => break,
-                ResultCode::Continue => (),
-                _ => return Err(exception),
-            }
+/// Handles the result of a `for` loop's start script, test, body, or next script, and
+/// goes on to the next one.
+pub(crate) fn for_result(
+    interp: &mut Interp,
+    mut state: ForLoop,
+    result: MoltResult,
+) -> Result<Step, Exception> {
+    match state.phase {
+        ForPhase::Start => {
+            result?;
         }
+        ForPhase::Test => {
+            if !result?.as_bool()? {
+                return Ok(Step::Deliver(molt_ok!()));
+            }
 
-        // Execute next script.  Break is allowed, but continue is not.
-        let result = interp.eval_value(next);
+            state.phase = ForPhase::Body;
+            let body = state.body.clone();
+            interp.push_eval_frame(EvalFrame::For(state));
+            return Ok(interp.push_body(&body));
+        }
+        ForPhase::Body => {
+            if let Err(exception) = result {
+                match exception.code() {
+                    ResultCode::Break => return Ok(Step::Deliver(molt_ok!())),
+                    ResultCode::Continue => (),
+                    _ => return Err(exception),
+                }
+            }
 
-        if let Err(exception) = result {
-            match exception.code() {
-                ResultCode::Break => break,
-                ResultCode::Continue => {
-                    return molt_err!("invoked \"continue\" outside of a loop");
+            // Execute next script.  Break is allowed, but continue is not.
+            state.phase = ForPhase::Next;
+            let next = state.next.clone();
+            interp.push_eval_frame(EvalFrame::For(state));
+            return Ok(interp.push_body(&next));
+        }
+        ForPhase::Next => {
+            if let Err(exception) = result {
+                match exception.code() {
+                    ResultCode::Break => return Ok(Step::Deliver(molt_ok!())),
+                    ResultCode::Continue => {
+                        return molt_err!("invoked \"continue\" outside of a loop");
+                    }
+                    _ => return Err(exception),
                 }
-                _ => return Err(exception),
             }
         }
     }
 
-    molt_opt_ok!()
+    state.phase = ForPhase::Test;
+
+    if let Some(result) = interp.expr_now(&state.test) {
+        return for_result(interp, state, result);
+    }
+
+    let test = state.test.clone();
+    interp.push_eval_frame(EvalFrame::For(state));
+    Ok(interp.push_expr(&test))
 }
 
-/// # foreach *varList* *list* *body*
+/// # foreach *varList* *list* ?*varList* *list* ...? *body*
 ///
 /// Loops over the items the list, assigning successive items to the variables in the
 /// *varList* and calling the *body* as a script once for each set of assignments.
 /// On the last iteration, the second and subsequents variables in the *varList* will
 /// be assigned the empty string if there are not enough list elements to fill them.
 ///
-/// ## TCL Liens
-///
-/// * In Standard TCL, `foreach` can loop over several lists at the same time.
-pub fn cmd_foreach(interp: &mut Interp, argv: &[Value]) -> MoltOptResult {
-    check_args(1, argv, 4, 4, "varList list body")?;
+/// Given several *varList*/*list* pairs, loops over all of the lists in parallel; the
+/// loop continues until all of the lists are exhausted, assigning the empty string to
+/// the variables of any lists that run out early.
+pub(crate) fn cmd_foreach(interp: &mut Interp, argv: &[Value]) -> Result<Step, Exception> {
+    let state = ForeachLoop::new(argv, None)?;
+    foreach_step(interp, state)
+}
 
-    let var_list = &*argv[1].as_list()?;
-    let list = &*argv[2].as_list()?;
-    let body = &argv[3];
+/// The state of a `foreach` or `lmap` loop.
+pub(crate) struct ForeachLoop {
+    pairs: Vec<(MoltList, MoltList)>,
+    body: Value,
+    iteration: usize,
+    iterations: usize,
 
-    let mut i = 0;
+    /// The results of the complete iterations of the body, for `lmap`.
+    results: Option<MoltList>,
+}
 
-    while i < list.len() {
-        for var in var_list {
-            if i < list.len() {
-                interp.set_var(var, list[i].clone())?;
-                i += 1;
-            } else {
-                interp.set_var(var, Value::empty())?;
-            }
-        }
+impl ForeachLoop {
+    fn new(argv: &[Value], results: Option<MoltList>) -> Result<Self, Exception> {
+        let (pairs, iterations) = foreach_pairs(argv)?;
+
+        Ok(Self {
+            pairs,
+            body: argv[argv.len() - 1].clone(),
+            iteration: 0,
+            iterations,
+            results,
+        })
+    }
+}
+
+/// Starts the next iteration of a `foreach` or `lmap` loop, or returns its result if
+/// it's done.
+fn foreach_step(interp: &mut Interp, mut state: ForeachLoop) -> Result<Step, Exception> {
+    if state.iteration == state.iterations {
+        return Ok(Step::Deliver(match state.results {
+            Some(results) => molt_ok!(results),
+            None => molt_ok!(),
+        }));
+    }
+
+    foreach_assign(interp, &state.pairs, state.iteration)?;
+    state.iteration += 1;
 
-        let result = interp.eval_value(body);
+    let body = state.body.clone();
+    interp.push_eval_frame(EvalFrame::Foreach(state));
+    Ok(interp.push_body(&body))
+}
 
-        if let Err(exception) = result {
-            match exception.code() {
-                ResultCode::Break => break,
-                ResultCode::Continue => (),
-                _ => return Err(exception),
+/// Handles the result of a `foreach` or `lmap` loop's body, and goes on to the next
+/// iteration.
+pub(crate) fn foreach_result(
+    interp: &mut Interp,
+    mut state: ForeachLoop,
+    result: MoltResult,
+) -> Result<Step, Exception> {
+    match result {
+        Ok(value) => {
+            if let Some(results) = &mut state.results {
+                results.push(value);
             }
         }
+        Err(exception) => match exception.code() {
+            ResultCode::Break => state.iteration = state.iterations,
+            ResultCode::Continue => (),
+            _ => return Err(exception),
+        },
     }
 
-    molt_opt_ok!()
+    foreach_step(interp, state)
 }
 
-/// # global ?*varName* ...?
-///
-/// Appends any number of values to a variable's value, which need not
-/// initially exist.
-pub fn cmd_global(interp: &mut Interp, argv: &[Value]) -> MoltOptResult {
-    // Accepts any number of arguments
+/// Gets the variable lists and lists of a `foreach` or `lmap` loop, and the number of
+/// iterations, checking the command's arguments.
+fn foreach_pairs(argv: &[Value]) -> Result<(Vec<(MoltList, MoltList)>, usize), Exception> {
+    let argsig = "varList list ?varList list ...? body";
+    check_args(1, argv, 4, 0, argsig)?;
 
-    // FIRST, if we're at the global scope this is a no-op.
-    if interp.scope_level() > 0 {
-        for name in &argv[1..] {
+    // The varList/list arguments must come in pairs.
+    if !argv.len().is_multiple_of(2) {
+        return molt_err!("wrong # args: should be \"{} {}\"", argv[0], argsig);
+    }
+
+    // Copy the lists, as the body might modify them.
+    let mut pairs: Vec<(MoltList, MoltList)> = Vec::new();
+    let mut iterations = 0;
+
+    for pair in argv[1..argv.len() - 1].chunks(2) {
+        let var_list = pair[0].to_list()?;
+        let list = pair[1].to_list()?;
+
+        if var_list.is_empty() {
+            return molt_err!("{} varlist is empty", argv[0]);
+        }
+
+        iterations = iterations.max(list.len().div_ceil(var_list.len()));
+        pairs.push((var_list, list));
+    }
+
+    Ok((pairs, iterations))
+}
+
+/// Assigns the variables of a `foreach` or `lmap` loop for the given iteration.
+fn foreach_assign(
+    interp: &mut Interp,
+    pairs: &[(MoltList, MoltList)],
+    iteration: usize,
+) -> Result<(), Exception> {
+    for (var_list, list) in pairs {
+        for (j, var) in var_list.iter().enumerate() {
+            let value = list.get(iteration * var_list.len() + j).cloned().unwrap_or_default();
+            interp.set_var(var, value)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// # format *formatString* ?*arg* ...?
+///
+/// Formats the arguments according to the format string, in the manner of C's
+/// `sprintf`, and returns the result.  Supports the `%d`, `%i`, `%u`, `%x`, `%X`,
+/// `%o`, `%b`, `%c`, `%s`, `%f`, `%e`, `%E`, `%g`, `%G`, and `%%` conversions; the
+/// `-`, `+`, space, `0`, and `#` flags; field widths and precisions, including `*`;
+/// and XPG `%n$` argument positions.
+///
+/// ## TCL Liens
+///
+/// * Size modifiers (`l`, `ll`, `h`, etc.) are accepted but ignored; integers are
+///   always formatted at their full width.
+/// * The floating-point conversions are only available with the `float` feature.
+/// * Does not support `%a` or `%p`.
+#[cfg(feature = "format-command")]
+pub fn cmd_format(_interp: &mut Interp, argv: &[Value]) -> MoltOptResult {
+    check_args(1, argv, 2, 0, "formatString ?arg ...?")?;
+
+    molt_opt_ok!(format::format(argv[1].as_str(), &argv[2..])?)
+}
+
+/// # global ?*varName* ...?
+///
+/// Links each named global variable into the current procedure's scope.  This is a no-op
+/// outside of a procedure.  With the `namespaces` feature, a qualified *varName* links the
+/// namespace variable, using its tail as the local name.
+pub fn cmd_global(interp: &mut Interp, argv: &[Value]) -> MoltOptResult {
+    // Accepts any number of arguments
+
+    // FIRST, if we're at the global scope this is a no-op.
+    if interp.scope_level() > 0 {
+        for name in &argv[1..] {
             // TODO: Should upvar take the name as a Value?
             interp.upvar(0, name.as_str());
         }
@@ -516,26 +1256,53 @@ enum IfWants {
 ///
 /// * Because we don't yet have an expression parser, the *expr* arguments are evaluated as
 ///   scripts that must return a boolean value.
-pub fn cmd_if(interp: &mut Interp, argv: &[Value]) -> MoltOptResult {
-    let mut argi = 1;
-    let mut wants = IfWants::Expr;
+pub(crate) fn cmd_if(interp: &mut Interp, argv: &[Value]) -> Result<Step, Exception> {
+    if_step(interp, argv, 1, IfWants::Expr)
+}
+
+/// Handles the result of one of the `if` command's expressions, and goes on with the
+/// command.
+pub(crate) fn if_result(
+    interp: &mut Interp,
+    argv: MoltList,
+    argi: usize,
+    result: MoltResult,
+) -> Result<Step, Exception> {
+    let wants = if_wants(result)?;
+    if_step(interp, &argv, argi + 1, wants)
+}
 
+/// Returns what the `if` command wants next, given the result of one of its expressions.
+fn if_wants(result: MoltResult) -> Result<IfWants, Exception> {
+    if result?.as_bool()? {
+        Ok(IfWants::ThenBody)
+    } else {
+        Ok(IfWants::SkipThenClause)
+    }
+}
+
+/// Parses the `if` command's words from the one at `argi`, pushing the next expression to
+/// evaluate or the selected script, if any.
+fn if_step(interp: &mut Interp, argv: &[Value], mut argi: usize, mut wants: IfWants) -> Result<Step, Exception> {
     while argi < argv.len() {
         match wants {
-            IfWants::Expr => {
-                wants = if interp.expr_bool(&argv[argi])? {
-                    IfWants::ThenBody
-                } else {
-                    IfWants::SkipThenClause
-                };
-            }
+            IfWants::Expr => match interp.expr_now(&argv[argi]) {
+                Some(result) => wants = if_wants(result)?,
+                None => {
+                    interp.push_eval_frame(EvalFrame::If {
+                        argv: argv.to_vec(),
+                        argi,
+                    });
+                    return Ok(interp.push_expr(&argv[argi]));
+                }
+            },
             IfWants::ThenBody => {
                 if argv[argi].as_str() == "then" {
                     argi += 1;
                 }
 
                 if argi < argv.len() {
-                    return interp.eval_value(&argv[argi]).map(Some);
+                    return Ok(interp.push_body(&argv[argi]));
                 } else {
                     break;
                 }
@@ -573,7 +1340,7 @@ pub fn cmd_if(interp: &mut Interp, argv: &[Value]) -> MoltOptResult {
                 }
 
                 if argi < argv.len() {
-                    return interp.eval_value(&argv[argi]).map(Some);
+                    return Ok(interp.push_body(&argv[argi]));
                 } else {
                     break;
                 }
@@ -597,7 +1364,7 @@ pub fn cmd_if(interp: &mut Interp, argv: &[Value]) -> MoltOptResult {
         )
     } else {
         // Looking for ElseBody, but there doesn't need to be one.
-        molt_opt_ok!() // temp
+        Ok(Step::Deliver(molt_ok!()))
     }
 }
 
@@ -629,17 +1396,22 @@ pub fn cmd_info(interp: &mut Interp, argv: &[Value]) -> MoltOptResult {
 }
 
 #[cfg(feature = "info")]
-static INFO_SUBCOMMANDS: [Subcommand; 11] = [
+static INFO_SUBCOMMANDS: [Subcommand; 16] = [
     Subcommand("args", cmd_info_args),
     Subcommand("body", cmd_info_body),
+    Subcommand("cmdcount", cmd_info_cmdcount),
     Subcommand("cmdtype", cmd_info_cmdtype),
     Subcommand("commands", cmd_info_commands),
     Subcommand("complete", cmd_info_complete),
+    Subcommand("coroutine", cmd_info_coroutine),
     Subcommand("default", cmd_info_default),
     Subcommand("exists", cmd_info_exists),
+    Subcommand("frame", cmd_info_frame),
     Subcommand("globals", cmd_info_globals),
+    Subcommand("level", cmd_info_level),
     Subcommand("locals", cmd_info_locals),
     Subcommand("procs", cmd_info_procs),
+    Subcommand("script", cmd_info_script),
     Subcommand("vars", cmd_info_vars),
 ];
 
@@ -657,6 +1429,15 @@ pub fn cmd_info_body(interp: &mut Interp, argv: &[Value]) -> MoltOptResult {
     interp.proc_body(argv[2].as_str()).map(Some)
 }
 
+/// # info cmdcount
+///
+/// Returns the number of commands the interpreter has executed.
+#[cfg(feature = "info")]
+pub fn cmd_info_cmdcount(interp: &mut Interp, argv: &[Value]) -> MoltOptResult {
+    check_args(2, argv, 2, 2, "")?;
+    molt_opt_ok!(interp.cmd_count() as MoltInt)
+}
+
 /// # info cmdtype *command*
 #[cfg(feature = "info")]
 pub fn cmd_info_cmdtype(interp: &mut Interp, argv: &[Value]) -> MoltOptResult {
@@ -665,9 +1446,36 @@ pub fn cmd_info_cmdtype(interp: &mut Interp, argv: &[Value]) -> MoltOptResult {
 }
 
 /// # info commands ?*pattern*?
+///
+/// With the `namespaces` feature, returns the commands visible in the current namespace,
+/// or the fully qualified names of the commands in the namespace named by a qualified
+/// *pattern*.
+#[cfg(feature = "info")]
+pub fn cmd_info_commands(interp: &mut Interp, argv: &[Value]) -> MoltOptResult {
+    check_args(2, argv, 2, 3, "?pattern?")?;
+
+    #[cfg(feature = "namespaces")]
+    return molt_opt_ok!(Value::from(
+        interp.visible_command_names(argv.get(2).map(|p| p.as_str()), false)
+    ));
+
+    #[cfg(not(feature = "namespaces"))]
+    molt_opt_ok!(Value::from(glob_filter(interp.command_names(), argv.get(2))))
+}
+
+/// # info coroutine
+///
+/// Returns the fully qualified name of the coroutine that is running, or the empty
+/// string if none is.
 #[cfg(feature = "info")]
-pub fn cmd_info_commands(interp: &mut Interp, _argv: &[Value]) -> MoltOptResult {
-    molt_opt_ok!(Value::from(interp.command_names()))
+pub fn cmd_info_coroutine(_interp: &mut Interp, argv: &[Value]) -> MoltOptResult {
+    check_args(2, argv, 2, 2, "")?;
+
+    #[cfg(feature = "coroutines")]
+    return Ok(Some(_interp.coroutine_name().unwrap_or_default()));
+
+    #[cfg(not(feature = "coroutines"))]
+    molt_opt_ok!()
 }
 
 /// # info default *procname* *arg* *varname*
@@ -703,31 +1511,199 @@ pub fn cmd_info_complete(interp: &mut Interp, argv: &[Value]) -> MoltOptResult {
     }
 }
 
-/// # info globals
-/// TODO: Add glob matching as a feature, and provide optional pattern argument.
+/// # info frame ?*number*?
+///
+/// With no argument, returns the depth of the frame stack: the number of active procedure
+/// calls, lambdas, `namespace eval` scripts, and sourced files.  Otherwise, returns a
+/// dictionary describing the frame at the given depth: counting from 1 at the bottom of
+/// the stack if *number* is positive, and relative to the current frame otherwise.
+///
+/// The dictionary's `type` is `proc` for a procedure or lambda, `eval` for a
+/// `namespace eval` script, or `source` for a sourced file; its `level` is the frame's
+/// stack level; and its `cmd` is the command that pushed the frame.  It also includes
+/// the `proc` name, the `lambda` expression, or the `file` name, as appropriate.
+///
+/// ## TCL Liens
+///
+/// * Only procedure calls, lambdas, `namespace eval` scripts, and sourced files have
+///   frames; the frame stack is empty at the global level, rather than including the
+///   command being executed.
+/// * Frames don't include line numbers.
+#[cfg(feature = "info")]
+pub fn cmd_info_frame(interp: &mut Interp, argv: &[Value]) -> MoltOptResult {
+    check_args(2, argv, 2, 3, "?number?")?;
+
+    let depth = interp.frame_depth();
+
+    if argv.len() == 2 {
+        return molt_opt_ok!(depth as MoltInt);
+    }
+
+    let frame = interp
+        .frame(stack_number(&argv[2], depth)?)
+        .expect("valid frame depth");
+
+    let mut info: MoltList = Vec::new();
+    info.push(Value::from("type"));
+
+    match frame.kind {
+        FrameKind::Proc | FrameKind::Lambda => info.push(Value::from("proc")),
+        #[cfg(feature = "namespaces")]
+        FrameKind::Namespace => info.push(Value::from("eval")),
+        #[cfg(feature = "std")]
+        FrameKind::Source(_) => info.push(Value::from("source")),
+    }
+
+    info.push(Value::from("level"));
+    info.push(Value::from(frame.level as MoltInt));
+    info.push(Value::from("cmd"));
+    info.push(Value::from(&frame.argv[..]));
+
+    match &frame.kind {
+        FrameKind::Proc => {
+            info.push(Value::from("proc"));
+            info.push(frame.argv[0].clone());
+        }
+        FrameKind::Lambda => {
+            info.push(Value::from("lambda"));
+            info.push(frame.argv[1].clone());
+        }
+        #[cfg(feature = "namespaces")]
+        FrameKind::Namespace => (),
+        #[cfg(feature = "std")]
+        FrameKind::Source(file) => {
+            info.push(Value::from("file"));
+            info.push(Value::from(file));
+        }
+    }
+
+    molt_opt_ok!(info)
+}
+
+/// # info globals ?*pattern*?
+#[cfg(feature = "info")]
+pub fn cmd_info_globals(interp: &mut Interp, argv: &[Value]) -> MoltOptResult {
+    check_args(2, argv, 2, 3, "?pattern?")?;
+    molt_opt_ok!(Value::from(glob_filter(interp.vars_in_global_scope(), argv.get(2))))
+}
+
+/// # info level ?*number*?
+///
+/// With no argument, returns the current stack level, where the global level is 0.
+/// Otherwise, returns the command words of the procedure call at the given level: an
+/// absolute level if *number* is positive, and relative to the current level otherwise.
+///
+/// ## TCL Liens
+///
+/// * A stack level pushed by Rust code, e.g., by the Molt test harness's `test`
+///   command, has no command words; `info level` returns the empty list for it.
+#[cfg(feature = "info")]
+pub fn cmd_info_level(interp: &mut Interp, argv: &[Value]) -> MoltOptResult {
+    check_args(2, argv, 2, 3, "?number?")?;
+
+    let level = interp.scope_level();
+
+    if argv.len() == 2 {
+        return molt_opt_ok!(level as MoltInt);
+    }
+
+    let level = stack_number(&argv[2], level)?;
+    molt_opt_ok!(interp.level_argv(level))
+}
+
+/// Converts the *number* argument of `info level` or `info frame` into an absolute
+/// level or depth, given the current one: positive numbers are absolute, and others are
+/// relative.  The result must be between 1 and the current level or depth.
 #[cfg(feature = "info")]
-pub fn cmd_info_globals(interp: &mut Interp, _argv: &[Value]) -> MoltOptResult {
-    molt_opt_ok!(Value::from(interp.vars_in_global_scope()))
+fn stack_number(number: &Value, current: usize) -> Result<usize, Exception> {
+    let num = number.as_int()?;
+    let absolute = if num > 0 { num } else { current as MoltInt + num };
+
+    if absolute < 1 || absolute > current as MoltInt {
+        return molt_err!("bad level \"{}\"", number);
+    }
+
+    Ok(absolute as usize)
 }
 
-/// # info locals
-/// TODO: Add glob matching as a feature, and provide optional pattern argument.
+/// # info locals ?*pattern*?
 #[cfg(feature = "info")]
-pub fn cmd_info_locals(interp: &mut Interp, _argv: &[Value]) -> MoltOptResult {
-    molt_opt_ok!(Value::from(interp.vars_in_local_scope()))
+pub fn cmd_info_locals(interp: &mut Interp, argv: &[Value]) -> MoltOptResult {
+    check_args(2, argv, 2, 3, "?pattern?")?;
+    molt_opt_ok!(Value::from(glob_filter(interp.vars_in_local_scope(), argv.get(2))))
 }
 
 /// # info procs ?*pattern*?
+///
+/// With the `namespaces` feature, the procedures are found as for `info commands`.
+#[cfg(feature = "info")]
+pub fn cmd_info_procs(interp: &mut Interp, argv: &[Value]) -> MoltOptResult {
+    check_args(2, argv, 2, 3, "?pattern?")?;
+
+    #[cfg(feature = "namespaces")]
+    return molt_opt_ok!(Value::from(
+        interp.visible_command_names(argv.get(2).map(|p| p.as_str()), true)
+    ));
+
+    #[cfg(not(feature = "namespaces"))]
+    molt_opt_ok!(Value::from(glob_filter(interp.proc_names(), argv.get(2))))
+}
+
+/// # info script
+///
+/// Returns the name of the file currently being sourced, or the empty string if none.
+///
+/// ## TCL Liens
+///
+/// * The script name can't be set.
 #[cfg(feature = "info")]
-pub fn cmd_info_procs(interp: &mut Interp, _argv: &[Value]) -> MoltOptResult {
-    molt_opt_ok!(Value::from(interp.proc_names()))
+pub fn cmd_info_script(interp: &mut Interp, argv: &[Value]) -> MoltOptResult {
+    check_args(2, argv, 2, 2, "")?;
+    molt_opt_ok!(interp.script_file().unwrap_or("").to_string())
 }
 
-/// # info vars
-/// TODO: Add glob matching as a feature, and provide optional pattern argument.
+/// # info vars ?*pattern*?
+///
+/// With the `namespaces` feature, a qualified *pattern* matches the fully qualified names
+/// of the variables in the namespace it names.
 #[cfg(feature = "info")]
-pub fn cmd_info_vars(interp: &mut Interp, _argv: &[Value]) -> MoltOptResult {
-    molt_opt_ok!(Value::from(interp.vars_in_scope()))
+pub fn cmd_info_vars(interp: &mut Interp, argv: &[Value]) -> MoltOptResult {
+    check_args(2, argv, 2, 3, "?pattern?")?;
+
+    #[cfg(feature = "namespaces")]
+    if let Some(pattern) = argv.get(2).filter(|p| namespace::is_qualified(p.as_str())) {
+        let pattern = interp.namespace_key(pattern.as_str()).into_owned();
+        let ns = namespace::qualifiers(&pattern);
+        let names = interp
+            .namespace_vars(ns)
+            .iter()
+            .map(|name| Value::from(namespace::display(&namespace::qualify(ns, name.as_str()))))
+            .collect();
+        let pattern = Value::from(namespace::display(&pattern));
+        return molt_opt_ok!(Value::from(glob_filter(names, Some(&pattern))));
+    }
+
+    molt_opt_ok!(Value::from(glob_filter(interp.vars_in_scope(), argv.get(2))))
+}
+
+/// Filters a list of names, retaining only those that match the glob pattern, if any.
+fn glob_filter(names: MoltList, pattern: Option<&Value>) -> MoltList {
+    match pattern {
+        Some(pattern) => names
+            .into_iter()
+            .filter(|name| util::glob_match(pattern.as_str(), name.as_str(), false))
+            .collect(),
+        None => names,
+    }
+}
+
+/// How `lsearch` and `switch` match strings against patterns.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum MatchMode {
+    Exact,
+    Glob,
+    #[cfg(feature = "regex")]
+    Regexp,
 }
 
 /// # join *list* ?*joinString*?
@@ -777,6 +1753,27 @@ pub fn cmd_lappend(interp: &mut Interp, argv: &[Value]) -> MoltOptResult {
     interp.set_var_return(&argv[1], Value::from(list)).map(Some)
 }
 
+/// # lassign *list* ?*varName* ...?
+///
+/// Assigns successive elements of the list to the named variables, assigning the
+/// empty string to any variables left over.  Returns the unassigned elements as a list.
+pub fn cmd_lassign(interp: &mut Interp, argv: &[Value]) -> MoltOptResult {
+    check_args(1, argv, 2, 0, "list ?varName ...?")?;
+
+    let list = argv[1].to_list()?;
+    let names = &argv[2..];
+
+    for (i, name) in names.iter().enumerate() {
+        interp.set_var(name, list.get(i).cloned().unwrap_or_default())?;
+    }
+
+    if names.len() < list.len() {
+        molt_opt_ok!(&list[names.len()..])
+    } else {
+        molt_opt_ok!("")
+    }
+}
+
 /// # lindex *list* ?*index* ...?
 ///
 /// Returns an element from the list, indexing into nested lists.
@@ -794,20 +1791,36 @@ pub fn lindex_into(list: &Value, indices: &[Value]) -> MoltOptResult {
     let mut value: Value = list.clone();
 
     for index_val in indices {
-        let next_value = {
-            let list = value.as_list()?;
-            let index = index_val.as_int()?;
+        // Don't hold the list across as_index; the index might share its data rep.
+        let len = value.as_list()?.len();
+        let index = index_val.as_index(len)?;
+        let index = usize::try_from(index).unwrap_or(len);
 
-            let index = usize::try_from(index).unwrap_or(list.len());
-
-            list.get(index).cloned().unwrap_or_default()
-        };
+        let next_value = value.as_list()?.get(index).cloned().unwrap_or_default();
         value = next_value;
     }
 
     molt_opt_ok!(value)
 }
 
+/// # linsert *list* *index* ?*element* ...?
+///
+/// Returns a new list with the elements inserted just before the *index*'th element
+/// of the list.  An *index* of `end` appends the elements to the list.
+pub fn cmd_linsert(_interp: &mut Interp, argv: &[Value]) -> MoltOptResult {
+    check_args(1, argv, 3, 0, "list index ?element ...?")?;
+
+    let mut list = argv[1].to_list()?;
+
+    // Here, "end" refers to the position after the last element.
+    let index = argv[2].as_index(list.len() + 1)?;
+    let index = index.clamp(0, list.len() as MoltInt) as usize;
+
+    list.splice(index..index, argv[3..].iter().cloned());
+
+    molt_opt_ok!(list)
+}
+
 /// # list ?*arg*...?
 ///
 /// Converts its arguments into a canonical list.
@@ -825,163 +1838,1231 @@ pub fn cmd_llength(_interp: &mut Interp, argv: &[Value]) -> MoltOptResult {
     molt_opt_ok!(argv[1].as_list()?.len() as MoltInt)
 }
 
-/// # pdump
+/// # lmap *varList* *list* ?*varList* *list* ...? *body*
 ///
-/// Dumps profile data.  Developer use only.
-#[cfg(feature = "std")]
-pub fn cmd_pdump(interp: &mut Interp, argv: &[Value]) -> MoltOptResult {
-    check_args(1, argv, 1, 1, "")?;
-
-    interp.profile_dump();
-
-    molt_opt_ok!()
+/// Loops over the lists as for `foreach`, and returns a list of the results of each
+/// iteration of the *body*.  Iterations ended by `continue` contribute nothing to the
+/// result; `break` ends the loop, returning the results so far.
+pub(crate) fn cmd_lmap(interp: &mut Interp, argv: &[Value]) -> Result<Step, Exception> {
+    let state = ForeachLoop::new(argv, Some(Vec::new()))?;
+    foreach_step(interp, state)
 }
 
-/// # pclear
+/// # lrange *list* *first* *last*
 ///
-/// Clears profile data.  Developer use only.
-#[cfg(feature = "std")]
-pub fn cmd_pclear(interp: &mut Interp, argv: &[Value]) -> MoltOptResult {
-    check_args(1, argv, 1, 1, "")?;
-
-    interp.profile_clear();
-
-    molt_opt_ok!()
+/// Returns a list of the elements from *first* to *last*, inclusive.
+pub fn cmd_lrange(_interp: &mut Interp, argv: &[Value]) -> MoltOptResult {
+    check_args(1, argv, 4, 4, "list first last")?;
+
+    // Don't hold the list across as_index; the indices might share its data rep.
+    let len = argv[1].as_list()?.len();
+    let first = argv[2].as_index(len)?;
+    let last = argv[3].as_index(len)?;
+
+    match clamp_range(first, last, len) {
+        Some((first, last)) => molt_opt_ok!(&argv[1].as_list()?[first..=last]),
+        None => molt_opt_ok!(""),
+    }
 }
 
-/// # proc *name* *args* *body*
+/// The greatest length of the result of `lrepeat` or `string repeat`, as in Tcl.
+const MAX_REPEAT_LEN: usize = i32::MAX as usize;
+
+/// # lrepeat *count* ?*element* ...?
 ///
-/// Defines a procedure.
-pub fn cmd_proc(interp: &mut Interp, argv: &[Value]) -> MoltOptResult {
-    check_args(1, argv, 4, 4, "name args body")?;
+/// Returns a list containing the elements repeated *count* times.
+pub fn cmd_lrepeat(_interp: &mut Interp, argv: &[Value]) -> MoltOptResult {
+    check_args(1, argv, 2, 0, "count ?value ...?")?;
 
-    // FIRST, get the arguments
-    let name = argv[1].as_str();
-    let args = &*argv[2].as_list()?;
+    let count = argv[1].as_int()?;
+
+    if count < 0 {
+        return molt_err!("bad count \"{}\": must be integer >= 0", count);
+    }
 
-    // NEXT, validate the argument specs
-    for arg in args {
-        let vec = arg.as_list()?;
+    let elements = &argv[2..];
 
-        if vec.is_empty() {
-            return molt_err!("argument with no name");
-        } else if vec.len() > 2 {
-            return molt_err!("too many fields in argument specifier \"{}\"", arg);
-        }
+    if elements.is_empty() {
+        return molt_opt_ok!("");
     }
 
-    // NEXT, add the command.
-    interp.add_proc(name, args, &argv[3]);
+    let len = match elements.len().checked_mul(count as usize) {
+        Some(len) if len <= MAX_REPEAT_LEN => len,
+        _ => return molt_err!("result of lrepeat would be too large"),
+    };
 
-    molt_opt_ok!()
-}
+    let mut list = Vec::with_capacity(len);
 
-/// # puts *string*
-///
-/// Outputs the string to stdout.
-///
-/// ## TCL Liens
-///
-/// * Does not support `-nonewline`
-/// * Does not support `channelId`
-#[cfg(feature = "std")]
-pub fn cmd_puts(_interp: &mut Interp, argv: &[Value]) -> MoltOptResult {
-    check_args(1, argv, 2, 2, "string")?;
+    for _ in 0..count {
+        list.extend_from_slice(elements);
+    }
 
-    println!("{}", argv[1]);
-    molt_opt_ok!()
+    molt_opt_ok!(list)
 }
 
-/// # rename *oldName* *newName*
+/// # lreplace *list* *first* *last* ?*element* ...?
 ///
-/// Renames the command called *oldName* to have the *newName*.  If the
-/// *newName* is "", the command is destroyed.
-pub fn cmd_rename(interp: &mut Interp, argv: &[Value]) -> MoltOptResult {
-    check_args(1, argv, 3, 3, "oldName newName")?;
+/// Returns a new list with the elements from *first* to *last* replaced by the given
+/// elements.  If *last* is less than *first*, the elements are inserted before *first*
+/// without deleting anything.
+pub fn cmd_lreplace(_interp: &mut Interp, argv: &[Value]) -> MoltOptResult {
+    check_args(1, argv, 4, 0, "list first last ?element ...?")?;
 
-    // FIRST, get the arguments
-    let old_name = argv[1].as_str();
-    let new_name = argv[2].as_str();
+    let mut list = argv[1].to_list()?;
+    let len = list.len() as MoltInt;
+    let first = argv[2].as_index(list.len())?.clamp(0, len);
+    let last = argv[3].as_index(list.len())?.min(len - 1);
 
-    if !interp.has_command(old_name) {
-        return molt_err!("can't rename \"{}\": command doesn't exist", old_name);
-    }
+    let end = if last < first { first } else { last + 1 };
 
-    // NEXT, rename or remove the command.
-    if new_name.is_empty() {
-        interp.remove_command(old_name);
-    } else {
-        interp.rename_command(old_name, new_name);
-    }
+    list.splice(first as usize..end as usize, argv[4..].iter().cloned());
 
-    molt_opt_ok!()
+    molt_opt_ok!(list)
 }
 
-/// # return ?-code code? ?-level level? ?value?
+/// # lreverse *list*
 ///
-/// Returns from a proc with the given *value*, which defaults to the empty result.
-/// See the documentation for **return** in The Molt Book for the option semantics.
+/// Returns the list with its elements in reverse order.
+pub fn cmd_lreverse(_interp: &mut Interp, argv: &[Value]) -> MoltOptResult {
+    check_args(1, argv, 2, 2, "list")?;
+
+    let mut list = argv[1].to_list()?;
+    list.reverse();
+
+    molt_opt_ok!(list)
+}
+
+/// # lsearch ?*options*? *list* *pattern*
+///
+/// Searches the list for elements that match the pattern, returning the index of the
+/// first match, or -1 if there is none.  The options are:
+///
+/// * `-glob`: Match elements using glob-style matching, as for `string match`.  This is
+///   the default.
+/// * `-exact`: Match elements that are equal to the pattern.
+/// * `-regexp`: Match elements using regular expressions, as for `regexp`.
+/// * `-sorted`: The list is sorted; use a binary search.  Implies `-exact`, and is
+///   ignored if `-glob` or `-regexp` is given.
+/// * `-ascii`, `-dictionary`, `-integer`, `-real`: How to compare elements with
+///   `-exact` and `-sorted`, as for `lsort`.  The default is `-ascii`.
+/// * `-increasing`, `-decreasing`: The order of a `-sorted` list.
+/// * `-nocase`: Compare without regard to case.
+/// * `-all`: Return a list of the indices of all matches.
+/// * `-inline`: Return the matching elements rather than their indices.
+/// * `-not`: Find the elements that don't match.
+/// * `-start` *index*: Start searching at the given index.
+/// * `-index` *indexList*: Match the element at the given index of each sublist, as
+///   for `lindex`.
 ///
 /// ## TCL Liens
 ///
-/// * Doesn't support all of TCL's fancy return machinery. Someday it will.
-pub fn cmd_return(_interp: &mut Interp, argv: &[Value]) -> MoltOptResult {
-    check_args(1, argv, 1, 0, "?options...? ?value?")?;
+/// * Does not support `-bisect` or `-subindices`.
+pub fn cmd_lsearch(interp: &mut Interp, argv: &[Value]) -> MoltOptResult {
+    check_args(1, argv, 3, 0, "?-option value ...? list pattern")?;
+
+    // FIRST, get the options.
+    let mut match_mode: Option<MatchMode> = None;
+    let mut sorted = false;
+    let mut all = false;
+    let mut inline = false;
+    let mut not = false;
+    let mut nocase = false;
+    let mut decreasing = false;
+    let mut mode = SortMode::Ascii;
+    let mut indices: MoltList = Vec::new();
+    let mut start: Option<&Value> = None;
 
-    // FIRST, set the defaults
-    let mut code = ResultCode::Okay;
-    let mut level: MoltInt = 1;
-    let mut error_code: Option<Value> = None;
-    let mut error_info: Option<Value> = None;
+    let arglen = argv.len();
+    let mut queue = argv[1..arglen - 2].iter();
 
-    // NEXT, with no arguments just return.
-    if argv.len() == 1 {
-        return Err(Exception::molt_return_ext(
-            None,
-            level as usize,
-            code,
-        ));
+    while let Some(opt) = queue.next() {
+        match opt.as_str() {
+            "-all" => all = true,
+            "-ascii" => mode = SortMode::Ascii,
+            "-decreasing" => decreasing = true,
+            "-dictionary" => mode = SortMode::Dictionary,
+            "-exact" => match_mode = Some(MatchMode::Exact),
+            "-glob" => match_mode = Some(MatchMode::Glob),
+            "-increasing" => decreasing = false,
+            "-index" => match queue.next() {
+                Some(index) => indices = index.to_list()?,
+                None => return molt_err!("\"-index\" option must be followed by list index"),
+            },
+            "-inline" => inline = true,
+            "-integer" => mode = SortMode::Integer,
+            "-nocase" => nocase = true,
+            "-not" => not = true,
+            #[cfg(feature = "float")]
+            "-real" => mode = SortMode::Real,
+            #[cfg(feature = "regex")]
+            "-regexp" => match_mode = Some(MatchMode::Regexp),
+            "-sorted" => sorted = true,
+            "-start" => match queue.next() {
+                Some(index) => start = Some(index),
+                None => return molt_err!("missing starting index"),
+            },
+            _ => return molt_err!(
+                "bad option \"{}\": must be -all, -ascii, -decreasing, -dictionary, -exact, -glob, -increasing, -index, -inline, -integer, -nocase, -not, -real, -regexp, -sorted, or -start",
+                opt
+            ),
+        }
     }
 
-    // NEXT, get the return value: the last argument, if there's an odd number of arguments
-    // after the command name.
-    let mut return_value = None;
+    // NEXT, get the list, pattern, and starting index.
+    let list = argv[arglen - 2].to_list()?;
+    let pattern = &argv[arglen - 1];
 
-    let opt_args: &[Value] = if argv.len() % 2 == 0 {
-        // odd number of args following the command name
-        return_value = Some(argv[argv.len() - 1].clone());
-        &argv[1..argv.len() - 1]
-    } else {
-        // even number of args following the command name
-        &argv[1..argv.len()]
+    let start = match start {
+        Some(index) => index.as_index(list.len())?.max(0) as usize,
+        None => 0,
     };
 
-    // NEXT, Get any options
-    for pair in opt_args.chunks(2) {
-        let val = &pair[1];
-        match pair[0].as_str() {
-            "-code" => {
-                code = ResultCode::from_value(val)?;
-            }
-            "-errorcode" => {
-                error_code = Some(val.clone());
-            }
-            "-errorinfo" => {
-                error_info = Some(val.clone());
+    // NEXT, a sorted search is exact unless another mode was given, in which case the
+    // list is searched linearly in that mode.
+    let default_mode = if sorted { MatchMode::Exact } else { MatchMode::Glob };
+    let match_mode = match_mode.unwrap_or(default_mode);
+    let sorted = sorted && match_mode == MatchMode::Exact;
+
+    if match_mode == MatchMode::Exact {
+        match mode {
+            SortMode::Integer => {
+                pattern.as_int()?;
             }
-            "-level" => {
-                // TODO: return better error:
-                // bad -level value: expected non-negative integer but got "{}"
-                level = val.as_int()?;
+            #[cfg(feature = "float")]
+            SortMode::Real => {
+                pattern.as_float()?;
             }
-            // TODO: In standard TCL there are no invalid options; all options are retained.
-            _ => return molt_err!("invalid return option: \"{}\"", pair[0]),
+            _ => (),
         }
     }
 
-    // NEXT, return the result: normally a Return exception, but could be "Ok".
-    if code == ResultCode::Error {
-        Err(Exception::molt_return_err(
+    // NEXT, find the matches.  Use a binary search if we can; otherwise, a linear one.
+    let mut matches: Vec<usize> = Vec::new();
+
+    if sorted && !all && !not {
+        let mut lo = start;
+        let mut hi = list.len().max(start);
+
+        // Find the first element not less than the pattern.
+        while lo < hi {
+            let mid = lo + (hi - lo) / 2;
+            let key = lsort_key(&list[mid..=mid], &indices)?;
+            let order = lsort_compare(interp, &mode, nocase, &key, pattern)?;
+            let order = if decreasing { order.reverse() } else { order };
+
+            if order == Ordering::Less {
+                lo = mid + 1;
+            } else {
+                hi = mid;
+            }
+        }
+
+        if lo < list.len() {
+            let key = lsort_key(&list[lo..=lo], &indices)?;
+
+            if lsort_compare(interp, &mode, nocase, &key, pattern)? == Ordering::Equal {
+                matches.push(lo);
+            }
+        }
+    } else {
+        #[cfg(feature = "regex")]
+        let regex = match match_mode {
+            MatchMode::Regexp => Some(Regex::new(pattern.as_str(), nocase)?),
+            _ => None,
+        };
+
+        for i in start..list.len() {
+            let key = lsort_key(&list[i..=i], &indices)?;
+
+            let matched = match match_mode {
+                MatchMode::Exact => {
+                    lsort_compare(interp, &mode, nocase, &key, pattern)? == Ordering::Equal
+                }
+                MatchMode::Glob => util::glob_match(pattern.as_str(), key.as_str(), nocase),
+                #[cfg(feature = "regex")]
+                MatchMode::Regexp => regex.as_ref().unwrap().is_match(key.as_str()),
+            };
+
+            if matched != not {
+                matches.push(i);
+
+                if !all {
+                    break;
+                }
+            }
+        }
+    }
+
+    // NEXT, return the result.
+    if inline {
+        let elements: MoltList = matches.iter().map(|i| list[*i].clone()).collect();
+
+        if all {
+            molt_opt_ok!(elements)
+        } else {
+            molt_opt_ok!(elements.into_iter().next().unwrap_or_default())
+        }
+    } else {
+        let indices: MoltList = matches.iter().map(|i| Value::from(*i as MoltInt)).collect();
+
+        if all {
+            molt_opt_ok!(indices)
+        } else {
+            molt_opt_ok!(indices.into_iter().next().unwrap_or_else(|| Value::from(-1)))
+        }
+    }
+}
+
+/// # lset *varName* ?*index* ...? *newValue*
+///
+/// Sets an element of the list in the variable, indexing into nested lists, and returns
+/// the new list.  An index of one past the end of a list appends the new value to it.
+/// If there are no indices, sets the variable to *newValue*.
+pub fn cmd_lset(interp: &mut Interp, argv: &[Value]) -> MoltOptResult {
+    check_args(1, argv, 3, 0, "listVar ?index? ?index ...? value")?;
+
+    let new_value = argv[argv.len() - 1].clone();
+
+    let new_list = if argv.len() == 3 {
+        new_value
+    } else {
+        let list = interp.var(&argv[1])?;
+
+        if argv.len() == 4 {
+            lset_into(&list, &argv[2].to_list()?, new_value)?
+        } else {
+            lset_into(&list, &argv[2..argv.len() - 1], new_value)?
+        }
+    };
+
+    interp.set_var_return(&argv[1], new_list).map(Some)
+}
+
+/// Returns a copy of the list with the element at the nested index path replaced
+/// by the value.
+pub fn lset_into(list: &Value, indices: &[Value], value: Value) -> MoltResult {
+    if indices.is_empty() {
+        return Ok(value);
+    }
+
+    let mut list = list.to_list()?;
+    let index = indices[0].as_index(list.len())?;
+
+    if index < 0 || index as usize > list.len() {
+        return molt_err!("list index out of range");
+    }
+
+    let index = index as usize;
+
+    if index == list.len() {
+        if indices.len() > 1 {
+            return molt_err!("list index out of range");
+        }
+        list.push(value);
+    } else {
+        list[index] = lset_into(&list[index], &indices[1..], value)?;
+    }
+
+    molt_ok!(list)
+}
+
+/// # lsort ?*options*? *list*
+///
+/// Sorts the elements of the list and returns the sorted list.  The sort is stable:
+/// elements that compare equal retain their relative order.  The options are:
+///
+/// * `-ascii`: Compare elements as strings, by Unicode code point.  This is the default.
+/// * `-dictionary`: Compare elements as strings, ignoring case except as a tie-breaker,
+///   and comparing embedded runs of digits as integers.
+/// * `-integer`: Compare elements as integers.
+/// * `-real`: Compare elements as floating-point numbers.
+/// * `-command` *command*: Compare elements by calling *command* with the two elements
+///   as additional arguments.  It must return an integer less than, equal to, or
+///   greater than zero.
+/// * `-increasing`: Sort in increasing order.  This is the default.
+/// * `-decreasing`: Sort in decreasing order.
+/// * `-nocase`: With `-ascii`, compare without regard to case.
+/// * `-unique`: Retain only the last of each set of elements that compare equal.
+/// * `-index` *indexList*: Compare the element at the given index of each sublist,
+///   as for `lindex`.
+/// * `-stride` *length*: Treat the list as groups of *length* elements, sorting the
+///   groups by their first element (or by the `-index` element of each group).
+pub fn cmd_lsort(interp: &mut Interp, argv: &[Value]) -> MoltOptResult {
+    check_args(1, argv, 2, 0, "?-option value ...? list")?;
+
+    // FIRST, get the options.
+    let mut mode = SortMode::Ascii;
+    let mut nocase = false;
+    let mut decreasing = false;
+    let mut unique = false;
+    let mut indices: MoltList = Vec::new();
+    let mut stride: usize = 1;
+
+    let mut queue = argv[1..argv.len() - 1].iter();
+
+    while let Some(opt) = queue.next() {
+        match opt.as_str() {
+            "-ascii" => mode = SortMode::Ascii,
+            "-dictionary" => mode = SortMode::Dictionary,
+            "-integer" => mode = SortMode::Integer,
+            #[cfg(feature = "float")]
+            "-real" => mode = SortMode::Real,
+            "-command" => match queue.next() {
+                Some(command) => mode = SortMode::Command(command.to_list()?),
+                None => return molt_err!("\"-command\" option must be followed by comparison command"),
+            },
+            "-increasing" => decreasing = false,
+            "-decreasing" => decreasing = true,
+            "-nocase" => nocase = true,
+            "-unique" => unique = true,
+            "-index" => match queue.next() {
+                Some(index) => indices = index.to_list()?,
+                None => return molt_err!("\"-index\" option must be followed by list index"),
+            },
+            "-stride" => match queue.next() {
+                Some(len) => {
+                    let len = len.as_int()?;
+                    if len < 2 {
+                        return molt_err!("stride length must be at least 2");
+                    }
+                    stride = len as usize;
+                }
+                None => return molt_err!("\"-stride\" option must be followed by stride length"),
+            },
+            _ => return molt_err!(
+                "bad option \"{}\": must be -ascii, -command, -decreasing, -dictionary, -increasing, -index, -integer, -nocase, -real, -stride, or -unique",
+                opt
+            ),
+        }
+    }
+
+    // NEXT, divide the list into groups, and get each group's sort key.
+    let list = argv[argv.len() - 1].to_list()?;
+
+    if !list.len().is_multiple_of(stride) {
+        return molt_err!("list size must be a multiple of the stride length");
+    }
+
+    let groups: Vec<&[Value]> = list.chunks(stride).collect();
+    let mut keys: MoltList = Vec::with_capacity(groups.len());
+
+    for group in &groups {
+        keys.push(lsort_key(group, &indices)?);
+    }
+
+    // NEXT, validate numeric keys up front, so that errors are reported in list order.
+    // This also caches each key's numeric rep for the comparisons.
+    match mode {
+        SortMode::Integer => {
+            for key in &keys {
+                key.as_int()?;
+            }
+        }
+        #[cfg(feature = "float")]
+        SortMode::Real => {
+            for key in &keys {
+                key.as_float()?;
+            }
+        }
+        _ => (),
+    }
+
+    // NEXT, sort the groups, stopping at the first error.
+    let mut order: Vec<usize> = (0..groups.len()).collect();
+
+    merge_sort(&mut order, |a, b| {
+        let order = lsort_compare(interp, &mode, nocase, &keys[a], &keys[b])?;
+        Ok(if decreasing { order.reverse() } else { order })
+    })?;
+
+    // NEXT, remove duplicates, retaining the last of each run of equal keys.
+    if unique {
+        let mut unique_order: Vec<usize> = Vec::with_capacity(order.len());
+
+        for i in order {
+            match unique_order.last_mut() {
+                Some(last) if lsort_compare(interp, &mode, nocase, &keys[*last], &keys[i])? == Ordering::Equal => {
+                    *last = i;
+                }
+                _ => unique_order.push(i),
+            }
+        }
+
+        order = unique_order;
+    }
+
+    // NEXT, build the sorted list.
+    let mut result: MoltList = Vec::with_capacity(list.len());
+
+    for i in order {
+        result.extend_from_slice(groups[i]);
+    }
+
+    molt_opt_ok!(result)
+}
+
+/// Sorts the items with a stable merge sort, stopping at the first error.  Unlike
+/// `slice::sort_by`, this can't panic if the comparison isn't a total order, as may be the
+/// case for an `lsort -command`.
+fn merge_sort<T, F>(items: &mut Vec<T>, mut compare: F) -> Result<(), Exception>
+where
+    T: Copy,
+    F: FnMut(T, T) -> Result<Ordering, Exception>,
+{
+    let len = items.len();
+    let mut merged: Vec<T> = Vec::with_capacity(len);
+    let mut width = 1;
+
+    while width < len {
+        merged.clear();
+
+        // Merge each pair of adjacent sorted runs of the given width.
+        let mut start = 0;
+
+        while start < len {
+            let mid = (start + width).min(len);
+            let end = (start + 2 * width).min(len);
+            let (mut i, mut j) = (start, mid);
+
+            while i < mid && j < end {
+                if compare(items[i], items[j])? == Ordering::Greater {
+                    merged.push(items[j]);
+                    j += 1;
+                } else {
+                    merged.push(items[i]);
+                    i += 1;
+                }
+            }
+
+            merged.extend_from_slice(&items[i..mid]);
+            merged.extend_from_slice(&items[j..end]);
+            start = end;
+        }
+
+        core::mem::swap(items, &mut merged);
+        width *= 2;
+    }
+
+    Ok(())
+}
+
+/// How `lsort` compares elements.
+enum SortMode {
+    Ascii,
+    Dictionary,
+    Integer,
+    #[cfg(feature = "float")]
+    Real,
+    Command(MoltList),
+}
+
+/// Gets the `lsort` key for a group of elements, given the `-index` list: the first
+/// index selects the element within the group, if the group has more than one element.
+fn lsort_key(group: &[Value], indices: &[Value]) -> MoltResult {
+    let (mut key, indices) = if group.len() > 1 && !indices.is_empty() {
+        let index = indices[0].as_index(group.len())?;
+
+        match usize::try_from(index).ok().and_then(|i| group.get(i)) {
+            Some(elem) => (elem.clone(), &indices[1..]),
+            None => {
+                return molt_err!(
+                    "when used with \"-stride\", the leading \"-index\" value must be within the group"
+                )
+            }
+        }
+    } else {
+        (group[0].clone(), indices)
+    };
+
+    for index_val in indices {
+        let len = key.as_list()?.len();
+        let index = index_val.as_index(len)?;
+
+        if index < 0 || index as usize >= len {
+            return molt_err!("element {} missing from sublist \"{}\"", index_val, key);
+        }
+
+        let next_key = key.as_list()?[index as usize].clone();
+        key = next_key;
+    }
+
+    Ok(key)
+}
+
+/// Compares two `lsort` keys.
+fn lsort_compare(
+    interp: &mut Interp,
+    mode: &SortMode,
+    nocase: bool,
+    key1: &Value,
+    key2: &Value,
+) -> Result<Ordering, Exception> {
+    match mode {
+        SortMode::Ascii if nocase => {
+            Ok(util::to_lowercase(key1.as_str()).cmp(&util::to_lowercase(key2.as_str())))
+        }
+        SortMode::Ascii => Ok(key1.as_str().cmp(key2.as_str())),
+        SortMode::Dictionary => Ok(util::dictionary_compare(key1.as_str(), key2.as_str())),
+        SortMode::Integer => Ok(key1.as_int()?.cmp(&key2.as_int()?)),
+        #[cfg(feature = "float")]
+        SortMode::Real => Ok(key1
+            .as_float()?
+            .partial_cmp(&key2.as_float()?)
+            .unwrap_or(Ordering::Equal)),
+        SortMode::Command(command) => {
+            let mut words = command.clone();
+            words.push(key1.clone());
+            words.push(key2.clone());
+
+            match interp.call(&words)?.as_int() {
+                Ok(int) => Ok(int.cmp(&0)),
+                Err(_) => molt_err!("-compare command returned non-integer result"),
+            }
+        }
+    }
+}
+
+/// # namespace *subcommand* ?*arg*...?
+#[cfg(feature = "namespaces")]
+pub(crate) fn cmd_namespace(interp: &mut Interp, argv: &[Value]) -> Result<Step, Exception> {
+    call_control_subcommand(interp, argv, &NAMESPACE_SUBCOMMANDS)
+}
+
+#[cfg(feature = "namespaces")]
+static NAMESPACE_SUBCOMMANDS: [(&str, SubcommandFunc); 9] = [
+    ("children", SubcommandFunc::Native(cmd_namespace_children)),
+    ("current", SubcommandFunc::Native(cmd_namespace_current)),
+    ("delete", SubcommandFunc::Native(cmd_namespace_delete)),
+    ("eval", SubcommandFunc::Control(cmd_namespace_eval)),
+    ("exists", SubcommandFunc::Native(cmd_namespace_exists)),
+    ("export", SubcommandFunc::Native(cmd_namespace_export)),
+    ("import", SubcommandFunc::Native(cmd_namespace_import)),
+    ("qualifiers", SubcommandFunc::Native(cmd_namespace_qualifiers)),
+    ("tail", SubcommandFunc::Native(cmd_namespace_tail)),
+];
+
+/// # namespace children ?*namespace*? ?*pattern*?
+///
+/// Returns the fully qualified names of the children of the namespace, which defaults to
+/// the current namespace.  A *pattern* that isn't absolute is taken relative to the
+/// namespace.
+#[cfg(feature = "namespaces")]
+fn cmd_namespace_children(interp: &mut Interp, argv: &[Value]) -> MoltOptResult {
+    check_args(2, argv, 2, 4, "?name? ?pattern?")?;
+
+    let parent = match argv.get(2) {
+        Some(name) => interp.namespace_key(name.as_str()).into_owned(),
+        None => interp.current_namespace().into(),
+    };
+
+    if !interp.namespace_exists(&parent) {
+        return molt_err!("namespace \"{}\" not found in \"{}\"",
+            argv[2], namespace::display(interp.current_namespace()));
+    }
+
+    let children = interp
+        .namespace_children(&parent)
+        .iter()
+        .map(|key| Value::from(namespace::display(key)))
+        .collect();
+    let pattern = argv
+        .get(3)
+        .map(|p| Value::from(namespace::display(&namespace::qualify(&parent, p.as_str()))));
+
+    molt_opt_ok!(Value::from(glob_filter(children, pattern.as_ref())))
+}
+
+/// # namespace current
+///
+/// Returns the fully qualified name of the current namespace.
+#[cfg(feature = "namespaces")]
+fn cmd_namespace_current(interp: &mut Interp, argv: &[Value]) -> MoltOptResult {
+    check_args(2, argv, 2, 2, "")?;
+    molt_opt_ok!(namespace::display(interp.current_namespace()))
+}
+
+/// # namespace delete ?*namespace* ...?
+///
+/// Deletes each namespace, along with its children, commands, and variables.
+///
+/// ## TCL Liens
+///
+/// * The global namespace can't be deleted.
+/// * A namespace is deleted immediately, even if a command within it is still executing.
+#[cfg(feature = "namespaces")]
+fn cmd_namespace_delete(interp: &mut Interp, argv: &[Value]) -> MoltOptResult {
+    check_args(2, argv, 2, 0, "?name name...?")?;
+
+    // FIRST, make sure they all exist, so that nothing is deleted on error.
+    for name in &argv[2..] {
+        let key = interp.namespace_key(name.as_str());
+
+        if key.is_empty() {
+            return molt_err!("can't delete the global namespace");
+        } else if !interp.namespace_exists(&key) {
+            return molt_err!("unknown namespace \"{}\" in namespace delete command", name);
+        }
+    }
+
+    // NEXT, delete them.
+    for name in &argv[2..] {
+        let key = interp.namespace_key(name.as_str()).into_owned();
+        interp.delete_namespace(&key);
+    }
+
+    molt_opt_ok!()
+}
+
+/// # namespace eval *namespace* *arg* ?*arg* ...?
+///
+/// Concatenates the arguments as for `concat` and evaluates the result as a script in the
+/// namespace, creating the namespace if it doesn't already exist.  The script runs in a
+/// new stack level, in which unqualified variable names refer to the namespace's variables.
+#[cfg(feature = "namespaces")]
+fn cmd_namespace_eval(interp: &mut Interp, argv: &[Value]) -> Result<Step, Exception> {
+    check_args(2, argv, 4, 0, "name arg ?arg...?")?;
+
+    let key = interp.namespace_key(argv[2].as_str()).into_owned();
+
+    if argv.len() == 4 {
+        Ok(interp.push_namespace_eval(&key, argv, &argv[3]))
+    } else {
+        let script = Value::from(list::concat_values(&argv[3..]));
+        Ok(interp.push_namespace_eval(&key, argv, &script))
+    }
+}
+
+/// # namespace exists *namespace*
+///
+/// Returns 1 if the namespace exists, and 0 otherwise.
+#[cfg(feature = "namespaces")]
+fn cmd_namespace_exists(interp: &mut Interp, argv: &[Value]) -> MoltOptResult {
+    check_args(2, argv, 3, 3, "name")?;
+    let key = interp.namespace_key(argv[2].as_str());
+    molt_opt_ok!(interp.namespace_exists(&key))
+}
+
+/// # namespace export ?-clear? ?*pattern* ...?
+///
+/// Adds the glob patterns to the current namespace's list of exported commands, first
+/// clearing the list if `-clear` is given.  With no arguments, returns the list.
+#[cfg(feature = "namespaces")]
+fn cmd_namespace_export(interp: &mut Interp, argv: &[Value]) -> MoltOptResult {
+    check_args(2, argv, 2, 0, "?-clear? ?pattern pattern...?")?;
+
+    let mut patterns = &argv[2..];
+
+    if patterns.is_empty() {
+        let exports: MoltList = interp.namespace_exports()?.iter().map(Value::from).collect();
+        return molt_opt_ok!(Value::from(exports));
+    }
+
+    if patterns[0].as_str() == "-clear" {
+        interp.namespace_exports()?.clear();
+        patterns = &patterns[1..];
+    }
+
+    for pattern in patterns {
+        if namespace::is_qualified(pattern.as_str()) {
+            return molt_err!("invalid export pattern \"{}\": pattern can't specify a namespace", pattern);
+        }
+    }
+
+    let exports = interp.namespace_exports()?;
+
+    for pattern in patterns {
+        if !exports.iter().any(|p| p == pattern.as_str()) {
+            exports.push(pattern.to_string());
+        }
+    }
+
+    molt_opt_ok!()
+}
+
+/// # namespace import ?-force? ?*pattern* ...?
+///
+/// Imports the commands matching each qualified glob pattern that are exported by their
+/// namespace into the current namespace.  It's an error to replace an existing command
+/// unless `-force` is given.
+///
+/// ## TCL Liens
+///
+/// * An imported command is a copy of the original, not a link to it: renaming or
+///   deleting the original doesn't affect it.
+/// * With no patterns, returns the empty list rather than the imported commands.
+#[cfg(feature = "namespaces")]
+fn cmd_namespace_import(interp: &mut Interp, argv: &[Value]) -> MoltOptResult {
+    check_args(2, argv, 2, 0, "?-force? ?pattern pattern...?")?;
+
+    let mut patterns = &argv[2..];
+    let force = patterns.first().is_some_and(|p| p.as_str() == "-force");
+
+    if force {
+        patterns = &patterns[1..];
+    }
+
+    for pattern in patterns {
+        interp.namespace_import(pattern.as_str(), force)?;
+    }
+
+    molt_opt_ok!()
+}
+
+/// # namespace qualifiers *string*
+///
+/// Returns the leading namespace qualifiers of the string, if any.
+#[cfg(feature = "namespaces")]
+fn cmd_namespace_qualifiers(_interp: &mut Interp, argv: &[Value]) -> MoltOptResult {
+    check_args(2, argv, 3, 3, "string")?;
+    molt_opt_ok!(namespace::qualifiers(argv[2].as_str()).to_string())
+}
+
+/// # namespace tail *string*
+///
+/// Returns the simple name at the end of the qualified string.
+#[cfg(feature = "namespaces")]
+fn cmd_namespace_tail(_interp: &mut Interp, argv: &[Value]) -> MoltOptResult {
+    check_args(2, argv, 3, 3, "string")?;
+    molt_opt_ok!(namespace::tail(argv[2].as_str()).to_string())
+}
+
+/// # pdump
+///
+/// Dumps profile data.  Developer use only.
+#[cfg(feature = "std")]
+pub fn cmd_pdump(interp: &mut Interp, argv: &[Value]) -> MoltOptResult {
+    check_args(1, argv, 1, 1, "")?;
+
+    interp.profile_dump();
+
+    molt_opt_ok!()
+}
+
+/// # pclear
+///
+/// Clears profile data.  Developer use only.
+#[cfg(feature = "std")]
+pub fn cmd_pclear(interp: &mut Interp, argv: &[Value]) -> MoltOptResult {
+    check_args(1, argv, 1, 1, "")?;
+
+    interp.profile_clear();
+
+    molt_opt_ok!()
+}
+
+/// # proc *name* *args* *body*
+///
+/// Defines a procedure.
+pub fn cmd_proc(interp: &mut Interp, argv: &[Value]) -> MoltOptResult {
+    check_args(1, argv, 4, 4, "name args body")?;
+
+    // FIRST, get the arguments
+    let name = argv[1].as_str();
+    let args = argv[2].to_list()?;
+
+    // NEXT, add the command, validating the argument specs.
+    interp.add_proc(name, &args, &argv[3])?;
+
+    molt_opt_ok!()
+}
+
+/// # puts *string*
+///
+/// Outputs the string to stdout.
+///
+/// ## TCL Liens
+///
+/// * Does not support `-nonewline`
+/// * Does not support `channelId`
+#[cfg(feature = "std")]
+pub fn cmd_puts(_interp: &mut Interp, argv: &[Value]) -> MoltOptResult {
+    check_args(1, argv, 2, 2, "string")?;
+
+    println!("{}", argv[1]);
+    molt_opt_ok!()
+}
+
+/// # regexp ?*switches*? *exp* *string* ?*matchVar*? ?*subMatchVar* ...?
+///
+/// Matches the regular expression *exp* against the *string*, returning 1 if it matches
+/// and 0 if it doesn't.  On a match, assigns the matched text to *matchVar* and the text
+/// matched by each parenthesized subexpression to the *subMatchVar*s, in order.  The
+/// switches are:
+///
+/// * `-all`: Find all non-overlapping matches, and return the number found.  The
+///   variables receive the last match.
+/// * `-indices`: Assign the start and end indices of each match rather than the text.
+/// * `-inline`: Return the match and submatches as a list rather than assigning them to
+///   variables.  With `-all`, returns them for every match.
+/// * `-nocase`: Match without regard to case.
+/// * `-start` *index*: Begin matching at the given character index.
+/// * `--`: Marks the end of the switches.
+///
+/// ## TCL Liens
+///
+/// * Supports a subset of TCL's regular expression syntax; backreferences, embedded
+///   options, and collating elements are not supported.
+/// * Patterns are limited in size, and groups may nest at most 32 deep; larger patterns
+///   are "too complex".
+/// * When several matches begin at the same place, prefers the one found by trying
+///   alternatives from left to right, rather than the longest.
+/// * Does not support `-about`, `-expanded`, `-line`, `-linestop`, or `-lineanchor`.
+#[cfg(feature = "regex")]
+pub fn cmd_regexp(interp: &mut Interp, argv: &[Value]) -> MoltOptResult {
+    check_args(1, argv, 3, 0, "?-switch ...? exp string ?matchVar? ?subMatchVar ...?")?;
+
+    // FIRST, get the switches.
+    let mut all = false;
+    let mut indices = false;
+    let mut inline = false;
+    let mut nocase = false;
+    let mut start: Option<&Value> = None;
+    let mut i = 1;
+
+    while i < argv.len() {
+        match argv[i].as_str() {
+            "-all" => all = true,
+            "-indices" => indices = true,
+            "-inline" => inline = true,
+            "-nocase" => nocase = true,
+            "-start" => {
+                i += 1;
+                match argv.get(i) {
+                    Some(index) => start = Some(index),
+                    None => return molt_err!("missing starting index"),
+                }
+            }
+            "--" => {
+                i += 1;
+                break;
+            }
+            opt if opt.starts_with('-') => {
+                return molt_err!(
+                    "bad switch \"{}\": must be -all, -indices, -inline, -nocase, -start, or --",
+                    opt
+                );
+            }
+            _ => break,
+        }
+        i += 1;
+    }
+
+    if argv.len() < i + 2 {
+        return molt_err!(
+            "wrong # args: should be \"{} ?-switch ...? exp string ?matchVar? ?subMatchVar ...?\"",
+            argv[0]
+        );
+    }
+
+    let var_names = &argv[i + 2..];
+
+    if inline && !var_names.is_empty() {
+        return molt_err!("regexp match variables not allowed when using -inline");
+    }
+
+    // NEXT, compile the expression and get the text to match.
+    let regex = Regex::new(argv[i].as_str(), nocase)?;
+    let text: Vec<char> = argv[i + 1].as_str().chars().collect();
+    let mut pos = regexp_start(start, text.len())?;
+
+    // NEXT, find the matches.
+    let mut count: MoltInt = 0;
+    let mut result: MoltList = Vec::new();
+    let mut last_match = None;
+
+    while let Some(captures) = regex.find_at(&text, pos) {
+        count += 1;
+
+        if inline {
+            result.extend(captures.iter().map(|cap| regexp_capture(&text, *cap, indices)));
+        }
+
+        // An empty match must not be found again.
+        let (first, end) = captures[0].unwrap();
+        pos = if end == first { end + 1 } else { end };
+        last_match = Some(captures);
+
+        if !all || pos > text.len() {
+            break;
+        }
+    }
+
+    // NEXT, return the matches, or assign them to the variables.
+    if inline {
+        return molt_opt_ok!(result);
+    }
+
+    if let Some(captures) = last_match {
+        for (j, var_name) in var_names.iter().enumerate() {
+            let cap = captures.get(j).copied().flatten();
+            interp.set_var(var_name, regexp_capture(&text, cap, indices))?;
+        }
+    }
+
+    molt_opt_ok!(count)
+}
+
+/// # regsub ?*switches*? *exp* *string* *subSpec* ?*varName*?
+///
+/// Matches the regular expression *exp* against the *string*, and replaces the match
+/// with *subSpec*, returning the result.  In *subSpec*, `&` and `\0` stand for the
+/// matched text, and `\1` through `\9` for the text matched by the corresponding
+/// parenthesized subexpression; `\&` and `\\` stand for `&` and `\`.  If *varName* is
+/// given, assigns the result to it and returns the number of matches.  The switches are:
+///
+/// * `-all`: Replace all non-overlapping matches, not just the first.
+/// * `-nocase`: Match without regard to case.
+/// * `-start` *index*: Begin matching at the given character index.
+/// * `--`: Marks the end of the switches.
+///
+/// ## TCL Liens
+///
+/// * Has the same regular expression liens as `regexp`.
+/// * Does not support `-expanded`, `-line`, `-linestop`, `-lineanchor`, or `-command`.
+#[cfg(feature = "regex")]
+pub fn cmd_regsub(interp: &mut Interp, argv: &[Value]) -> MoltOptResult {
+    check_args(1, argv, 4, 0, "?-switch ...? exp string subSpec ?varName?")?;
+
+    // FIRST, get the switches.
+    let mut all = false;
+    let mut nocase = false;
+    let mut start: Option<&Value> = None;
+    let mut i = 1;
+
+    while i < argv.len() {
+        match argv[i].as_str() {
+            "-all" => all = true,
+            "-nocase" => nocase = true,
+            "-start" => {
+                i += 1;
+                match argv.get(i) {
+                    Some(index) => start = Some(index),
+                    None => return molt_err!("missing starting index"),
+                }
+            }
+            "--" => {
+                i += 1;
+                break;
+            }
+            opt if opt.starts_with('-') => {
+                return molt_err!("bad switch \"{}\": must be -all, -nocase, -start, or --", opt);
+            }
+            _ => break,
+        }
+        i += 1;
+    }
+
+    if argv.len() < i + 3 || argv.len() > i + 4 {
+        return molt_err!(
+            "wrong # args: should be \"{} ?-switch ...? exp string subSpec ?varName?\"",
+            argv[0]
+        );
+    }
+
+    // NEXT, compile the expression and get the text to match.
+    let regex = Regex::new(argv[i].as_str(), nocase)?;
+    let text: Vec<char> = argv[i + 1].as_str().chars().collect();
+    let sub_spec = argv[i + 2].as_str();
+    let mut pos = regexp_start(start, text.len())?;
+
+    // NEXT, build the result, copying the text between the matches.
+    let mut result = String::new();
+    let mut copied = 0;
+    let mut count: MoltInt = 0;
+
+    while let Some(captures) = regex.find_at(&text, pos) {
+        count += 1;
+
+        let (first, end) = captures[0].unwrap();
+        result.extend(&text[copied..first]);
+        regsub_expand(&mut result, sub_spec, &text, &captures);
+        copied = end;
+
+        // An empty match must not be found again.
+        if end == first {
+            if end < text.len() {
+                result.push(text[end]);
+            }
+            copied = end + 1;
+        }
+        pos = copied;
+
+        if !all || pos > text.len() {
+            break;
+        }
+    }
+
+    if copied < text.len() {
+        result.extend(&text[copied..]);
+    }
+
+    // NEXT, return the result, or assign it to the variable.
+    match argv.get(i + 3) {
+        Some(var_name) => {
+            interp.set_var(var_name, Value::from(result))?;
+            molt_opt_ok!(count)
+        }
+        None => molt_opt_ok!(result),
+    }
+}
+
+/// Gets the character index at which `regexp` or `regsub` should begin matching.
+#[cfg(feature = "regex")]
+fn regexp_start(start: Option<&Value>, len: usize) -> Result<usize, Exception> {
+    match start {
+        Some(index) => Ok(index.as_index(len)?.clamp(0, len as MoltInt) as usize),
+        None => Ok(0),
+    }
+}
+
+/// Returns the text matched by a capture group, or its indices, as for `regexp`.
+#[cfg(feature = "regex")]
+fn regexp_capture(text: &[char], cap: Option<(usize, usize)>, indices: bool) -> Value {
+    match (cap, indices) {
+        (Some((first, end)), true) => Value::from(vec![
+            Value::from(first as MoltInt),
+            Value::from(end as MoltInt - 1),
+        ]),
+        (None, true) => Value::from(vec![Value::from(-1), Value::from(-1)]),
+        (Some((first, end)), false) => Value::from(text[first..end].iter().collect::<String>()),
+        (None, false) => Value::empty(),
+    }
+}
+
+/// Appends the substitution for a match to the result, as for `regsub`.
+#[cfg(feature = "regex")]
+fn regsub_expand(result: &mut String, sub_spec: &str, text: &[char], captures: &Captures) {
+    let mut chars = sub_spec.chars();
+
+    while let Some(ch) = chars.next() {
+        let group = match ch {
+            '&' => 0,
+            '\\' => match chars.clone().next() {
+                Some(digit @ '0'..='9') => {
+                    chars.next();
+                    digit as usize - '0' as usize
+                }
+                Some(escaped @ ('&' | '\\')) => {
+                    chars.next();
+                    result.push(escaped);
+                    continue;
+                }
+                _ => {
+                    result.push('\\');
+                    continue;
+                }
+            },
+            _ => {
+                result.push(ch);
+                continue;
+            }
+        };
+
+        if let Some(Some((first, end))) = captures.get(group) {
+            result.extend(&text[*first..*end]);
+        }
+    }
+}
+
+/// # rename *oldName* *newName*
+///
+/// Renames the command called *oldName* to have the *newName*.  If the
+/// *newName* is "", the command is destroyed.
+pub fn cmd_rename(interp: &mut Interp, argv: &[Value]) -> MoltOptResult {
+    check_args(1, argv, 3, 3, "oldName newName")?;
+
+    // FIRST, get the arguments
+    let old_name = argv[1].as_str();
+    let new_name = argv[2].as_str();
+
+    if !interp.has_command(old_name) {
+        return molt_err!("can't rename \"{}\": command doesn't exist", old_name);
+    }
+
+    #[cfg(feature = "namespaces")]
+    if !new_name.is_empty() {
+        let key = interp.namespace_key(new_name);
+
+        if !interp.namespace_exists(namespace::qualifiers(&key)) {
+            return molt_err!("can't rename to \"{}\": unknown namespace", new_name);
+        }
+    }
+
+    // NEXT, rename or remove the command.
+    if new_name.is_empty() {
+        interp.remove_command(old_name);
+    } else {
+        interp.rename_command(old_name, new_name);
+    }
+
+    molt_opt_ok!()
+}
+
+/// # return ?-code code? ?-level level? ?value?
+///
+/// Returns from a proc with the given *value*, which defaults to the empty result.
+/// See the documentation for **return** in The Molt Book for the option semantics.
+///
+/// ## TCL Liens
+///
+/// * Doesn't support all of TCL's fancy return machinery. Someday it will.
+pub fn cmd_return(_interp: &mut Interp, argv: &[Value]) -> MoltOptResult {
+    check_args(1, argv, 1, 0, "?options...? ?value?")?;
+
+    // FIRST, set the defaults
+    let mut code = ResultCode::Okay;
+    let mut level: MoltInt = 1;
+    let mut error_code: Option<Value> = None;
+    let mut error_info: Option<Value> = None;
+
+    // NEXT, with no arguments just return.
+    if argv.len() == 1 {
+        return Err(Exception::molt_return_ext(
+            None,
+            level as usize,
+            code,
+        ));
+    }
+
+    // NEXT, get the return value: the last argument, if there's an odd number of arguments
+    // after the command name.
+    let mut return_value = None;
+
+    let opt_args: &[Value] = if argv.len().is_multiple_of(2) {
+        // odd number of args following the command name
+        return_value = Some(argv[argv.len() - 1].clone());
+        &argv[1..argv.len() - 1]
+    } else {
+        // even number of args following the command name
+        &argv[1..argv.len()]
+    };
+
+    // NEXT, Get any options
+    for pair in opt_args.chunks(2) {
+        let val = &pair[1];
+        match pair[0].as_str() {
+            "-code" => {
+                code = ResultCode::from_value(val)?;
+            }
+            "-errorcode" => {
+                error_code = Some(val.clone());
+            }
+            "-errorinfo" => {
+                error_info = Some(val.clone());
+            }
+            "-level" => {
+                // TODO: return better error:
+                // bad -level value: expected non-negative integer but got "{}"
+                level = val.as_int()?;
+            }
+            // TODO: In standard TCL there are no invalid options; all options are retained.
+            _ => return molt_err!("invalid return option: \"{}\"", pair[0]),
+        }
+    }
+
+    // NEXT, return the result: normally a Return exception, but could be "Ok".
+    if code == ResultCode::Error {
+        Err(Exception::molt_return_err(
             return_value,
             level as usize,
             error_code,
@@ -999,6 +3080,54 @@ pub fn cmd_return(_interp: &mut Interp, argv: &[Value]) -> MoltOptResult {
     }
 }
 
+/// # scan *string* *format* ?*varName* ...?
+///
+/// Parses the string according to the format, in the manner of C's `sscanf`.  With
+/// variable names, assigns the converted values to the variables and returns the number
+/// of conversions performed, or -1 if the end of the string was reached before the first
+/// conversion.  Without them, returns a list of the converted values, with an empty
+/// string for each conversion that wasn't performed.
+///
+/// Supports the `%d`, `%u`, `%i`, `%x`, `%X`, `%o`, `%b`, `%c`, `%s`, `%f`, `%e`,
+/// `%g`, `%[...]`, `%n`, and `%%` conversions, field widths, `*` to suppress
+/// assignment, and XPG `%n$` positions.  `%i` reads an integer whose prefix gives its
+/// radix: `0x` for hexadecimal, `0b` for binary, a leading `0` for octal, and decimal
+/// otherwise.
+///
+/// ## TCL Liens
+///
+/// * The floating-point conversions are only available with the `float` feature.
+#[cfg(feature = "scan-command")]
+pub fn cmd_scan(interp: &mut Interp, argv: &[Value]) -> MoltOptResult {
+    check_args(1, argv, 3, 0, "string format ?varName ...?")?;
+
+    let var_names = &argv[3..];
+    let result = scan::scan(argv[1].as_str(), argv[2].as_str(), var_names.len())?;
+
+    // FIRST, in inline mode return the values.
+    if var_names.is_empty() {
+        if result.count == -1 {
+            return molt_opt_ok!("");
+        }
+
+        let list: MoltList = result.values.into_iter().map(Option::unwrap_or_default).collect();
+        return molt_opt_ok!(list);
+    }
+
+    // NEXT, assign the variables.
+    if var_names.len() != result.values.len() {
+        return molt_err!("different numbers of variable names and field specifiers");
+    }
+
+    for (name, value) in var_names.iter().zip(result.values) {
+        if let Some(value) = value {
+            interp.set_var(name, value)?;
+        }
+    }
+
+    molt_opt_ok!(result.count)
+}
+
 /// # set *varName* ?*newValue*?
 ///
 /// Sets variable *varName* to *newValue*, returning the value.
@@ -1023,10 +3152,50 @@ pub fn cmd_source(interp: &mut Interp, argv: &[Value]) -> MoltOptResult {
 
     let filename = argv[1].as_str();
 
-    match std::fs::read_to_string(filename) {
-        Ok(script) => interp.eval(&script).map(Some),
-        Err(e) => molt_err!("couldn't read file \"{}\": {}", filename, e),
+    let script = match std::fs::read_to_string(filename) {
+        Ok(script) => script,
+        Err(e) => return molt_err!("couldn't read file \"{}\": {}", filename, e),
+    };
+
+    #[cfg(feature = "info")]
+    interp.push_frame(FrameKind::Source(filename.into()), argv);
+
+    let result = interp.eval(&script);
+
+    #[cfg(feature = "info")]
+    interp.pop_frame();
+
+    result.map(Some)
+}
+
+/// # split *string* ?*splitChars*?
+///
+/// Splits the string into a list at each occurrence of any of the characters in
+/// *splitChars*, which defaults to white space.  Adjacent split characters produce empty
+/// list elements.  If *splitChars* is the empty string, splits the string into its
+/// individual characters.
+pub fn cmd_split(_interp: &mut Interp, argv: &[Value]) -> MoltOptResult {
+    check_args(1, argv, 2, 3, "string ?splitChars?")?;
+
+    let string = argv[1].as_str();
+
+    if string.is_empty() {
+        return molt_opt_ok!("");
     }
+
+    let list: MoltList = if argv.len() == 2 {
+        string.split(util::is_whitespace).map(|s| Value::from(s.to_string())).collect()
+    } else {
+        let split_chars = argv[2].as_str();
+
+        if split_chars.is_empty() {
+            string.chars().map(|ch| Value::from(ch.to_string())).collect()
+        } else {
+            string.split(|ch| split_chars.contains(ch)).map(|s| Value::from(s.to_string())).collect()
+        }
+    };
+
+    molt_opt_ok!(list)
 }
 
 /// # string *subcommand* ?*arg*...?
@@ -1041,20 +3210,25 @@ static STRING_SUBCOMMANDS: &[Subcommand] = &[
     Subcommand("compare", cmd_string_compare),
     Subcommand("equal", cmd_string_equal),
     Subcommand("first", cmd_string_first),
-    // Subcommand("index", cmd_string_todo),
+    Subcommand("index", cmd_string_index),
+    Subcommand("is", cmd_string_is),
     Subcommand("last", cmd_string_last),
     Subcommand("length", cmd_string_length),
     #[cfg(feature = "dict")]
     Subcommand("map", cmd_string_map),
+    Subcommand("match", cmd_string_match),
     Subcommand("range", cmd_string_range),
-    // Subcommand("replace", cmd_string_todo),
-    // Subcommand("repeat", cmd_string_todo),
-    // Subcommand("reverse", cmd_string_todo),
+    Subcommand("repeat", cmd_string_repeat),
+    Subcommand("replace", cmd_string_replace),
+    Subcommand("reverse", cmd_string_reverse),
     Subcommand("tolower", cmd_string_tolower),
+    Subcommand("totitle", cmd_string_totitle),
     Subcommand("toupper", cmd_string_toupper),
     Subcommand("trim", cmd_string_trim),
     Subcommand("trimleft", cmd_string_trim),
     Subcommand("trimright", cmd_string_trim),
+    Subcommand("wordend", cmd_string_wordend),
+    Subcommand("wordstart", cmd_string_wordstart),
 ];
 
 /// string cat ?*arg* ...?
@@ -1170,23 +3344,204 @@ pub fn cmd_string_first(_interp: &mut Interp, argv: &[Value]) -> MoltOptResult {
         0
     };
 
-    let pos_byte: Option<usize> = haystack
-        .char_indices()
-        .nth(start_char)
-        .and_then(|(start_byte, _)| haystack[start_byte..].find(needle));
+    let tail = match haystack.char_indices().nth(start_char) {
+        Some((start_byte, _)) => &haystack[start_byte..],
+        None => return molt_opt_ok!(-1),
+    };
 
-    let pos_char: MoltInt = match pos_byte {
+    let pos_char: MoltInt = match tail.find(needle) {
         None => -1,
-        Some(b) => haystack[b..]
-            .char_indices()
-            .take_while(|(i, _)| *i < b)
-            .count() as MoltInt
-            + start_char as MoltInt
+        Some(b) => (tail[..b].chars().count() + start_char) as MoltInt,
     };
 
     molt_opt_ok!(pos_char)
 }
 
+/// string index *string* *charIndex*
+#[cfg(feature = "string-command")]
+pub fn cmd_string_index(_interp: &mut Interp, argv: &[Value]) -> MoltOptResult {
+    check_args(2, argv, 4, 4, "string charIndex")?;
+
+    let string = argv[2].as_str();
+    let index = argv[3].as_index(string.chars().count())?;
+
+    match usize::try_from(index).ok().and_then(|i| string.chars().nth(i)) {
+        Some(ch) => molt_opt_ok!(ch.to_string()),
+        None => molt_opt_ok!(""),
+    }
+}
+
+/// string is *class* ?-strict? ?-failindex *varname*? *string*
+///
+/// Returns 1 if the string is a member of the character class, and 0 otherwise.  An empty
+/// string is a member of every class unless `-strict` is given.  If `-failindex` is given
+/// and the result is 0, the variable is set to the index of the first character that
+/// isn't a member of the class.  For the numeric and `boolean` classes, that's where the
+/// longest valid prefix ends, or -1 if the string is a number that is out of range; for the
+/// `list` class, it's where the element that isn't valid begins.
+///
+/// ## TCL Liens
+///
+/// * For the `dict`, `true`, and `false` classes, the failure index is always 0.
+#[cfg(feature = "string-command")]
+pub fn cmd_string_is(interp: &mut Interp, argv: &[Value]) -> MoltOptResult {
+    check_args(2, argv, 4, 7, "class ?-strict? ?-failindex var? str")?;
+
+    // FIRST, find the class.
+    let class = argv[2].as_str();
+    let test = match STRING_IS_CLASSES.iter().find(|(name, _)| *name == class) {
+        Some((_, test)) => test,
+        None => {
+            let mut msg = String::new();
+            let last = STRING_IS_CLASSES.len() - 1;
+            for (i, (name, _)) in STRING_IS_CLASSES.iter().enumerate() {
+                match i {
+                    0 => (),
+                    _ if i == last => msg.push_str(", or "),
+                    _ => msg.push_str(", "),
+                }
+                msg.push_str(name);
+            }
+            return molt_err!("bad class \"{}\": must be {}", class, msg);
+        }
+    };
+
+    // NEXT, get the options.
+    let arglen = argv.len();
+    let mut strict = false;
+    let mut fail_var = None;
+    let mut queue = argv[3..arglen - 1].iter();
+
+    while let Some(opt) = queue.next() {
+        match opt.as_str() {
+            "-strict" => strict = true,
+            "-failindex" => match queue.next() {
+                Some(var) => fail_var = Some(var),
+                None => {
+                    return molt_err!(
+                        "wrong # args: should be \"string is class ?-strict? ?-failindex var? str\""
+                    )
+                }
+            },
+            _ => return molt_err!("bad option \"{}\": must be -strict or -failindex", opt),
+        }
+    }
+
+    // NEXT, test the string.  The empty string is a valid list and dict regardless.
+    let text = argv[arglen - 1].as_str();
+
+    let result = if text.is_empty() && class != "list" && class != "dict" {
+        if strict { Err(0) } else { Ok(()) }
+    } else {
+        test(text)
+    };
+
+    match result {
+        Ok(()) => molt_opt_ok!(true),
+        Err(index) => {
+            if let Some(var) = fail_var {
+                interp.set_var(var, Value::from(index))?;
+            }
+            molt_opt_ok!(false)
+        }
+    }
+}
+
+/// A `string is` class test: returns the index of the first invalid character on failure,
+/// or -1 if the string is a number that is out of range.
+#[cfg(feature = "string-command")]
+type StringIsTest = fn(&str) -> Result<(), MoltInt>;
+
+#[cfg(feature = "string-command")]
+static STRING_IS_CLASSES: &[(&str, StringIsTest)] = &[
+    ("alnum", |s| string_is_chars(s, util::is_alphanumeric)),
+    ("alpha", |s| string_is_chars(s, util::is_alphabetic)),
+    ("ascii", |s| string_is_chars(s, |ch| ch.is_ascii())),
+    ("boolean", |s| string_is_number(s, Value::get_bool(s).is_ok(), read_bool)),
+    ("control", |s| string_is_chars(s, char::is_control)),
+    #[cfg(feature = "dict")]
+    ("dict", |s| match list::get_list(s) {
+        Ok(list) if list.len().is_multiple_of(2) => Ok(()),
+        _ => Err(0),
+    }),
+    ("digit", |s| string_is_chars(s, util::is_digit)),
+    #[cfg(feature = "float")]
+    ("double", |s| string_is_number(s, Value::get_float(s).is_ok(), util::read_float)),
+    ("entier", |s| string_is_number(s, Value::get_int(s).is_ok(), util::read_int)),
+    ("false", |s| match Value::get_bool(s) {
+        Ok(false) => Ok(()),
+        _ => Err(0),
+    }),
+    ("graph", |s| string_is_chars(s, |ch| !util::is_whitespace(ch) && !ch.is_control())),
+    ("integer", |s| string_is_number(s, Value::get_int(s).is_ok(), util::read_int)),
+    ("list", |s| list::get_list_or_index(s).map(|_| ()).map_err(|index| index as MoltInt)),
+    ("lower", |s| string_is_chars(s, util::is_lowercase)),
+    ("print", |s| string_is_chars(s, |ch| !ch.is_control())),
+    ("punct", |s| string_is_chars(s, |ch| ch.is_ascii_punctuation())),
+    ("space", |s| string_is_chars(s, util::is_whitespace)),
+    ("true", |s| match Value::get_bool(s) {
+        Ok(true) => Ok(()),
+        _ => Err(0),
+    }),
+    ("upper", |s| string_is_chars(s, util::is_uppercase)),
+    ("wideinteger", |s| string_is_number(s, Value::get_int(s).is_ok(), util::read_int)),
+    ("wordchar", |s| string_is_chars(s, util::is_varname_char)),
+    ("xdigit", |s| string_is_chars(s, |ch| ch.is_ascii_hexdigit())),
+];
+
+/// Verifies that every character in the string passes the test, returning the index of
+/// the first that doesn't.
+#[cfg(feature = "string-command")]
+fn string_is_chars(text: &str, test: fn(char) -> bool) -> Result<(), MoltInt> {
+    match text.chars().position(|ch| !test(ch)) {
+        Some(index) => Err(index as MoltInt),
+        None => Ok(()),
+    }
+}
+
+/// Checks the result of parsing the string as a number or boolean.  On failure, returns
+/// the index where the longest valid prefix read by `read` ends, including any following
+/// white space; 0 if there is no such prefix; or -1 if the whole string is valid but out
+/// of range.
+#[cfg(feature = "string-command")]
+fn string_is_number(
+    text: &str,
+    valid: bool,
+    read: fn(&mut Tokenizer) -> Option<String>,
+) -> Result<(), MoltInt> {
+    if valid {
+        return Ok(());
+    }
+
+    let mut p = Tokenizer::new(text);
+    p.skip_while(util::is_whitespace);
+
+    if read(&mut p).is_none() {
+        return Err(0);
+    }
+
+    p.skip_while(util::is_whitespace);
+
+    if p.at_end() {
+        Err(-1)
+    } else {
+        Err(text[..p.mark()].chars().count() as MoltInt)
+    }
+}
+
+/// Reads the longest boolean word at the head of the input, for `string is boolean`.
+#[cfg(feature = "string-command")]
+fn read_bool(p: &mut Tokenizer) -> Option<String> {
+    let rest = p.as_str();
+    let word = ["false", "true", "yes", "off", "no", "on", "1", "0"]
+        .into_iter()
+        .filter(|word| rest.get(..word.len()).is_some_and(|head| head.eq_ignore_ascii_case(word)))
+        .max_by_key(|word| word.len())?;
+
+    p.skip_over(word.len());
+    Some(String::from(word))
+}
+
 /// string last *needleString* *haystackString* ?*lastIndex*?
 #[cfg(feature = "string-command")]
 pub fn cmd_string_last(_interp: &mut Interp, argv: &[Value]) -> MoltOptResult {
@@ -1320,66 +3675,406 @@ pub fn cmd_string_map(_interp: &mut Interp, argv: &[Value]) -> MoltOptResult {
     molt_opt_ok!(result)
 }
 
+/// string match ?-nocase? *pattern* *string*
+///
+/// Returns 1 if the string matches the glob-style pattern, and 0 otherwise.
+#[cfg(feature = "string-command")]
+pub fn cmd_string_match(_interp: &mut Interp, argv: &[Value]) -> MoltOptResult {
+    check_args(2, argv, 4, 5, "?-nocase? pattern string")?;
+
+    let nocase = if argv.len() == 5 {
+        if argv[2].as_str() != "-nocase" {
+            return molt_err!("bad option \"{}\": must be -nocase", argv[2]);
+        }
+        true
+    } else {
+        false
+    };
+
+    let arglen = argv.len();
+    molt_opt_ok!(util::glob_match(argv[arglen - 2].as_str(), argv[arglen - 1].as_str(), nocase))
+}
+
 /// string range *string* *first* *last*
 #[cfg(feature = "string-command")]
 pub fn cmd_string_range(_interp: &mut Interp, argv: &[Value]) -> MoltOptResult {
     check_args(2, argv, 5, 5, "string first last")?;
 
     let string = argv[2].as_str();
-    let first = argv[3].as_int()?;
-    let last = argv[4].as_int()?;
+    let len = string.chars().count();
+    let first = argv[3].as_index(len)?;
+    let last = argv[4].as_index(len)?;
+
+    let substr = match clamp_range(first, last, len) {
+        Some((first, last)) => string.chars().skip(first).take(last - first + 1).collect(),
+        None => String::new(),
+    };
+
+    molt_opt_ok!(substr)
+}
+
+/// string repeat *string* *count*
+#[cfg(feature = "string-command")]
+pub fn cmd_string_repeat(_interp: &mut Interp, argv: &[Value]) -> MoltOptResult {
+    check_args(2, argv, 4, 4, "string count")?;
+
+    let count = argv[3].as_int()?;
 
-    if last < 0 {
+    if count <= 0 {
         return molt_opt_ok!("");
     }
 
-    let clamp = { |i: MoltInt| if i < 0 {
-            0
-        } else {
-            i
+    let string = argv[2].as_str();
+
+    match string.len().checked_mul(count as usize) {
+        Some(len) if len <= MAX_REPEAT_LEN => molt_opt_ok!(string.repeat(count as usize)),
+        _ => molt_err!("result of string repeat would be too large"),
+    }
+}
+
+/// string replace *string* *first* *last* ?*newstring*?
+///
+/// Replaces the characters from *first* to *last* with *newstring*, or removes them if
+/// *newstring* is omitted.  If the range is empty, returns the string unchanged.
+#[cfg(feature = "string-command")]
+pub fn cmd_string_replace(_interp: &mut Interp, argv: &[Value]) -> MoltOptResult {
+    check_args(2, argv, 5, 6, "string first last ?newstring?")?;
+
+    let string = argv[2].as_str();
+    let len = string.chars().count();
+    let first = argv[3].as_index(len)?;
+    let last = argv[4].as_index(len)?;
+
+    let (first, last) = match clamp_range(first, last, len) {
+        Some(range) => range,
+        None => return molt_opt_ok!(argv[2].clone()),
+    };
+
+    let mut result: String = string.chars().take(first).collect();
+    if argv.len() == 6 {
+        result.push_str(argv[5].as_str());
+    }
+    result.extend(string.chars().skip(last + 1));
+
+    molt_opt_ok!(result)
+}
+
+/// string reverse *string*
+#[cfg(feature = "string-command")]
+pub fn cmd_string_reverse(_interp: &mut Interp, argv: &[Value]) -> MoltOptResult {
+    check_args(2, argv, 3, 3, "string")?;
+
+    let reversed: String = argv[2].as_str().chars().rev().collect();
+    molt_opt_ok!(reversed)
+}
+
+/// string tolower *string*
+#[cfg(feature = "string-command")]
+pub fn cmd_string_tolower(_interp: &mut Interp, argv: &[Value]) -> MoltOptResult {
+    check_args(2, argv, 3, 3, "string")?;
+
+    let lower = util::to_lowercase(argv[2].as_str());
+    molt_opt_ok!(lower)
+}
+
+/// string totitle *string* ?*first*? ?*last*?
+///
+/// Converts the first character in the range to upper case, and the rest to lower case.
+/// The range defaults to the entire string.
+///
+/// ## TCL Liens
+///
+/// * Converts the first character to upper case rather than to Unicode title case.
+#[cfg(feature = "string-command")]
+pub fn cmd_string_totitle(_interp: &mut Interp, argv: &[Value]) -> MoltOptResult {
+    check_args(2, argv, 3, 5, "string ?first? ?last?")?;
+
+    let string = argv[2].as_str();
+    let len = string.chars().count();
+    let first = if argv.len() >= 4 { argv[3].as_index(len)? } else { 0 };
+    let last = if argv.len() == 5 {
+        argv[4].as_index(len)?
+    } else if argv.len() == 4 {
+        first
+    } else {
+        len as MoltInt - 1
+    };
+
+    let (first, last) = match clamp_range(first, last, len) {
+        Some(range) => range,
+        None => return molt_opt_ok!(argv[2].clone()),
+    };
+
+    let head: String = string.chars().take(first).collect();
+    let title: String = string.chars().skip(first).take(1).collect();
+    let rest: String = string.chars().skip(first + 1).take(last - first).collect();
+    let tail: String = string.chars().skip(last + 1).collect();
+
+    molt_opt_ok!(format!(
+        "{}{}{}{}",
+        head,
+        util::to_uppercase(&title),
+        util::to_lowercase(&rest),
+        tail
+    ))
+}
+
+/// string toupper *string*
+#[cfg(feature = "string-command")]
+pub fn cmd_string_toupper(_interp: &mut Interp, argv: &[Value]) -> MoltOptResult {
+    check_args(2, argv, 3, 3, "string")?;
+
+    let upper = util::to_uppercase(argv[2].as_str());
+    molt_opt_ok!(upper)
+}
+
+/// string (trim|trimleft|trimright) *string*
+#[cfg(feature = "string-command")]
+pub fn cmd_string_trim(_interp: &mut Interp, argv: &[Value]) -> MoltOptResult {
+    check_args(2, argv, 3, 3, "string")?;
+
+    let s = argv[2].as_str();
+    let trimmed = match argv[1].as_str() {
+        "trimleft" => s.trim_start_matches(util::is_whitespace),
+        "trimright" => s.trim_end_matches(util::is_whitespace),
+        _ => s.trim_matches(util::is_whitespace),
+    };
+
+    molt_opt_ok!(trimmed.to_string())
+}
+
+/// string wordend *string* *charIndex*
+///
+/// Returns the index just after the end of the word containing the character at
+/// *charIndex*.  A word is a run of alphanumeric characters and underscores, or any
+/// other single character.
+#[cfg(feature = "string-command")]
+pub fn cmd_string_wordend(_interp: &mut Interp, argv: &[Value]) -> MoltOptResult {
+    check_args(2, argv, 4, 4, "string index")?;
+
+    let chars: Vec<char> = argv[2].as_str().chars().collect();
+    let index = argv[3].as_index(chars.len())?.max(0) as usize;
+
+    if index >= chars.len() {
+        return molt_opt_ok!(chars.len() as MoltInt);
+    }
+
+    let end = match chars[index..].iter().position(|ch| !util::is_varname_char(*ch)) {
+        Some(0) => index + 1,
+        Some(count) => index + count,
+        None => chars.len(),
+    };
+
+    molt_opt_ok!(end as MoltInt)
+}
+
+/// string wordstart *string* *charIndex*
+///
+/// Returns the index of the first character of the word containing the character at
+/// *charIndex*.  A word is a run of alphanumeric characters and underscores, or any
+/// other single character.
+#[cfg(feature = "string-command")]
+pub fn cmd_string_wordstart(_interp: &mut Interp, argv: &[Value]) -> MoltOptResult {
+    check_args(2, argv, 4, 4, "string index")?;
+
+    let chars: Vec<char> = argv[2].as_str().chars().collect();
+    let index = argv[3].as_index(chars.len())?;
+    let index = index.min(chars.len() as MoltInt - 1);
+
+    if index <= 0 {
+        return molt_opt_ok!(0);
+    }
+
+    let index = index as usize;
+
+    let start = if util::is_varname_char(chars[index]) {
+        match chars[..index].iter().rposition(|ch| !util::is_varname_char(*ch)) {
+            Some(i) => i + 1,
+            None => 0,
+        }
+    } else {
+        index
+    };
+
+    molt_opt_ok!(start as MoltInt)
+}
+
+/// Clamps the range of indices *first* to *last* to fit a string or list of length
+/// *len*, returning `None` if the resulting range is empty.
+fn clamp_range(first: MoltInt, last: MoltInt, len: usize) -> Option<(usize, usize)> {
+    let first = first.max(0);
+    let last = last.min(len as MoltInt - 1);
+
+    if first > last {
+        None
+    } else {
+        Some((first as usize, last as usize))
+    }
+}
+
+/// # subst ?-nobackslashes? ?-nocommands? ?-novariables? *string*
+///
+/// Performs backslash, command, and variable substitution on the *string*, as for a
+/// quoted word, and returns the result.  The options disable the corresponding
+/// substitutions.  A `break` in a command substitution ends the substitution, returning
+/// the result so far; a `continue` substitutes the empty string.
+pub(crate) fn cmd_subst(interp: &mut Interp, argv: &[Value]) -> Result<Step, Exception> {
+    check_args(1, argv, 2, 0, "?-nobackslashes? ?-nocommands? ?-novariables? string")?;
+
+    let mut flags = SubstFlags::ALL;
+
+    for opt in &argv[1..argv.len() - 1] {
+        match opt.as_str() {
+            "-nobackslashes" => flags.backslashes = false,
+            "-nocommands" => flags.commands = false,
+            "-novariables" => flags.variables = false,
+            _ => {
+                return molt_err!(
+                    "bad switch \"{}\": must be -nobackslashes, -nocommands, or -novariables",
+                    opt
+                );
+            }
+        }
+    }
+
+    Ok(interp.push_subst(&argv[argv.len() - 1], flags))
+}
+
+/// # switch ?*options*? *string* *pattern* *body* ?*pattern* *body* ...?
+/// # switch ?*options*? *string* {*pattern* *body* ?*pattern* *body* ...?}
+///
+/// Matches the *string* against each *pattern* in turn, and evaluates the *body* of the
+/// first pattern that matches, returning its result.  If no pattern matches, returns the
+/// empty string.  The options are:
+///
+/// * `-exact`: Match the string exactly against each pattern.  This is the default.
+/// * `-glob`: Match the string against each pattern using glob-style matching.
+/// * `-regexp`: Match the string against each pattern as a regular expression, as for
+///   `regexp`.
+/// * `-nocase`: Compare without regard to case.
+/// * `--`: Marks the end of the options; use it if *string* might begin with `-`.
+///
+/// A body of `-` means "fall through": use the body of the following pattern.  If
+/// the final pattern is `default`, it matches any string.
+///
+/// ## TCL Liens
+///
+/// * Does not support `-matchvar` or `-indexvar`.
+#[cfg(feature = "switch-command")]
+pub(crate) fn cmd_switch(interp: &mut Interp, argv: &[Value]) -> Result<Step, Exception> {
+    match switch_body(argv)? {
+        Some(body) => Ok(interp.push_body(&body)),
+        None => Ok(Step::Deliver(molt_ok!())),
+    }
+}
+
+/// Matches the `switch` command's string against its patterns to select the body to
+/// evaluate, if any.
+#[cfg(feature = "switch-command")]
+fn switch_body(argv: &[Value]) -> Result<Option<Value>, Exception> {
+    check_args(1, argv, 3, 0, "?-option ...? string ?pattern body ...? ?default body?")?;
+
+    // FIRST, get the options.  Options can only appear if there are at least two
+    // arguments following them.
+    let mut match_mode = MatchMode::Exact;
+    let mut nocase = false;
+    let mut i = 1;
+
+    while i < argv.len() - 2 {
+        match argv[i].as_str() {
+            "-exact" => match_mode = MatchMode::Exact,
+            "-glob" => match_mode = MatchMode::Glob,
+            #[cfg(feature = "regex")]
+            "-regexp" => match_mode = MatchMode::Regexp,
+            "-nocase" => nocase = true,
+            "--" => {
+                i += 1;
+                break;
+            }
+            opt if opt.starts_with('-') => {
+                return molt_err!("bad option \"{}\": must be -exact, -glob, -nocase, -regexp, or --", opt);
+            }
+            _ => break,
+        }
+        i += 1;
+    }
+
+    let string = argv[i].as_str();
+
+    // NEXT, get the pattern/body pairs, which are either the remaining arguments or
+    // a single list.
+    let list;
+    let pairs: &[Value] = if argv.len() == i + 2 {
+        list = argv[i + 1].to_list()?;
+        if list.is_empty() {
+            return molt_err!(
+                "wrong # args: should be \"{} ?-option ...? string {{?pattern body ...? ?default body?}}\"",
+                argv[0]
+            );
         }
+        &list
+    } else {
+        &argv[i + 1..]
     };
 
-    let substr = string
-        .chars()
-        .skip(clamp(first) as usize)
-        .take((clamp(last) - clamp(first) + 1) as usize)
-        .collect::<String>();
+    if !pairs.len().is_multiple_of(2) {
+        return molt_err!("extra switch pattern with no body");
+    }
 
-    molt_opt_ok!(substr)
-}
+    let last = pairs.len() - 2;
 
-/// string tolower *string*
-#[cfg(feature = "string-command")]
-pub fn cmd_string_tolower(_interp: &mut Interp, argv: &[Value]) -> MoltOptResult {
-    check_args(2, argv, 3, 3, "string")?;
+    if pairs[last + 1].as_str() == "-" {
+        return molt_err!("no body specified for pattern \"{}\"", pairs[last]);
+    }
 
-    let lower = util::to_lowercase(argv[2].as_str());
-    molt_opt_ok!(lower)
-}
+    // NEXT, find the first matching pattern.
 
-/// string toupper *string*
-#[cfg(feature = "string-command")]
-pub fn cmd_string_toupper(_interp: &mut Interp, argv: &[Value]) -> MoltOptResult {
-    check_args(2, argv, 3, 3, "string")?;
+    for (index, pair) in pairs.chunks(2).enumerate() {
+        let pattern = pair[0].as_str();
 
-    let upper = util::to_uppercase(argv[2].as_str());
-    molt_opt_ok!(upper)
-}
+        let matched = (index * 2 == last && pattern == "default")
+            || match match_mode {
+                MatchMode::Exact if nocase => {
+                    util::to_lowercase(pattern) == util::to_lowercase(string)
+                }
+                MatchMode::Exact => pattern == string,
+                MatchMode::Glob => util::glob_match(pattern, string, nocase),
+                #[cfg(feature = "regex")]
+                MatchMode::Regexp => Regex::new(pattern, nocase)?.is_match(string),
+            };
 
-/// string (trim|trimleft|trimright) *string*
-#[cfg(feature = "string-command")]
-pub fn cmd_string_trim(_interp: &mut Interp, argv: &[Value]) -> MoltOptResult {
-    check_args(2, argv, 3, 3, "string")?;
+        if matched {
+            // Skip fall-through bodies to find the body to evaluate; the final body
+            // is known not to be a fall-through.
+            let body = pairs[index * 2 + 1..]
+                .iter()
+                .step_by(2)
+                .find(|body| body.as_str() != "-")
+                .unwrap();
+            return Ok(Some(body.clone()));
+        }
+    }
 
-    let s = argv[2].as_str();
-    let trimmed = match argv[1].as_str() {
-        "trimleft" => s.trim_start_matches(util::is_whitespace),
-        "trimright" => s.trim_end_matches(util::is_whitespace),
-        _ => s.trim_matches(util::is_whitespace),
-    };
+    Ok(None)
+}
 
-    molt_opt_ok!(trimmed.to_string())
+/// # tailcall *command* ?*arg* ...?
+///
+/// Replaces the procedure or lambda that is executing with a call to the command: the
+/// procedure returns, the command is called in its place by the procedure's caller, and
+/// the command's result becomes the procedure's result.  The command is resolved in the
+/// procedure's namespace.  A tail-recursive procedure thus runs in constant stack space.
+///
+/// ## TCL Liens
+///
+/// * The command is resolved when `tailcall` is called, rather than when the procedure
+///   returns.
+/// * Within `uplevel`, replaces the innermost procedure that is executing rather than the
+///   procedure whose scope is in use.
+pub fn cmd_tailcall(interp: &mut Interp, argv: &[Value]) -> MoltOptResult {
+    check_args(1, argv, 2, 0, "command ?arg ...?")?;
+
+    interp.tailcall(&argv[1..]).map(Some)
 }
 
 /// throw *type* *message*
@@ -1424,6 +4119,445 @@ pub fn cmd_time(interp: &mut Interp, argv: &[Value]) -> MoltOptResult {
     molt_opt_ok!("{} nanoseconds per iteration", avg)
 }
 
+/// # trace *subcommand* ?*arg*...?
+#[cfg(feature = "traces")]
+pub fn cmd_trace(interp: &mut Interp, argv: &[Value]) -> MoltOptResult {
+    interp.call_subcommand(argv, 1, &TRACE_SUBCOMMANDS)
+}
+
+#[cfg(feature = "traces")]
+static TRACE_SUBCOMMANDS: [Subcommand; 3] = [
+    Subcommand("add", cmd_trace_add),
+    Subcommand("info", cmd_trace_info),
+    Subcommand("remove", cmd_trace_remove),
+];
+
+/// # trace add *type* *name* *opList* *command*
+///
+/// Adds a trace of the given *type*, `execution` or `variable`.
+///
+/// `trace add execution` *name* *opList* *command* arranges for the *command* prefix to
+/// be called when the named command is entered or returns, as selected by the operations
+/// in *opList*: `enter` or `leave`.  On entry, the *command* is called with two additional
+/// arguments: the traced command's words as a list, and `enter`.  On return, it is called
+/// with four: the words, the integer result code, the result value, and `leave`.  See
+/// `Interp::trace_execution` for the details.
+///
+/// `trace add variable` *name* *opList* *command* arranges for the *command* prefix to be
+/// called whenever the variable is accessed with one of the operations in *opList*:
+/// `read`, `write`, or `unset`.  The command is called with three additional arguments:
+/// the variable name as used in the access, the array index or the empty string, and the
+/// operation.  See `Interp::trace_var` for the details.
+///
+/// ## TCL Liens
+///
+/// * Command traces are not supported.
+/// * The `enterstep` and `leavestep` execution operations are not supported.
+/// * The `array` variable operation is not supported.
+/// * `array get` and `array names` don't fire read traces.
+#[cfg(feature = "traces")]
+fn cmd_trace_add(interp: &mut Interp, argv: &[Value]) -> MoltOptResult {
+    check_args(2, argv, 6, 6, "type name opList command")?;
+
+    match argv[2].as_str() {
+        "execution" => {
+            let ops = parse_exec_ops(&argv[4])?;
+            interp.trace_execution_command(argv[3].as_str(), ops, &argv[5])?;
+            molt_opt_ok!()
+        }
+        "variable" => {
+            let ops = parse_var_ops(&argv[4])?;
+            interp.trace_var_command(argv[3].as_str(), ops, &argv[5]);
+            molt_opt_ok!()
+        }
+        other => bad_trace_type(other),
+    }
+}
+
+/// # trace info *type* *name*
+///
+/// Returns a list of the traces of the given *type* on the command or variable, most
+/// recent first.  Each trace is a pair of the trace's *opList* and *command*.
+#[cfg(feature = "traces")]
+fn cmd_trace_info(interp: &mut Interp, argv: &[Value]) -> MoltOptResult {
+    check_args(2, argv, 4, 4, "type name")?;
+
+    match argv[2].as_str() {
+        "execution" => {
+            let info: MoltList = interp
+                .exec_trace_info(argv[3].as_str())?
+                .into_iter()
+                .map(|(ops, command)| Value::from(vec![exec_ops_value(ops), command]))
+                .collect();
+            molt_opt_ok!(info)
+        }
+        "variable" => {
+            let info: MoltList = interp
+                .var_trace_info(argv[3].as_str())
+                .into_iter()
+                .map(|(ops, command)| Value::from(vec![var_ops_value(ops), command]))
+                .collect();
+            molt_opt_ok!(info)
+        }
+        other => bad_trace_type(other),
+    }
+}
+
+/// # trace remove *type* *name* *opList* *command*
+///
+/// Removes the trace of the given *type* on the command or variable with the given
+/// *opList* and *command*, if any.
+#[cfg(feature = "traces")]
+fn cmd_trace_remove(interp: &mut Interp, argv: &[Value]) -> MoltOptResult {
+    check_args(2, argv, 6, 6, "type name opList command")?;
+
+    match argv[2].as_str() {
+        "execution" => {
+            let ops = parse_exec_ops(&argv[4])?;
+            interp.untrace_execution_command(argv[3].as_str(), ops, &argv[5]);
+            molt_opt_ok!()
+        }
+        "variable" => {
+            let ops = parse_var_ops(&argv[4])?;
+            interp.untrace_var_command(argv[3].as_str(), ops, &argv[5]);
+            molt_opt_ok!()
+        }
+        other => bad_trace_type(other),
+    }
+}
+
+#[cfg(feature = "traces")]
+fn bad_trace_type(name: &str) -> MoltOptResult {
+    molt_err!("bad option \"{}\": must be execution or variable", name)
+}
+
+/// Parses an execution `trace` *opList*.
+#[cfg(feature = "traces")]
+fn parse_exec_ops(value: &Value) -> Result<ExecOps, Exception> {
+    let list = value.as_list()?;
+
+    if list.is_empty() {
+        return molt_err!("bad operation list \"\": must be one or more of enter or leave");
+    }
+
+    let mut ops = ExecOps::default();
+
+    for op in list.iter() {
+        match op.as_str() {
+            "enter" => ops.enter = true,
+            "leave" => ops.leave = true,
+            other => return molt_err!("bad operation \"{}\": must be enter or leave", other),
+        }
+    }
+
+    Ok(ops)
+}
+
+/// Formats a set of execution operations as a `trace` *opList*.
+#[cfg(feature = "traces")]
+fn exec_ops_value(ops: ExecOps) -> Value {
+    let mut list = MoltList::new();
+
+    if ops.enter {
+        list.push(Value::from("enter"));
+    }
+
+    if ops.leave {
+        list.push(Value::from("leave"));
+    }
+
+    Value::from(list)
+}
+
+/// Parses a variable `trace` *opList*.
+#[cfg(feature = "traces")]
+fn parse_var_ops(value: &Value) -> Result<VarOps, Exception> {
+    let list = value.as_list()?;
+
+    if list.is_empty() {
+        return molt_err!(
+            "bad operation list \"\": must be one or more of read, unset, or write");
+    }
+
+    let mut ops = VarOps::default();
+
+    for op in list.iter() {
+        match op.as_str() {
+            "read" => ops.read = true,
+            "write" => ops.write = true,
+            "unset" => ops.unset = true,
+            other => return molt_err!(
+                "bad operation \"{}\": must be read, unset, or write", other),
+        }
+    }
+
+    Ok(ops)
+}
+
+/// Formats a set of variable operations as a `trace` *opList*.
+#[cfg(feature = "traces")]
+fn var_ops_value(ops: VarOps) -> Value {
+    let list: MoltList = [VarOp::Read, VarOp::Write, VarOp::Unset]
+        .into_iter()
+        .filter(|op| ops.contains(*op))
+        .map(|op| Value::from(op.as_str()))
+        .collect();
+    Value::from(list)
+}
+
+/// # try *body* ?*handler* ...? ?finally *script*?
+///
+/// Evaluates the body, and then the script of the first handler that matches the body's
+/// result, if any.  The handlers are:
+///
+/// * `on` *code* *variableList* *script*: Matches if the body's result code is *code*,
+///   which may be `ok`, `error`, `return`, `break`, `continue`, or an integer.
+/// * `trap` *pattern* *variableList* *script*: Matches if the body threw an error whose
+///   `errorCode` list begins with the elements of the *pattern* list.
+///
+/// The matching handler assigns the body's result and its return options dictionary to
+/// the first and second variables named in its *variableList*, if any, and evaluates its
+/// script, whose result becomes the result of `try`.  A script of `-` means "fall through":
+/// use the script of the following handler.  If no handler matches, the body's result is
+/// the result of `try`.
+///
+/// The `finally` script, if given, is evaluated last whatever the outcome.  If it returns
+/// normally its result is discarded; otherwise its result replaces the result of `try`.
+///
+/// ## TCL Liens
+///
+/// * An error in a handler or `finally` script doesn't add the `-during` option to the
+///   return options.
+/// * Without the `dict` feature there are no return options, so a *variableList* may name
+///   only the result variable.
+pub(crate) fn cmd_try(interp: &mut Interp, argv: &[Value]) -> Result<Step, Exception> {
+    try_clauses(argv)?;
+
+    // NEXT, evaluate the body.
+    interp.push_eval_frame(EvalFrame::Try {
+        argv: argv.to_vec(),
+        handled: false,
+    });
+    Ok(interp.push_body(&argv[1]))
+}
+
+/// Handles the result of a `try` command's body or handler: evaluates the first matching
+/// handler, if the body's result hasn't been handled yet, and then the `finally` script,
+/// if any.
+pub(crate) fn try_result(
+    interp: &mut Interp,
+    argv: MoltList,
+    handled: bool,
+    mut result: MoltResult,
+) -> Result<Step, Exception> {
+    // The clauses were checked when the command was started.
+    let (handlers, finally) = try_clauses(&argv)?;
+
+    if !handled {
+        match try_handler(interp, &handlers, &result) {
+            Ok(Some(script)) => {
+                drop(handlers);
+                interp.push_eval_frame(EvalFrame::Try {
+                    argv,
+                    handled: true,
+                });
+                return Ok(interp.push_body(&script));
+            }
+            Ok(None) => (),
+            Err(exception) => result = Err(exception),
+        }
+    }
+
+    // NEXT, evaluate the finally script.  Its result replaces ours only if it is
+    // exceptional.
+    match finally {
+        Some(script) => {
+            let script = script.clone();
+            drop(handlers);
+            interp.push_eval_frame(EvalFrame::Finally { result });
+            Ok(interp.push_body(&script))
+        }
+        None => Ok(Step::Deliver(result)),
+    }
+}
+
+/// Handles the result of a `try` command's `finally` script, given the result it replaces
+/// only if it is exceptional.
+pub(crate) fn finally_result(saved: MoltResult, result: MoltResult) -> Step {
+    match result {
+        Ok(_) => Step::Deliver(saved),
+        Err(exception) => Step::Deliver(Err(exception)),
+    }
+}
+
+/// Parses the `try` command's handlers and its `finally` script, if any, so that errors
+/// in them are found before the body is evaluated.
+fn try_clauses(
+    argv: &[Value],
+) -> Result<(Vec<TryHandler<'_>>, Option<&Value>), Exception> {
+    check_args(1, argv, 2, 0, "body ?handler ...? ?finally script?")?;
+
+    let mut handlers: Vec<TryHandler> = Vec::new();
+    let mut finally: Option<&Value> = None;
+    let mut i = 2;
+
+    while i < argv.len() {
+        match argv[i].as_str() {
+            "on" => {
+                if argv.len() < i + 4 {
+                    return molt_err!(
+                        "wrong # args to on clause: must be \"... on code variableList script\""
+                    );
+                }
+                let code = match ResultCode::from_value(&argv[i + 1]) {
+                    Ok(code) => code,
+                    Err(_) => {
+                        return molt_err!(
+                            "bad completion code \"{}\": must be ok, error, return, break, continue, or an integer",
+                            argv[i + 1]
+                        );
+                    }
+                };
+                handlers.push(TryHandler {
+                    matcher: TryMatch::Code(code),
+                    vars: try_var_list(&argv[i + 2])?,
+                    script: &argv[i + 3],
+                });
+                i += 4;
+            }
+            "trap" => {
+                if argv.len() < i + 4 {
+                    return molt_err!(
+                        "wrong # args to trap clause: must be \"... trap pattern variableList script\""
+                    );
+                }
+                handlers.push(TryHandler {
+                    matcher: TryMatch::Trap(argv[i + 1].to_list()?.to_vec()),
+                    vars: try_var_list(&argv[i + 2])?,
+                    script: &argv[i + 3],
+                });
+                i += 4;
+            }
+            "finally" => {
+                if argv.len() != i + 2 {
+                    return molt_err!(
+                        "wrong # args to finally clause: must be \"... finally script\""
+                    );
+                }
+                finally = Some(&argv[i + 1]);
+                i += 2;
+            }
+            other => {
+                return molt_err!("bad handler \"{}\": must be finally, on, or trap", other);
+            }
+        }
+    }
+
+    if handlers.last().is_some_and(|handler| handler.script.as_str() == "-") {
+        return molt_err!("last non-finally clause must not have a body of \"-\"");
+    }
+
+    Ok((handlers, finally))
+}
+
+/// Finds the first `try` handler that matches the body's result, assigns the result and
+/// its return options to the handler's variables, and returns the handler's script, if
+/// any handler matches.
+fn try_handler(
+    interp: &mut Interp,
+    handlers: &[TryHandler],
+    result: &MoltResult,
+) -> Result<Option<Value>, Exception> {
+    let index = match handlers.iter().position(|handler| handler.matches(result)) {
+        Some(index) => index,
+        None => return Ok(None),
+    };
+
+    let vars = &handlers[index].vars;
+
+    if let Some(var) = vars.first() {
+        let value = match result {
+            Ok(val) => val.clone(),
+            Err(exception) => exception.value(),
+        };
+        interp.set_var(var, value)?;
+    }
+
+    // The variable list names the options variable only with the dict feature.
+    #[cfg(feature = "dict")]
+    if let Some(var) = vars.get(1) {
+        interp.set_var(var, interp.return_options(result))?;
+    }
+
+    // Skip fall-through scripts to find the script to evaluate; the final script is
+    // known not to be a fall-through.
+    let script = handlers[index..]
+        .iter()
+        .map(|handler| handler.script)
+        .find(|script| script.as_str() != "-")
+        .unwrap();
+
+    Ok(Some(script.clone()))
+}
+
+/// How a `try` handler matches the body's result.
+enum TryMatch {
+    /// `on`: matches the result code.
+    Code(ResultCode),
+
+    /// `trap`: matches a prefix of the error code.
+    Trap(MoltList),
+}
+
+/// A `try` handler.
+pub(crate) struct TryHandler<'a> {
+    matcher: TryMatch,
+    vars: MoltList,
+    script: &'a Value,
+}
+
+impl TryHandler<'_> {
+    /// Determines whether the handler matches the result.
+    fn matches(&self, result: &MoltResult) -> bool {
+        match (&self.matcher, result) {
+            (TryMatch::Code(code), Ok(_)) => *code == ResultCode::Okay,
+            (TryMatch::Code(code), Err(exception)) => *code == exception.code(),
+            (TryMatch::Trap(pattern), Err(exception)) if exception.code() == ResultCode::Error => {
+                match exception.error_code().to_list() {
+                    Ok(error_code) => {
+                        pattern.len() <= error_code.len()
+                            && pattern
+                                .iter()
+                                .zip(error_code.iter())
+                                .all(|(p, c)| p.as_str() == c.as_str())
+                    }
+                    Err(_) => false,
+                }
+            }
+            _ => false,
+        }
+    }
+}
+
+/// Gets a `try` handler's variable list, which names at most two variables.
+fn try_var_list(value: &Value) -> Result<MoltList, Exception> {
+    let vars = value.to_list()?.to_vec();
+
+    if vars.len() > 2 {
+        return molt_err!("bad variable list \"{}\": must name at most two variables", value);
+    }
+
+    #[cfg(not(feature = "dict"))]
+    if vars.len() > 1 {
+        return molt_err!(
+            "bad variable list \"{}\": return options are not available without dicts",
+            value
+        );
+    }
+
+    Ok(vars)
+}
+
 /// # unset ?-nocomplain? *varName*
 ///
 /// Removes the variable from the interpreter.  This is a no op if
@@ -1452,24 +4586,286 @@ pub fn cmd_unset(interp: &mut Interp, argv: &[Value]) -> MoltOptResult {
     molt_opt_ok!()
 }
 
+/// # uplevel ?*level*? *arg* ?*arg* ...?
+///
+/// Concatenates the arguments as for `concat` and evaluates the result as a script in the
+/// scope at the given *level*.  The *level* may be a relative level, e.g., `1` for the
+/// caller's scope, or an absolute level such as `#0` for the global scope; it defaults to
+/// `1`.  Result codes such as `return` and `break` propagate from the script just as
+/// if it had been evaluated at the current level.
+pub(crate) fn cmd_uplevel(interp: &mut Interp, argv: &[Value]) -> Result<Step, Exception> {
+    check_args(1, argv, 2, 0, "?level? command ?arg ...?")?;
+
+    // FIRST, get the level, if any.  A lone argument is always the script.
+    let level = if argv.len() > 2 {
+        get_level(interp, &argv[1])?
+    } else {
+        None
+    };
+
+    let (level, start) = match level {
+        Some(level) => (level, 2),
+        None if interp.scope_level() > 0 => (interp.scope_level() - 1, 1),
+        None => return molt_err!("bad level \"1\""),
+    };
+
+    // NEXT, evaluate the script at that level.
+    if argv.len() == start + 1 {
+        Ok(interp.push_uplevel(level, &argv[start]))
+    } else {
+        let script = Value::from(list::concat_values(&argv[start..]));
+        Ok(interp.push_uplevel(level, &script))
+    }
+}
+
+/// # upvar ?*level*? *otherVar* *localVar* ?*otherVar* *localVar* ...?
+///
+/// Links the variable *localVar* in the current scope to the variable *otherVar* in the
+/// scope at the given *level*, so that *localVar* becomes an alias for *otherVar*.  The
+/// *level* may be a relative level, e.g., `1` for the caller's scope, or an absolute level
+/// such as `#0` for the global scope; it defaults to `1`.  Array variables may be linked in
+/// the same way as scalars.
+///
+/// ## TCL Liens
+///
+/// * *otherVar* can't be an individual array element.
+pub fn cmd_upvar(interp: &mut Interp, argv: &[Value]) -> MoltOptResult {
+    let argsig = "?level? otherVar localVar ?otherVar localVar ...?";
+    check_args(1, argv, 3, 0, argsig)?;
+
+    // FIRST, get the level, if any.
+    let (level, start) = match get_level(interp, &argv[1])? {
+        Some(level) => (level, 2),
+        None if interp.scope_level() > 0 => (interp.scope_level() - 1, 1),
+        None => return molt_err!("bad level \"1\""),
+    };
+
+    let pairs = &argv[start..];
+
+    if pairs.is_empty() || !pairs.len().is_multiple_of(2) {
+        return molt_err!("wrong # args: should be \"{} {}\"", argv[0], argsig);
+    }
+
+    // NEXT, link the variables.
+    for pair in pairs.chunks(2) {
+        let other = pair[0].as_var_name();
+        let mine = pair[1].as_var_name();
+
+        if other.index().is_some() {
+            return molt_err!("can't upvar to array element \"{}\"", pair[0]);
+        }
+
+        if mine.index().is_some() {
+            return molt_err!(
+                "bad variable name \"{}\": can't create a scalar variable that looks like an array element",
+                pair[1]
+            );
+        }
+
+        interp.upvar_alias(level, other.name(), mine.name())?;
+    }
+
+    molt_opt_ok!()
+}
+
+/// Interprets the argument as a stack level for `upvar` and similar commands, returning
+/// the absolute level.  A level is either a non-negative integer, counting up from the
+/// current scope, or `#` followed by an absolute level, counting down from the global scope.
+/// Returns `None` if the argument doesn't look like a level, and an error if it looks
+/// like a level but isn't valid.
+pub(crate) fn get_level(interp: &Interp, arg: &Value) -> Result<Option<usize>, Exception> {
+    let text = arg.as_str();
+    let current = interp.scope_level() as MoltInt;
+
+    let level = if let Some(abs) = text.strip_prefix('#') {
+        match Value::get_int(abs) {
+            Ok(level) => level,
+            Err(_) => return molt_err!("bad level \"{}\"", text),
+        }
+    } else if text.starts_with(|ch: char| ch.is_ascii_digit()) {
+        match Value::get_int(text) {
+            Ok(rel) => current - rel,
+            Err(_) => return molt_err!("bad level \"{}\"", text),
+        }
+    } else {
+        return Ok(None);
+    };
+
+    if level < 0 || level > current {
+        molt_err!("bad level \"{}\"", text)
+    } else {
+        Ok(Some(level as usize))
+    }
+}
+
+/// # variable ?*name* *value* ...? *name* ?*value*?
+///
+/// Declares namespace variables, giving each one the *value*, if any.  In a procedure,
+/// each variable is also linked into the procedure's scope, using its tail as the local
+/// name.  A qualified *name* is relative to the current namespace.
+///
+/// ## TCL Liens
+///
+/// * A variable declared without a value isn't created until it is set.
+#[cfg(feature = "namespaces")]
+pub fn cmd_variable(interp: &mut Interp, argv: &[Value]) -> MoltOptResult {
+    check_args(1, argv, 2, 0, "?name value...? name ?value?")?;
+
+    for pair in argv[1..].chunks(2) {
+        let name = pair[0].as_str();
+
+        if pair[0].as_var_name().index().is_some() {
+            return molt_err!("can't define \"{}\": name refers to an element in an array", name);
+        }
+
+        // FIRST, get the variable's fully qualified name.
+        let qualified = namespace::display(&interp.namespace_key(name));
+
+        // NEXT, link it into the procedure's scope, if need be.
+        if !interp.in_namespace_frame() {
+            interp.upvar_alias(0, &qualified, namespace::tail(name))?;
+        }
+
+        // NEXT, set the value.
+        if let Some(value) = pair.get(1) {
+            interp.set_scalar(&qualified, value.clone())?;
+        }
+    }
+
+    molt_opt_ok!()
+}
+
 /// # while *test* *command*
 ///
 /// A standard "while" loop.  *test* is a boolean expression; *command* is a script to
 /// execute so long as the expression is true.
-pub fn cmd_while(interp: &mut Interp, argv: &[Value]) -> MoltOptResult {
+pub(crate) fn cmd_while(interp: &mut Interp, argv: &[Value]) -> Result<Step, Exception> {
     check_args(1, argv, 3, 3, "test command")?;
 
-    while interp.expr_bool(&argv[1])? {
-        let result = interp.eval_value(&argv[2]);
+    while_test(interp, argv[1].clone(), argv[2].clone())
+}
 
-        if let Err(exception) = result {
-            match exception.code() {
-                ResultCode::Break => break,
-                ResultCode::Continue => (),
-                _ => return Err(exception),
-            }
+/// Tests a `while` loop's expression, pushing it if it can't be evaluated at once.
+fn while_test(interp: &mut Interp, test: Value, body: Value) -> Result<Step, Exception> {
+    if let Some(result) = interp.expr_now(&test) {
+        return while_result(interp, test, body, true, result);
+    }
+
+    let expr = test.clone();
+    interp.push_eval_frame(EvalFrame::While {
+        test,
+        body,
+        testing: true,
+    });
+    Ok(interp.push_expr(&expr))
+}
+
+/// Handles the result of a `while` loop's test or body, and goes on to the next one.
+pub(crate) fn while_result(
+    interp: &mut Interp,
+    test: Value,
+    body: Value,
+    testing: bool,
+    result: MoltResult,
+) -> Result<Step, Exception> {
+    if testing {
+        if !result?.as_bool()? {
+            return Ok(Step::Deliver(molt_ok!()));
         }
+
+        let script = body.clone();
+        interp.push_eval_frame(EvalFrame::While {
+            test,
+            body,
+            testing: false,
+        });
+        return Ok(interp.push_body(&script));
     }
 
-    molt_opt_ok!()
+    if let Err(exception) = result {
+        match exception.code() {
+            ResultCode::Break => return Ok(Step::Deliver(molt_ok!())),
+            ResultCode::Continue => (),
+            _ => return Err(exception),
+        }
+    }
+
+    while_test(interp, test, body)
+}
+
+/// # yield ?*value*?
+///
+/// Suspends the coroutine that is running, which returns the *value*, or the empty
+/// string, to its caller.  When the coroutine is resumed, returns the argument its
+/// command was called with, or the empty string.  See `coroutine`.
+///
+/// Within native commands that evaluate scripts themselves, such as `time`, returns the
+/// error "cannot yield: C stack busy"; see `coroutine`.
+#[cfg(feature = "coroutines")]
+pub(crate) fn cmd_yield(interp: &mut Interp, argv: &[Value]) -> Result<Step, Exception> {
+    check_args(1, argv, 1, 2, "?returnValue?")?;
+    can_yield(interp, argv)?;
+
+    Ok(Step::Yield(argv.get(1).cloned().unwrap_or_default()))
+}
+
+/// # yieldto *command* ?*arg* ...?
+///
+/// Suspends the coroutine that is running, and calls the command with the arguments in
+/// its place; the command's result is returned to the coroutine's caller.  When the
+/// coroutine is resumed, returns the list of the arguments its command was called with.
+/// See `coroutine`.
+///
+/// ## TCL Liens
+///
+/// * The command is resolved when the coroutine has been suspended, in its caller's
+///   namespace.
+#[cfg(feature = "coroutines")]
+pub(crate) fn cmd_yieldto(interp: &mut Interp, argv: &[Value]) -> Result<Step, Exception> {
+    check_args(1, argv, 2, 0, "command ?arg ...?")?;
+    can_yield(interp, argv)?;
+
+    Ok(Step::YieldTo(Box::new(argv[1..].to_vec())))
+}
+
+/// Checks that `yield` or `yieldto` can yield the coroutine that is running, if any.
+#[cfg(feature = "coroutines")]
+fn can_yield(interp: &Interp, argv: &[Value]) -> Result<(), Exception> {
+    if interp.can_yield() {
+        Ok(())
+    } else if interp.coroutine_name().is_some() {
+        molt_err!("cannot yield: C stack busy")
+    } else {
+        molt_err!("{} can only be called in a coroutine", argv[0])
+    }
+}
+
+/// A subcommand of an ensemble command that has control structures among its
+/// subcommands.
+#[cfg(any(feature = "dict", feature = "namespaces"))]
+enum SubcommandFunc {
+    Native(CommandFunc),
+    Control(ControlFunc),
+}
+
+/// Calls the subcommand of an ensemble command that has control structures among its
+/// subcommands, as `Interp::call_subcommand` does for other ensembles.
+#[cfg(any(feature = "dict", feature = "namespaces"))]
+fn call_control_subcommand(
+    interp: &mut Interp,
+    argv: &[Value],
+    subcommands: &[(&str, SubcommandFunc)],
+) -> Result<Step, Exception> {
+    check_args(1, argv, 2, 0, "subcommand ?arg ...?")?;
+
+    match subcommands.iter().find(|(name, _)| *name == argv[1].as_str()) {
+        Some((_, SubcommandFunc::Native(func))) => {
+            Ok(Step::Deliver(func(interp, argv).map(Option::unwrap_or_default)))
+        }
+        Some((_, SubcommandFunc::Control(func))) => func(interp, argv),
+        None => {
+            let names: Vec<&str> = subcommands.iter().map(|(name, _)| *name).collect();
+            Err(unknown_subcommand(argv[1].as_str(), &names))
+        }
+    }
 }
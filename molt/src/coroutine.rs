@@ -0,0 +1,50 @@
+//! Coroutines
+//!
+//! A coroutine is a command that calls another command, which can suspend itself by calling
+//! `yield`; calling the coroutine again, from Molt code or with `Interp::resume_coroutine`,
+//! resumes it where it left off.  See the `coroutine` command.
+//!
+//! Coroutines are run by the evaluator; see the `eval` module.  When a coroutine yields,
+//! its evaluator frames, its variable scopes, and its call frames are saved in its
+//! `Coroutine` until it is resumed.
+
+#[cfg(feature = "info")]
+use crate::interp::Frame;
+use crate::eval::EvalFrame;
+use crate::interp::TailCall;
+use crate::scope::DetachedScopes;
+use alloc::boxed::Box;
+use alloc::vec::Vec;
+use core::cell::RefCell;
+
+/// A coroutine's state.
+#[derive(Default)]
+pub(crate) struct Coroutine {
+    /// The coroutine's context, or `None` while it is running.
+    pub context: RefCell<Option<Context>>,
+}
+
+/// The context of a suspended coroutine.
+#[derive(Default)]
+pub(crate) struct Context {
+    /// The coroutine's evaluator frames, innermost last.
+    pub frames: Vec<EvalFrame>,
+
+    /// The coroutine's variable scopes.
+    pub scopes: DetachedScopes,
+
+    /// The coroutine's call frames, for `info level` and `info frame`.
+    #[cfg(feature = "info")]
+    pub call_frames: Vec<Frame>,
+
+    /// The pending tail calls of the coroutine's procedures.
+    pub tailcalls: Vec<Option<Box<TailCall>>>,
+
+    /// The number of scripts being evaluated as by `Interp::eval_value`, for the recursion
+    /// limit.
+    pub depth: usize,
+
+    /// Whether the coroutine was suspended by `yieldto`, and so takes any number of
+    /// arguments when it is resumed.
+    pub yieldto: bool,
+}
@@ -23,6 +23,25 @@ pub fn dict_new() -> MoltDict {
     IndexMap::default()
 }
 
+/// Given a Value containing a dictionary and a list of keys, returns the value at the
+/// end of the path of keys through the (possibly nested) dictionary.  All of the keys
+/// must exist.
+pub(crate) fn dict_path_get(dict_val: &Value, keys: &[Value]) -> MoltResult {
+    let mut value = dict_val.clone();
+
+    for key in keys {
+        let dict = value.as_dict()?;
+
+        if let Some(val) = dict.get(key) {
+            value = val.clone();
+        } else {
+            return molt_err!("key \"{}\" not known in dictionary", key);
+        }
+    }
+
+    molt_ok!(value)
+}
+
 /// Given a Value containing a dictionary, a list of keys, and a value,
 /// inserts the value into the (possibly nested) dictionary, returning the new
 /// dictionary value.
@@ -99,7 +118,7 @@ pub(crate) fn dict_to_string(dict: &MoltDict) -> String {
 /// Converts a vector of values into a dictionary.  The list must have
 /// an even number of elements.
 pub(crate) fn list_to_dict(list: &[Value]) -> MoltDict {
-    assert!(list.len() % 2 == 0);
+    assert!(list.len().is_multiple_of(2));
 
     let mut dict = dict_new();
 
@@ -125,4 +144,16 @@ mod tests {
 
         assert_eq!(dict_to_string(&dict), "abc 123");
     }
+
+    #[test]
+    fn test_dict_path_get() {
+        let dict = Value::from("a 1 b {x 2}");
+        let keys = |s: &str| -> MoltList { s.split(' ').map(|k| Value::from(k.to_string())).collect() };
+
+        assert_eq!(dict_path_get(&dict, &[]).unwrap(), dict);
+        assert_eq!(dict_path_get(&dict, &keys("a")).unwrap().as_str(), "1");
+        assert_eq!(dict_path_get(&dict, &keys("b x")).unwrap().as_str(), "2");
+        assert!(dict_path_get(&dict, &keys("c")).is_err());
+        assert!(dict_path_get(&dict, &keys("a x")).is_err());
+    }
 }
@@ -0,0 +1,278 @@
+//! The Evaluator
+//!
+//! Within a coroutine, scripts are evaluated without recursing in Rust, so that the
+//! coroutine can yield anywhere: the interpreter keeps its continuation as an explicit
+//! stack of frames, one for each script being evaluated, including procedure bodies, each
+//! command whose words are being evaluated, and each control structure that is waiting for
+//! a script or expression of its own.  Each frame receives the result of the frame above
+//! it when that frame is done.  The control structures, which are implemented as
+//! `ControlFunc`s rather than `CommandFunc`s, push frames to receive the results of their
+//! scripts rather than evaluating them themselves.
+//!
+//! Outside of a coroutine nothing can yield, so scripts, words, and expressions are
+//! evaluated at once, and the frames pushed by a command are run as soon as it returns.
+//! Only the command tail called by a procedure is left for the run of the evaluator to
+//! start, so that tail calls run in constant space.
+//!
+//! Rust code that evaluates a script, e.g., a native command that calls
+//! `Interp::eval_value`, runs the evaluator on the frames it pushes until they are done.
+//! A coroutine's frames are run by the coroutine's own run of the evaluator, and are set
+//! aside when it yields; it can yield wherever no Rust code is waiting for a nested run
+//! to finish, i.e., anywhere except within native commands that evaluate scripts, such as
+//! `time`, `lsort -command`, and traces.
+//!
+//! Within a coroutine, command substitutions that are part of a word, and those in
+//! expressions and `subst` templates, are evaluated by evaluating the word, expression, or
+//! template again once the substitution is done, replaying the substitutions made so far.
+
+use crate::commands::{self, ForLoop, ForeachLoop};
+#[cfg(feature = "dict")]
+use crate::commands::DictLoop;
+use crate::interp::{Interp, TailCall};
+use crate::parser::{Script, SubstFlags};
+#[cfg(feature = "traces")]
+use crate::trace::ExecTrace;
+use crate::types::{Exception, MoltList, MoltResult};
+use crate::value::Value;
+use alloc::boxed::Box;
+use alloc::rc::Rc;
+use alloc::vec::Vec;
+
+/// A function used to implement a control structure: a command that evaluates scripts or
+/// expressions of its own.  It checks its arguments and returns the step that starts it,
+/// usually after pushing a frame to receive the result of the script it pushes.
+pub(crate) type ControlFunc = fn(&mut Interp, &[Value]) -> Result<Step, Exception>;
+
+/// What the evaluator does next.
+pub(crate) enum Step {
+    /// Start evaluating the script whose frame was just pushed.
+    Enter,
+
+    /// Call the command tail called by a procedure or lambda in its place.
+    TailCall(Box<TailCall>),
+
+    /// Deliver the result to the innermost frame, or return it if the frames pushed by the
+    /// current run of the evaluator are done.
+    Deliver(MoltResult),
+
+    /// Suspend the coroutine, returning the value to its caller.
+    #[cfg(feature = "coroutines")]
+    Yield(Value),
+
+    /// Suspend the coroutine, calling the command in the caller's place.
+    #[cfg(feature = "coroutines")]
+    YieldTo(Box<MoltList>),
+}
+
+/// A frame on the evaluator's stack.
+pub(crate) enum EvalFrame {
+    /// A script being evaluated, with the index of its next command and how it's finished.
+    Script {
+        script: Rc<Script>,
+        next: usize,
+        kind: ScriptKind,
+
+        /// The words of the command before `next`, once it has been called, and whether it
+        /// is a procedure, for the stack trace of an error it returns.
+        #[cfg(feature = "error-stack-trace")]
+        called: Option<(MoltList, bool)>,
+    },
+
+    /// A command waiting for a command substitution in one of its words.
+    Words(CommandWords),
+
+    /// A traced command called in place of a procedure or lambda by `tailcall`, which
+    /// counts as a nesting level until it returns.
+    #[cfg(feature = "traces")]
+    TailCall,
+
+    /// An expression waiting for a command substitution.
+    #[cfg(feature = "expr")]
+    Expr { expr: Value, replay: Replay },
+
+    /// A `subst` template waiting for a command substitution.
+    Subst {
+        template: Value,
+        flags: SubstFlags,
+        replay: Replay,
+    },
+
+    /// A traced command, whose leave traces are called when it returns.
+    #[cfg(feature = "traces")]
+    Traced {
+        traces: Vec<Rc<ExecTrace>>,
+        words: MoltList,
+    },
+
+    /// A script evaluated by `uplevel`, with the stack index of the scope to make current
+    /// again when it is done.
+    Uplevel { saved: usize },
+
+    /// A script evaluated by `namespace eval`, whose namespace frame is popped when it is
+    /// done.
+    #[cfg(feature = "namespaces")]
+    Namespace,
+
+    /// A `catch` command, with its words.
+    Catch { argv: MoltList },
+
+    /// A `try` command, with its words; `handled` is true once a handler's script is
+    /// being evaluated.
+    Try { argv: MoltList, handled: bool },
+
+    /// A `try` command's `finally` script, with the result it replaces only if the script
+    /// is exceptional.
+    Finally { result: MoltResult },
+
+    /// An `if` command, with its words, testing the expression at `argi`.
+    If { argv: MoltList, argi: usize },
+
+    /// A `while` loop, testing its expression or evaluating its body.
+    While {
+        test: Value,
+        body: Value,
+        testing: bool,
+    },
+
+    /// A `for` loop.
+    For(ForLoop),
+
+    /// A `foreach` or `lmap` loop.
+    Foreach(ForeachLoop),
+
+    /// A `dict for`, `dict map`, or `dict filter` loop.
+    #[cfg(feature = "dict")]
+    DictLoop(DictLoop),
+
+    /// A `dict update` command, with its words.
+    #[cfg(feature = "dict")]
+    DictUpdate { argv: MoltList },
+
+    /// A `dict with` command, with its words and the keys whose variables it set.
+    #[cfg(feature = "dict")]
+    DictWith { argv: MoltList, keys: MoltList },
+}
+
+/// How the evaluation of a script is finished.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub(crate) enum ScriptKind {
+    /// A command substitution.
+    Subst,
+
+    /// A script evaluated as by `Interp::eval_value`, e.g., a control structure's body.
+    Body,
+
+    /// A procedure or lambda's body, whose scope is popped when it's done.
+    Proc,
+}
+
+/// A command in a script whose words are being evaluated.
+pub(crate) struct CommandWords {
+    /// The script, and the index of the command in it.
+    pub script: Rc<Script>,
+    pub index: usize,
+
+    /// How the script is finished.
+    pub kind: ScriptKind,
+
+    /// The index of the next word to evaluate, and the words evaluated so far.
+    pub next: usize,
+    pub words: MoltList,
+
+    /// The result of the previous command, if any, which is the script's result if the
+    /// command turns out to have no words.
+    pub last: Option<Value>,
+
+    /// The substitutions made so far in the word at `next`, if it is being evaluated
+    /// again.
+    pub replay: Option<Replay>,
+}
+
+impl EvalFrame {
+    /// Delivers the result of the frame above it to a frame that belongs to a control
+    /// structure.  The evaluator handles its own frames.
+    pub(crate) fn resume(self, interp: &mut Interp, result: MoltResult) -> Step {
+        let step = match self {
+            EvalFrame::Catch { argv } => {
+                Ok(Step::Deliver(commands::catch_result(interp, &argv, &result)))
+            }
+            EvalFrame::Try { argv, handled } => commands::try_result(interp, argv, handled, result),
+            EvalFrame::Finally { result: saved } => Ok(commands::finally_result(saved, result)),
+            EvalFrame::If { argv, argi } => commands::if_result(interp, argv, argi, result),
+            EvalFrame::While {
+                test,
+                body,
+                testing,
+            } => commands::while_result(interp, test, body, testing, result),
+            EvalFrame::For(state) => commands::for_result(interp, state, result),
+            EvalFrame::Foreach(state) => commands::foreach_result(interp, state, result),
+            #[cfg(feature = "dict")]
+            EvalFrame::DictLoop(state) => commands::dict_loop_result(interp, state, result),
+            #[cfg(feature = "dict")]
+            EvalFrame::DictUpdate { argv } => {
+                Ok(Step::Deliver(commands::dict_update_result(interp, &argv, result)))
+            }
+            #[cfg(feature = "dict")]
+            EvalFrame::DictWith { argv, keys } => {
+                Ok(Step::Deliver(commands::dict_with_result(interp, &argv, &keys, result)))
+            }
+            _ => unreachable!("evaluator frame delivered to a control structure"),
+        };
+
+        step.unwrap_or_else(|exception| Step::Deliver(Err(exception)))
+    }
+}
+
+/// The substitutions made so far in a word, expression, or template that is being
+/// evaluated within a coroutine, so that it can be evaluated again, replaying them, once
+/// the command substitution it stopped for is done.
+#[derive(Default)]
+pub(crate) struct Replay {
+    /// The results of the variable and command substitutions made so far, in order.
+    results: Vec<MoltResult>,
+
+    /// The index of the next result to replay.
+    next: usize,
+
+    /// The command substitution that evaluation stopped for, if any.
+    pending: Option<Rc<Script>>,
+}
+
+impl Replay {
+    /// Replays the next substitution, or makes it and records its result.
+    pub fn variable(&mut self, subst: impl FnOnce() -> MoltResult) -> MoltResult {
+        if let Some(result) = self.results.get(self.next) {
+            self.next += 1;
+            return result.clone();
+        }
+
+        let result = subst();
+        self.results.push(result.clone());
+        self.next += 1;
+        result
+    }
+
+    /// Replays the next substitution, which is the command substitution of the script;
+    /// or, if it hasn't been made yet, stops evaluation to make it by returning an error
+    /// that the caller passes along.
+    pub fn script(&mut self, script: &Rc<Script>) -> MoltResult {
+        if let Some(result) = self.results.get(self.next) {
+            self.next += 1;
+            return result.clone();
+        }
+
+        self.pending = Some(Rc::clone(script));
+        Err(Exception::molt_err(Value::empty()))
+    }
+
+    /// Takes the command substitution that evaluation stopped for, if any.
+    pub fn take_pending(&mut self) -> Option<Rc<Script>> {
+        self.pending.take()
+    }
+
+    /// Records the result of the command substitution, and rewinds, to evaluate again.
+    pub fn resume(&mut self, result: MoltResult) {
+        self.results.push(result);
+        self.next = 0;
+    }
+}
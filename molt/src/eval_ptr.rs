@@ -175,6 +175,12 @@ impl<'a> EvalPtr<'a> {
         }
     }
 
+    /// Is the input at a namespace separator, "::"?
+    #[cfg(feature = "namespaces")]
+    pub fn next_is_namespace_sep(&mut self) -> bool {
+        self.tok.as_str().starts_with("::")
+    }
+
     /// Skips past any whitespace at the current point, *including* newlines.
     /// When this is complete we will be at the end of the script or on a non-white-space
     /// character.
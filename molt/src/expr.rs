@@ -3,6 +3,7 @@
 //! * Ultimately, the command should probably move to commands.rs.
 //!   But this is convenient for now.
 
+use crate::eval::Replay;
 use crate::eval_ptr::EvalPtr;
 use crate::interp::Interp;
 use crate::list;
@@ -10,6 +11,7 @@ use crate::parser::Word;
 use crate::tokenizer::Tokenizer;
 use crate::*;
 
+use alloc::rc::Rc;
 use alloc::string::String;
 use alloc::format;
 
@@ -169,15 +171,20 @@ struct ExprInfo<'a> {
 
     // No Evaluation if > 0
     no_eval: i32,
+
+    // The replay of the substitutions, if the expression is being evaluated within a
+    // coroutine.
+    replay: Option<&'a mut Replay>,
 }
 
 impl<'a> ExprInfo<'a> {
-    fn new(expr: &'a str) -> Self {
+    fn new(expr: &'a str, replay: Option<&'a mut Replay>) -> Self {
         Self {
             original_expr: expr.to_string(),
             expr: Tokenizer::new(expr),
             token: -1,
             no_eval: 0,
+            replay,
         }
     }
 }
@@ -261,9 +268,10 @@ const OP_STRINGS: [&str; 36] = [
 //------------------------------------------------------------------------------------------------
 // Public API
 
-/// Evaluates an expression and returns its value.
-pub fn expr(interp: &mut Interp, expr: &Value) -> MoltResult {
-    let value = expr_top_level(interp, expr.as_str())?;
+/// Evaluates an expression and returns its value.  With a replay, its substitutions are
+/// replayed from it, or made and recorded in it; see the `eval` module.
+pub fn expr(interp: &mut Interp, expr: &Value, replay: Option<&mut Replay>) -> MoltResult {
+    let value = expr_top_level(interp, expr.as_str(), replay)?;
 
     match value.vtype {
         Type::Int => molt_ok!(Value::from(value.int)),
@@ -277,8 +285,8 @@ pub fn expr(interp: &mut Interp, expr: &Value) -> MoltResult {
 // Expression Internals
 
 /// Provides top-level functionality shared by molt_expr_string, molt_expr_int, etc.
-fn expr_top_level(interp: &mut Interp, string: &str) -> DatumResult {
-    let info = &mut ExprInfo::new(string);
+fn expr_top_level(interp: &mut Interp, string: &str, replay: Option<&mut Replay>) -> DatumResult {
+    let info = &mut ExprInfo::new(string, replay);
 
     let result = expr_get_value(interp, info, -1);
 
@@ -900,7 +908,7 @@ fn expr_lex(interp: &mut Interp, info: &mut ExprInfo) -> DatumResult {
         Some('$') => {
             let mut ctx = EvalPtr::from_tokenizer(&p);
             ctx.set_no_eval(info.no_eval > 0);
-            let var_val = parse_and_eval_variable(interp, &mut ctx)?;
+            let var_val = parse_and_eval_variable(interp, &mut ctx, info.replay.as_deref_mut())?;
             info.token = VALUE;
             info.expr = ctx.to_tokenizer();
             if info.no_eval > 0 {
@@ -912,7 +920,7 @@ fn expr_lex(interp: &mut Interp, info: &mut ExprInfo) -> DatumResult {
         Some('[') => {
             let mut ctx = EvalPtr::from_tokenizer(&p);
             ctx.set_no_eval(info.no_eval > 0);
-            let script_val = parse_and_eval_script(interp, &mut ctx)?;
+            let script_val = parse_and_eval_script(interp, &mut ctx, info.replay.as_deref_mut())?;
             info.token = VALUE;
             info.expr = ctx.to_tokenizer();
             if info.no_eval > 0 {
@@ -924,7 +932,7 @@ fn expr_lex(interp: &mut Interp, info: &mut ExprInfo) -> DatumResult {
         Some('"') => {
             let mut ctx = EvalPtr::from_tokenizer(&p);
             ctx.set_no_eval(info.no_eval > 0);
-            let val = parse_and_eval_quoted_word(interp, &mut ctx)?;
+            let val = parse_and_eval_quoted_word(interp, &mut ctx, info.replay.as_deref_mut())?;
             info.token = VALUE;
             info.expr = ctx.to_tokenizer();
             if info.no_eval > 0 {
@@ -1083,58 +1091,57 @@ fn expr_lex(interp: &mut Interp, info: &mut ExprInfo) -> DatumResult {
             info.token = BIT_NOT;
             Ok(Datum::none())
         }
-        Some(_) => {
-            if p.has(util::is_alphabetic) {
-                let mut str = String::new();
-                while p.has(util::is_alphanumeric) {
-                    str.push(p.next().unwrap());
-                }
+        Some(_) if p.has(util::is_alphabetic) => {
+            let mut str = String::new();
+            while p.has(util::is_alphanumeric) {
+                str.push(p.next().unwrap());
+            }
 
-                // NOTE: Could use get_boolean to test for the boolean constants, but it's
-                // probably overkill.
-                match str.as_ref() {
-                    "true" | "yes" | "on" => {
-                        info.expr = p;
-                        info.token = VALUE;
-                        Ok(Datum::int(1))
-                    }
-                    "false" | "no" | "off" => {
-                        info.expr = p;
-                        info.token = VALUE;
-                        Ok(Datum::int(0))
-                    }
-                    "eq" => {
-                        info.expr = p;
-                        info.token = STRING_EQ;
-                        Ok(Datum::none())
-                    }
-                    "ne" => {
-                        info.expr = p;
-                        info.token = STRING_NE;
-                        Ok(Datum::none())
-                    }
-                    "in" => {
-                        info.expr = p;
-                        info.token = IN;
-                        Ok(Datum::none())
-                    }
-                    "ni" => {
-                        info.expr = p;
-                        info.token = NI;
-                        Ok(Datum::none())
-                    }
-                    _ => {
-                        info.expr = p;
-                        expr_math_func(interp, info, &str)
-                    }
+            // NOTE: Could use get_boolean to test for the boolean constants, but it's
+            // probably overkill.
+            match str.as_ref() {
+                "true" | "yes" | "on" => {
+                    info.expr = p;
+                    info.token = VALUE;
+                    Ok(Datum::int(1))
+                }
+                "false" | "no" | "off" => {
+                    info.expr = p;
+                    info.token = VALUE;
+                    Ok(Datum::int(0))
+                }
+                "eq" => {
+                    info.expr = p;
+                    info.token = STRING_EQ;
+                    Ok(Datum::none())
+                }
+                "ne" => {
+                    info.expr = p;
+                    info.token = STRING_NE;
+                    Ok(Datum::none())
+                }
+                "in" => {
+                    info.expr = p;
+                    info.token = IN;
+                    Ok(Datum::none())
+                }
+                "ni" => {
+                    info.expr = p;
+                    info.token = NI;
+                    Ok(Datum::none())
+                }
+                _ => {
+                    info.expr = p;
+                    expr_math_func(interp, info, &str)
                 }
-            } else {
-                p.skip();
-                info.expr = p;
-                info.token = UNKNOWN;
-                Ok(Datum::none())
             }
         }
+        Some(_) => {
+            p.skip();
+            info.expr = p;
+            info.token = UNKNOWN;
+            Ok(Datum::none())
+        }
         None => {
             p.skip();
             info.expr = p;
@@ -1145,12 +1152,16 @@ fn expr_lex(interp: &mut Interp, info: &mut ExprInfo) -> DatumResult {
 }
 
 // Parses a variable reference.  A bare "$" is an error.
-fn parse_and_eval_variable(interp: &mut Interp, ctx: &mut EvalPtr) -> MoltResult {
+fn parse_and_eval_variable(
+    interp: &mut Interp,
+    ctx: &mut EvalPtr,
+    replay: Option<&mut Replay>,
+) -> MoltResult {
     // FIRST, skip the '$'
     ctx.skip_char('$');
 
     // NEXT, make sure this is really a variable reference.
-    if !ctx.next_is_varname_char() && !ctx.next_is('{') {
+    if !parser::next_is_varname_start(ctx) {
         return molt_err!("invalid character \"$\"");
     }
 
@@ -1160,14 +1171,18 @@ fn parse_and_eval_variable(interp: &mut Interp, ctx: &mut EvalPtr) -> MoltResult
     if ctx.is_no_eval() {
         Ok(Value::empty())
     } else {
-        interp.eval_word(&word)
+        interp.eval_word(&word, replay)
     }
 }
 
 /// Parses and evaluates an interpolated script in Molt input, i.e., a string beginning with
 /// a "[", returning a MoltResult.  If the no_eval flag is set, returns an empty value.
 /// This is used to handled interpolated scripts in expressions.
-fn parse_and_eval_script(interp: &mut Interp, ctx: &mut EvalPtr) -> MoltResult {
+fn parse_and_eval_script(
+    interp: &mut Interp,
+    ctx: &mut EvalPtr,
+    replay: Option<&mut Replay>,
+) -> MoltResult {
     // FIRST, skip the '['
     ctx.skip_char('[');
 
@@ -1179,7 +1194,7 @@ fn parse_and_eval_script(interp: &mut Interp, ctx: &mut EvalPtr) -> MoltResult {
     let result = if ctx.is_no_eval() {
         Ok(Value::empty())
     } else {
-        interp.eval_script(&script)
+        interp.eval_word(&Word::Script(Rc::new(script)), replay)
     };
 
     ctx.set_bracket_term(old_flag);
@@ -1199,13 +1214,17 @@ fn parse_and_eval_script(interp: &mut Interp, ctx: &mut EvalPtr) -> MoltResult {
 /// Parses and evaluates a quoted word in Molt input, i.e., a string beginning with
 /// a double quote, returning a MoltResult.  If the no_eval flag is set, returns an empty
 /// value.  This is used to handle double-quoted strings in expressions.
-fn parse_and_eval_quoted_word(interp: &mut Interp, ctx: &mut EvalPtr) -> MoltResult {
+fn parse_and_eval_quoted_word(
+    interp: &mut Interp,
+    ctx: &mut EvalPtr,
+    replay: Option<&mut Replay>,
+) -> MoltResult {
     let word = parser::parse_quoted_word(ctx)?;
 
     if ctx.is_no_eval() {
         Ok(Value::empty())
     } else {
-        interp.eval_word(&word)
+        interp.eval_word(&word, replay)
     }
 }
 
@@ -1589,21 +1608,21 @@ mod tests {
     fn call_expr() {
         let mut interp = Interp::new();
 
-        let result = expr(&mut interp, &Value::from("1 + 1"));
+        let result = expr(&mut interp, &Value::from("1 + 1"), None);
         assert!(result.is_ok());
         assert_eq!(result.unwrap().as_int().unwrap(), 2);
 
-        let result = expr(&mut interp, &Value::from("1.1 + 1.1"));
+        let result = expr(&mut interp, &Value::from("1.1 + 1.1"), None);
         assert!(result.is_ok());
         let flt: MoltFloat = result.unwrap().as_float().unwrap();
         assert!(near(flt, 2.2));
 
-        let result = expr(&mut interp, &Value::from("[set x foo]"));
+        let result = expr(&mut interp, &Value::from("[set x foo]"), None);
         assert!(result.is_ok());
         assert_eq!(result.unwrap().as_str(), "foo");
     }
 
     fn near(x: MoltFloat, target: MoltFloat) -> bool {
-        x >= target - std::f64::EPSILON && x <= target + std::f64::EPSILON
+        x >= target - f64::EPSILON && x <= target + f64::EPSILON
     }
 }
@@ -0,0 +1,510 @@
+//! String Formatting
+//!
+//! This module implements the `printf`-style formatting used by the `format` command.
+//! It works on top of `core::fmt`, and so is available without the standard library.
+//! The floating-point conversions are only available with the `float` feature.
+
+use crate::molt_err;
+use crate::types::*;
+use crate::value::Value;
+
+use alloc::string::String;
+use core::fmt::Write as _;
+
+#[cfg(feature = "i64")]
+type MoltUInt = u64;
+#[cfg(not(feature = "i64"))]
+type MoltUInt = u32;
+
+/// The largest field width or precision.  `core::fmt` panics on precisions above
+/// `u16::MAX`, and `%e` formats one more digit than the precision.
+const MAX_FIELD: usize = u16::MAX as usize - 1;
+
+/// A parsed field specifier.
+#[derive(Default)]
+struct Spec {
+    left: bool,
+    zero: bool,
+    plus: bool,
+    space: bool,
+    alt: bool,
+    width: usize,
+    precision: Option<usize>,
+}
+
+/// Formats the arguments according to the format string, as for the `format` command.
+pub(crate) fn format(fmt: &str, args: &[Value]) -> Result<String, Exception> {
+    let mut result = String::new();
+    let mut chars = fmt.chars().peekable();
+
+    // The index of the next argument, and whether we've seen XPG `%n$` specifiers
+    // and ordinary ones.
+    let mut next_arg = 0;
+    let mut got_xpg = false;
+    let mut got_sequential = false;
+
+    while let Some(ch) = chars.next() {
+        if ch != '%' {
+            result.push(ch);
+            continue;
+        }
+
+        if chars.peek() == Some(&'%') {
+            chars.next();
+            result.push('%');
+            continue;
+        }
+
+        // FIRST, check for an XPG position, e.g., "%2$d".  A leading "0" is a flag.
+        let mut spec = Spec::default();
+        let mut number = match chars.peek() {
+            Some('1'..='9') => read_number(&mut chars),
+            _ => None,
+        };
+
+        if number.is_some() && chars.peek() == Some(&'$') {
+            chars.next();
+
+            if got_sequential {
+                return mix_error();
+            }
+            got_xpg = true;
+
+            next_arg = number.take().unwrap_or(1) - 1;
+        } else {
+            if got_xpg {
+                return mix_error();
+            }
+            got_sequential = true;
+        }
+
+        // NEXT, get the flags.  A leading number that isn't a position is the width,
+        // so there can't be any flags.
+        if number.is_none() {
+            while let Some(&ch) = chars.peek() {
+                match ch {
+                    '-' => spec.left = true,
+                    '0' => spec.zero = true,
+                    '+' => spec.plus = true,
+                    ' ' => spec.space = true,
+                    '#' => spec.alt = true,
+                    _ => break,
+                }
+                chars.next();
+            }
+        }
+
+        // NEXT, get the width.  A negative `*` width means left-justify.
+        if chars.peek() == Some(&'*') {
+            chars.next();
+            let width = next_int(args, &mut next_arg, got_xpg)?;
+
+            if width < 0 {
+                spec.left = true;
+            }
+            spec.width = usize::try_from(width.unsigned_abs()).unwrap_or(usize::MAX);
+        } else if let Some(width) = number.or_else(|| read_number(&mut chars)) {
+            spec.width = width;
+        }
+
+        if spec.width > MAX_FIELD {
+            return molt_err!("field width too large");
+        }
+
+        // NEXT, get the precision.  A negative `*` precision is ignored.
+        if chars.peek() == Some(&'.') {
+            chars.next();
+
+            if chars.peek() == Some(&'*') {
+                chars.next();
+                let precision = next_int(args, &mut next_arg, got_xpg)?;
+                spec.precision = usize::try_from(precision).ok();
+            } else {
+                spec.precision = Some(read_number(&mut chars).unwrap_or(0));
+            }
+
+            if spec.precision.is_some_and(|precision| precision > MAX_FIELD) {
+                return molt_err!("precision too large");
+            }
+        }
+
+        // NEXT, skip any size modifiers; all integers are MoltInts.
+        while let Some('h' | 'l' | 'L' | 'j' | 'q' | 't' | 'z') = chars.peek() {
+            chars.next();
+        }
+
+        // NEXT, format the argument.
+        let conv = match chars.next() {
+            Some(conv) => conv,
+            None => return molt_err!("format string ended in middle of field specifier"),
+        };
+
+        let field = match conv {
+            'd' | 'i' => {
+                let int = next_int(args, &mut next_arg, got_xpg)?;
+                let sign = if int < 0 { "-" } else { spec.sign() };
+                format_int(&spec, sign, "", int.unsigned_abs() as MoltUInt, 10, false)
+            }
+            'u' => format_int(&spec, "", "", next_uint(args, &mut next_arg, got_xpg)?, 10, false),
+            'x' | 'X' => {
+                let uint = next_uint(args, &mut next_arg, got_xpg)?;
+                let prefix = match (spec.alt && uint != 0, conv) {
+                    (true, 'x') => "0x",
+                    (true, _) => "0X",
+                    _ => "",
+                };
+                format_int(&spec, "", prefix, uint, 16, conv == 'X')
+            }
+            'o' => {
+                let uint = next_uint(args, &mut next_arg, got_xpg)?;
+                let prefix = if spec.alt && uint != 0 { "0" } else { "" };
+                format_int(&spec, "", prefix, uint, 8, false)
+            }
+            'b' => {
+                let uint = next_uint(args, &mut next_arg, got_xpg)?;
+                let prefix = if spec.alt && uint != 0 { "0b" } else { "" };
+                format_int(&spec, "", prefix, uint, 2, false)
+            }
+            'c' => {
+                let int = next_int(args, &mut next_arg, got_xpg)?;
+                let ch = u32::try_from(int).ok().and_then(char::from_u32).unwrap_or('\u{FFFD}');
+                let mut buff = String::new();
+                buff.push(ch);
+                pad(&spec, "", &buff, false)
+            }
+            's' => {
+                let value = next_value(args, &mut next_arg, got_xpg)?;
+                let string = value.as_str();
+
+                match spec.precision {
+                    Some(precision) => {
+                        let truncated: String = string.chars().take(precision).collect();
+                        pad(&spec, "", &truncated, false)
+                    }
+                    None => pad(&spec, "", string, false),
+                }
+            }
+            #[cfg(feature = "float")]
+            'f' | 'e' | 'E' | 'g' | 'G' => {
+                let flt = next_value(args, &mut next_arg, got_xpg)?.as_float()?;
+                format_float(&spec, conv, flt)
+            }
+            _ => {
+                let mut buff = String::new();
+                buff.push(conv);
+                return molt_err!("bad field specifier \"{}\"", buff);
+            }
+        };
+
+        result.push_str(&field);
+    }
+
+    Ok(result)
+}
+
+impl Spec {
+    /// The sign to display for a non-negative number.
+    fn sign(&self) -> &'static str {
+        if self.plus {
+            "+"
+        } else if self.space {
+            " "
+        } else {
+            ""
+        }
+    }
+}
+
+fn mix_error() -> Result<String, Exception> {
+    molt_err!("cannot mix \"%\" and \"%n$\" conversion specifiers")
+}
+
+/// Reads a decimal number from the format string, if there is one.
+fn read_number(chars: &mut core::iter::Peekable<core::str::Chars>) -> Option<usize> {
+    let mut number: Option<usize> = None;
+
+    while let Some(digit) = chars.peek().and_then(|ch| ch.to_digit(10)) {
+        chars.next();
+        number = Some(number.unwrap_or(0).saturating_mul(10).saturating_add(digit as usize));
+    }
+
+    number
+}
+
+/// Gets the next argument, updating the argument index.
+fn next_value<'a>(args: &'a [Value], next_arg: &mut usize, got_xpg: bool) -> Result<&'a Value, Exception> {
+    match args.get(*next_arg) {
+        Some(value) => {
+            *next_arg += 1;
+            Ok(value)
+        }
+        None if got_xpg => molt_err!("\"%n$\" argument index out of range"),
+        None => molt_err!("not enough arguments for all format specifiers"),
+    }
+}
+
+fn next_int(args: &[Value], next_arg: &mut usize, got_xpg: bool) -> Result<MoltInt, Exception> {
+    next_value(args, next_arg, got_xpg)?.as_int()
+}
+
+/// Gets the next argument as an unsigned integer, i.e., a negative integer is
+/// reinterpreted as its two's complement bit pattern.
+fn next_uint(args: &[Value], next_arg: &mut usize, got_xpg: bool) -> Result<MoltUInt, Exception> {
+    Ok(next_int(args, next_arg, got_xpg)? as MoltUInt)
+}
+
+/// Formats an unsigned integer in the given radix.  The sign and prefix precede any
+/// zero padding.
+fn format_int(
+    spec: &Spec,
+    sign: &str,
+    prefix: &str,
+    uint: MoltUInt,
+    radix: u32,
+    upper: bool,
+) -> String {
+    let mut digits = String::new();
+
+    // FIRST, get the digits.  The precision is the minimum number of digits; a zero
+    // value with a zero precision has no digits at all.
+    if uint != 0 || spec.precision != Some(0) {
+        match (radix, upper) {
+            (16, true) => write!(digits, "{:X}", uint),
+            (16, false) => write!(digits, "{:x}", uint),
+            (8, _) => write!(digits, "{:o}", uint),
+            (2, _) => write!(digits, "{:b}", uint),
+            _ => write!(digits, "{}", uint),
+        }
+        .unwrap();
+    }
+
+    if let Some(precision) = spec.precision {
+        while digits.len() < precision {
+            digits.insert(0, '0');
+        }
+    }
+
+    // NEXT, pad to the width.  Zero padding is ignored when there's a precision.
+    let mut head = String::from(sign);
+    head.push_str(prefix);
+    pad(spec, &head, &digits, spec.precision.is_none())
+}
+
+/// Pads the head and body to the field width.  The head is a sign or radix prefix; if
+/// zero padding is allowed and requested, the zeros go between the head and the body.
+fn pad(spec: &Spec, head: &str, body: &str, zero_ok: bool) -> String {
+    let len = head.chars().count() + body.chars().count();
+    let fill = spec.width.saturating_sub(len);
+    let mut result = String::with_capacity(len + fill);
+
+    if spec.left {
+        result.push_str(head);
+        result.push_str(body);
+        result.extend(core::iter::repeat_n(' ', fill));
+    } else if spec.zero && zero_ok {
+        result.push_str(head);
+        result.extend(core::iter::repeat_n('0', fill));
+        result.push_str(body);
+    } else {
+        result.extend(core::iter::repeat_n(' ', fill));
+        result.push_str(head);
+        result.push_str(body);
+    }
+
+    result
+}
+
+/// Formats a floating-point number for the `f`, `e`, and `g` conversions.
+#[cfg(feature = "float")]
+fn format_float(spec: &Spec, conv: char, flt: MoltFloat) -> String {
+    let sign = if flt.is_sign_negative() && !flt.is_nan() { "-" } else { spec.sign() };
+
+    if !flt.is_finite() {
+        let body = if flt.is_nan() { "NaN" } else { "Inf" };
+        return pad(spec, sign, body, false);
+    }
+
+    let flt = flt.abs();
+    let precision = spec.precision.unwrap_or(6);
+    let mut body = String::new();
+
+    match conv {
+        'f' => {
+            write!(body, "{:.*}", precision, flt).unwrap();
+            if spec.alt && precision == 0 {
+                body.push('.');
+            }
+        }
+        'e' | 'E' => body = format_exp(flt, precision, spec.alt, conv == 'E'),
+        _ => {
+            // %g uses %e if the exponent is less than -4 or not less than the
+            // precision, and %f otherwise, with the precision giving the number of
+            // significant digits.  Trailing zeros are removed unless "#" is given.
+            let precision = precision.max(1);
+            let exp = exponent(flt, precision - 1);
+
+            if exp < -4 || exp >= precision as i32 {
+                body = format_exp(flt, precision - 1, spec.alt, conv == 'G');
+            } else {
+                let decimals = (precision as i32 - 1 - exp) as usize;
+                write!(body, "{:.*}", decimals, flt).unwrap();
+                if spec.alt && decimals == 0 {
+                    body.push('.');
+                }
+            }
+
+            if !spec.alt {
+                body = strip_zeros(&body);
+            }
+        }
+    }
+
+    pad(spec, sign, &body, true)
+}
+
+/// Formats a non-negative float in exponential notation, C-style: the exponent always
+/// has a sign and at least two digits.
+#[cfg(feature = "float")]
+fn format_exp(flt: MoltFloat, precision: usize, alt: bool, upper: bool) -> String {
+    let mut buff = String::new();
+    write!(buff, "{:.*e}", precision, flt).unwrap();
+
+    // Rust produces, e.g., "1.5e-7" or "1.5e7".
+    let (mantissa, exp) = buff.split_once('e').unwrap_or((&buff, "0"));
+    let (exp_sign, exp_digits) = match exp.strip_prefix('-') {
+        Some(digits) => ('-', digits),
+        None => ('+', exp),
+    };
+
+    let mut result = String::from(mantissa);
+    if alt && precision == 0 {
+        result.push('.');
+    }
+    result.push(if upper { 'E' } else { 'e' });
+    result.push(exp_sign);
+    if exp_digits.len() < 2 {
+        result.push('0');
+    }
+    result.push_str(exp_digits);
+    result
+}
+
+/// Gets the decimal exponent of a non-negative float when rounded to the given
+/// number of decimal places in exponential notation.
+#[cfg(feature = "float")]
+fn exponent(flt: MoltFloat, precision: usize) -> i32 {
+    let mut buff = String::new();
+    write!(buff, "{:.*e}", precision, flt).unwrap();
+
+    buff.split_once('e')
+        .and_then(|(_, exp)| exp.parse().ok())
+        .unwrap_or(0)
+}
+
+/// Removes trailing zeros after the decimal point, and the decimal point itself if
+/// nothing follows it, preserving any exponent.
+#[cfg(feature = "float")]
+fn strip_zeros(body: &str) -> String {
+    let (mantissa, exp) = match body.find(['e', 'E']) {
+        Some(i) => body.split_at(i),
+        None => (body, ""),
+    };
+
+    let mut result = String::from(mantissa);
+
+    if result.contains('.') {
+        let trimmed = result.trim_end_matches('0').trim_end_matches('.').len();
+        result.truncate(trimmed);
+    }
+
+    result.push_str(exp);
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec::Vec;
+
+    fn fmt(fmt: &str, args: &[&'static str]) -> String {
+        let args: Vec<Value> = args.iter().map(|arg| Value::from(*arg)).collect();
+        format(fmt, &args).unwrap()
+    }
+
+    #[test]
+    fn test_format_int() {
+        assert_eq!(fmt("%d|%i", &["42", "-42"]), "42|-42");
+        assert_eq!(fmt("%5d|%-5d|%05d", &["42", "42", "-42"]), "   42|42   |-0042");
+        assert_eq!(fmt("%+d|% d|%+d", &["42", "42", "-42"]), "+42| 42|-42");
+        assert_eq!(fmt("%.4d|%6.4d|%06.4d", &["42", "42", "42"]), "0042|  0042|  0042");
+        assert_eq!(fmt("%.0d|%d", &["0", "0"]), "|0");
+        assert_eq!(fmt("%x|%X|%#x|%#X", &["255", "255", "255", "0"]), "ff|FF|0xff|0");
+        assert_eq!(fmt("%o|%#o|%b|%#b", &["8", "8", "5", "5"]), "10|010|101|0b101");
+        assert_eq!(fmt("%#06x", &["255"]), "0x00ff");
+        assert_eq!(fmt("%u", &["42"]), "42");
+    }
+
+    #[cfg(feature = "i64")]
+    #[test]
+    fn test_format_unsigned() {
+        assert_eq!(fmt("%x|%u", &["-1", "-1"]), "ffffffffffffffff|18446744073709551615");
+        assert_eq!(format("%d", &[Value::from(MoltInt::MIN)]).unwrap(), "-9223372036854775808");
+    }
+
+    #[test]
+    fn test_format_str() {
+        assert_eq!(fmt("%s|%5s|%-5s|%.2s", &["abc", "abc", "abc", "abc"]), "abc|  abc|abc  |ab");
+        assert_eq!(fmt("%3s|%.1s", &["αβ", "αβ"]), " αβ|α");
+        assert_eq!(fmt("%c%c|%3c", &["65", "946", "66"]), "Aβ|  B");
+        assert_eq!(fmt("100%%", &[]), "100%");
+    }
+
+    #[test]
+    fn test_format_star_and_xpg() {
+        assert_eq!(fmt("%*d|%-*d|%.*s", &["4", "1", "-4", "2", "2", "abc"]), "   1|2   |ab");
+        assert_eq!(fmt("%2$s %1$s", &["a", "b"]), "b a");
+        assert_eq!(fmt("%1$s %1$s", &["a"]), "a a");
+        assert_eq!(fmt("%1$*d", &["3", "7"]), "  7");
+    }
+
+    #[cfg(feature = "float")]
+    #[test]
+    fn test_format_float() {
+        assert_eq!(fmt("%f|%.2f|%8.3f|%-8.1f|", &["1.5", "3.14159", "2.5", "2"]), "1.500000|3.14|   2.500|2.0     |");
+        assert_eq!(fmt("%+.1f|%08.2f|%.0f|%#.0f", &["1", "-1.5", "2.5", "3"]), "+1.0|-0001.50|2|3.");
+        assert_eq!(fmt("%e|%.2E|%.0e", &["12345.678", "0.00012", "5"]), "1.234568e+04|1.20E-04|5e+00");
+        assert_eq!(fmt("%e", &["1e100"]), "1.000000e+100");
+        assert_eq!(fmt("%g|%g|%g|%g", &["100000", "1000000", "0.0001", "0.00001"]), "100000|1e+06|0.0001|1e-05");
+        assert_eq!(fmt("%g|%.3g|%#g|%G", &["1.5", "3.14159", "1.5", "1e-10"]), "1.5|3.14|1.50000|1E-10");
+        assert_eq!(fmt("%g|%.0g", &["0", "123"]), "0|1e+02");
+        assert_eq!(fmt("%f|%5.1f|%g", &["inf", "-inf", "nan"]), "Inf| -Inf|NaN");
+    }
+
+    #[test]
+    fn test_format_errors() {
+        let args = [Value::from("x")];
+        assert_eq!(format("%d", &[]), molt_err!("not enough arguments for all format specifiers"));
+        assert_eq!(format("%d", &args), molt_err!("expected integer but got \"x\""));
+        assert_eq!(format("%y", &args), molt_err!("bad field specifier \"y\""));
+        assert_eq!(format("%5", &args), molt_err!("format string ended in middle of field specifier"));
+        assert_eq!(format("%2$s", &args), molt_err!("\"%n$\" argument index out of range"));
+        assert_eq!(
+            format("%1$s %s", &args),
+            molt_err!("cannot mix \"%\" and \"%n$\" conversion specifiers")
+        );
+        assert_eq!(
+            format("%s %1$s", &args),
+            molt_err!("cannot mix \"%\" and \"%n$\" conversion specifiers")
+        );
+        assert_eq!(format("%99999999999999999999d", &args), molt_err!("field width too large"));
+        assert_eq!(
+            format("%*d", &[Value::from(MoltInt::MIN), Value::from(1)]),
+            molt_err!("field width too large")
+        );
+        assert_eq!(format("%.70000f", &args), molt_err!("precision too large"));
+        assert_eq!(
+            format("%.*s", &[Value::from(MoltInt::MAX), Value::from("x")]),
+            molt_err!("precision too large")
+        );
+    }
+}
@@ -367,24 +367,42 @@
 
 use crate::check_args;
 use crate::commands;
+#[cfg(feature = "coroutines")]
+use crate::coroutine::{Context, Coroutine};
 #[cfg(feature = "dict")]
 use crate::dict::dict_new;
+use crate::eval::{CommandWords, ControlFunc, EvalFrame, Replay, ScriptKind, Step};
 #[cfg(feature = "expr")]
 use crate::expr;
 use crate::molt_err;
 use crate::molt_ok;
+#[cfg(feature = "namespaces")]
+use crate::namespace::{self, Namespace};
 use crate::parser;
 use crate::parser::Script;
+use crate::parser::SubstFlags;
 use crate::parser::Word;
+#[cfg(feature = "coroutines")]
+use crate::scope;
 use crate::scope::ScopeStack;
+#[cfg(feature = "traces")]
+use crate::trace::{ExecTrace, ExecTraceFunc, TraceCallback, VarTrace, VarTraceFunc};
 use crate::types::*;
+#[cfg(feature = "namespaces")]
+use crate::util;
 use crate::value::Value;
 use alloc::rc::Rc;
+use alloc::borrow::Cow;
 use alloc::borrow::ToOwned as _;
 use alloc::string::String;
+#[cfg(any(all(feature = "namespaces", feature = "info"), feature = "traces"))]
+use alloc::string::ToString as _;
 use alloc::vec::Vec;
-#[cfg(feature = "closure-commands")]
+use core::ops::ControlFlow;
 use alloc::boxed::Box;
+#[cfg(feature = "traces")]
+use core::cell::Cell;
+#[cfg(any(feature = "error-stack-trace", feature = "traces"))]
 use alloc::format;
 use indexmap::IndexMap;
 
@@ -425,12 +443,47 @@ pub struct Interp {
     // Variable Table
     scopes: ScopeStack,
 
+    // Namespace Table, by key; the global namespace is "".
+    #[cfg(feature = "namespaces")]
+    namespaces: IndexMap<String, Namespace, MoltHasher>,
+
+    // Execution Traces, by command key.
+    #[cfg(feature = "traces")]
+    exec_traces: IndexMap<String, Vec<Rc<ExecTrace>>, MoltHasher>,
+
+    // The ID of the next variable or execution trace.
+    #[cfg(feature = "traces")]
+    next_trace_id: usize,
+
+    // The call frame stack, for `info level` and `info frame`.
+    #[cfg(feature = "info")]
+    frames: Vec<Frame>,
+
+    // The number of commands executed, for `info cmdcount`.
+    #[cfg(feature = "info")]
+    cmd_count: usize,
+
     // Defines the recursion limit for Interp::eval().
     recursion_limit: usize,
 
     // Current number of eval levels.
     num_levels: usize,
 
+    // The pending tail calls of the procedures and lambdas being executed, innermost last.
+    tailcalls: Vec<Option<Box<TailCall>>>,
+
+    // The evaluator's stack of frames; see the `eval` module.
+    eval_frames: Vec<EvalFrame>,
+
+    // The number of runs of the evaluator in progress.
+    #[cfg(feature = "coroutines")]
+    eval_depth: usize,
+
+    // The keys of the coroutines being run, innermost last, each with the number of runs
+    // of the evaluator in progress when it is run.
+    #[cfg(feature = "coroutines")]
+    coroutines: Vec<(String, usize)>,
+
     // Profile Map
     #[cfg(feature = "std")]
     profile_map: IndexMap<String, ProfileRecord, MoltHasher>,
@@ -446,21 +499,27 @@ enum Command {
 
     /// A Molt procedure
     Proc(Procedure),
+
+    /// A control structure, executed by the evaluator.
+    Control(ControlFunc),
+
+    /// A coroutine.
+    #[cfg(feature = "coroutines")]
+    Coroutine(Coroutine),
 }
 
 #[cfg(feature = "closure-commands")]
 type BoxedClosure  = Box<dyn Fn(&mut Interp, &[Value]) -> Result<Option<Value>, Exception>>;
 
 impl Command {
-    /// Execute the command according to its kind.
-    fn execute(&self, interp: &mut Interp, argv: &[Value]) -> MoltResult {
+    /// Executes the command, if it is native; other commands are started by the
+    /// evaluator.
+    fn execute(&self, interp: &mut Interp, argv: &[Value]) -> Option<MoltResult> {
         match self {
-            Command::Native(func) => {
-                Ok(func(interp, argv)?.unwrap_or_default())
-            }
+            Command::Native(func) => Some(func(interp, argv).map(Option::unwrap_or_default)),
             #[cfg(feature = "closure-commands")]
-            Command::Closure(func) => Ok(func(interp, argv)?.unwrap_or_default()),
-            Command::Proc(proc) => proc.execute(interp, argv),
+            Command::Closure(func) => Some(func(interp, argv).map(Option::unwrap_or_default)),
+            _ => None,
         }
     }
 
@@ -471,6 +530,9 @@ impl Command {
             #[cfg(feature = "closure-commands")]
             Command::Closure(_) => Value::from("closure"),
             Command::Proc(_) => Value::from("proc"),
+            Command::Control(_) => Value::from("native"),
+            #[cfg(feature = "coroutines")]
+            Command::Coroutine(_) => Value::from("coroutine"),
         }
     }
 
@@ -480,6 +542,19 @@ impl Command {
     }
 }
 
+/// A command to be called in place of a procedure or lambda, as scheduled by `tailcall`.
+pub(crate) struct TailCall {
+    /// The command's key in the command table.
+    #[cfg(feature = "traces")]
+    key: String,
+
+    /// The command, as resolved when `tailcall` was called.
+    cmd: Rc<Command>,
+
+    /// The command's words.
+    words: MoltList,
+}
+
 #[cfg(feature = "std")]
 struct ProfileRecord {
     count: u128,
@@ -515,11 +590,30 @@ impl Interp {
             recursion_limit: 1000,
             commands: IndexMap::default(),
             scopes: ScopeStack::new(),
+            #[cfg(feature = "namespaces")]
+            namespaces: IndexMap::default(),
+            #[cfg(feature = "traces")]
+            exec_traces: IndexMap::default(),
+            #[cfg(feature = "traces")]
+            next_trace_id: 0,
+            #[cfg(feature = "info")]
+            frames: Vec::new(),
+            #[cfg(feature = "info")]
+            cmd_count: 0,
             num_levels: 0,
+            tailcalls: Vec::new(),
+            eval_frames: Vec::new(),
+            #[cfg(feature = "coroutines")]
+            eval_depth: 0,
+            #[cfg(feature = "coroutines")]
+            coroutines: Vec::new(),
             #[cfg(feature = "std")]
             profile_map: IndexMap::default(),
         };
 
+        #[cfg(feature = "namespaces")]
+        interp.namespaces.insert(String::new(), Namespace::default());
+
         interp.set_scalar("errorInfo", Value::empty()).map_err(|_| ()).unwrap();
         interp
     }
@@ -550,7 +644,7 @@ impl Interp {
         static NEW_COMMANDS: &[(&str, CommandFunc)] = &[
             ("append", commands::cmd_append),
             ("break", commands::cmd_break),
-            ("catch", commands::cmd_catch),
+            ("concat", commands::cmd_concat),
             ("continue", commands::cmd_continue),
             ("error", commands::cmd_error),
             ("global", commands::cmd_global),
@@ -559,28 +653,50 @@ impl Interp {
             ("incr", commands::cmd_incr),
             ("join", commands::cmd_join),
             ("lappend", commands::cmd_lappend),
+            ("lassign", commands::cmd_lassign),
             ("lindex", commands::cmd_lindex),
+            ("linsert", commands::cmd_linsert),
             ("list", commands::cmd_list),
             ("llength", commands::cmd_llength),
+            ("lrange", commands::cmd_lrange),
+            ("lrepeat", commands::cmd_lrepeat),
+            ("lreplace", commands::cmd_lreplace),
+            ("lreverse", commands::cmd_lreverse),
+            ("lsearch", commands::cmd_lsearch),
+            ("lset", commands::cmd_lset),
+            ("lsort", commands::cmd_lsort),
             ("proc", commands::cmd_proc),
             ("rename", commands::cmd_rename),
             ("return", commands::cmd_return),
             ("set", commands::cmd_set),
+            ("split", commands::cmd_split),
+            ("tailcall", commands::cmd_tailcall),
             ("throw", commands::cmd_throw),
             ("unset", commands::cmd_unset),
-            ("foreach", commands::cmd_foreach),
-            ("for", commands::cmd_for),
-            ("if", commands::cmd_if),
-            ("while", commands::cmd_while),
+            ("upvar", commands::cmd_upvar),
 
             #[cfg(feature = "string-command")]
             ("string", commands::cmd_string),
 
-            #[cfg(feature = "expr")]
-            ("expr", commands::cmd_expr),
+            #[cfg(feature = "format-command")]
+            ("format", commands::cmd_format),
+            #[cfg(feature = "scan-command")]
+            ("scan", commands::cmd_scan),
+
+            #[cfg(feature = "regex")]
+            ("regexp", commands::cmd_regexp),
+            #[cfg(feature = "regex")]
+            ("regsub", commands::cmd_regsub),
+
+            #[cfg(feature = "namespaces")]
+            ("variable", commands::cmd_variable),
+
+            #[cfg(feature = "traces")]
+            ("trace", commands::cmd_trace),
+
+            #[cfg(feature = "coroutines")]
+            ("coroutine", commands::cmd_coroutine),
 
-            #[cfg(feature = "dict")]
-            ("dict", commands::cmd_dict),
             #[cfg(feature = "info")]
             ("info", commands::cmd_info),
 
@@ -605,6 +721,43 @@ impl Interp {
             interp.add_command(name, func);
         }
 
+        // Control structures evaluate scripts and expressions of their own, and so are
+        // executed by the evaluator.
+        static CONTROL_COMMANDS: &[(&str, ControlFunc)] = &[
+            ("apply", commands::cmd_apply),
+            ("catch", commands::cmd_catch),
+            ("eval", commands::cmd_eval),
+            ("for", commands::cmd_for),
+            ("foreach", commands::cmd_foreach),
+            ("if", commands::cmd_if),
+            ("lmap", commands::cmd_lmap),
+            ("subst", commands::cmd_subst),
+            ("try", commands::cmd_try),
+            ("uplevel", commands::cmd_uplevel),
+            ("while", commands::cmd_while),
+
+            #[cfg(feature = "switch-command")]
+            ("switch", commands::cmd_switch),
+
+            #[cfg(feature = "expr")]
+            ("expr", commands::cmd_expr),
+
+            #[cfg(feature = "dict")]
+            ("dict", commands::cmd_dict),
+
+            #[cfg(feature = "namespaces")]
+            ("namespace", commands::cmd_namespace),
+
+            #[cfg(feature = "coroutines")]
+            ("yield", commands::cmd_yield),
+            #[cfg(feature = "coroutines")]
+            ("yieldto", commands::cmd_yieldto),
+        ];
+
+        for &(name, func) in CONTROL_COMMANDS {
+            interp.insert_command(name, Rc::new(Command::Control(func)));
+        }
+
         // Populate the environment variable.
         // TODO: Really should be a "linked" variable, where sets to it are tracked and
         // written back to the environment.
@@ -692,19 +845,8 @@ impl Interp {
         // Tricky, though.  Don't want to have to parse it as a list.  Need a quick way
         // to determine if something is already a list.  (Might need two methods!)
 
-        // FIRST, check the number of nesting levels
-        self.num_levels += 1;
-
-        if self.num_levels > self.recursion_limit {
-            self.num_levels -= 1;
-            return molt_err!("too many nested calls to Interp::eval (infinite loop?)");
-        }
-
-        // NEXT, evaluate the script and translate the result to Ok or Error
-        let mut result = self.eval_script(&*value.as_script()?);
-
-        // NEXT, decrement the number of nesting levels.
-        self.num_levels -= 1;
+        // FIRST, evaluate the script, checking the number of nesting levels.
+        let mut result = self.eval_nested(|interp| interp.push_body(value));
 
         // NEXT, translate and return the result.
         if self.num_levels == 0 {
@@ -723,120 +865,235 @@ impl Interp {
                     // TODO: Better error message
                     ResultCode::Other(_) => molt_err!("unexpected result code."),
                 };
-            }
-        }
 
-        if let Err(exception) = &result {
-            if exception.is_error() {
-                self.set_global_error_data(exception.error_data())?;
+                // NEXT, save the error data.  The script's own errors were saved when it
+                // returned, but the translation can produce new ones.
+                if let Err(exception) = &result {
+                    if exception.is_error() {
+                        self.set_global_error_data(exception.error_data())?;
+                    }
+                }
             }
         }
 
         result
     }
 
-    /// Saves the error exception data
-    fn set_global_error_data(&mut self, error_data: Option<&ErrorData>) -> Result<(), Exception> {
-        if let Some(data) = error_data {
-            // TODO: Might want a public method for this.  Or, if I implement namespaces, that's
-            // sufficient.
-            self.scopes.set_global("errorInfo", data.error_info())?;
-            self.scopes.set_global("errorCode", data.error_code())?;
-        }
+    /// Evaluates a script in the scope at the given level, as the `uplevel` command does.
+    /// The level is the absolute level, not the level relative to the current stack level,
+    /// i.e., level=0 is the global scope.  The scope at that level is temporarily made the
+    /// current scope; procedures called by the script are called from that scope.
+    ///
+    /// The result is as for [`eval_value`](#method.eval_value); in particular, when called
+    /// from within a command, `return`, `break`, and `continue` propagate to the caller
+    /// just as they would from a script evaluated at the current level.
+    ///
+    /// Panics if the level is greater than the current scope level.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use remolt::Interp;
+    /// use remolt::types::*;
+    /// use remolt::molt_ok;
+    /// # fn dummy() -> MoltResult {
+    /// let mut interp = Interp::new();
+    ///
+    /// interp.push_scope();
+    /// interp.eval_at_level(0, &Value::from("set a 1"))?;
+    /// interp.pop_scope();
+    ///
+    /// assert_eq!(interp.scalar("a")?.as_int()?, 1);
+    /// # molt_ok!()
+    /// # }
+    /// ```
+    pub fn eval_at_level(&mut self, level: usize, script: &Value) -> MoltResult {
+        let index = self.scopes.index_of_level(level);
+        let saved = self.scopes.set_current(index);
+        let result = self.eval_value(script);
+        self.scopes.set_current(saved);
+        result
+    }
 
-        Ok(())
+    /// Calls a command given its name and arguments as a list of words, without parsing
+    /// them as a script; the first word is the command name.  Returns the command's
+    /// result, or the empty string if the list of words is empty.
+    ///
+    /// Use this to call a command prefix provided by the caller, e.g., the comparison
+    /// command given to `lsort -command`, with additional arguments.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use remolt::Interp;
+    /// use remolt::types::*;
+    /// use remolt::molt_ok;
+    /// # fn dummy() -> MoltResult {
+    /// let mut interp = Interp::new();
+    ///
+    /// let words = [Value::from("list"), Value::from("a b"), Value::from("c")];
+    /// let result = interp.call(&words)?;
+    ///
+    /// assert_eq!(result.as_str(), "{a b} c");
+    /// # molt_ok!()
+    /// # }
+    /// ```
+    pub fn call(&mut self, words: &[Value]) -> MoltResult {
+        self.eval_nested(|interp| interp.start_words(words))
     }
 
-    /// Evaluates a parsed Script, producing a normal MoltResult.
-    /// Also used by expr.rs.
-    pub(crate) fn eval_script(&mut self, script: &Script) -> MoltResult {
-        let mut result_value = None;
+    /// Schedules a tail call, as the `tailcall` command does: when the body of the
+    /// innermost procedure or lambda returns, the command will be called in its place.  The
+    /// command is resolved in the current namespace.  Returns the exception that ends the
+    /// body.
+    pub(crate) fn tailcall(&mut self, words: &[Value]) -> MoltResult {
+        if self.tailcalls.is_empty() {
+            return molt_err!("tailcall can only be called from a proc or lambda");
+        }
 
-        for word_vec in script.commands() {
-            let words = self.eval_word_vec(word_vec.words())?;
+        let name = words[0].as_str();
+        let (_key, cmd) = match self.find_command(name) {
+            Some(found) => found,
+            None => return molt_err!("invalid command name \"{}\"", name),
+        };
 
-            if words.is_empty() {
-                break;
-            }
+        let call = Box::new(TailCall {
+            #[cfg(feature = "traces")]
+            key: _key.clone(),
+            cmd: Rc::clone(cmd),
+            words: words.to_vec(),
+        });
+        *self.tailcalls.last_mut().unwrap() = Some(call);
 
-            let name = words[0].as_str();
+        Err(Exception::molt_return(Value::empty()))
+    }
 
-            let cmd = self.commands.get(name)
-                .ok_or_else(|| Exception::molt_err(Value::from(format!("invalid command name \"{}\"", name))))?;
+    /// Performs backslash, variable, and command substitution on the string value of the
+    /// template, as the `subst` command does, and returns the result.  The parsed template
+    /// is cached in the `Value`, so rendering the same template repeatedly is efficient.
+    ///
+    /// A `break` in a command substitution ends the substitution, returning the result so
+    /// far; a `continue` substitutes the empty string.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use remolt::Interp;
+    /// use remolt::types::*;
+    /// use remolt::molt_ok;
+    /// # fn dummy() -> MoltResult {
+    /// let mut interp = Interp::new();
+    ///
+    /// interp.set_scalar("name", Value::from("pump"))?;
+    /// let template = Value::from("$name: [expr {2 + 2}] bar");
+    /// let result = interp.subst(&template)?;
+    ///
+    /// assert_eq!(result.as_str(), "pump: 4 bar");
+    /// # molt_ok!()
+    /// # }
+    /// ```
+    pub fn subst(&mut self, template: &Value) -> MoltResult {
+        self.subst_with(template, SubstFlags::ALL, None)
+    }
 
-            // let start = Instant::now();
-            let cmd = Rc::clone(cmd);
-            let result = cmd.execute(self, words.as_slice());
-            // self.profile_save(&format!("cmd.execute({})", name), start);
+    /// Performs the substitutions enabled by the flags on the template.  With a replay, the
+    /// substitutions are replayed from it, or made and recorded in it; see the `eval`
+    /// module.  Used by the `subst` command.
+    pub(crate) fn subst_with(
+        &mut self,
+        template: &Value,
+        flags: SubstFlags,
+        mut replay: Option<&mut Replay>,
+    ) -> MoltResult {
+        let subst = template.as_subst(flags)?;
+        let mut result = String::new();
+
+        for word in subst.words() {
+            match self.eval_word(word, replay.as_deref_mut()) {
+                Ok(value) => result.push_str(value.as_str()),
+                Err(exception) => match exception.code() {
+                    ResultCode::Break => break,
+                    ResultCode::Continue => (),
+                    ResultCode::Error => return Err(exception),
+                    _ => result.push_str(exception.value().as_str()),
+                },
+            }
+        }
 
-            match result {
-                Ok(v) => result_value = Some(v),
-                #[cfg(feature = "error-stack-trace")]
-                Err(mut exception) if exception.code() == ResultCode::Error => {
-                    // TODO: I think this needs to be done up above.
-                    // // Handle the return -code, -level protocol
-                    // if exception.code() == ResultCode::Return {
-                    //     exception.decrement_level();
-                    // }
-
-                    // FIRST, new error, an error from within a proc, or an error from
-                    // within some other body (ignored).
-                    if exception.is_new_error() {
-                        exception.add_error_info("    while executing");
-                    } else if cmd.is_proc() {
-                        exception.add_error_info("    invoked from within");
-                        exception.add_error_info(&format!(
-                                "    (procedure \"{}\" line TODO)",
-                                name
-                                ));
-                    } else {
-                        return Err(exception);
-                    }
+        molt_ok!(result)
+    }
 
-                    // TODO: Add command.  In standard TCL, this is the text of the command
-                    // before interpolation; at present, we don't have that info in a
-                    // convenient form.  For now, just convert the final words to a string.
-                    exception.add_error_info(&format!("\"{}\"", &crate::list::list_to_string(&words)));
-                    return Err(exception);
-                }
-                Err(e) => return Err(e),
-            }
+    /// Saves the error exception data
+    fn set_global_error_data(&mut self, error_data: Option<&ErrorData>) -> Result<(), Exception> {
+        if let Some(data) = error_data {
+            // TODO: Might want a public method for this.  Or, if I implement namespaces, that's
+            // sufficient.
+            self.scopes.set_global("errorInfo", data.error_info())?;
+            self.scopes.set_global("errorCode", data.error_code())?;
         }
 
-        Ok(result_value.unwrap_or_default())
+        Ok(())
     }
 
     /// Evaluates a WordVec, producing a list of Values.  The expansion operator is handled
     /// as a special case.
-    fn eval_word_vec(&mut self, words: &[Word]) -> Result<MoltList, Exception> {
+    fn eval_word_vec(
+        &mut self,
+        words: &[Word],
+        mut replay: Option<&mut Replay>,
+    ) -> Result<MoltList, Exception> {
         let mut list: MoltList = Vec::new();
 
         for word in words {
-            if let Word::Expand(word_to_expand) = word {
-                let value = self.eval_word(word_to_expand)?;
-                for val in &*value.as_list()? {
-                    list.push(val.clone());
-                }
-            } else {
-                list.push(self.eval_word(word)?);
-            }
+            self.eval_word_into(word, &mut list, replay.as_deref_mut())?;
         }
 
         Ok(list)
     }
 
-    /// Evaluates a single word, producing a value.  This is also used by expr.rs.
-    pub(crate) fn eval_word(&mut self, word: &Word) -> MoltResult {
+    /// Evaluates a word of a command, adding its value to the list, or its elements if it
+    /// is expanded.
+    fn eval_word_into(
+        &mut self,
+        word: &Word,
+        list: &mut MoltList,
+        replay: Option<&mut Replay>,
+    ) -> Result<(), Exception> {
+        if let Word::Expand(word_to_expand) = word {
+            let value = self.eval_word(word_to_expand, replay)?;
+            for val in &*value.as_list()? {
+                list.push(val.clone());
+            }
+        } else {
+            list.push(self.eval_word(word, replay)?);
+        }
+
+        Ok(())
+    }
+
+    /// Evaluates a single word, producing a value.  With a replay, the word's variable and
+    /// command substitutions are replayed from it, or made and recorded in it; see the
+    /// `eval` module.  This is also used by expr.rs.
+    pub(crate) fn eval_word(&mut self, word: &Word, mut replay: Option<&mut Replay>) -> MoltResult {
         match word {
             Word::Value(val) => Ok(val.clone()),
-            Word::VarRef(name) => self.scalar(name),
+            Word::VarRef(name) => match replay {
+                Some(replay) => replay.variable(|| self.scalar(name)),
+                None => self.scalar(name),
+            },
             Word::ArrayRef(name, index_word) => {
-                let index = self.eval_word(index_word)?;
-                self.element(name, index.as_str())
+                let index = self.eval_word(index_word, replay.as_deref_mut())?;
+                match replay {
+                    Some(replay) => replay.variable(|| self.element(name, index.as_str())),
+                    None => self.element(name, index.as_str()),
+                }
             }
-            Word::Script(script) => self.eval_script(script),
+            Word::Script(script) => match replay {
+                Some(replay) => replay.script(script),
+                None => self.eval_script(script),
+            },
             Word::Tokens(tokens) => {
-                let tlist = self.eval_word_vec(tokens)?;
+                let tlist = self.eval_word_vec(tokens, replay)?;
                 let string: String = tlist.iter().map(|i| i.as_str()).collect();
                 Ok(Value::from(string))
             }
@@ -939,8 +1196,13 @@ impl Interp {
     #[cfg(feature = "expr")]
     pub fn expr(&mut self, expr: &Value) -> MoltResult {
         // Evaluate the expression and set the errorInfo/errorCode.
-        let result = expr::expr(self, expr);
+        let result = expr::expr(self, expr, None);
+        self.expr_result(result)
+    }
 
+    /// Saves the error data of an expression's result, if it's an error.
+    #[cfg(feature = "expr")]
+    fn expr_result(&mut self, result: MoltResult) -> MoltResult {
         if let Err(exception) = &result {
             self.set_global_error_data(exception.error_data())?;
         }
@@ -1025,106 +1287,755 @@ impl Interp {
     }
 
     //--------------------------------------------------------------------------------------------
-    // Variable Handling
+    // The Evaluator
+    //
+    // See the `eval` module for an overview.
 
-    /// Retrieves the value of the named variable in the current scope.  The `var_name` may
-    /// name a scalar variable or an array element.  This is the normal way to retrieve the
-    /// value of a variable named by a command argument.
-    ///
-    /// Returns an error if the variable is a scalar and the name names an array element,
-    /// and vice versa.
-    ///
-    /// # Example
-    ///
-    /// ```
-    /// use remolt::types::*;
-    /// use remolt::Interp;
-    /// use remolt::molt_ok;
-    /// # fn dummy() -> MoltResult {
-    /// let mut interp = Interp::new();
-    ///
-    /// // Set the value of the scalar variable "a" using a script.
-    /// interp.eval("set a 1")?;
-    ///
-    /// // The value of the scalar variable "a".
-    /// let val = interp.var(&Value::from("a"))?;
-    /// assert_eq!(val.as_str(), "1");
-    ///
-    /// // Set the value of the array element "b(1)" using a script.
-    /// interp.eval("set b(1) Howdy")?;
-    ///
-    /// // The value of the array element "b(1)":
-    /// let val = interp.var(&Value::from("b(1)"))?;
-    /// assert_eq!(val.as_str(), "Howdy");
-    /// # molt_ok!()
-    /// # }
-    /// ```
-    pub fn var(&self, var_name: &Value) -> MoltResult {
-        let var_name = &*var_name.as_var_name();
-        match var_name.index() {
-            Some(index) => self.element(var_name.name(), index),
-            None => self.scalar(var_name.name()),
+    /// Runs the evaluator on the frames pushed by `start` until they are done, and returns
+    /// the result.  This is how Rust code evaluates scripts: the run is nested within the
+    /// run, if any, that called the Rust code.
+    fn eval_nested(&mut self, start: impl FnOnce(&mut Self) -> Step) -> MoltResult {
+        let base = self.eval_frames.len();
+
+        #[cfg(feature = "coroutines")]
+        {
+            self.eval_depth += 1;
         }
-    }
 
-    /// Returns 1 if the named variable is defined and exists, and 0 otherwise.
-    pub fn var_exists(&self, var_name: &Value) -> bool {
-        let var_name = &*var_name.as_var_name();
-        match var_name.index() {
-            Some(index) => self.scopes.elem_exists(var_name.name(), index),
-            None => self.scopes.exists(var_name.name()),
+        let step = match start(self) {
+            Step::Deliver(result) if self.eval_frames.len() == base => Step::Deliver(result),
+            step => self.run(base, step),
+        };
+
+        #[cfg(feature = "coroutines")]
+        {
+            self.eval_depth -= 1;
+        }
+
+        match step {
+            Step::Deliver(result) => result,
+            _ => unreachable!("yield within a nested run"),
         }
     }
 
-    /// Sets the value of the variable in the current scope.  The `var_name` may name a
-    /// scalar variable or an array element.  This is the usual way to assign a value to
-    /// a variable named by a command argument.
-    ///
-    /// Returns an error if the variable is scalar and the name names an array element,
-    /// and vice-versa.
-    ///
-    /// # Example
-    ///
-    /// ```
-    /// use remolt::types::*;
-    /// use remolt::Interp;
-    /// use remolt::molt_ok;
-    /// # fn dummy() -> MoltResult {
-    /// let mut interp = Interp::new();
-    ///
-    /// // Set the value of the scalar variable "a"
-    /// let scalar = Value::from("a");  // The variable name
-    /// interp.set_var(&scalar, Value::from("1"))?;
-    /// assert_eq!(interp.var(&scalar)?.as_str(), "1");
-    ///
-    /// // Set the value of the array element "b(1)":
-    /// let element = Value::from("b(1)");  // The variable name
-    /// interp.set_var(&element, Value::from("howdy"))?;
-    /// assert_eq!(interp.var(&element)?.as_str(), "howdy");
-    /// # molt_ok!()
-    /// # }
-    /// ```
-    pub fn set_var(&mut self, var_name: &Value, value: Value) -> Result<(), Exception> {
-        let var_name = &*var_name.as_var_name();
-        match var_name.index() {
-            Some(index) => self.set_element(var_name.name(), index, value),
-            None => self.set_scalar(var_name.name(), value),
+    /// Runs the evaluator: delivers results to the frames above `base`, innermost first,
+    /// until they are done or the coroutine being run yields.
+    fn run(&mut self, base: usize, mut step: Step) -> Step {
+        loop {
+            step = match step {
+                Step::Enter => match self.eval_frames.pop() {
+                    Some(EvalFrame::Script {
+                        script, next, kind, ..
+                    }) => self.eval_commands(script, next, kind, None),
+                    _ => unreachable!("entered a frame other than a script"),
+                },
+                Step::TailCall(call) => self.start_tail_call(*call),
+                Step::Deliver(result) if self.eval_frames.len() > base => {
+                    let frame = self.eval_frames.pop().unwrap();
+                    self.deliver(frame, result)
+                }
+                _ => return step,
+            }
         }
     }
 
-    /// Sets the value of the variable in the current scope, return its value.  The `var_name`
-    /// may name a
-    /// scalar variable or an array element.  This is the usual way to assign a value to
-    /// a variable named by a command argument when the command is expected to return the
-    /// value.
-    ///
-    /// Returns an error if the variable is scalar and the name names an array element,
-    /// and vice-versa.
-    ///
-    /// # Example
-    ///
-    /// ```
-    /// use remolt::types::*;
+    /// Delivers the result of the frame above it to a frame.  Frames that have more to do
+    /// push themselves back onto the stack.
+    fn deliver(&mut self, frame: EvalFrame, result: MoltResult) -> Step {
+        match frame {
+            EvalFrame::Script {
+                script,
+                next,
+                kind,
+                #[cfg(feature = "error-stack-trace")]
+                called,
+            } => {
+                #[cfg(feature = "error-stack-trace")]
+                let result = match called {
+                    Some((words, is_proc)) => with_error_info(result, is_proc, &words),
+                    None => result,
+                };
+
+                match result {
+                    Ok(value) => self.eval_commands(script, next, kind, Some(value)),
+                    Err(exception) => self.end_script(kind, Err(exception)),
+                }
+            }
+            EvalFrame::Words(cmd) => self.resume_words(cmd, result),
+            #[cfg(feature = "traces")]
+            EvalFrame::TailCall => {
+                self.num_levels -= 1;
+                Step::Deliver(result)
+            }
+            #[cfg(feature = "expr")]
+            EvalFrame::Expr { expr, mut replay } => {
+                replay.resume(result);
+                self.replay_expr(expr, replay)
+            }
+            EvalFrame::Subst {
+                template,
+                flags,
+                mut replay,
+            } => {
+                replay.resume(result);
+                self.replay_subst(template, flags, replay)
+            }
+            #[cfg(feature = "traces")]
+            EvalFrame::Traced { traces, words } => self.end_traced(&traces, &words, result),
+            EvalFrame::Uplevel { saved } => {
+                self.scopes.set_current(saved);
+                Step::Deliver(result)
+            }
+            #[cfg(feature = "namespaces")]
+            EvalFrame::Namespace => {
+                self.scopes.pop();
+
+                #[cfg(feature = "info")]
+                self.pop_frame();
+
+                Step::Deliver(result)
+            }
+            frame => frame.resume(self, result),
+        }
+    }
+
+    /// Evaluates a parsed script as a command substitution.
+    pub(crate) fn eval_script(&mut self, script: &Rc<Script>) -> MoltResult {
+        self.eval_nested(|interp| interp.start_script(Rc::clone(script)))
+    }
+
+    /// Starts evaluating a script as a command substitution.
+    fn start_script(&mut self, script: Rc<Script>) -> Step {
+        self.eval_commands(script, 0, ScriptKind::Subst, None)
+    }
+
+    /// Pushes a script to be evaluated as by `eval_value`, e.g., a control structure body,
+    /// and returns the step that starts it.  It's evaluated at once unless a coroutine could
+    /// yield within it.
+    pub(crate) fn push_body(&mut self, body: &Value) -> Step {
+        match self.enter_body(body) {
+            Ok(script) => self.enter_script(script, ScriptKind::Body),
+            Err(exception) => Step::Deliver(Err(exception)),
+        }
+    }
+
+    /// Evaluates a body or procedure body at once, or, if a coroutine could yield within
+    /// it, pushes it and returns the step that starts it.
+    fn enter_script(&mut self, script: Rc<Script>, kind: ScriptKind) -> Step {
+        if !self.can_yield() {
+            return self.eval_commands(script, 0, kind, None);
+        }
+
+        self.eval_frames.push(EvalFrame::Script {
+            script,
+            next: 0,
+            kind,
+            #[cfg(feature = "error-stack-trace")]
+            called: None,
+        });
+        Step::Enter
+    }
+
+    /// Parses a script to be evaluated as a body, counting it as a nesting level.
+    fn enter_body(&mut self, body: &Value) -> Result<Rc<Script>, Exception> {
+        if self.num_levels >= self.recursion_limit {
+            return molt_err!("too many nested calls to Interp::eval (infinite loop?)");
+        }
+
+        let script = body.as_script()?;
+        self.num_levels += 1;
+        Ok(script)
+    }
+
+    /// Finishes evaluating a script.  A body's error data is saved as `eval_value` does, and
+    /// a procedure's scope is popped.
+    fn end_script(&mut self, kind: ScriptKind, mut result: MoltResult) -> Step {
+        if kind != ScriptKind::Subst {
+            self.num_levels -= 1;
+
+            if let Err(exception) = &result {
+                if exception.is_error() {
+                    if let Err(exception) = self.set_global_error_data(exception.error_data()) {
+                        result = Err(exception);
+                    }
+                }
+            }
+        }
+
+        if kind == ScriptKind::Proc {
+            self.end_proc(result)
+        } else {
+            Step::Deliver(result)
+        }
+    }
+
+    /// Evaluates a script's commands from the one at `index`, given the result of the
+    /// previous command, if any, until the script is done or, within a coroutine, a command
+    /// needs frames of its own.
+    fn eval_commands(
+        &mut self,
+        script: Rc<Script>,
+        mut index: usize,
+        kind: ScriptKind,
+        mut last: Option<Value>,
+    ) -> Step {
+        let commands = script.commands();
+
+        loop {
+            let word_vec = match commands.get(index) {
+                Some(command) => command.words(),
+                None => return self.end_script(kind, Ok(last.unwrap_or_default())),
+            };
+
+            // FIRST, evaluate the words and call the command.  Unless the coroutine being
+            // run could yield within them, the words are evaluated at once, running the
+            // evaluator again for any command substitutions.
+            let result = if self.can_yield() {
+                self.eval_command(CommandWords {
+                    script: Rc::clone(&script),
+                    index,
+                    kind,
+                    next: 0,
+                    words: Vec::new(),
+                    last,
+                    replay: None,
+                })
+            } else {
+                let mut words = Vec::new();
+
+                for word in word_vec {
+                    if let Err(exception) = self.eval_word_into(word, &mut words, None) {
+                        return self.end_script(kind, Err(exception));
+                    }
+                }
+
+                if words.is_empty() {
+                    return self.end_script(kind, Ok(last.unwrap_or_default()));
+                }
+
+                self.call_words(&script, index, kind, words)
+            };
+
+            last = match result {
+                ControlFlow::Continue(Ok(value)) => Some(value),
+                ControlFlow::Continue(Err(exception)) => return self.end_script(kind, Err(exception)),
+                ControlFlow::Break(step) => return step,
+            };
+            index += 1;
+        }
+    }
+
+    /// Continues evaluating a command's words once the command substitution it was waiting
+    /// for is done, and then the rest of its script.
+    fn resume_words(&mut self, mut cmd: CommandWords, result: MoltResult) -> Step {
+        match &mut cmd.replay {
+            Some(replay) => replay.resume(result),
+            None => {
+                let value = match result {
+                    Ok(value) => value,
+                    Err(exception) => return self.end_script(cmd.kind, Err(exception)),
+                };
+
+                if let Word::Expand(_) = cmd.script.commands()[cmd.index].words()[cmd.next] {
+                    match value.as_list() {
+                        Ok(list) => cmd.words.extend(list.iter().cloned()),
+                        Err(exception) => return self.end_script(cmd.kind, Err(exception)),
+                    }
+                } else {
+                    cmd.words.push(value);
+                }
+
+                cmd.next += 1;
+            }
+        }
+
+        let (script, index, kind) = (Rc::clone(&cmd.script), cmd.index, cmd.kind);
+
+        match self.eval_command(cmd) {
+            ControlFlow::Continue(Ok(value)) => self.eval_commands(script, index + 1, kind, Some(value)),
+            ControlFlow::Continue(Err(exception)) => self.end_script(kind, Err(exception)),
+            ControlFlow::Break(step) => step,
+        }
+    }
+
+    /// Evaluates a command's remaining words and calls it, as `call_words` does.
+    fn eval_command(&mut self, cmd: CommandWords) -> ControlFlow<Step, MoltResult> {
+        // FIRST, evaluate the words.  As in standard TCL, a command with no words ends the
+        // script.
+        let cmd = self.eval_words(cmd)?;
+
+        if cmd.words.is_empty() {
+            return ControlFlow::Break(self.end_script(cmd.kind, Ok(cmd.last.unwrap_or_default())));
+        }
+
+        // NEXT, call it.
+        self.call_words(&cmd.script, cmd.index, cmd.kind, cmd.words)
+    }
+
+    /// Calls the command at `index` in a script given its words, and returns its result;
+    /// or, if within a coroutine it pushes frames of its own, pushes a frame to continue
+    /// the script when it's done, and returns the step to take next.
+    fn call_words(
+        &mut self,
+        script: &Rc<Script>,
+        index: usize,
+        kind: ScriptKind,
+        words: MoltList,
+    ) -> ControlFlow<Step, MoltResult> {
+        // FIRST, look up the command.
+        let name = words[0].as_str();
+        let (_key, command) = match self.find_command(name) {
+            Some(found) => found,
+            None => return ControlFlow::Continue(molt_err!("invalid command name \"{}\"", name)),
+        };
+
+        #[cfg(feature = "traces")]
+        let traces = self.exec_traces_of(_key);
+
+        // let start = Instant::now();
+        let command = Rc::clone(command);
+
+        #[cfg(feature = "info")]
+        {
+            self.cmd_count += 1;
+        }
+
+        // NEXT, execute untraced native commands at once.
+        #[cfg(feature = "traces")]
+        let untraced = traces.is_none();
+        #[cfg(not(feature = "traces"))]
+        let untraced = true;
+
+        if untraced {
+            if let Some(result) = command.execute(self, &words) {
+                // self.profile_save(&format!("cmd.execute({})", name), start);
+                #[cfg(feature = "error-stack-trace")]
+                let result = with_error_info(result, false, &words);
+
+                return ControlFlow::Continue(result);
+            }
+        }
+
+        // NEXT, start other commands.  Those that are done at once, e.g., `expr` outside
+        // of a coroutine, return their results as native commands do.  Outside of a
+        // coroutine nothing can yield, so the frames pushed by the others are run at once;
+        // within one, the others continue the script when they return.
+        let base = self.eval_frames.len();
+
+        #[cfg(feature = "traces")]
+        let step = match traces {
+            Some(traces) => self.start_traced(traces, &command, &words),
+            None => self.start_command(&command, &words),
+        };
+        #[cfg(not(feature = "traces"))]
+        let step = self.start_command(&command, &words);
+
+        let result = match step {
+            Step::Deliver(result) if self.eval_frames.len() == base => result,
+            step if !self.can_yield() => match self.run(base, step) {
+                Step::Deliver(result) => result,
+                _ => unreachable!("yield within a nested run"),
+            },
+            step => {
+                self.eval_frames.insert(
+                    base,
+                    EvalFrame::Script {
+                        script: Rc::clone(script),
+                        next: index + 1,
+                        kind,
+                        #[cfg(feature = "error-stack-trace")]
+                        called: Some((words, command.is_proc())),
+                    },
+                );
+                return ControlFlow::Break(step);
+            }
+        };
+
+        #[cfg(feature = "error-stack-trace")]
+        let result = with_error_info(result, command.is_proc(), &words);
+
+        ControlFlow::Continue(result)
+    }
+
+    /// Evaluates a command's words from the one at `cmd.next`, within a coroutine that could
+    /// yield within them.  A command substitution that makes up an entire word is evaluated
+    /// by pushing the command, to receive its result; so is one that is part of a word,
+    /// which is evaluated again once the substitution is done.
+    fn eval_words(&mut self, mut cmd: CommandWords) -> ControlFlow<Step, CommandWords> {
+        let script = Rc::clone(&cmd.script);
+        let word_vec = script.commands()[cmd.index].words();
+
+        while cmd.next < word_vec.len() {
+            let word = &word_vec[cmd.next];
+
+            let substitution = match word {
+                Word::Script(sub) => Some(sub),
+                Word::Expand(word) => match &**word {
+                    Word::Script(sub) => Some(sub),
+                    _ => None,
+                },
+                _ => None,
+            };
+
+            if let Some(sub) = substitution {
+                self.eval_frames.push(EvalFrame::Words(cmd));
+                return ControlFlow::Break(self.start_script(Rc::clone(sub)));
+            }
+
+            let result = match word {
+                Word::Tokens(_) | Word::ArrayRef(..) | Word::Expand(_) => {
+                    let mut replay = cmd.replay.take().unwrap_or_default();
+                    let result = self.eval_word_into(word, &mut cmd.words, Some(&mut replay));
+
+                    if let Some(sub) = replay.take_pending() {
+                        cmd.replay = Some(replay);
+                        self.eval_frames.push(EvalFrame::Words(cmd));
+                        return ControlFlow::Break(self.start_script(sub));
+                    }
+
+                    result
+                }
+                _ => self.eval_word_into(word, &mut cmd.words, None),
+            };
+
+            if let Err(exception) = result {
+                return ControlFlow::Break(self.end_script(cmd.kind, Err(exception)));
+            }
+
+            cmd.next += 1;
+        }
+
+        ControlFlow::Continue(cmd)
+    }
+
+    /// Starts calling a command given its words, as `call` does.
+    fn start_words(&mut self, words: &[Value]) -> Step {
+        if words.is_empty() {
+            return Step::Deliver(molt_ok!());
+        }
+
+        #[cfg(feature = "info")]
+        {
+            self.cmd_count += 1;
+        }
+
+        let name = words[0].as_str();
+        let (_key, cmd) = match self.find_command(name) {
+            Some(found) => found,
+            None => return Step::Deliver(molt_err!("invalid command name \"{}\"", name)),
+        };
+
+        #[cfg(feature = "traces")]
+        if let Some(traces) = self.exec_traces_of(_key) {
+            let cmd = Rc::clone(cmd);
+            return self.start_traced(traces, &cmd, words);
+        }
+
+        let cmd = Rc::clone(cmd);
+        self.start_command(&cmd, words)
+    }
+
+    /// Starts a command without calling its execution traces: executes it, if it's
+    /// native, or pushes its frames.
+    fn start_command(&mut self, cmd: &Command, words: &[Value]) -> Step {
+        if let Some(result) = cmd.execute(self, words) {
+            return Step::Deliver(result);
+        }
+
+        match cmd {
+            Command::Proc(proc) => self.call_proc(proc, false, words),
+            Command::Control(func) => {
+                func(self, words).unwrap_or_else(|exception| Step::Deliver(Err(exception)))
+            }
+            #[cfg(feature = "coroutines")]
+            Command::Coroutine(co) => Step::Deliver(self.call_coroutine(co, words)),
+            _ => unreachable!("native command not executed"),
+        }
+    }
+
+    /// Calls a procedure, or a lambda for `apply`, starting its body.
+    pub(crate) fn call_proc(&mut self, proc: &Procedure, lambda: bool, argv: &[Value]) -> Step {
+        #[cfg(feature = "info")]
+        self.push_frame(if lambda { FrameKind::Lambda } else { FrameKind::Proc }, argv);
+
+        self.push_scope();
+        self.tailcalls.push(None);
+
+        #[cfg(feature = "namespaces")]
+        self.scopes.set_namespace(Rc::clone(&proc.namespace));
+
+        let (name, args) = if lambda {
+            ("apply lambdaExpr", &argv[2..])
+        } else {
+            (argv[0].as_str(), &argv[1..])
+        };
+
+        let entered = proc
+            .bind_args(self, name, args)
+            .and_then(|_| self.enter_body(&proc.body));
+
+        match entered {
+            Ok(script) => self.enter_script(script, ScriptKind::Proc),
+            Err(exception) => self.end_proc(Err(exception)),
+        }
+    }
+
+    /// Returns from a procedure or lambda, popping its scope.  The command it tail called,
+    /// if any, is started in its place by the run of the evaluator, rather than within the
+    /// body that called it.  Tail calls thus run in constant space, except that a traced
+    /// command's leave traces must wait for it; so, like nested scripts, traced tail calls
+    /// count against the recursion limit.
+    fn end_proc(&mut self, result: MoltResult) -> Step {
+        let tail = self.tailcalls.pop().flatten();
+        self.pop_scope();
+
+        #[cfg(feature = "info")]
+        self.pop_frame();
+
+        let result = Procedure::translate_result(result);
+
+        match tail {
+            Some(call) if result.is_ok() => Step::TailCall(call),
+            _ => Step::Deliver(result),
+        }
+    }
+
+    /// Starts the command tail called by a procedure or lambda in its place.
+    fn start_tail_call(&mut self, call: TailCall) -> Step {
+        #[cfg(feature = "info")]
+        {
+            self.cmd_count += 1;
+        }
+
+        #[cfg(feature = "traces")]
+        if let Some(traces) = self.exec_traces_of(&call.key) {
+            if self.num_levels >= self.recursion_limit {
+                return Step::Deliver(molt_err!(
+                    "too many nested calls to Interp::eval (infinite loop?)"
+                ));
+            }
+
+            self.num_levels += 1;
+            self.eval_frames.push(EvalFrame::TailCall);
+            return self.start_traced(traces, &call.cmd, &call.words);
+        }
+
+        self.start_command(&call.cmd, &call.words)
+    }
+
+    /// Pushes an expression to be evaluated, e.g., a control structure's condition, and
+    /// returns the step that starts it.  It's evaluated at once unless a coroutine could
+    /// yield within it.
+    pub(crate) fn push_expr(&mut self, expr: &Value) -> Step {
+        cfg_if::cfg_if! {
+            if #[cfg(feature = "expr")] {
+                if self.can_yield() {
+                    self.replay_expr(expr.clone(), Replay::default())
+                } else {
+                    Step::Deliver(self.expr(expr))
+                }
+            } else {
+                self.push_body(expr)
+            }
+        }
+    }
+
+    /// Evaluates an expression at once, unless a coroutine could yield within it, in which
+    /// case it returns `None` and the caller pushes the expression instead.
+    pub(crate) fn expr_now(&mut self, _expr: &Value) -> Option<MoltResult> {
+        #[cfg(feature = "expr")]
+        if !self.can_yield() {
+            return Some(self.expr(_expr));
+        }
+
+        None
+    }
+
+    /// Evaluates an expression, replaying the substitutions made so far, or pushes it to
+    /// wait for the next command substitution.
+    #[cfg(feature = "expr")]
+    fn replay_expr(&mut self, expr: Value, mut replay: Replay) -> Step {
+        let result = expr::expr(self, &expr, Some(&mut replay));
+
+        match replay.take_pending() {
+            Some(script) => {
+                self.eval_frames.push(EvalFrame::Expr { expr, replay });
+                self.start_script(script)
+            }
+            None => Step::Deliver(self.expr_result(result)),
+        }
+    }
+
+    /// Pushes a template to be substituted, as by the `subst` command, and returns the
+    /// step that starts it.  It's substituted at once unless a coroutine could yield
+    /// within it.
+    pub(crate) fn push_subst(&mut self, template: &Value, flags: SubstFlags) -> Step {
+        if self.can_yield() {
+            self.replay_subst(template.clone(), flags, Replay::default())
+        } else {
+            Step::Deliver(self.subst_with(template, flags, None))
+        }
+    }
+
+    /// Substitutes a template, replaying the substitutions made so far, or pushes it to
+    /// wait for the next command substitution.
+    fn replay_subst(&mut self, template: Value, flags: SubstFlags, mut replay: Replay) -> Step {
+        let result = self.subst_with(&template, flags, Some(&mut replay));
+
+        match replay.take_pending() {
+            Some(script) => {
+                self.eval_frames.push(EvalFrame::Subst {
+                    template,
+                    flags,
+                    replay,
+                });
+                self.start_script(script)
+            }
+            None => Step::Deliver(result),
+        }
+    }
+
+    /// Pushes a script to be evaluated in the scope at the given level, as the `uplevel`
+    /// command does, and returns the step that starts it.
+    pub(crate) fn push_uplevel(&mut self, level: usize, script: &Value) -> Step {
+        let index = self.scopes.index_of_level(level);
+        let saved = self.scopes.set_current(index);
+        self.eval_frames.push(EvalFrame::Uplevel { saved });
+        self.push_body(script)
+    }
+
+    /// Pushes a script to be evaluated in the namespace with the given key, creating it if
+    /// need be, as the `namespace eval` command does, and returns the step that starts it.
+    /// The script is evaluated in a new namespace frame.
+    #[cfg(feature = "namespaces")]
+    pub(crate) fn push_namespace_eval(&mut self, key: &str, _argv: &[Value], script: &Value) -> Step {
+        #[cfg(feature = "info")]
+        self.push_frame(FrameKind::Namespace, _argv);
+
+        self.ensure_namespace(key);
+        self.scopes.push_namespace(Rc::from(key));
+        self.eval_frames.push(EvalFrame::Namespace);
+        self.push_body(script)
+    }
+
+    /// Pushes a control structure's frame, to receive the result of the script or
+    /// expression it pushes next.
+    pub(crate) fn push_eval_frame(&mut self, frame: EvalFrame) {
+        self.eval_frames.push(frame);
+    }
+
+    /// Determines whether the coroutine being run, if any, can yield here: i.e., whether
+    /// the evaluator is running its frames, rather than frames pushed by Rust code that it
+    /// called.
+    pub(crate) fn can_yield(&self) -> bool {
+        #[cfg(feature = "coroutines")]
+        return matches!(self.coroutines.last(), Some((_, depth)) if *depth == self.eval_depth);
+
+        #[cfg(not(feature = "coroutines"))]
+        false
+    }
+
+    //--------------------------------------------------------------------------------------------
+    // Variable Handling
+
+    /// Retrieves the value of the named variable in the current scope.  The `var_name` may
+    /// name a scalar variable or an array element.  This is the normal way to retrieve the
+    /// value of a variable named by a command argument.
+    ///
+    /// Returns an error if the variable is a scalar and the name names an array element,
+    /// and vice versa.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use remolt::types::*;
+    /// use remolt::Interp;
+    /// use remolt::molt_ok;
+    /// # fn dummy() -> MoltResult {
+    /// let mut interp = Interp::new();
+    ///
+    /// // Set the value of the scalar variable "a" using a script.
+    /// interp.eval("set a 1")?;
+    ///
+    /// // The value of the scalar variable "a".
+    /// let val = interp.var(&Value::from("a"))?;
+    /// assert_eq!(val.as_str(), "1");
+    ///
+    /// // Set the value of the array element "b(1)" using a script.
+    /// interp.eval("set b(1) Howdy")?;
+    ///
+    /// // The value of the array element "b(1)":
+    /// let val = interp.var(&Value::from("b(1)"))?;
+    /// assert_eq!(val.as_str(), "Howdy");
+    /// # molt_ok!()
+    /// # }
+    /// ```
+    pub fn var(&mut self, var_name: &Value) -> MoltResult {
+        let var_name = &*var_name.as_var_name();
+        match var_name.index() {
+            Some(index) => self.element(var_name.name(), index),
+            None => self.scalar(var_name.name()),
+        }
+    }
+
+    /// Returns 1 if the named variable is defined and exists, and 0 otherwise.
+    pub fn var_exists(&self, var_name: &Value) -> bool {
+        let var_name = &*var_name.as_var_name();
+        match var_name.index() {
+            Some(index) => self.scopes.elem_exists(var_name.name(), index),
+            None => self.scopes.exists(var_name.name()),
+        }
+    }
+
+    /// Sets the value of the variable in the current scope.  The `var_name` may name a
+    /// scalar variable or an array element.  This is the usual way to assign a value to
+    /// a variable named by a command argument.
+    ///
+    /// Returns an error if the variable is scalar and the name names an array element,
+    /// and vice-versa.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use remolt::types::*;
+    /// use remolt::Interp;
+    /// use remolt::molt_ok;
+    /// # fn dummy() -> MoltResult {
+    /// let mut interp = Interp::new();
+    ///
+    /// // Set the value of the scalar variable "a"
+    /// let scalar = Value::from("a");  // The variable name
+    /// interp.set_var(&scalar, Value::from("1"))?;
+    /// assert_eq!(interp.var(&scalar)?.as_str(), "1");
+    ///
+    /// // Set the value of the array element "b(1)":
+    /// let element = Value::from("b(1)");  // The variable name
+    /// interp.set_var(&element, Value::from("howdy"))?;
+    /// assert_eq!(interp.var(&element)?.as_str(), "howdy");
+    /// # molt_ok!()
+    /// # }
+    /// ```
+    pub fn set_var(&mut self, var_name: &Value, value: Value) -> Result<(), Exception> {
+        let var_name = &*var_name.as_var_name();
+        match var_name.index() {
+            Some(index) => self.set_element(var_name.name(), index, value),
+            None => self.set_scalar(var_name.name(), value),
+        }
+    }
+
+    /// Sets the value of the variable in the current scope, return its value.  The `var_name`
+    /// may name a
+    /// scalar variable or an array element.  This is the usual way to assign a value to
+    /// a variable named by a command argument when the command is expected to return the
+    /// value.
+    ///
+    /// Returns an error if the variable is scalar and the name names an array element,
+    /// and vice-versa.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use remolt::types::*;
     /// use remolt::Interp;
     /// use remolt::molt_ok;
     /// # fn dummy() -> MoltResult {
@@ -1143,6 +2054,17 @@ impl Interp {
     /// ```
     pub fn set_var_return(&mut self, var_name: &Value, value: Value) -> MoltResult {
         self.set_var(var_name, value.clone())?;
+
+        // A write trace may have changed the value.
+        #[cfg(feature = "traces")]
+        if self.scopes.has_traces() {
+            let var_name = &*var_name.as_var_name();
+            return match var_name.index() {
+                Some(index) => self.scopes.get_elem(var_name.name(), index),
+                None => self.scopes.get(var_name.name()),
+            };
+        }
+
         Ok(value)
     }
 
@@ -1168,7 +2090,12 @@ impl Interp {
     /// # molt_ok!()
     /// # }
     /// ```
-    pub fn scalar(&self, name: &str) -> MoltResult {
+    pub fn scalar(&mut self, name: &str) -> MoltResult {
+        #[cfg(feature = "traces")]
+        if self.scopes.has_traces() {
+            self.fire_var_traces(name, None, VarOp::Read)?;
+        }
+
         self.scopes.get(name)
     }
 
@@ -1193,7 +2120,14 @@ impl Interp {
     /// # }
     /// ```
     pub fn set_scalar(&mut self, name: &str, value: Value) -> Result<(), Exception> {
-        self.scopes.set(name, value)
+        self.scopes.set(name, value)?;
+
+        #[cfg(feature = "traces")]
+        if self.scopes.has_traces() {
+            self.fire_var_traces(name, None, VarOp::Write)?;
+        }
+
+        Ok(())
     }
 
     /// Retrieves the value of the named array element in the current scope.
@@ -1219,7 +2153,12 @@ impl Interp {
     /// # molt_ok!()
     /// # }
     /// ```
-    pub fn element(&self, name: &str, index: &str) -> MoltResult {
+    pub fn element(&mut self, name: &str, index: &str) -> MoltResult {
+        #[cfg(feature = "traces")]
+        if self.scopes.has_traces() {
+            self.fire_var_traces(name, Some(index), VarOp::Read)?;
+        }
+
         self.scopes.get_elem(name, index)
     }
 
@@ -1244,7 +2183,14 @@ impl Interp {
     /// # }
     /// ```
     pub fn set_element(&mut self, name: &str, index: &str, value: Value) -> Result<(), Exception> {
-        self.scopes.set_elem(name, index, value)
+        self.scopes.set_elem(name, index, value)?;
+
+        #[cfg(feature = "traces")]
+        if self.scopes.has_traces() {
+            self.fire_var_traces(name, Some(index), VarOp::Write)?;
+        }
+
+        Ok(())
     }
 
     /// Unsets a variable, whether scalar or array, given its name in the current scope.  For
@@ -1271,6 +2217,14 @@ impl Interp {
     /// # }
     /// ```
     pub fn unset(&mut self, name: &str) {
+        #[cfg(feature = "traces")]
+        if self.scopes.has_traces() && self.scopes.exists(name) {
+            let traces = self.scopes.take_traces(name);
+            self.scopes.unset(name);
+            let _ = self.call_var_traces(&traces, name, None, VarOp::Unset);
+            return;
+        }
+
         self.scopes.unset(name);
     }
 
@@ -1327,6 +2281,13 @@ impl Interp {
     /// # }
     /// ```
     pub fn unset_element(&mut self, array_name: &str, index: &str) {
+        #[cfg(feature = "traces")]
+        if self.scopes.has_traces() && self.scopes.elem_exists(array_name, index) {
+            self.scopes.unset_element(array_name, index);
+            let _ = self.fire_var_traces(array_name, Some(index), VarOp::Unset);
+            return;
+        }
+
         self.scopes.unset_element(array_name, index);
     }
 
@@ -1393,35 +2354,774 @@ impl Interp {
     /// This method is used to implement the `upvar` command, which allows variables to be
     /// passed by name; client code should rarely need to access it directly.
     pub fn upvar(&mut self, level: usize, name: &str) {
-        assert!(level <= self.scopes.current(), "Invalid scope level");
+        assert!(level <= self.scopes.level(), "Invalid scope level");
         self.scopes.upvar(level, name);
     }
 
-    /// Pushes a variable scope (i.e., a stack level) onto the scope stack.
-    ///
-    /// Procs use this to define their local scope.  Client code should seldom need to call
-    /// this directly, but it can be useful in a few cases.  For example, the Molt
-    /// test harness's `test` command runs its body in a local scope as an aid to test
-    /// cleanup.
-    ///
-    /// **Note:** a command that pushes a scope must also call `Interp::pop_scope` before it
-    /// exits!
-    pub fn push_scope(&mut self) {
-        self.scopes.push();
+    /// Links the variable `my_name` in the current scope to the variable `other_name` at
+    /// the given scope level, so that the two names refer to the same variable.  As with
+    /// `Interp::upvar`, the level is the absolute level; it may be the current level
+    /// provided that the names differ.
+    ///
+    /// This method is used to implement the `upvar` command.  It's an error if `my_name`
+    /// already exists in the current scope and isn't itself a link, or if the variable
+    /// would be linked to itself.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use remolt::Interp;
+    /// use remolt::types::*;
+    /// use remolt::molt_ok;
+    /// # fn dummy() -> MoltResult {
+    /// let mut interp = Interp::new();
+    /// interp.set_scalar("a", Value::from(1))?;
+    ///
+    /// interp.push_scope();
+    /// interp.upvar_alias(0, "a", "b")?;
+    /// interp.set_scalar("b", Value::from(2))?;
+    /// interp.pop_scope();
+    ///
+    /// assert_eq!(interp.scalar("a")?.as_int()?, 2);
+    /// # molt_ok!()
+    /// # }
+    /// ```
+    pub fn upvar_alias(
+        &mut self,
+        level: usize,
+        other_name: &str,
+        my_name: &str,
+    ) -> Result<(), Exception> {
+        assert!(level <= self.scopes.level(), "Invalid scope level");
+        self.scopes.upvar_alias(level, other_name, my_name)
+    }
+
+    /// Pushes a variable scope (i.e., a stack level) onto the scope stack.
+    ///
+    /// Procs use this to define their local scope.  Client code should seldom need to call
+    /// this directly, but it can be useful in a few cases.  For example, the Molt
+    /// test harness's `test` command runs its body in a local scope as an aid to test
+    /// cleanup.
+    ///
+    /// **Note:** a command that pushes a scope must also call `Interp::pop_scope` before it
+    /// exits!
+    pub fn push_scope(&mut self) {
+        self.scopes.push();
+    }
+
+    /// Pops a variable scope (i.e., a stack level) off of the scope stack.  Calls to
+    /// `Interp::push_scope` and `Interp::pop_scope` must exist in pairs.
+    ///
+    /// The scope's variables are unset, firing their unset traces in the caller's scope.
+    pub fn pop_scope(&mut self) {
+        #[cfg(feature = "traces")]
+        if self.scopes.has_traces() {
+            let traced = self.scopes.take_scope_traces();
+            self.scopes.pop();
+
+            for (name, traces) in traced {
+                let _ = self.call_var_traces(&traces, &name, None, VarOp::Unset);
+            }
+            return;
+        }
+
+        self.scopes.pop();
+    }
+
+    /// Return the current scope level.  The global scope is level `0`; each call to
+    /// `Interp::push_scope` adds a level, and each call to `Interp::pop_scope` removes it.
+    /// This method is used with `Interp::upvar` to access the caller's scope when a variable
+    /// is passed by name.
+    pub fn scope_level(&self) -> usize {
+        self.scopes.level()
+    }
+
+    //-----------------------------------------------------------------------------------
+    // Call Frames
+
+    /// Pushes a call frame for the command onto the frame stack.  Frames that push a
+    /// variable scope must be pushed just before the scope is.
+    #[cfg(feature = "info")]
+    pub(crate) fn push_frame(&mut self, kind: FrameKind, argv: &[Value]) {
+        let level = match kind {
+            #[cfg(feature = "std")]
+            FrameKind::Source(_) => self.scopes.level(),
+            _ => self.scopes.level() + 1,
+        };
+
+        self.frames.push(Frame {
+            kind,
+            argv: argv.to_vec(),
+            level,
+        });
+    }
+
+    /// Pops a call frame off of the frame stack.  Calls to `Interp::push_frame` and
+    /// `Interp::pop_frame` must exist in pairs.
+    #[cfg(feature = "info")]
+    pub(crate) fn pop_frame(&mut self) {
+        self.frames.pop();
+    }
+
+    /// Gets the command words of the procedure call, lambda, or `namespace eval` that
+    /// pushed the scope at the given level, which must be no greater than the current
+    /// level.  Returns the empty list if the scope was pushed by Rust code.
+    ///
+    /// Frames that are no longer on the current chain of callers, because of `uplevel`,
+    /// can only be found at levels above the current level, so the most recent frame at
+    /// the level is the right one.
+    #[cfg(feature = "info")]
+    pub(crate) fn level_argv(&self, level: usize) -> MoltList {
+        self.frames
+            .iter()
+            .rev()
+            .find(|frame| frame.level == level && frame.pushes_scope())
+            .map(|frame| frame.argv.clone())
+            .unwrap_or_default()
+    }
+
+    /// Gets the depth of the frame stack.
+    #[cfg(feature = "info")]
+    pub(crate) fn frame_depth(&self) -> usize {
+        self.frames.len()
+    }
+
+    /// Gets the frame at the given depth, counting from 1 at the bottom of the stack.
+    #[cfg(feature = "info")]
+    pub(crate) fn frame(&self, depth: usize) -> Option<&Frame> {
+        depth.checked_sub(1).and_then(|i| self.frames.get(i))
+    }
+
+    /// Gets the name of the file currently being sourced, if any.
+    #[cfg(feature = "info")]
+    pub(crate) fn script_file(&self) -> Option<&str> {
+        #[cfg(feature = "std")]
+        for frame in self.frames.iter().rev() {
+            if let FrameKind::Source(file) = &frame.kind {
+                return Some(file);
+            }
+        }
+
+        None
+    }
+
+    /// Gets the number of commands the interpreter has executed.
+    #[cfg(feature = "info")]
+    pub(crate) fn cmd_count(&self) -> usize {
+        self.cmd_count
+    }
+
+    //-----------------------------------------------------------------------------------
+    // Variable Traces
+
+    /// Adds a trace to the named variable, which need not exist yet: the closure is called
+    /// whenever the variable is read, written, or unset, as selected by `ops`.  The name
+    /// may name an array, in which case the trace fires for each of its elements, or a
+    /// single array element.  Returns an ID for use with
+    /// [`untrace_var`](#method.untrace_var).
+    ///
+    /// The closure is passed the variable name as used in the access, the array index (or
+    /// the empty string), and the operation.  A read trace is called before the value is
+    /// retrieved, and a write trace after the value is set, so either may change the value.
+    /// An error returned by a read or write trace is returned as an error accessing the
+    /// variable; errors from unset traces are ignored.  A trace doesn't fire for its own
+    /// accesses to the variable.
+    ///
+    /// Traces are attached to the variable itself, so accesses through `upvar` and `global`
+    /// aliases fire them, and they are removed when the variable is unset.  A procedure's
+    /// local variables are unset when it returns, firing their unset traces in the caller's
+    /// scope.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use remolt::Interp;
+    /// use remolt::types::*;
+    /// use remolt::molt_ok;
+    /// # fn dummy() -> MoltResult {
+    /// let mut interp = Interp::new();
+    ///
+    /// interp.trace_var("cfg(duty)", VarOps { write: true, ..VarOps::default() },
+    ///     |interp, name, index, _op| {
+    ///         let duty = interp.element(name, index)?.as_int()?;
+    ///         interp.set_scalar("applied", Value::from(duty))
+    ///     });
+    ///
+    /// interp.eval("set cfg(duty) 75")?;
+    /// assert_eq!(interp.scalar("applied")?.as_int()?, 75);
+    /// # molt_ok!()
+    /// # }
+    /// ```
+    #[cfg(feature = "traces")]
+    pub fn trace_var(
+        &mut self,
+        name: &str,
+        ops: VarOps,
+        func: impl Fn(&mut Interp, &str, &str, VarOp) -> Result<(), Exception> + 'static,
+    ) -> usize {
+        self.add_var_trace(name, ops, TraceCallback::Closure(Box::new(func)))
+    }
+
+    /// Removes the trace with the given ID, as returned by
+    /// [`trace_var`](#method.trace_var), from the named variable.
+    #[cfg(feature = "traces")]
+    pub fn untrace_var(&mut self, name: &str, id: usize) {
+        let var_name = parser::parse_varname_literal(name);
+        self.scopes.remove_trace(var_name.name(), |trace| trace.id == id);
+    }
+
+    /// Adds a trace that calls the command prefix to the named variable.  Used by the
+    /// `trace add variable` command.
+    #[cfg(feature = "traces")]
+    pub(crate) fn trace_var_command(&mut self, name: &str, ops: VarOps, command: &Value) {
+        self.add_var_trace(name, ops, TraceCallback::Command(command.clone()));
+    }
+
+    /// Removes the most recent trace on the named variable that calls the command prefix
+    /// for the given operations.  Used by the `trace remove variable` command.
+    #[cfg(feature = "traces")]
+    pub(crate) fn untrace_var_command(&mut self, name: &str, ops: VarOps, command: &Value) {
+        let var_name = parser::parse_varname_literal(name);
+
+        self.scopes.remove_trace(var_name.name(), |trace| {
+            trace.ops == ops
+                && trace.index.as_deref() == var_name.index()
+                && matches!(&trace.callback, TraceCallback::Command(cmd) if cmd == command)
+        });
+    }
+
+    /// Gets the operations and command prefixes of the named variable's command traces,
+    /// most recent first.  Used by the `trace info variable` command.
+    #[cfg(feature = "traces")]
+    pub(crate) fn var_trace_info(&self, name: &str) -> Vec<(VarOps, Value)> {
+        let var_name = parser::parse_varname_literal(name);
+
+        self.scopes
+            .traces(var_name.name())
+            .iter()
+            .filter(|trace| trace.index.as_deref() == var_name.index())
+            .filter_map(|trace| match &trace.callback {
+                TraceCallback::Command(cmd) => Some((trace.ops, cmd.clone())),
+                TraceCallback::Closure(_) => None,
+            })
+            .collect()
+    }
+
+    /// Adds a trace to the named variable or array element.
+    #[cfg(feature = "traces")]
+    fn add_var_trace(&mut self, name: &str, ops: VarOps, callback: TraceCallback<VarTraceFunc>) -> usize {
+        let var_name = parser::parse_varname_literal(name);
+        let id = self.next_trace_id;
+        self.next_trace_id += 1;
+
+        let trace = VarTrace {
+            id,
+            ops,
+            index: var_name.index().map(Into::into),
+            callback,
+            active: Cell::new(false),
+        };
+        self.scopes.add_trace(var_name.name(), Rc::new(trace));
+        id
+    }
+
+    /// Fires the named variable's traces for the operation on the variable or array element.
+    #[cfg(feature = "traces")]
+    fn fire_var_traces(&mut self, name: &str, index: Option<&str>, op: VarOp) -> Result<(), Exception> {
+        let traces = self.scopes.traces(name);
+        self.call_var_traces(&traces, name, index, op)
+    }
+
+    /// Calls the traces that fire for the operation on the variable or array element.
+    /// An error from a read or write trace becomes an error accessing the variable.
+    #[cfg(feature = "traces")]
+    fn call_var_traces(
+        &mut self,
+        traces: &[Rc<VarTrace>],
+        name: &str,
+        index: Option<&str>,
+        op: VarOp,
+    ) -> Result<(), Exception> {
+        for trace in traces.iter().filter(|trace| trace.fires_on(index, op)) {
+            trace.active.set(true);
+            let result = match &trace.callback {
+                TraceCallback::Command(cmd) => cmd.to_list().and_then(|mut words| {
+                    words.push(Value::from(name.to_string()));
+                    words.push(Value::from(index.unwrap_or("").to_string()));
+                    words.push(Value::from(op.as_str()));
+                    self.call(&words).map(|_| ())
+                }),
+                TraceCallback::Closure(func) => func(self, name, index.unwrap_or(""), op),
+            };
+            trace.active.set(false);
+
+            if let Err(exception) = result {
+                let full_name = match index {
+                    Some(index) => format!("{}({})", name, index),
+                    None => name.to_string(),
+                };
+
+                match op {
+                    VarOp::Read => return molt_err!("can't read \"{}\": {}", full_name, exception.value()),
+                    VarOp::Write => return molt_err!("can't set \"{}\": {}", full_name, exception.value()),
+                    VarOp::Unset => (),
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    //-----------------------------------------------------------------------------------
+    // Execution Traces
+
+    /// Adds a trace to the named command: the closure is called when the command is
+    /// entered and when it returns, as selected by `ops`.  Returns an ID for use with
+    /// [`untrace_execution`](#method.untrace_execution), or an error if there is no such
+    /// command.
+    ///
+    /// The closure is passed the command's words, after substitution, and the command's
+    /// result when it returns (or `None` when it is entered).  An error returned by an
+    /// enter trace is returned in place of executing the command; an error returned by a
+    /// leave trace replaces the command's result.  A trace doesn't fire for its own calls
+    /// to the command.
+    ///
+    /// Traces follow the command when it is renamed, and are removed when it is deleted
+    /// or redefined.  When no traces are defined, commands execute at full speed.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use remolt::Interp;
+    /// use remolt::types::*;
+    /// use remolt::molt_ok;
+    /// # fn dummy() -> MoltResult {
+    /// let mut interp = Interp::new();
+    ///
+    /// interp.eval("proc pump {rate} {return $rate}")?;
+    /// interp.trace_execution("pump", ExecOps { enter: true, ..ExecOps::default() },
+    ///     |interp, argv, _result| {
+    ///         let log = Value::from(&argv[1..]);
+    ///         interp.set_scalar("audit", log)
+    ///     })?;
+    ///
+    /// interp.eval("pump 40")?;
+    /// assert_eq!(interp.scalar("audit")?.as_str(), "40");
+    /// # molt_ok!()
+    /// # }
+    /// ```
+    #[cfg(feature = "traces")]
+    pub fn trace_execution(
+        &mut self,
+        name: &str,
+        ops: ExecOps,
+        func: impl Fn(&mut Interp, &[Value], Option<&MoltResult>) -> Result<(), Exception> + 'static,
+    ) -> Result<usize, Exception> {
+        self.add_exec_trace(name, ops, TraceCallback::Closure(Box::new(func)))
+    }
+
+    /// Removes the trace with the given ID, as returned by
+    /// [`trace_execution`](#method.trace_execution), from the named command.
+    #[cfg(feature = "traces")]
+    pub fn untrace_execution(&mut self, name: &str, id: usize) {
+        self.remove_exec_trace(name, |trace| trace.id == id);
+    }
+
+    /// Adds an execution trace that calls the command prefix to the named command.  Used
+    /// by the `trace add execution` command.
+    #[cfg(feature = "traces")]
+    pub(crate) fn trace_execution_command(
+        &mut self,
+        name: &str,
+        ops: ExecOps,
+        command: &Value,
+    ) -> Result<(), Exception> {
+        self.add_exec_trace(name, ops, TraceCallback::Command(command.clone()))?;
+        Ok(())
+    }
+
+    /// Removes the most recent execution trace on the named command that calls the
+    /// command prefix for the given operations.  Used by the `trace remove execution`
+    /// command.
+    #[cfg(feature = "traces")]
+    pub(crate) fn untrace_execution_command(&mut self, name: &str, ops: ExecOps, command: &Value) {
+        self.remove_exec_trace(name, |trace| {
+            trace.ops == ops
+                && matches!(&trace.callback, TraceCallback::Command(cmd) if cmd == command)
+        });
+    }
+
+    /// Gets the operations and command prefixes of the named command's command traces,
+    /// most recent first, or an error if there is no such command.  Used by the
+    /// `trace info execution` command.
+    #[cfg(feature = "traces")]
+    pub(crate) fn exec_trace_info(&self, name: &str) -> Result<Vec<(ExecOps, Value)>, Exception> {
+        let key = match self.find_command(name) {
+            Some((key, _)) => key,
+            None => return molt_err!("unknown command \"{}\"", name),
+        };
+
+        let traces = match self.exec_traces.get(key) {
+            Some(traces) => traces,
+            None => return Ok(Vec::new()),
+        };
+
+        Ok(traces
+            .iter()
+            .rev()
+            .filter_map(|trace| match &trace.callback {
+                TraceCallback::Command(cmd) => Some((trace.ops, cmd.clone())),
+                TraceCallback::Closure(_) => None,
+            })
+            .collect())
+    }
+
+    /// Adds an execution trace to the named command.
+    #[cfg(feature = "traces")]
+    fn add_exec_trace(
+        &mut self,
+        name: &str,
+        ops: ExecOps,
+        callback: TraceCallback<ExecTraceFunc>,
+    ) -> Result<usize, Exception> {
+        let key = match self.find_command(name) {
+            Some((key, _)) => key.clone(),
+            None => return molt_err!("unknown command \"{}\"", name),
+        };
+
+        let id = self.next_trace_id;
+        self.next_trace_id += 1;
+
+        let trace = ExecTrace {
+            id,
+            ops,
+            callback,
+            active: Cell::new(false),
+        };
+        self.exec_traces.entry(key).or_default().push(Rc::new(trace));
+        Ok(id)
+    }
+
+    /// Removes the most recent of the named command's execution traces that satisfies
+    /// the predicate, if any.
+    #[cfg(feature = "traces")]
+    fn remove_exec_trace(&mut self, name: &str, pred: impl Fn(&ExecTrace) -> bool) {
+        let key = match self.find_command(name) {
+            Some((key, _)) => key.clone(),
+            None => return,
+        };
+
+        if let Some(traces) = self.exec_traces.get_mut(&key) {
+            if let Some(i) = traces.iter().rposition(|trace| pred(trace)) {
+                traces.remove(i);
+
+                if traces.is_empty() {
+                    self.exec_traces.shift_remove(&key);
+                }
+            }
+        }
+    }
+
+    /// Discards the execution traces on the command with the given key, as when the
+    /// command is deleted or redefined.
+    #[cfg(feature = "traces")]
+    fn forget_exec_traces(&mut self, key: &str) {
+        if !self.exec_traces.is_empty() {
+            self.exec_traces.shift_remove(key);
+        }
+    }
+
+    /// Gets the execution traces on the command with the given key, if any.  This is
+    /// cheap when no execution traces are defined.
+    #[cfg(feature = "traces")]
+    fn exec_traces_of(&self, key: &str) -> Option<Vec<Rc<ExecTrace>>> {
+        if self.exec_traces.is_empty() {
+            None
+        } else {
+            self.exec_traces.get(key).cloned()
+        }
+    }
+
+    /// Starts a traced command, calling its enter traces, most recent first, and pushing a
+    /// frame to call its leave traces when it returns.
+    #[cfg(feature = "traces")]
+    fn start_traced(&mut self, traces: Vec<Rc<ExecTrace>>, cmd: &Command, words: &[Value]) -> Step {
+        // FIRST, call the enter traces.  An error prevents the command from executing.
+        for trace in traces.iter().rev().filter(|trace| trace.fires_on(false)) {
+            if let Err(exception) = self.call_exec_trace(trace, words, None) {
+                return Step::Deliver(Err(exception));
+            }
+        }
+
+        // NEXT, start the command.
+        self.eval_frames.push(EvalFrame::Traced {
+            traces,
+            words: words.to_vec(),
+        });
+        self.start_command(cmd, words)
+    }
+
+    /// Calls the leave traces of a traced command that has returned, in order of creation.
+    /// An error replaces the command's result.
+    #[cfg(feature = "traces")]
+    fn end_traced(&mut self, traces: &[Rc<ExecTrace>], words: &[Value], result: MoltResult) -> Step {
+        for trace in traces.iter().filter(|trace| trace.fires_on(true)) {
+            if let Err(exception) = self.call_exec_trace(trace, words, Some(&result)) {
+                return Step::Deliver(Err(exception));
+            }
+        }
+
+        Step::Deliver(result)
+    }
+
+    /// Calls an execution trace.  A command trace is passed the command's words as a list,
+    /// then the result code and value if the command has returned, and then the operation.
+    #[cfg(feature = "traces")]
+    fn call_exec_trace(
+        &mut self,
+        trace: &ExecTrace,
+        words: &[Value],
+        result: Option<&MoltResult>,
+    ) -> Result<(), Exception> {
+        trace.active.set(true);
+        let status = match &trace.callback {
+            TraceCallback::Command(cmd) => cmd.to_list().and_then(|mut cmd_words| {
+                cmd_words.push(Value::from(words));
+
+                match result {
+                    Some(Ok(value)) => {
+                        cmd_words.push(Value::from(ResultCode::Okay.as_int()));
+                        cmd_words.push(value.clone());
+                        cmd_words.push(Value::from("leave"));
+                    }
+                    Some(Err(exception)) => {
+                        cmd_words.push(Value::from(exception.code().as_int()));
+                        cmd_words.push(exception.value());
+                        cmd_words.push(Value::from("leave"));
+                    }
+                    None => cmd_words.push(Value::from("enter")),
+                }
+
+                self.call(&cmd_words).map(|_| ())
+            }),
+            TraceCallback::Closure(func) => func(self, words, result),
+        };
+        trace.active.set(false);
+        status
+    }
+
+    //-----------------------------------------------------------------------------------
+    // Coroutines
+
+    /// Creates a coroutine command with the given name, as the `coroutine` command does,
+    /// and starts the coroutine by calling the command with the given words.  Returns the
+    /// value passed to `yield` when the coroutine first yields; or, if the command returns
+    /// without yielding, its result, in which case the coroutine command is deleted.
+    ///
+    /// The command is called at the global level, in the global namespace.  Returns an
+    /// error if a command with the given name already exists.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use remolt::types::*;
+    /// # use remolt::Interp;
+    /// # use remolt::molt_ok;
+    /// # fn dummy() -> MoltResult {
+    /// let mut interp = Interp::new();
+    /// interp.eval("proc summer {} { set sum 0; while 1 { incr sum [yield $sum] } }")?;
+    ///
+    /// let sum = interp.create_coroutine("acc", &[Value::from("summer")])?;
+    /// assert_eq!(sum.as_str(), "0");
+    /// # molt_ok!()
+    /// # }
+    /// ```
+    #[cfg(feature = "coroutines")]
+    pub fn create_coroutine(&mut self, name: &str, words: &[Value]) -> MoltResult {
+        let key = self.command_key(name).into_owned();
+
+        if self.commands.contains_key(&key) {
+            return molt_err!("command \"{}\" already exists", name);
+        }
+
+        let cmd = Rc::new(Command::Coroutine(Coroutine::default()));
+        self.insert_command(name, Rc::clone(&cmd));
+
+        match &*cmd {
+            Command::Coroutine(co) => {
+                self.run_coroutine(co, key, Context::default(), |interp| interp.start_words(words))
+            }
+            _ => unreachable!(),
+        }
+    }
+
+    /// Resumes the named coroutine, as calling its command does: the `yield` that
+    /// suspended it returns the given value.  Returns the value passed to `yield` when
+    /// the coroutine next yields; or, if the coroutine's command returns, its result, in
+    /// which case the coroutine command is deleted.  Use
+    /// [`has_command`](#method.has_command) to find out whether a coroutine is still
+    /// alive.
+    ///
+    /// This is how a Rust client drives a coroutine one message at a time.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use remolt::types::*;
+    /// # use remolt::Interp;
+    /// # use remolt::molt_ok;
+    /// # fn dummy() -> MoltResult {
+    /// let mut interp = Interp::new();
+    /// interp.eval("proc summer {} { set sum 0; while 1 { incr sum [yield $sum] } }")?;
+    /// interp.create_coroutine("acc", &[Value::from("summer")])?;
+    ///
+    /// assert_eq!(interp.resume_coroutine("acc", Value::from(5))?.as_str(), "5");
+    /// assert_eq!(interp.resume_coroutine("acc", Value::from(2))?.as_str(), "7");
+    /// assert!(interp.has_command("acc"));
+    /// # molt_ok!()
+    /// # }
+    /// ```
+    #[cfg(feature = "coroutines")]
+    pub fn resume_coroutine(&mut self, name: &str, value: Value) -> MoltResult {
+        let cmd = match self.find_command(name) {
+            Some((_, cmd)) if matches!(&**cmd, Command::Coroutine(_)) => Rc::clone(cmd),
+            _ => return molt_err!("coroutine \"{}\" not found", name),
+        };
+
+        match &*cmd {
+            Command::Coroutine(co) => {
+                self.call_coroutine(co, &[Value::from(String::from(name)), value])
+            }
+            _ => unreachable!(),
+        }
+    }
+
+    /// Gets the fully qualified name of the coroutine being run, if any, for
+    /// `info coroutine`.
+    #[cfg(feature = "coroutines")]
+    pub(crate) fn coroutine_name(&self) -> Option<Value> {
+        #[cfg(feature = "namespaces")]
+        return self.coroutines.last().map(|(key, _)| Value::from(namespace::display(key)));
+
+        #[cfg(not(feature = "namespaces"))]
+        self.coroutines.last().map(|(key, _)| Value::from(key))
     }
 
-    /// Pops a variable scope (i.e., a stack level) off of the scope stack.  Calls to
-    /// `Interp::push_scope` and `Interp::pop_scope` must exist in pairs.
-    pub fn pop_scope(&mut self) {
-        self.scopes.pop();
+    /// Resumes the coroutine when its command is called.
+    #[cfg(feature = "coroutines")]
+    fn call_coroutine(&mut self, co: &Coroutine, argv: &[Value]) -> MoltResult {
+        // FIRST, get the value for `yield` or `yieldto` to return.  The coroutine's
+        // context is taken while it is running.
+        let yieldto = match &*co.context.borrow() {
+            Some(context) => context.yieldto,
+            None => return molt_err!("coroutine \"{}\" is already running", argv[0]),
+        };
+
+        let value = if yieldto {
+            Value::from(&argv[1..])
+        } else {
+            check_args(1, argv, 1, 2, "?arg?")?;
+            argv.get(1).cloned().unwrap_or_default()
+        };
+
+        // NEXT, run it.
+        let key = match self.find_command(argv[0].as_str()) {
+            Some((key, _)) => key.clone(),
+            None => argv[0].as_str().to_owned(),
+        };
+        let context = co.context.borrow_mut().take().unwrap();
+
+        self.run_coroutine(co, key, context, |_| Step::Deliver(Ok(value)))
     }
 
-    /// Return the current scope level.  The global scope is level `0`; each call to
-    /// `Interp::push_scope` adds a level, and each call to `Interp::pop_scope` removes it.
-    /// This method is used with `Interp::upvar` to access the caller's scope when a variable
-    /// is passed by name.
-    pub fn scope_level(&self) -> usize {
-        self.scopes.current()
+    /// Runs the coroutine in its context, starting with the step returned by `start`,
+    /// until it yields or its command returns.
+    #[cfg(feature = "coroutines")]
+    fn run_coroutine(
+        &mut self,
+        co: &Coroutine,
+        key: String,
+        mut context: Context,
+        start: impl FnOnce(&mut Self) -> Step,
+    ) -> MoltResult {
+        // FIRST, make the coroutine's scopes, frames, call frames, and tail calls current.
+        // The coroutine runs at the global level, whatever the level of its caller.
+        let current = self.scopes.set_current(0);
+        let detached_base = context.scopes.base();
+        let scope_base = self.scopes.attach(core::mem::take(&mut context.scopes));
+
+        for frame in &mut context.frames {
+            if let EvalFrame::Uplevel { saved } = frame {
+                *saved = scope::relocate(*saved, detached_base, scope_base);
+            }
+        }
+
+        let base = self.eval_frames.len();
+        self.eval_frames.append(&mut context.frames);
+        #[cfg(feature = "info")]
+        let frame_base = self.frames.len();
+        #[cfg(feature = "info")]
+        self.frames.append(&mut context.call_frames);
+        let tailcall_base = self.tailcalls.len();
+        self.tailcalls.append(&mut context.tailcalls);
+        let levels = self.num_levels;
+        self.num_levels += context.depth;
+        self.eval_depth += 1;
+        self.coroutines.push((key, self.eval_depth));
+
+        // NEXT, run it.
+        let step = match start(self) {
+            Step::Deliver(result) if self.eval_frames.len() == base => Step::Deliver(result),
+            step => self.run(base, step),
+        };
+
+        // NEXT, set its state aside again.
+        self.coroutines.pop();
+        self.eval_depth -= 1;
+        context.depth = self.num_levels - levels;
+        self.num_levels = levels;
+        context.tailcalls = self.tailcalls.split_off(tailcall_base);
+        #[cfg(feature = "info")]
+        {
+            context.call_frames = self.frames.split_off(frame_base);
+        }
+        context.frames = self.eval_frames.split_off(base);
+        context.scopes = self.scopes.detach(scope_base, current);
+
+        // NEXT, suspend it, or delete it if it's done.
+        match step {
+            Step::Yield(value) => {
+                context.yieldto = false;
+                *co.context.borrow_mut() = Some(context);
+                Ok(value)
+            }
+            Step::YieldTo(words) => {
+                context.yieldto = true;
+                *co.context.borrow_mut() = Some(context);
+                self.call(&words)
+            }
+            Step::Deliver(result) => {
+                self.delete_coroutine(co);
+                result
+            }
+            Step::Enter | Step::TailCall(_) => unreachable!("run returned a step to take"),
+        }
+    }
+
+    /// Deletes the coroutine's command, once its command has returned.
+    #[cfg(feature = "coroutines")]
+    fn delete_coroutine(&mut self, co: &Coroutine) {
+        let key = self.commands.iter().find_map(|(key, cmd)| match &**cmd {
+            Command::Coroutine(other) if core::ptr::eq(other, co) => Some(key.clone()),
+            _ => None,
+        });
+
+        if let Some(key) = key {
+            #[cfg(feature = "traces")]
+            self.forget_exec_traces(&key);
+
+            self.commands.shift_remove(&key);
+        }
     }
 
     //-----------------------------------------------------------------------------------
@@ -1432,6 +3132,14 @@ impl Interp {
     /// Unsets an array variable givee its name.  Nothing happens if the variable doesn't
     /// exist, or if the variable is not an array variable.
     pub(crate) fn array_unset(&mut self, array_name: &str) {
+        #[cfg(feature = "traces")]
+        if self.scopes.has_traces() && self.scopes.array_exists(array_name) {
+            let traces = self.scopes.take_traces(array_name);
+            self.scopes.array_unset(array_name);
+            let _ = self.call_var_traces(&traces, array_name, None, VarOp::Unset);
+            return;
+        }
+
         self.scopes.array_unset(array_name);
     }
 
@@ -1504,8 +3212,16 @@ impl Interp {
     /// # }
     /// ```
     pub fn array_set(&mut self, array_name: &str, kvlist: &[Value]) -> Result<(), Exception> {
-        if kvlist.len() % 2 == 0 {
+        if kvlist.len().is_multiple_of(2) {
             self.scopes.array_set(array_name, kvlist)?;
+
+            #[cfg(feature = "traces")]
+            if self.scopes.has_traces() {
+                for kv in kvlist.chunks(2) {
+                    self.fire_var_traces(array_name, Some(kv[0].as_str()), VarOp::Write)?;
+                }
+            }
+
             Ok(())
         } else {
             molt_err!("list must have an even number of elements")
@@ -1560,7 +3276,7 @@ impl Interp {
 
     #[cfg(feature = "closure-commands")]
     pub fn add_command_closure(&mut self, name: &str, func: impl (Fn(&mut Interp, &[Value]) -> MoltOptResult) + 'static) {
-        self.commands.insert(name.into(), Rc::new(Command::Closure(Box::new(func))));
+        self.insert_command(name, Rc::new(Command::Closure(Box::new(func))));
     }
 
     /// Adds a binary command with no related context to the interpreter.  This is the normal
@@ -1569,32 +3285,96 @@ impl Interp {
     /// If the command needs access to some form of application or context data,
     /// use [`add_context_command`](#method.add_context_command) instead.  See the
     /// [module level documentation](index.html) for an overview and examples.
+    ///
+    /// With the `namespaces` feature, the name may be qualified, e.g., `mylib::init`; it is
+    /// resolved relative to the current namespace, and the namespace is created if need be.
     pub fn add_command(&mut self, name: &str, func: CommandFunc) {
-        self.commands
-            .insert(name.into(), Rc::new(Command::Native(func)));
+        self.insert_command(name, Rc::new(Command::Native(func)));
     }
 
     /// Adds a procedure to the interpreter.
     ///
     /// This is how to add a Molt `proc` to the interpreter.  The arguments are the same
-    /// as for the `proc` command and the `commands::cmd_proc` function.
+    /// as for the `proc` command and the `commands::cmd_proc` function.  Returns an
+    /// error if the parameter list is invalid.
+    pub(crate) fn add_proc(&mut self, name: &str, parms: &[Value], body: &Value) -> Result<(), Exception> {
+        #[allow(unused_mut)]
+        let mut proc = Procedure::new(parms, body)?;
+        let key = self.command_key(name);
+
+        // With namespaces, the procedure runs in the namespace in which it's defined,
+        // which must already exist.
+        #[cfg(feature = "namespaces")]
+        {
+            let ns = namespace::qualifiers(&key);
+
+            if !self.namespaces.contains_key(ns) {
+                return molt_err!("can't create procedure \"{}\": unknown namespace", name);
+            }
+
+            proc.namespace = Rc::from(ns);
+        }
+
+        #[cfg(feature = "traces")]
+        self.forget_exec_traces(&key);
+
+        self.commands.insert(key.into_owned(), Rc::new(Command::Proc(proc)));
+        Ok(())
+    }
+
+    /// Adds the command to the command table under the given name, creating the command's
+    /// namespace if need be.
+    fn insert_command(&mut self, name: &str, cmd: Rc<Command>) {
+        let key = self.command_key(name).into_owned();
+
+        #[cfg(feature = "namespaces")]
+        self.ensure_namespace(namespace::qualifiers(&key));
+
+        #[cfg(feature = "traces")]
+        self.forget_exec_traces(&key);
+
+        self.commands.insert(key, cmd);
+    }
+
+    /// Returns the command table key for a command being defined with the given name.
+    /// With namespaces, this is the name qualified relative to the current namespace.
+    fn command_key<'a>(&self, name: &'a str) -> Cow<'a, str> {
+        #[cfg(feature = "namespaces")]
+        return namespace::qualify(self.scopes.namespace(), name);
+
+        #[cfg(not(feature = "namespaces"))]
+        Cow::Borrowed(name)
+    }
+
+    /// Looks up the command with the given name, returning its key in the command table
+    /// and the command itself.
     ///
-    /// TODO: If this method is ever made public, the parameter list validation done
-    /// in cmd_proc should be moved here.
-    pub(crate) fn add_proc(&mut self, name: &str, parms: &[Value], body: &Value) {
-        let proc = Procedure {
-            parms: parms.to_owned(),
-            body: body.clone(),
-        };
+    /// With namespaces, a name is resolved relative to the current namespace, falling back
+    /// to the global namespace.  In the global namespace, this is a single lookup.
+    #[cfg(feature = "namespaces")]
+    fn find_command(&self, name: &str) -> Option<(&String, &Rc<Command>)> {
+        let ns = self.scopes.namespace();
+
+        if ns.is_empty() || namespace::is_absolute(name) {
+            self.commands.get_key_value(&*namespace::qualify("", name))
+        } else {
+            self.commands
+                .get_key_value(&*namespace::qualify(ns, name))
+                .or_else(|| self.commands.get_key_value(name))
+        }
+    }
 
-        self.commands
-            .insert(name.into(), Rc::new(Command::Proc(proc)));
+    /// Looks up the command with the given name, returning its key in the command table
+    /// and the command itself.
+    #[cfg(not(feature = "namespaces"))]
+    fn find_command(&self, name: &str) -> Option<(&String, &Rc<Command>)> {
+        self.commands.get_key_value(name)
     }
 
     /// Determines whether or not the interpreter contains a command with the given
     /// name.
     pub fn has_command(&self, name: &str) -> bool {
-        self.commands.contains_key(name)
+        self.find_command(name).is_some()
     }
 
     /// Renames the command.
@@ -1622,10 +3402,22 @@ impl Interp {
     /// # }
     /// ```
     pub fn rename_command(&mut self, old_name: &str, new_name: &str) {
-        if let Some(cmd) = self.commands.get(old_name) {
+        if let Some((key, cmd)) = self.find_command(old_name) {
+            let key = key.clone();
             let cmd = Rc::clone(cmd);
-            self.commands.remove(old_name);
-            self.commands.insert(new_name.into(), cmd);
+            self.commands.remove(&key);
+
+            #[cfg(feature = "traces")]
+            let traces = self.exec_traces.shift_remove(&key);
+
+            self.insert_command(new_name, cmd);
+
+            // The command's execution traces follow it.
+            #[cfg(feature = "traces")]
+            if let Some(traces) = traces {
+                let new_key = self.command_key(new_name).into_owned();
+                self.exec_traces.insert(new_key, traces);
+            }
         }
     }
 
@@ -1647,10 +3439,17 @@ impl Interp {
     /// assert!(!interp.has_command("set"));
     /// ```
     pub fn remove_command(&mut self, name: &str) {
-        self.commands.remove(name);
+        if let Some((key, _)) = self.find_command(name) {
+            let key = key.clone();
+            self.commands.remove(&key);
+
+            #[cfg(feature = "traces")]
+            self.forget_exec_traces(&key);
+        }
     }
 
-    /// Gets a vector of the names of the existing commands.
+    /// Gets a vector of the names of the existing commands.  With the `namespaces` feature,
+    /// the names of commands outside the global namespace are fully qualified.
     ///
     /// # Example
     ///
@@ -1669,8 +3468,7 @@ impl Interp {
         let vec: MoltList = self
             .commands
             .keys()
-            .cloned()
-            .map(|x| Value::from(&x))
+            .map(command_name)
             .collect();
 
         vec
@@ -1679,7 +3477,7 @@ impl Interp {
     /// Returns the body of the named procedure, or an error if the name doesn't
     /// name a procedure.
     pub fn command_type(&self, command: &str) -> MoltResult {
-        if let Some(cmd) = self.commands.get(command) {
+        if let Some((_, cmd)) = self.find_command(command) {
             molt_ok!(cmd.cmdtype())
         } else {
             molt_err!("\"{}\" isn't a command", command)
@@ -1706,7 +3504,7 @@ impl Interp {
             .commands
             .iter()
             .filter(|(_, cmd)| cmd.is_proc())
-            .map(|(name, _)| Value::from(name))
+            .map(|(key, _)| command_name(key))
             .collect();
 
         vec
@@ -1715,7 +3513,7 @@ impl Interp {
     /// Returns the body of the named procedure, or an error if the name doesn't
     /// name a procedure.
     pub fn proc_body(&self, procname: &str) -> MoltResult {
-        if let Some(cmd) = self.commands.get(procname) {
+        if let Some((_, cmd)) = self.find_command(procname) {
             if let Command::Proc(proc) = &**cmd {
                 return molt_ok!(proc.body.clone());
             }
@@ -1727,7 +3525,7 @@ impl Interp {
     /// Returns a list of the names of the arguments of the named procedure, or an
     /// error if the name doesn't name a procedure.
     pub fn proc_args(&self, procname: &str) -> MoltResult {
-        if let Some(cmd) = self.commands.get(procname) {
+        if let Some((_, cmd)) = self.find_command(procname) {
             if let Command::Proc(proc) = &**cmd {
                 // Note: the item is guaranteed to be parsible as a list of 1 or 2 elements.
                 let vec: MoltList = proc
@@ -1746,7 +3544,7 @@ impl Interp {
     /// Returns an error if the procedure has no such argument, or the `procname` doesn't name
     /// a procedure.
     pub fn proc_default(&self, procname: &str, arg: &str) -> Result<Option<Value>, Exception> {
-        if let Some(cmd) = self.commands.get(procname) {
+        if let Some((_, cmd)) = self.find_command(procname) {
             if let Command::Proc(proc) = &**cmd {
                 for argvec in &proc.parms {
                     let argvec = argvec.as_list()?; // Should never fail
@@ -1769,6 +3567,184 @@ impl Interp {
         molt_err!("\"{}\" isn't a procedure", procname)
     }
 
+    //--------------------------------------------------------------------------------------------
+    // Namespaces
+
+    /// Gets the names of the commands visible in the current namespace whose names match
+    /// the glob pattern, if any, for `info commands` and `info procs`.  A qualified
+    /// pattern matches the fully qualified names of the commands in the namespace it names.
+    #[cfg(all(feature = "namespaces", feature = "info"))]
+    pub(crate) fn visible_command_names(&self, pattern: Option<&str>, procs_only: bool) -> MoltList {
+        let ns = self.scopes.namespace();
+        let cmds = self
+            .commands
+            .iter()
+            .filter(|(_, cmd)| !procs_only || cmd.is_proc())
+            .map(|(key, _)| key);
+
+        match pattern {
+            Some(pattern) if namespace::is_qualified(pattern) => {
+                let pattern = namespace::qualify(ns, pattern);
+                let parent = namespace::qualifiers(&pattern);
+
+                cmds.filter(|key| namespace::qualifiers(key) == parent)
+                    .filter(|key| util::glob_match(&pattern, key, false))
+                    .map(|key| Value::from(namespace::display(key)))
+                    .collect()
+            }
+            _ => cmds
+                .filter(|key| {
+                    let parent = namespace::qualifiers(key);
+                    parent == &**ns
+                        || (parent.is_empty()
+                            && !self.commands.contains_key(&*namespace::qualify(ns, key)))
+                })
+                .map(|key| namespace::tail(key))
+                .filter(|name| pattern.is_none_or(|p| util::glob_match(p, name, false)))
+                .map(|name| Value::from(name.to_string()))
+                .collect(),
+        }
+    }
+
+    /// Returns the current namespace's key; the global namespace is "".
+    #[cfg(feature = "namespaces")]
+    pub(crate) fn current_namespace(&self) -> &str {
+        self.scopes.namespace()
+    }
+
+    /// Returns the key of the namespace with the given name, relative to the current
+    /// namespace.
+    #[cfg(feature = "namespaces")]
+    pub(crate) fn namespace_key<'a>(&self, name: &'a str) -> Cow<'a, str> {
+        match namespace::qualify(self.scopes.namespace(), name) {
+            Cow::Borrowed(key) => Cow::Borrowed(key.trim_end_matches(':')),
+            Cow::Owned(key) => Cow::Owned(key.trim_end_matches(':').into()),
+        }
+    }
+
+    /// Determines whether the namespace with the given key exists.
+    #[cfg(feature = "namespaces")]
+    pub(crate) fn namespace_exists(&self, key: &str) -> bool {
+        self.namespaces.contains_key(key)
+    }
+
+    /// Creates the namespace with the given key, and its parents, if they don't already
+    /// exist.
+    #[cfg(feature = "namespaces")]
+    pub(crate) fn ensure_namespace(&mut self, key: &str) {
+        if !self.namespaces.contains_key(key) {
+            self.ensure_namespace(namespace::qualifiers(key));
+            self.namespaces.insert(key.into(), Namespace::default());
+        }
+    }
+
+    /// Gets the keys of the children of the namespace with the given key.
+    #[cfg(feature = "namespaces")]
+    pub(crate) fn namespace_children(&self, key: &str) -> Vec<String> {
+        self.namespaces
+            .keys()
+            .filter(|child| !child.is_empty() && namespace::qualifiers(child) == key)
+            .cloned()
+            .collect()
+    }
+
+    /// Deletes the namespace with the given key, along with its children, its commands,
+    /// and its variables.  The global namespace can't be deleted.
+    #[cfg(feature = "namespaces")]
+    pub(crate) fn delete_namespace(&mut self, key: &str) {
+        assert!(!key.is_empty(), "Deleted global namespace!");
+        let within = |name: &str| {
+            name.starts_with(key)
+                && (name.len() == key.len() || name[key.len()..].starts_with(namespace::SEP))
+        };
+
+        self.namespaces.retain(|name, _| !within(name));
+        self.commands.retain(|name, _| !within(namespace::qualifiers(name)));
+        #[cfg(feature = "traces")]
+        self.exec_traces.retain(|name, _| !within(namespace::qualifiers(name)));
+        self.scopes.unset_namespace(key);
+    }
+
+    /// Gets the names of the variables in the namespace with the given key.
+    #[cfg(all(feature = "namespaces", feature = "info"))]
+    pub(crate) fn namespace_vars(&self, key: &str) -> MoltList {
+        self.scopes.vars_in_namespace(key)
+    }
+
+    /// Determines whether the current scope is a namespace frame rather than a procedure's
+    /// local scope.
+    #[cfg(feature = "namespaces")]
+    pub(crate) fn in_namespace_frame(&self) -> bool {
+        self.scopes.is_ns_frame()
+    }
+
+    /// Gets the export patterns of the current namespace.  It's an error if the current
+    /// namespace has been deleted while its code is still running.
+    #[cfg(feature = "namespaces")]
+    pub(crate) fn namespace_exports(&mut self) -> Result<&mut Vec<String>, Exception> {
+        let ns = self.scopes.namespace();
+
+        match self.namespaces.get_mut(&**ns) {
+            Some(namespace) => Ok(&mut namespace.exports),
+            None => molt_err!("namespace \"{}\" not found", namespace::display(ns)),
+        }
+    }
+
+    /// Imports the commands matching the pattern, a qualified command name that may
+    /// contain glob characters, into the current namespace.  Only commands exported by
+    /// their namespace are imported.  It's an error if a command would replace an existing
+    /// command, unless `force` is set.
+    #[cfg(feature = "namespaces")]
+    pub(crate) fn namespace_import(&mut self, pattern: &str, force: bool) -> Result<(), Exception> {
+        let current: String = self.current_namespace().into();
+
+        if !self.namespaces.contains_key(&current) {
+            return molt_err!("namespace \"{}\" not found", namespace::display(&current));
+        }
+
+        let key = namespace::qualify(&current, pattern);
+        let source = namespace::qualifiers(&key);
+        let tail = namespace::tail(&key);
+
+        let exports = match self.namespaces.get(source) {
+            Some(ns) => &ns.exports,
+            None => return molt_err!("unknown namespace in import pattern \"{}\"", pattern),
+        };
+
+        if source == current {
+            return molt_err!(
+                "import pattern \"{}\" tries to import from namespace \"{}\" into itself",
+                pattern,
+                namespace::display(source)
+            );
+        }
+
+        // FIRST, find the matching exported commands.
+        let imports: Vec<(String, Rc<Command>)> = self
+            .commands
+            .iter()
+            .filter(|(key, _)| namespace::qualifiers(key) == source)
+            .map(|(key, cmd)| (namespace::tail(key), cmd))
+            .filter(|(name, _)| util::glob_match(tail, name, false))
+            .filter(|(name, _)| exports.iter().any(|p| util::glob_match(p, name, false)))
+            .map(|(name, cmd)| (namespace::qualify(&current, name).into_owned(), Rc::clone(cmd)))
+            .collect();
+
+        // NEXT, add them to the current namespace.
+        for (key, cmd) in imports {
+            if !force && self.commands.contains_key(&key) {
+                return molt_err!("can't import command \"{}\": already exists", namespace::tail(&key));
+            }
+
+            #[cfg(feature = "traces")]
+            self.forget_exec_traces(&key);
+
+            self.commands.insert(key, cmd);
+        }
+
+        Ok(())
+    }
+
     /// Calls a subcommand of the current command, looking up its name in an array of
     /// `Subcommand` tuples.
     ///
@@ -1868,6 +3844,52 @@ impl Interp {
     }
 }
 
+/// Returns the name of the command with the given key, as presented to the user: commands
+/// outside the global namespace are fully qualified.
+fn command_name(key: &String) -> Value {
+    #[cfg(feature = "namespaces")]
+    if namespace::is_qualified(key) {
+        return Value::from(namespace::display(key));
+    }
+
+    Value::from(key)
+}
+
+/// Adds the command to the stack trace of the result, if it's an error.
+#[cfg(feature = "error-stack-trace")]
+fn with_error_info(result: MoltResult, is_proc: bool, words: &[Value]) -> MoltResult {
+    match result {
+        Err(exception) if exception.code() == ResultCode::Error => {
+            Err(add_error_info(exception, is_proc, words))
+        }
+        _ => result,
+    }
+}
+
+/// Adds the command to the stack trace of an error returned by it.
+#[cfg(feature = "error-stack-trace")]
+fn add_error_info(mut exception: Exception, is_proc: bool, words: &[Value]) -> Exception {
+    // FIRST, new error, an error from within a proc, or an error from
+    // within some other body (ignored).
+    if exception.is_new_error() {
+        exception.add_error_info("    while executing");
+    } else if is_proc {
+        exception.add_error_info("    invoked from within");
+        exception.add_error_info(&format!(
+                "    (procedure \"{}\" line TODO)",
+                words[0]
+                ));
+    } else {
+        return exception;
+    }
+
+    // TODO: Add command.  In standard TCL, this is the text of the command
+    // before interpolation; at present, we don't have that info in a
+    // convenient form.  For now, just convert the final words to a string.
+    exception.add_error_info(&format!("\"{}\"", &crate::list::list_to_string(words)));
+    exception
+}
+
 /// How a procedure is defined: as an argument list and a body script.
 /// The argument list is a list of Values, and the body is a Value; each will
 /// retain its parsed form.
@@ -1875,23 +3897,148 @@ impl Interp {
 /// NOTE: We do not save the procedure's name; the name exists only in the
 /// commands table, and can be changed there freely.  The procedure truly doesn't
 /// know what its name is except when it is being executed.
-struct Procedure {
+///
+/// The anonymous functions called by `apply` are also `Procedure`s, cached in the
+/// lambda expression's `Value`.
+#[derive(Debug)]
+pub(crate) struct Procedure {
     /// The procedure's parameter list.  Each item in the list is a name or a
-    /// name/default value pair.  (This is verified by the `proc` command.)
+    /// name/default value pair.  (This is verified by `Procedure::new`.)
     parms: MoltList,
 
     /// The procedure's body string, as a Value.  As such, it retains both its
     /// string value, as needed for introspection, and its parsed Script.
     body: Value,
+
+    /// The namespace in which the procedure runs, as a key.
+    #[cfg(feature = "namespaces")]
+    pub(crate) namespace: Rc<str>,
+}
+
+/// The kind of a call frame.
+#[cfg(feature = "info")]
+#[derive(Debug)]
+pub(crate) enum FrameKind {
+    /// A procedure call.
+    Proc,
+
+    /// A lambda called by `apply`.
+    Lambda,
+
+    /// A `namespace eval` script.
+    #[cfg(feature = "namespaces")]
+    Namespace,
+
+    /// A file being sourced, by name.
+    #[cfg(feature = "std")]
+    Source(String),
+}
+
+/// A call frame: the context of a procedure call, a lambda, a `namespace eval` script,
+/// or a sourced file, as reported by `info level` and `info frame`.
+#[cfg(feature = "info")]
+#[derive(Debug)]
+pub(crate) struct Frame {
+    /// The kind of frame.
+    pub kind: FrameKind,
+
+    /// The words of the command that pushed the frame.
+    pub argv: MoltList,
+
+    /// The level of the frame's variable scope.
+    pub level: usize,
+}
+
+#[cfg(feature = "info")]
+impl Frame {
+    /// Returns true if the frame pushed a variable scope, i.e., if it isn't a sourced file.
+    fn pushes_scope(&self) -> bool {
+        #[cfg(feature = "std")]
+        if let FrameKind::Source(_) = self.kind {
+            return false;
+        }
+
+        true
+    }
 }
 
 impl Procedure {
-    fn execute(&self, interp: &mut Interp, argv: &[Value]) -> MoltResult {
-        // FIRST, push the proc's local scope onto the stack.
-        interp.push_scope();
+    /// Creates a procedure from its parameter list and body, validating the parameter
+    /// list.
+    pub(crate) fn new(parms: &[Value], body: &Value) -> Result<Self, Exception> {
+        for parm in parms {
+            let vec = parm.as_list()?;
+
+            if vec.is_empty() {
+                return molt_err!("argument with no name");
+            } else if vec.len() > 2 {
+                return molt_err!("too many fields in argument specifier \"{}\"", parm);
+            }
+        }
+
+        Ok(Self {
+            parms: parms.to_owned(),
+            body: body.clone(),
+            #[cfg(feature = "namespaces")]
+            namespace: Rc::from(""),
+        })
+    }
+
+    /// Parses a lambda expression, as used by `apply`: a list of a parameter list, a
+    /// body, and an optional namespace.
+    pub(crate) fn from_lambda(lambda: &Value) -> Result<Self, Exception> {
+        let vec = lambda.to_list()?;
+
+        if vec.len() != 2 && vec.len() != 3 {
+            return molt_err!("can't interpret \"{}\" as a lambda expression", lambda);
+        }
+
+        #[allow(unused_mut)]
+        let mut proc = Self::new(&vec[0].to_list()?, &vec[1])?;
+
+        // The namespace is relative to the global namespace.
+        #[cfg(feature = "namespaces")]
+        if let Some(ns) = vec.get(2) {
+            proc.namespace = Rc::from(namespace::qualify("", ns.as_str().trim_end_matches(':')));
+        }
+
+        Ok(proc)
+    }
+
+    /// Translates the result of the procedure's body into the procedure's result.
+    fn translate_result(result: MoltResult) -> MoltResult {
+        if let Err(mut exception) = result {
+            // FIRST, handle the return -code, -level protocol.  A code returned explicitly,
+            // e.g., by `return -code break`, propagates to the caller as is.
+            if exception.code() == ResultCode::Return {
+                exception.decrement_level();
+
+                return match exception.code() {
+                    ResultCode::Okay => Ok(exception.value()),
+                    _ => Err(exception),
+                };
+            }
+
+            return match exception.code() {
+                ResultCode::Okay => Ok(exception.value()),
+                ResultCode::Error => Err(exception),
+                ResultCode::Return => Err(exception), // -level > 0
+                ResultCode::Break => molt_err!("invoked \"break\" outside of a loop"),
+                ResultCode::Continue => molt_err!("invoked \"continue\" outside of a loop"),
+                // TODO: Better error message
+                ResultCode::Other(_) => molt_err!("unexpected result code."),
+            };
+        }
+
+        // NEXT, return the computed result.
+        // Note: no need for special handling for return, break, continue;
+        // interp.eval() returns only Ok or a real error.
+        result
+    }
 
-        // NEXT, process the proc's argument list.
-        let mut argi = 1; // Skip the proc's name
+    /// Sets the procedure's parameters in the current scope from the arguments.
+    fn bind_args(&self, interp: &mut Interp, name: &str, args: &[Value]) -> Result<(), Exception> {
+        let mut argi = 0;
 
         for (speci, spec) in self.parms.iter().enumerate() {
             // FIRST, get the parameter as a vector.  It should be a list of
@@ -1903,17 +4050,17 @@ impl Procedure {
             // if any.  Note that "args" has special meaning only if it's the
             // final arg spec in the list.
             if vec[0].as_str() == "args" && speci == self.parms.len() - 1 {
-                interp.set_scalar("args", Value::from(&argv[argi..]))?;
+                interp.set_scalar("args", Value::from(&args[argi..]))?;
 
                 // We've processed all of the args
-                argi = argv.len();
+                argi = args.len();
                 break;
             }
 
             // NEXT, do we have a matching argument?
-            if argi < argv.len() {
+            if argi < args.len() {
                 // Pair them up
-                interp.set_scalar(vec[0].as_str(), argv[argi].clone())?;
+                interp.set_scalar(vec[0].as_str(), args[argi].clone())?;
                 argi += 1;
                 continue;
             }
@@ -1923,51 +4070,24 @@ impl Procedure {
                 interp.set_scalar(vec[0].as_str(), vec[1].clone())?;
             } else {
                 // We don't; we're missing a required argument.
-                return self.wrong_num_args(&argv[0]);
+                return self.wrong_num_args(name);
             }
         }
 
         // NEXT, do we have any arguments left over?
-
-        if argi != argv.len() {
-            return self.wrong_num_args(&argv[0]);
-        }
-
-        // NEXT, evaluate the proc's body, getting the result.
-        let result = interp.eval_value(&self.body);
-
-        // NEXT, pop the scope off of the stack; we're done with it.
-        interp.pop_scope();
-
-        if let Err(mut exception) = result {
-            // FIRST, handle the return -code, -level protocol
-            if exception.code() == ResultCode::Return {
-                exception.decrement_level();
-            }
-
-            return match exception.code() {
-                ResultCode::Okay => Ok(exception.value()),
-                ResultCode::Error => Err(exception),
-                ResultCode::Return => Err(exception), // -level > 0
-                ResultCode::Break => molt_err!("invoked \"break\" outside of a loop"),
-                ResultCode::Continue => molt_err!("invoked \"continue\" outside of a loop"),
-                // TODO: Better error message
-                ResultCode::Other(_) => molt_err!("unexpected result code."),
-            };
+        if argi != args.len() {
+            return self.wrong_num_args(name);
         }
 
-        // NEXT, return the computed result.
-        // Note: no need for special handling for return, break, continue;
-        // interp.eval() returns only Ok or a real error.
-        result
+        Ok(())
     }
 
     // Outputs the wrong # args message for the proc.  The name is passed in
     // because it can be changed via the `rename` command.
-    fn wrong_num_args(&self, name: &Value) -> MoltResult {
+    fn wrong_num_args(&self, name: &str) -> Result<(), Exception> {
         let mut msg = String::new();
         msg.push_str("wrong # args: should be \"");
-        msg.push_str(name.as_str());
+        msg.push_str(name);
 
         for (i, arg) in self.parms.iter().enumerate() {
             msg.push(' ');
@@ -2155,4 +4275,94 @@ mod tests {
             ))
         ));
     }
+
+    #[cfg(feature = "traces")]
+    #[test]
+    fn test_trace_var() {
+        let mut interp = Interp::new();
+
+        let id = interp.trace_var("x", VarOps::ALL, |interp, name, _, op| {
+            interp.eval(&format!("lappend log {} {}", name, op.as_str()))?;
+            Ok(())
+        });
+
+        assert!(interp.eval("set x 1; set x; unset x").is_ok());
+        assert_eq!(interp.scalar("log").unwrap().as_str(), "x write x read x unset");
+
+        // Unsetting the variable removed the trace.
+        interp.untrace_var("x", id);
+        assert!(interp.eval("set log {}; set x 1").is_ok());
+        assert_eq!(interp.scalar("log").unwrap().as_str(), "");
+
+        // Popping a scope unsets its variables.
+        interp.push_scope();
+        interp.set_scalar("y", Value::from(1)).unwrap();
+        interp.trace_var("y", VarOps::ALL, |interp, name, _, op| {
+            interp.set_scalar("popped", Value::from(format!("{} {}", name, op.as_str())))
+        });
+        interp.pop_scope();
+        assert_eq!(interp.scalar("popped").unwrap().as_str(), "y unset");
+    }
+
+    #[cfg(feature = "traces")]
+    #[test]
+    fn test_trace_execution() {
+        let mut interp = Interp::new();
+        assert!(interp.trace_execution("nosuch", ExecOps::default(), |_, _, _| Ok(())).is_err());
+
+        let ops = ExecOps { enter: true, leave: true };
+        let id = interp
+            .trace_execution("llength", ops, |interp, argv, result| {
+                let event = match result {
+                    None => "enter".into(),
+                    Some(Ok(value)) => value.to_string(),
+                    Some(Err(exception)) => exception.value().to_string(),
+                };
+                interp.set_element("log", argv[1].as_str(), Value::from(event))
+            })
+            .unwrap();
+
+        assert_eq!(interp.eval("llength {a b}"), Ok(Value::from(2)));
+        assert_eq!(interp.element("log", "a b").unwrap().as_str(), "2");
+
+        // A leave trace sees errors.
+        assert!(interp.eval("llength \"{\"").is_err());
+        assert_eq!(interp.element("log", "{").unwrap().as_str(), "unmatched open brace in list");
+
+        interp.untrace_execution("llength", id);
+        assert!(interp.eval("llength {c}").is_ok());
+        assert!(interp.element("log", "c").is_err());
+    }
+
+    #[cfg(feature = "coroutines")]
+    #[test]
+    fn test_resume_coroutine() {
+        let mut interp = Interp::new();
+        assert!(interp.resume_coroutine("nosuch", Value::empty()).is_err());
+
+        // A protocol handler, driven one message at a time.
+        interp
+            .eval(
+                "proc handler {} {
+                    set count 0
+                    while 1 {
+                        set cmd [yield $count]
+                        if {$cmd eq \"quit\"} break
+                        incr count
+                    }
+                    return \"handled $count\"
+                }",
+            )
+            .unwrap();
+
+        assert_eq!(interp.create_coroutine("h", &[Value::from("handler")]), Ok(Value::from(0)));
+        assert!(interp.create_coroutine("h", &[Value::from("handler")]).is_err());
+        assert_eq!(interp.resume_coroutine("h", Value::from("a")), Ok(Value::from(1)));
+        assert_eq!(interp.resume_coroutine("h", Value::from("b")), Ok(Value::from(2)));
+        assert!(interp.has_command("h"));
+
+        assert_eq!(interp.resume_coroutine("h", Value::from("quit")).unwrap().as_str(), "handled 2");
+        assert!(!interp.has_command("h"));
+        assert!(interp.resume_coroutine("h", Value::empty()).is_err());
+    }
 }
@@ -34,20 +34,33 @@ pub use crate::test_harness::test_harness;
 pub use crate::types::*;
 
 mod commands;
+#[cfg(feature = "coroutines")]
+mod coroutine;
 #[cfg(feature = "dict")]
 pub mod dict;
+mod eval;
 mod eval_ptr;
 #[cfg(feature = "expr")]
 mod expr;
+#[cfg(feature = "format-command")]
+mod format;
 pub mod interp;
 mod list;
 mod tokenizer;
 #[macro_use]
 mod macros;
+#[cfg(feature = "namespaces")]
+mod namespace;
 mod parser;
+#[cfg(feature = "regex")]
+mod regex;
+#[cfg(feature = "scan-command")]
+mod scan;
 mod scope;
 #[cfg(all(feature = "closure-commands", any(test, feature = "std")))]
 pub mod test_harness;
+#[cfg(feature = "traces")]
+mod trace;
 pub mod types;
 mod util;
 pub mod value;
@@ -16,7 +16,15 @@ use alloc::vec::Vec;
 pub(crate) fn get_list(str: &str) -> Result<MoltList, Exception> {
     let mut ctx = Tokenizer::new(str);
 
-    parse_list(&mut ctx)
+    parse_list(&mut ctx).map_err(|(_, exception)| exception)
+}
+
+/// Parses a list-formatted string into a vector, as `get_list` does, but on failure
+/// returns the character index of the item that couldn't be parsed.
+pub(crate) fn get_list_or_index(str: &str) -> Result<MoltList, usize> {
+    let mut ctx = Tokenizer::new(str);
+
+    parse_list(&mut ctx).map_err(|(mark, _)| str[..mark].chars().count())
 }
 
 // Is the character a valid whitespace character in list syntax?
@@ -32,7 +40,9 @@ fn is_list_white(ch: char) -> bool {
     }
 }
 
-fn parse_list(ctx: &mut Tokenizer) -> Result<MoltList, Exception> {
+/// Parses the list, returning on failure the error and the mark of the item that couldn't
+/// be parsed.
+fn parse_list(ctx: &mut Tokenizer) -> Result<MoltList, (usize, Exception)> {
     // FIRST, skip any list whitespace.
     ctx.skip_while(is_list_white);
 
@@ -41,7 +51,8 @@ fn parse_list(ctx: &mut Tokenizer) -> Result<MoltList, Exception> {
     while !ctx.at_end() {
         // FIRST, get the next item; there has to be one.
         // Throw an error if there's a formatting problem.
-        items.push(parse_item(ctx)?);
+        let mark = ctx.mark();
+        items.push(parse_item(ctx).map_err(|exception| (mark, exception))?);
 
         // NEXT, skip whitespace to the end or the next item.
         ctx.skip_while(is_list_white);
@@ -133,7 +144,8 @@ fn parse_quoted_item(ctx: &mut Tokenizer) -> MoltResult {
                 item.push(ctx.backslash_subst());
                 start = ctx.mark();
             }
-            _ => unreachable!(),
+            // The input ended without a closing quote.
+            _ => break,
         }
     }
 
@@ -202,6 +214,26 @@ pub fn list_to_string(list: &[Value]) -> String {
     text
 }
 
+/// Concatenates the values into a single string the way the `concat` command does: leading
+/// and trailing whitespace is trimmed from each value, empty values are dropped, and the
+/// remainder are joined with single spaces.
+pub(crate) fn concat_values(values: &[Value]) -> String {
+    let mut text = String::new();
+
+    for value in values {
+        let item = value.as_str().trim_matches(is_list_white);
+
+        if !item.is_empty() {
+            if !text.is_empty() {
+                text.push(' ');
+            }
+            text.push_str(item);
+        }
+    }
+
+    text
+}
+
 fn brace_item(item: &str, out: &mut String) {
     out.push('{');
     out.push_str(item);
@@ -284,6 +316,20 @@ fn get_mode(word: &str) -> Mode {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_concat_values() {
+        assert_eq!(concat_values(&[]), "");
+        assert_eq!(concat_values(&[Value::from("a")]), "a");
+        assert_eq!(
+            concat_values(&[Value::from(" a b "), Value::from("\tc\n")]),
+            "a b c"
+        );
+        assert_eq!(
+            concat_values(&[Value::from("a"), Value::from("  "), Value::from("{b c}")]),
+            "a {b c}"
+        );
+    }
+
     #[test]
     fn test_list_to_string() {
         assert_eq!(list_to_string(&[Value::from("a")]), "a");
@@ -337,6 +383,7 @@ mod tests {
         assert_eq!(pqi("\"abc\""), "abc|".to_string());
         assert_eq!(pqi("\"abc\"  "), "abc|  ".to_string());
         assert_eq!(pqi("\"a\\x77-\""), "aw-|".to_string());
+        assert_eq!(pqi("\"abc"), "Err".to_string());
     }
 
     fn pqi(input: &str) -> String {
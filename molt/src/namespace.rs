@@ -0,0 +1,112 @@
+//! Namespace Names
+//!
+//! Molt namespaces partition the command table and the global variables, so that libraries
+//! can define commands and variables without clobbering each other.  A namespace is named
+//! by a `::`-separated path from the global namespace, e.g., `::a::b`.
+//!
+//! Internally, namespaced commands and variables are stored in the interpreter's command
+//! table and in the global scope under their fully qualified names, written *without* the
+//! leading `::`: the variable `::a::b::x` is stored in the global scope as `a::b::x`, and
+//! the global variable `::x` as simply `x`.  The global namespace itself is the empty
+//! string.  This module contains the routines for converting between the names used in
+//! Molt code and these keys.
+
+use alloc::borrow::Cow;
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+
+/// The namespace separator.
+pub(crate) const SEP: &str = "::";
+
+/// A namespace defined in the interpreter.
+#[derive(Default, Debug)]
+pub(crate) struct Namespace {
+    /// The glob patterns of the namespace's exported commands.
+    pub exports: Vec<String>,
+}
+
+/// Returns true if the name is absolute, i.e., if it begins with `::`.
+pub(crate) fn is_absolute(name: &str) -> bool {
+    name.starts_with(SEP)
+}
+
+/// Returns true if the name contains a namespace separator.
+pub(crate) fn is_qualified(name: &str) -> bool {
+    name.contains(SEP)
+}
+
+/// Qualifies a name relative to the namespace `ns`, returning its key: its fully
+/// qualified name without the leading `::`.  Absolute names are qualified relative to
+/// the global namespace.
+pub(crate) fn qualify<'a>(ns: &str, name: &'a str) -> Cow<'a, str> {
+    if is_absolute(name) {
+        Cow::Borrowed(name.trim_start_matches(':'))
+    } else if ns.is_empty() {
+        Cow::Borrowed(name)
+    } else {
+        Cow::Owned(format!("{}{}{}", ns, SEP, name))
+    }
+}
+
+/// Returns the qualifiers of the name: everything before the final `::`, or the empty
+/// string if there is none, as the `namespace qualifiers` command does.
+pub(crate) fn qualifiers(name: &str) -> &str {
+    match name.rfind(SEP) {
+        Some(index) => name[..index].trim_end_matches(':'),
+        None => "",
+    }
+}
+
+/// Returns the tail of the name: everything after the final `::`, as the `namespace tail`
+/// command does.
+pub(crate) fn tail(name: &str) -> &str {
+    match name.rfind(SEP) {
+        Some(index) => &name[index + SEP.len()..],
+        None => name,
+    }
+}
+
+/// Returns the fully qualified name of the namespace or command with the given key, as
+/// it is displayed to the user.
+pub(crate) fn display(key: &str) -> String {
+    format!("{}{}", SEP, key)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_qualify() {
+        assert_eq!(qualify("", "x"), "x");
+        assert_eq!(qualify("a", "x"), "a::x");
+        assert_eq!(qualify("a", "b::x"), "a::b::x");
+        assert_eq!(qualify("a", "::x"), "x");
+        assert_eq!(qualify("a", "::b::x"), "b::x");
+        assert_eq!(qualify("", "::"), "");
+    }
+
+    #[test]
+    fn test_qualifiers() {
+        assert_eq!(qualifiers("x"), "");
+        assert_eq!(qualifiers("::x"), "");
+        assert_eq!(qualifiers("a::x"), "a");
+        assert_eq!(qualifiers("::a::b::x"), "::a::b");
+        assert_eq!(qualifiers("a:::x"), "a");
+    }
+
+    #[test]
+    fn test_tail() {
+        assert_eq!(tail("x"), "x");
+        assert_eq!(tail("::x"), "x");
+        assert_eq!(tail("::a::b::x"), "x");
+        assert_eq!(tail("a::"), "");
+    }
+
+    #[test]
+    fn test_display() {
+        assert_eq!(display(""), "::");
+        assert_eq!(display("a::b"), "::a::b");
+    }
+}
@@ -64,6 +64,7 @@ use crate::check_args;
 use alloc::string::{String, ToString as _};
 use alloc::vec::Vec;
 use alloc::boxed::Box;
+use alloc::rc::Rc;
 
 /// A compiled script, which can be executed in the context of an interpreter.
 #[derive(Debug, PartialEq)]
@@ -117,8 +118,9 @@ pub(crate) enum Word {
     /// represented by a `Word` since it can include various substitutions.
     ArrayRef(String, Box<Word>),
 
-    /// Script(script): A nested script, e.g., `[foo 1 2 3]`.
-    Script(Script),
+    /// Script(script): A nested script, e.g., `[foo 1 2 3]`.  It's shared so that a
+    /// suspended coroutine can hold on to it.
+    Script(Rc<Script>),
 
     /// Tokens(words...): A list of `Words` that will be concatenated into a single `Value`,
     /// e.g., `a $x [foo] bar` or `foo.$x`.
@@ -133,6 +135,46 @@ pub(crate) enum Word {
     String(String),
 }
 
+/// A parsed `subst` template: a list of string literals and substitutions, which are
+/// concatenated to produce the result.
+#[derive(Debug, PartialEq)]
+pub(crate) struct Subst {
+    // The substitutions the template was parsed for.
+    flags: SubstFlags,
+
+    // The literals and substitutions, in order.
+    words: Vec<Word>,
+}
+
+impl Subst {
+    /// Return the substitutions the template was parsed for.
+    pub fn flags(&self) -> SubstFlags {
+        self.flags
+    }
+
+    /// Return the list of words for evaluation.
+    pub fn words(&self) -> &[Word] {
+        &self.words
+    }
+}
+
+/// The substitutions performed by `subst`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) struct SubstFlags {
+    pub backslashes: bool,
+    pub commands: bool,
+    pub variables: bool,
+}
+
+impl SubstFlags {
+    /// All three substitutions.
+    pub const ALL: SubstFlags = SubstFlags {
+        backslashes: true,
+        commands: true,
+        variables: true,
+    };
+}
+
 /// Parses a script, given as a string slice.  Returns a parsed `Script` (or an error).
 pub(crate) fn parse(input: &str) -> Result<Script, Exception> {
     // FIRST, create an EvalPtr as a parsing aid; then parse the script.
@@ -291,7 +333,7 @@ pub(crate) fn parse_quoted_word(ctx: &mut EvalPtr) -> Result<Word, Exception> {
             if start != ctx.mark() {
                 tokens.push_str(ctx.token(start));
             }
-            tokens.push(Word::Script(parse_brackets(ctx)?));
+            tokens.push(Word::Script(Rc::new(parse_brackets(ctx)?)));
             start = ctx.mark();
         } else if ctx.next_is('$') {
             if start != ctx.mark() {
@@ -323,6 +365,48 @@ pub(crate) fn parse_quoted_word(ctx: &mut EvalPtr) -> Result<Word, Exception> {
     molt_err!("missing \"")
 }
 
+/// Parses a `subst` template: the entire input is processed as though it were the
+/// body of a quoted word, but only the substitutions enabled by the flags are performed.
+/// Double quotes have no special meaning.
+pub(crate) fn parse_subst(input: &str, flags: SubstFlags) -> Result<Subst, Exception> {
+    let mut ctx = EvalPtr::new(input);
+    let mut tokens = Tokens::new();
+    let mut start = ctx.mark();
+
+    while !ctx.at_end() {
+        if flags.commands && ctx.next_is('[') {
+            if start != ctx.mark() {
+                tokens.push_str(ctx.token(start));
+            }
+            tokens.push(Word::Script(Rc::new(parse_brackets(&mut ctx)?)));
+            start = ctx.mark();
+        } else if flags.variables && ctx.next_is('$') {
+            if start != ctx.mark() {
+                tokens.push_str(ctx.token(start));
+            }
+            parse_dollar(&mut ctx, &mut tokens)?;
+            start = ctx.mark();
+        } else if flags.backslashes && ctx.next_is('\\') {
+            if start != ctx.mark() {
+                tokens.push_str(ctx.token(start));
+            }
+            tokens.push_char(ctx.backslash_subst());
+            start = ctx.mark();
+        } else {
+            ctx.skip();
+        }
+    }
+
+    if start != ctx.mark() {
+        tokens.push_str(ctx.token(start));
+    }
+
+    Ok(Subst {
+        flags,
+        words: tokens.take_list(),
+    })
+}
+
 /// Parses a bare word, handling backslash, variable, and command substitution.
 fn parse_bare_word(ctx: &mut EvalPtr, index_flag: bool) -> Result<Word, Exception> {
     let mut tokens = Tokens::new();
@@ -337,7 +421,7 @@ fn parse_bare_word(ctx: &mut EvalPtr, index_flag: bool) -> Result<Word, Exceptio
             if start != ctx.mark() {
                 tokens.push_str(ctx.token(start));
             }
-            tokens.push(Word::Script(parse_brackets(ctx)?));
+            tokens.push(Word::Script(Rc::new(parse_brackets(ctx)?)));
             start = ctx.mark();
         } else if ctx.next_is('$') {
             if start != ctx.mark() {
@@ -395,7 +479,7 @@ fn parse_dollar(ctx: &mut EvalPtr, tokens: &mut Tokens) -> Result<(), Exception>
 
     // NEXT, make sure this is really a variable reference.  If it isn't
     // just return a "$".
-    if !ctx.next_is_varname_char() && !ctx.next_is('{') {
+    if !next_is_varname_start(ctx) {
         tokens.push_char('$');
     } else {
         tokens.push(parse_varname(ctx)?);
@@ -404,6 +488,18 @@ fn parse_dollar(ctx: &mut EvalPtr, tokens: &mut Tokens) -> Result<(), Exception>
     Ok(())
 }
 
+/// Does the input begin a variable name, following a "$"?
+///
+/// Also used by expr.rs.
+pub(crate) fn next_is_varname_start(ctx: &mut EvalPtr) -> bool {
+    #[cfg(feature = "namespaces")]
+    if ctx.next_is_namespace_sep() {
+        return true;
+    }
+
+    ctx.next_is_varname_char() || ctx.next_is('{')
+}
+
 /// Parses a variable name; the "$" has already been consumed.  Handles both braced
 /// and non-braced variable names, including array names.
 ///
@@ -431,6 +527,14 @@ pub(crate) fn parse_varname(ctx: &mut EvalPtr) -> Result<Word, Exception> {
     } else {
         let start = ctx.mark();
         ctx.skip_while(is_varname_char);
+
+        // With namespaces, the name may contain "::" separators.
+        #[cfg(feature = "namespaces")]
+        while ctx.next_is_namespace_sep() {
+            ctx.skip_while(|ch| ch == ':');
+            ctx.skip_while(is_varname_char);
+        }
+
         let name = ctx.token(start).to_string();
 
         if !ctx.next_is('(') {
@@ -440,6 +544,10 @@ pub(crate) fn parse_varname(ctx: &mut EvalPtr) -> Result<Word, Exception> {
             // Array; parse out the word that evaluates to the index.
             ctx.skip();
             let index = parse_bare_word(ctx, true)?;
+
+            if !ctx.next_is(')') {
+                return molt_err!("missing )");
+            }
             ctx.skip_char(')');
             Ok(Word::ArrayRef(name, Box::new(index)))
         }
@@ -535,6 +643,15 @@ impl Tokens {
         self.got_string = true;
     }
 
+    /// Takes the accumulated tokens as a list of `Word`s.
+    fn take_list(mut self) -> Vec<Word> {
+        if self.got_string {
+            self.list.push(Word::String(self.string));
+        }
+
+        self.list
+    }
+
     /// Takes the accumulated tokens as a single `Word`, either `Word::Value` or
     /// `Word::Tokens`.
     fn take(mut self) -> Word {
@@ -834,7 +951,7 @@ mod tests {
             Ok((
                 Word::Tokens(vec![
                     Word::String("a".into()),
-                    Word::Script(pbrack("[list b]").unwrap()),
+                    Word::Script(Rc::new(pbrack("[list b]").unwrap())),
                     Word::String("c".into()),
                 ]),
                 " ".into()
@@ -851,6 +968,46 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_parse_subst() {
+        // All substitutions
+        assert_eq!(
+            psubst("a\\x77$x[list b]\"c", SubstFlags::ALL),
+            Ok(vec![
+                Word::String("aw".into()),
+                Word::VarRef("x".into()),
+                Word::Script(Rc::new(pbrack("[list b]").unwrap())),
+                Word::String("\"c".into()),
+            ])
+        );
+
+        // No substitutions
+        let none = SubstFlags {
+            backslashes: false,
+            commands: false,
+            variables: false,
+        };
+        assert_eq!(
+            psubst("a\\x77$x[list b]", none),
+            Ok(vec![Word::String("a\\x77$x[list b]".into())])
+        );
+
+        // Empty template
+        assert_eq!(psubst("", SubstFlags::ALL), Ok(vec![]));
+
+        // Missing close bracket
+        assert_eq!(
+            psubst("a[list b", SubstFlags::ALL),
+            molt_err!("missing close-bracket")
+        );
+    }
+
+    fn psubst(input: &str, flags: SubstFlags) -> Result<Vec<Word>, Exception> {
+        let subst = parse_subst(input, flags)?;
+        assert_eq!(subst.flags(), flags);
+        Ok(subst.words)
+    }
+
     fn pqw(input: &str) -> Result<(Word, String), Exception> {
         let mut ctx = EvalPtr::new(input);
         let word = parse_quoted_word(&mut ctx)?;
@@ -924,7 +1081,7 @@ mod tests {
             Ok((
                 Word::Tokens(vec![
                     Word::String("a".into()),
-                    Word::Script(pbrack("[list b]").unwrap()),
+                    Word::Script(Rc::new(pbrack("[list b]").unwrap())),
                     Word::String("c".into()),
                 ]),
                 " ".into()
@@ -987,6 +1144,8 @@ mod tests {
                 "".into()
             ))
         );
+        assert_eq!(pvar("$a("), molt_err!("missing )"));
+        assert_eq!(pvar("$a(1"), molt_err!("missing )"));
 
         // Braced var names
         assert_eq!(pvar("${a}b"), Ok((Word::VarRef("a".into()), "b".into())));
@@ -1009,6 +1168,22 @@ mod tests {
         assert_eq!(pvar("$."), Ok((Word::Value(Value::from("$")), ".".into())));
     }
 
+    #[cfg(feature = "namespaces")]
+    #[test]
+    fn test_parse_dollar_qualified() {
+        assert_eq!(pvar("$::a"), Ok((Word::VarRef("::a".into()), "".into())));
+        assert_eq!(pvar("$a::b."), Ok((Word::VarRef("a::b".into()), ".".into())));
+        assert_eq!(pvar("$::a::b:c"), Ok((Word::VarRef("::a::b".into()), ":c".into())));
+        assert_eq!(pvar("$a:b"), Ok((Word::VarRef("a".into()), ":b".into())));
+        assert_eq!(
+            pvar("$a::b(1)"),
+            Ok((
+                Word::ArrayRef("a::b".into(), Box::new(Word::Value(Value::from("1")))),
+                "".into()
+            ))
+        );
+    }
+
     fn pvar(input: &str) -> Result<(Word, String), Exception> {
         let mut ctx = EvalPtr::new(input);
         let mut tokens = Tokens::new();
@@ -0,0 +1,847 @@
+//! Regular Expressions
+//!
+//! This module implements the compact regular expression engine used by the `regexp`
+//! and `regsub` commands.  A pattern is compiled into a small program for a Pike VM,
+//! which runs all possible matches in lock step.  It never backtracks, so the time to
+//! match is bounded by the length of the program times the length of the input.
+//!
+//! The syntax is a subset of Tcl's Advanced Regular Expressions:
+//!
+//! * Atoms: literal characters, `.`, bracket expressions `[...]` and `[^...]` with
+//!   ranges and the classes `[:alpha:]`, `[:digit:]`, `[:alnum:]`, `[:space:]`,
+//!   `[:upper:]`, `[:lower:]`, `[:punct:]`, `[:xdigit:]`, and `[:word:]`, groups `(...)`,
+//!   and non-capturing groups `(?:...)`.
+//! * Quantifiers: `*`, `+`, `?`, `{m}`, `{m,}`, and `{m,n}`, each of which may be
+//!   followed by `?` to make it non-greedy.
+//! * Anchors: `^`, `$`, `\A`, `\Z`, and the word boundaries `\y`, `\Y`, `\m`, and `\M`.
+//! * Escapes: `\d`, `\D`, `\w`, `\W`, `\s`, and `\S`; `\n`, `\t`, `\r`, `\f`, `\v`,
+//!   `\a`, `\e`, `\0`, `\xhh`, `\uhhhh`, and `\Uhhhhhhhh`; and a backslash followed by
+//!   any other non-alphanumeric character, which matches that character.
+//!
+//! When there are several possible matches starting at the same point, the engine
+//! prefers the one found by trying alternatives from left to right, greedy quantifiers
+//! first, as Perl does.
+
+use crate::types::*;
+use crate::util;
+
+use alloc::boxed::Box;
+use alloc::string::String;
+use alloc::vec;
+use alloc::vec::Vec;
+
+/// The most instructions a compiled program may have.  Counted repetitions copy their
+/// operand, so this keeps nested counts from using unbounded memory.
+const MAX_PROGRAM: usize = 10_000;
+
+/// The most deeply groups may nest.  The parser and compiler recurse once per level, so
+/// this bounds their stack usage.
+const MAX_DEPTH: usize = 32;
+
+/// The most repetitions a `{m,n}` quantifier may specify.
+const MAX_COUNT: u32 = 255;
+
+/// The start and end character indices of each capture group in a match, beginning
+/// with the match as a whole; `None` for groups that didn't participate.
+pub(crate) type Captures = Vec<Option<(usize, usize)>>;
+
+/// A compiled regular expression.
+pub(crate) struct Regex {
+    /// The program.
+    prog: Vec<Inst>,
+
+    /// The number of capture groups, not counting the match as a whole.
+    groups: usize,
+
+    /// Whether to match without regard to case.
+    nocase: bool,
+}
+
+/// A Pike VM instruction.
+enum Inst {
+    /// Matches the character, which is folded to lower case if matching ignores case.
+    Char(char),
+    /// Matches any character.
+    Any,
+    /// Matches any character in the class.
+    Class(Class),
+    /// Matches the empty string if the assertion holds.
+    Assert(Assertion),
+    /// Saves the current position in the given capture slot.
+    Save(usize),
+    /// Continues at both targets, preferring the first.
+    Split(usize, usize),
+    /// Continues at the target.
+    Jump(usize),
+    /// The pattern has matched.
+    Match,
+}
+
+#[derive(Clone, Copy)]
+enum Assertion {
+    StartText,
+    EndText,
+    WordBoundary,
+    NotWordBoundary,
+    WordStart,
+    WordEnd,
+}
+
+/// A bracket expression, or one of the class escapes.
+struct Class {
+    items: Vec<ClassItem>,
+    negated: bool,
+}
+
+enum ClassItem {
+    Range(char, char),
+    /// A named class, and whether it's negated.
+    Named(Named, bool),
+}
+
+#[derive(Clone, Copy)]
+enum Named {
+    Alnum,
+    Alpha,
+    Digit,
+    Lower,
+    Punct,
+    Space,
+    Upper,
+    Word,
+    Xdigit,
+}
+
+/// The parsed pattern.
+enum Node {
+    Empty,
+    Char(char),
+    Any,
+    Class(Class),
+    Assert(Assertion),
+    /// A group, and its capture index if it's capturing.
+    Group(Box<Node>, Option<usize>),
+    Concat(Vec<Node>),
+    Alt(Vec<Node>),
+    /// An operand, its minimum and maximum counts, and whether it's greedy.
+    Repeat(Box<Node>, u32, Option<u32>, bool),
+}
+
+impl Regex {
+    /// Compiles the pattern, returning an error if it's invalid.
+    pub fn new(pattern: &str, nocase: bool) -> Result<Regex, Exception> {
+        let mut parser = Parser {
+            chars: pattern.chars().collect(),
+            pos: 0,
+            groups: 0,
+            depth: 0,
+        };
+
+        let node = parser.parse_alt();
+        let node = match node {
+            Ok(_) if parser.pos < parser.chars.len() => Err("parentheses () not balanced"),
+            node => node,
+        };
+
+        let mut regex = Regex {
+            prog: Vec::new(),
+            groups: parser.groups,
+            nocase,
+        };
+
+        let compiled = node.and_then(|node| {
+            regex.prog.push(Inst::Save(0));
+            regex.compile(&node)?;
+            regex.prog.push(Inst::Save(1));
+            regex.prog.push(Inst::Match);
+            Ok(())
+        });
+
+        match compiled {
+            Ok(()) => Ok(regex),
+            Err(msg) => molt_err!("couldn't compile regular expression pattern: {}", msg),
+        }
+    }
+
+    /// Returns whether the pattern matches anywhere in the text.
+    pub fn is_match(&self, text: &str) -> bool {
+        let chars: Vec<char> = text.chars().collect();
+        self.find_at(&chars, 0).is_some()
+    }
+
+    /// Finds the first match in the text starting at or after the given character
+    /// index.  Positions are relative to the start of the text, so `^` only matches at
+    /// index 0.
+    pub fn find_at(&self, text: &[char], start: usize) -> Option<Captures> {
+        let mut clist: Vec<(usize, Vec<Option<usize>>)> = Vec::new();
+        let mut nlist: Vec<(usize, Vec<Option<usize>>)> = Vec::new();
+        let mut visited = vec![usize::MAX; self.prog.len()];
+        let mut matched: Option<Vec<Option<usize>>> = None;
+        let mut pos = start;
+
+        while pos <= text.len() {
+            // FIRST, until there's a match, start a new, lowest-priority thread here.
+            if matched.is_none() {
+                let slots = vec![None; 2 * (self.groups + 1)];
+                self.add_thread(&mut clist, &mut visited, 0, pos, text, slots);
+            }
+
+            if clist.is_empty() && matched.is_some() {
+                break;
+            }
+
+            // NEXT, step each thread in priority order.  When a thread matches, the
+            // lower-priority threads are dropped.
+            let ch = text.get(pos).copied();
+
+            for (pc, slots) in clist.drain(..) {
+                if let Inst::Match = self.prog[pc] {
+                    matched = Some(slots);
+                    break;
+                }
+
+                if let Some(ch) = ch {
+                    if self.step(&self.prog[pc], ch) {
+                        self.add_thread(&mut nlist, &mut visited, pc + 1, pos + 1, text, slots);
+                    }
+                }
+            }
+
+            core::mem::swap(&mut clist, &mut nlist);
+            pos += 1;
+        }
+
+        matched.map(|slots| {
+            slots
+                .chunks(2)
+                .map(|pair| match (pair[0], pair[1]) {
+                    (Some(start), Some(end)) => Some((start, end)),
+                    _ => None,
+                })
+                .collect()
+        })
+    }
+
+    /// Adds a thread at the given instruction to the list, following jumps, splits,
+    /// saves, and assertions, in priority order.
+    fn add_thread(
+        &self,
+        list: &mut Vec<(usize, Vec<Option<usize>>)>,
+        visited: &mut [usize],
+        pc: usize,
+        pos: usize,
+        text: &[char],
+        slots: Vec<Option<usize>>,
+    ) {
+        let mut stack = vec![(pc, slots)];
+
+        while let Some((pc, mut slots)) = stack.pop() {
+            if visited[pc] == pos {
+                continue;
+            }
+            visited[pc] = pos;
+
+            match &self.prog[pc] {
+                Inst::Jump(target) => stack.push((*target, slots)),
+                Inst::Split(first, second) => {
+                    stack.push((*second, slots.clone()));
+                    stack.push((*first, slots));
+                }
+                Inst::Save(slot) => {
+                    slots[*slot] = Some(pos);
+                    stack.push((pc + 1, slots));
+                }
+                Inst::Assert(assertion) => {
+                    if check(*assertion, text, pos) {
+                        stack.push((pc + 1, slots));
+                    }
+                }
+                _ => list.push((pc, slots)),
+            }
+        }
+    }
+
+    /// Returns whether the instruction consumes the character.
+    fn step(&self, inst: &Inst, ch: char) -> bool {
+        match inst {
+            Inst::Char(c) => *c == self.fold(ch),
+            Inst::Any => true,
+            Inst::Class(class) => class.matches(ch, self.fold(ch)),
+            _ => false,
+        }
+    }
+
+    fn fold(&self, ch: char) -> char {
+        if self.nocase {
+            util::fold_case(ch)
+        } else {
+            ch
+        }
+    }
+
+    /// Compiles the node, appending its instructions to the program.
+    fn compile(&mut self, node: &Node) -> Result<(), &'static str> {
+        if self.prog.len() > MAX_PROGRAM {
+            return Err("regular expression is too complex");
+        }
+
+        match node {
+            Node::Empty => (),
+            Node::Char(ch) => {
+                let ch = self.fold(*ch);
+                self.prog.push(Inst::Char(ch));
+            }
+            Node::Any => self.prog.push(Inst::Any),
+            Node::Class(class) => {
+                let items = class
+                    .items
+                    .iter()
+                    .map(|item| match item {
+                        ClassItem::Range(lo, hi) => {
+                            let (lo, hi) = (self.fold(*lo), self.fold(*hi));
+                            ClassItem::Range(lo.min(hi), lo.max(hi))
+                        }
+                        ClassItem::Named(named, negated) => ClassItem::Named(*named, *negated),
+                    })
+                    .collect();
+                self.prog.push(Inst::Class(Class {
+                    items,
+                    negated: class.negated,
+                }));
+            }
+            Node::Assert(assertion) => self.prog.push(Inst::Assert(*assertion)),
+            Node::Group(node, index) => match index {
+                Some(index) => {
+                    self.prog.push(Inst::Save(2 * index));
+                    self.compile(node)?;
+                    self.prog.push(Inst::Save(2 * index + 1));
+                }
+                None => self.compile(node)?,
+            },
+            Node::Concat(nodes) => {
+                for node in nodes {
+                    self.compile(node)?;
+                }
+            }
+            Node::Alt(nodes) => {
+                let mut jumps = Vec::new();
+
+                for (i, node) in nodes.iter().enumerate() {
+                    if i + 1 == nodes.len() {
+                        self.compile(node)?;
+                    } else {
+                        let split = self.prog.len();
+                        self.prog.push(Inst::Split(split + 1, 0));
+                        self.compile(node)?;
+                        jumps.push(self.prog.len());
+                        self.prog.push(Inst::Jump(0));
+                        self.prog[split] = Inst::Split(split + 1, self.prog.len());
+                    }
+                }
+
+                let end = self.prog.len();
+                for jump in jumps {
+                    self.prog[jump] = Inst::Jump(end);
+                }
+            }
+            Node::Repeat(node, min, max, greedy) => {
+                for _ in 0..*min {
+                    self.compile(node)?;
+                }
+
+                match max {
+                    None => {
+                        // A loop: split into the operand or out, and jump back.
+                        let split = self.prog.len();
+                        self.prog.push(Inst::Jump(0));
+                        self.compile(node)?;
+                        self.prog.push(Inst::Jump(split));
+                        self.prog[split] = self.split(split + 1, self.prog.len(), *greedy);
+                    }
+                    Some(max) => {
+                        for _ in *min..*max {
+                            let split = self.prog.len();
+                            self.prog.push(Inst::Jump(0));
+                            self.compile(node)?;
+                            self.prog[split] = self.split(split + 1, self.prog.len(), *greedy);
+                        }
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Returns a split that prefers taking the operand if greedy, and skipping it if not.
+    fn split(&self, operand: usize, skip: usize, greedy: bool) -> Inst {
+        if greedy {
+            Inst::Split(operand, skip)
+        } else {
+            Inst::Split(skip, operand)
+        }
+    }
+}
+
+/// Returns whether the assertion holds at the given position.
+fn check(assertion: Assertion, text: &[char], pos: usize) -> bool {
+    let before = pos > 0 && is_word_char(text[pos - 1]);
+    let after = pos < text.len() && is_word_char(text[pos]);
+
+    match assertion {
+        Assertion::StartText => pos == 0,
+        Assertion::EndText => pos == text.len(),
+        Assertion::WordBoundary => before != after,
+        Assertion::NotWordBoundary => before == after,
+        Assertion::WordStart => !before && after,
+        Assertion::WordEnd => before && !after,
+    }
+}
+
+fn is_word_char(ch: char) -> bool {
+    util::is_alphanumeric(ch) || ch == '_'
+}
+
+impl Class {
+    /// Returns whether the character is in the class.  Ranges are checked against the
+    /// folded character, since their bounds are folded at compile time.
+    fn matches(&self, ch: char, folded: char) -> bool {
+        let found = self.items.iter().any(|item| match item {
+            ClassItem::Range(lo, hi) => *lo <= folded && folded <= *hi,
+            ClassItem::Named(named, negated) => named.matches(ch) != *negated,
+        });
+
+        found != self.negated
+    }
+}
+
+impl Named {
+    fn matches(self, ch: char) -> bool {
+        match self {
+            Named::Alnum => util::is_alphanumeric(ch),
+            Named::Alpha => util::is_alphabetic(ch),
+            Named::Digit => util::is_digit(ch),
+            Named::Lower => util::is_lowercase(ch),
+            Named::Punct => ch.is_ascii_punctuation(),
+            Named::Space => util::is_whitespace(ch),
+            Named::Upper => util::is_uppercase(ch),
+            Named::Word => is_word_char(ch),
+            Named::Xdigit => ch.is_ascii_hexdigit(),
+        }
+    }
+}
+
+/// A recursive-descent parser for patterns.
+struct Parser {
+    chars: Vec<char>,
+    pos: usize,
+    groups: usize,
+    /// The number of groups enclosing the current position.
+    depth: usize,
+}
+
+type ParseResult = Result<Node, &'static str>;
+
+impl Parser {
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.pos).copied()
+    }
+
+    fn next(&mut self) -> Option<char> {
+        let ch = self.peek();
+        if ch.is_some() {
+            self.pos += 1;
+        }
+        ch
+    }
+
+    fn eat(&mut self, ch: char) -> bool {
+        if self.peek() == Some(ch) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Parses a series of alternatives.
+    fn parse_alt(&mut self) -> ParseResult {
+        let mut nodes = vec![self.parse_concat()?];
+
+        while self.eat('|') {
+            nodes.push(self.parse_concat()?);
+        }
+
+        if nodes.len() == 1 {
+            Ok(nodes.pop().unwrap())
+        } else {
+            Ok(Node::Alt(nodes))
+        }
+    }
+
+    /// Parses a series of quantified atoms.
+    fn parse_concat(&mut self) -> ParseResult {
+        let mut nodes = Vec::new();
+
+        while !matches!(self.peek(), None | Some('|') | Some(')')) {
+            nodes.push(self.parse_repeat()?);
+        }
+
+        match nodes.len() {
+            0 => Ok(Node::Empty),
+            1 => Ok(nodes.pop().unwrap()),
+            _ => Ok(Node::Concat(nodes)),
+        }
+    }
+
+    /// Parses an atom and its quantifier, if any.
+    fn parse_repeat(&mut self) -> ParseResult {
+        let atom = self.parse_atom()?;
+
+        let quantifier = match self.peek() {
+            Some(ch @ ('*' | '+' | '?' | '{')) => ch,
+            _ => return Ok(atom),
+        };
+        self.pos += 1;
+
+        let (min, max) = match quantifier {
+            '*' => (0, None),
+            '+' => (1, None),
+            '?' => (0, Some(1)),
+            _ => self.parse_count()?,
+        };
+
+        let greedy = !self.eat('?');
+
+        if matches!(atom, Node::Assert(_))
+            || matches!(self.peek(), Some('*') | Some('+') | Some('?') | Some('{'))
+        {
+            return Err("quantifier operand invalid");
+        }
+
+        Ok(Node::Repeat(Box::new(atom), min, max, greedy))
+    }
+
+    /// Parses the body of a `{m,n}` count, following the open brace.
+    fn parse_count(&mut self) -> Result<(u32, Option<u32>), &'static str> {
+        let min = self.parse_number().ok_or("braces {} not balanced")?;
+        let max = if self.eat(',') {
+            self.parse_number()
+        } else {
+            Some(min)
+        };
+
+        if !self.eat('}') {
+            return Err("braces {} not balanced");
+        }
+
+        match max {
+            Some(max) if max < min || max > MAX_COUNT => Err("invalid repetition count(s)"),
+            _ if min > MAX_COUNT => Err("invalid repetition count(s)"),
+            _ => Ok((min, max)),
+        }
+    }
+
+    fn parse_number(&mut self) -> Option<u32> {
+        let start = self.pos;
+        let mut number: u32 = 0;
+
+        while let Some(digit) = self.peek().and_then(|ch| ch.to_digit(10)) {
+            number = number.saturating_mul(10).saturating_add(digit);
+            self.pos += 1;
+        }
+
+        if self.pos > start {
+            Some(number)
+        } else {
+            None
+        }
+    }
+
+    /// Parses a single atom.
+    fn parse_atom(&mut self) -> ParseResult {
+        match self.next() {
+            Some('(') => {
+                let index = if self.chars[self.pos..].starts_with(&['?', ':']) {
+                    self.pos += 2;
+                    None
+                } else {
+                    self.groups += 1;
+                    Some(self.groups)
+                };
+
+                if self.depth == MAX_DEPTH {
+                    return Err("regular expression is too complex");
+                }
+
+                self.depth += 1;
+                let node = self.parse_alt()?;
+                self.depth -= 1;
+
+                if !self.eat(')') {
+                    return Err("parentheses () not balanced");
+                }
+                Ok(Node::Group(Box::new(node), index))
+            }
+            Some('.') => Ok(Node::Any),
+            Some('^') => Ok(Node::Assert(Assertion::StartText)),
+            Some('$') => Ok(Node::Assert(Assertion::EndText)),
+            Some('[') => self.parse_class(),
+            Some('\\') => self.parse_escape(),
+            Some('*') | Some('+') | Some('?') | Some('{') => Err("quantifier operand invalid"),
+            Some(ch) => Ok(Node::Char(ch)),
+            None => Ok(Node::Empty),
+        }
+    }
+
+    /// Parses an escape outside a bracket expression, following the backslash.
+    fn parse_escape(&mut self) -> ParseResult {
+        let node = match self.peek() {
+            Some('A') => Node::Assert(Assertion::StartText),
+            Some('Z') => Node::Assert(Assertion::EndText),
+            Some('y') => Node::Assert(Assertion::WordBoundary),
+            Some('Y') => Node::Assert(Assertion::NotWordBoundary),
+            Some('m') => Node::Assert(Assertion::WordStart),
+            Some('M') => Node::Assert(Assertion::WordEnd),
+            Some(ch) if ch.is_ascii_digit() && ch != '0' => {
+                return Err("backreferences are not supported");
+            }
+            _ => {
+                return Ok(match self.parse_class_escape()? {
+                    Ok(ch) => Node::Char(ch),
+                    Err(item) => Node::Class(Class {
+                        items: vec![item],
+                        negated: false,
+                    }),
+                });
+            }
+        };
+
+        self.pos += 1;
+        Ok(node)
+    }
+
+    /// Parses an escape that's valid in a bracket expression, following the backslash:
+    /// either a single character or a class.
+    fn parse_class_escape(&mut self) -> Result<Result<char, ClassItem>, &'static str> {
+        let ch = match self.next() {
+            Some(ch) => ch,
+            None => return Err("invalid escape \\ sequence"),
+        };
+
+        let named = |named, negated| Ok(Err(ClassItem::Named(named, negated)));
+
+        match ch {
+            'd' => named(Named::Digit, false),
+            'D' => named(Named::Digit, true),
+            's' => named(Named::Space, false),
+            'S' => named(Named::Space, true),
+            'w' => named(Named::Word, false),
+            'W' => named(Named::Word, true),
+            'a' => Ok(Ok('\x07')),
+            'e' => Ok(Ok('\x1b')),
+            'f' => Ok(Ok('\x0c')),
+            'n' => Ok(Ok('\n')),
+            'r' => Ok(Ok('\r')),
+            't' => Ok(Ok('\t')),
+            'v' => Ok(Ok('\x0b')),
+            '0' => Ok(Ok('\0')),
+            'x' => self.parse_hex(2).map(Ok),
+            'u' => self.parse_hex(4).map(Ok),
+            'U' => self.parse_hex(8).map(Ok),
+            ch if ch.is_alphanumeric() => Err("invalid escape \\ sequence"),
+            ch => Ok(Ok(ch)),
+        }
+    }
+
+    /// Parses up to `max` hex digits as a character code.
+    fn parse_hex(&mut self, max: usize) -> Result<char, &'static str> {
+        let mut code: u32 = 0;
+        let mut count = 0;
+
+        while count < max {
+            match self.peek().and_then(|ch| ch.to_digit(16)) {
+                Some(digit) => {
+                    code = code * 16 + digit;
+                    self.pos += 1;
+                    count += 1;
+                }
+                None => break,
+            }
+        }
+
+        match char::from_u32(code) {
+            Some(ch) if count > 0 => Ok(ch),
+            _ => Err("invalid escape \\ sequence"),
+        }
+    }
+
+    /// Parses a bracket expression, following the open bracket.
+    fn parse_class(&mut self) -> ParseResult {
+        let negated = self.eat('^');
+        let mut items = Vec::new();
+        let mut first = true;
+
+        loop {
+            let lo = match self.next() {
+                None => return Err("brackets [] not balanced"),
+                Some(']') if !first => break,
+                Some('[') if self.peek() == Some(':') => {
+                    items.push(self.parse_named_class()?);
+                    first = false;
+                    continue;
+                }
+                Some('\\') => match self.parse_class_escape()? {
+                    Ok(ch) => ch,
+                    Err(item) => {
+                        items.push(item);
+                        first = false;
+                        continue;
+                    }
+                },
+                Some(ch) => ch,
+            };
+            first = false;
+
+            // NEXT, see if it's a range.
+            if self.peek() == Some('-') && !matches!(self.chars.get(self.pos + 1), None | Some(']')) {
+                self.pos += 1;
+                let hi = match self.next() {
+                    Some('\\') => match self.parse_class_escape()? {
+                        Ok(ch) => ch,
+                        Err(_) => return Err("invalid character range"),
+                    },
+                    Some(ch) => ch,
+                    None => return Err("brackets [] not balanced"),
+                };
+
+                if hi < lo {
+                    return Err("invalid character range");
+                }
+                items.push(ClassItem::Range(lo, hi));
+            } else {
+                items.push(ClassItem::Range(lo, lo));
+            }
+        }
+
+        Ok(Node::Class(Class { items, negated }))
+    }
+
+    /// Parses a `[:name:]` class, following the open bracket.
+    fn parse_named_class(&mut self) -> Result<ClassItem, &'static str> {
+        let rest: String = self.chars[self.pos..].iter().collect();
+        let end = rest.find(":]").ok_or("brackets [] not balanced")?;
+
+        let named = match &rest[1..end] {
+            "alnum" => Named::Alnum,
+            "alpha" => Named::Alpha,
+            "digit" => Named::Digit,
+            "lower" => Named::Lower,
+            "punct" => Named::Punct,
+            "space" => Named::Space,
+            "upper" => Named::Upper,
+            "word" => Named::Word,
+            "xdigit" => Named::Xdigit,
+            _ => return Err("invalid character class"),
+        };
+
+        self.pos += rest[..end + 2].chars().count();
+        Ok(ClassItem::Named(named, false))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn find(pattern: &str, text: &str) -> Option<Vec<Option<String>>> {
+        let regex = Regex::new(pattern, false).unwrap();
+        let chars: Vec<char> = text.chars().collect();
+        regex.find_at(&chars, 0).map(|caps| {
+            caps.iter()
+                .map(|cap| cap.map(|(start, end)| chars[start..end].iter().collect()))
+                .collect()
+        })
+    }
+
+    fn matched(pattern: &str, text: &str) -> Option<String> {
+        find(pattern, text).map(|caps| caps[0].clone().unwrap())
+    }
+
+    #[test]
+    fn test_literals_and_classes() {
+        assert_eq!(matched("abc", "xxabcxx"), Some("abc".into()));
+        assert_eq!(matched("a.c", "abc"), Some("abc".into()));
+        assert_eq!(matched("[a-c]+", "xxbcaxx"), Some("bca".into()));
+        assert_eq!(matched("[^a-c]+", "abxyc"), Some("xy".into()));
+        assert_eq!(matched("[]a]+", "x]a]"), Some("]a]".into()));
+        assert_eq!(matched("[[:digit:]]+", "ab123"), Some("123".into()));
+        assert_eq!(matched(r"\d+\s\w+", "x 12 ab_c"), Some("12 ab_c".into()));
+        assert_eq!(matched(r"[\d.]+", "v1.25"), Some("1.25".into()));
+        assert_eq!(matched(r"\x41\.", "A."), Some("A.".into()));
+        assert_eq!(matched("abc", "abd"), None);
+    }
+
+    #[test]
+    fn test_quantifiers() {
+        assert_eq!(matched("ab*", "abbbc"), Some("abbb".into()));
+        assert_eq!(matched("ab*?", "abbbc"), Some("a".into()));
+        assert_eq!(matched("ab+", "ac ab"), Some("ab".into()));
+        assert_eq!(matched("ab?c", "ac"), Some("ac".into()));
+        assert_eq!(matched("a{2,3}", "aaaa"), Some("aaa".into()));
+        assert_eq!(matched("a{2}", "aaaa"), Some("aa".into()));
+        assert_eq!(matched("a{2,}", "aaaa"), Some("aaaa".into()));
+        assert_eq!(matched("(a*)*b", "aab"), Some("aab".into()));
+    }
+
+    #[test]
+    fn test_alternation_and_groups() {
+        assert_eq!(matched("cat|dog", "hotdog"), Some("dog".into()));
+        assert_eq!(
+            find("(a+)(b+)?(c)", "aac"),
+            Some(vec![Some("aac".into()), Some("aa".into()), None, Some("c".into())])
+        );
+        assert_eq!(
+            find("(?:a|b)+(c)", "abac"),
+            Some(vec![Some("abac".into()), Some("c".into())])
+        );
+    }
+
+    #[test]
+    fn test_anchors() {
+        assert_eq!(matched("^ab", "abab"), Some("ab".into()));
+        assert_eq!(matched("^b", "ab"), None);
+        assert_eq!(matched("b$", "abb"), Some("b".into()));
+        assert_eq!(matched(r"\mfoo\M", "afoo foo"), Some("foo".into()));
+        assert_eq!(find(r"\yfoo\y", "foobar"), None);
+    }
+
+    #[test]
+    fn test_nocase() {
+        let regex = Regex::new("[a-c]B", true).unwrap();
+        assert!(regex.is_match("xAbx"));
+        assert!(!regex.is_match("xdbx"));
+    }
+
+    #[test]
+    fn test_errors() {
+        assert!(Regex::new("(ab", false).is_err());
+        assert!(Regex::new("ab)", false).is_err());
+        assert!(Regex::new("[ab", false).is_err());
+        assert!(Regex::new("*a", false).is_err());
+        assert!(Regex::new("a**", false).is_err());
+        assert!(Regex::new("a{3,2}", false).is_err());
+        assert!(Regex::new("a{2", false).is_err());
+        assert!(Regex::new(r"(a)\1", false).is_err());
+        assert!(Regex::new(r"\q", false).is_err());
+        assert!(Regex::new("[[:foo:]]", false).is_err());
+        assert!(Regex::new("(a{255}){255}", false).is_err());
+
+        let nested = |depth| format!("{}a{}", "(".repeat(depth), ")".repeat(depth));
+        assert!(Regex::new(&nested(MAX_DEPTH), false).is_ok());
+        assert_eq!(
+            Regex::new(&nested(20000), false).map(|_| ()),
+            molt_err!("couldn't compile regular expression pattern: regular expression is too complex")
+        );
+    }
+}
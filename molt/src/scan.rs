@@ -0,0 +1,509 @@
+//! String Scanning
+//!
+//! This module implements the `sscanf`-style parsing used by the `scan` command.  Numbers
+//! are read using the same routines as the rest of the interpreter, and so honor the
+//! `i64` and `float` features.
+
+use crate::tokenizer::Tokenizer;
+use crate::types::*;
+use crate::util;
+use crate::value::Value;
+
+use alloc::string::{String, ToString as _};
+use alloc::vec::Vec;
+
+/// A parsed element of the format string.
+enum Field {
+    /// White space in the format, which matches any amount of white space in the input.
+    Space,
+
+    /// A character that must match the input exactly.
+    Literal(char),
+
+    /// A conversion.
+    Conversion(Conversion),
+}
+
+/// A conversion specifier.
+struct Conversion {
+    /// The index of the result, or None if the conversion is suppressed with `*`.
+    slot: Option<usize>,
+
+    /// The maximum number of characters to read, if any.
+    width: Option<usize>,
+
+    /// What to read.
+    kind: Kind,
+}
+
+enum Kind {
+    /// An integer in the given radix.  Hexadecimal integers may have a "0x" prefix.  If
+    /// the radix is 0, as for `%i`, the prefix gives it: "0x" for hexadecimal, "0b" for
+    /// binary, a leading "0" for octal, and decimal otherwise.
+    Int(u32),
+    #[cfg(feature = "float")]
+    Float,
+    /// A single character, returned as its code point.
+    Char,
+    /// A run of non-white-space characters.
+    Str,
+    /// A run of characters from a set: the set, and whether it's negated.
+    Set(Vec<SetItem>, bool),
+    /// The number of characters scanned so far.
+    Count,
+}
+
+/// A character or range of characters in a `%[...]` set.
+enum SetItem {
+    Char(char),
+    Range(char, char),
+}
+
+/// The result of scanning a string.
+pub(crate) struct ScanResult {
+    /// The converted values, one per unsuppressed conversion; `None` for those that
+    /// weren't reached.
+    pub values: Vec<Option<Value>>,
+
+    /// The number of conversions that produced values, or -1 if the input ran out
+    /// before the first conversion.
+    pub count: MoltInt,
+}
+
+/// Scans the input according to the format string, as for the `scan` command.  The number
+/// of variables is the number of variable names the command was given, or 0 if it returns
+/// the values inline.
+pub(crate) fn scan(input: &str, fmt: &str, num_vars: usize) -> Result<ScanResult, Exception> {
+    // FIRST, parse the format string, so that format errors are reported regardless of
+    // the input.
+    let (fields, num_slots) = parse_format(fmt, num_vars)?;
+
+    // NEXT, scan the input.
+    let mut values: Vec<Option<Value>> = Vec::new();
+    values.resize(num_slots, None);
+
+    let mut p = Tokenizer::new(input);
+    let mut count = 0;
+    let mut underflow = false;
+
+    for field in &fields {
+        let conv = match field {
+            Field::Space => {
+                p.skip_while(util::is_whitespace);
+                continue;
+            }
+            Field::Literal(ch) => {
+                if p.at_end() {
+                    underflow = true;
+                    break;
+                } else if p.is(*ch) {
+                    p.skip();
+                    continue;
+                } else {
+                    break;
+                }
+            }
+            Field::Conversion(conv) => conv,
+        };
+
+        // NEXT, all conversions but %c, %[...], and %n skip leading white space.
+        match conv.kind {
+            Kind::Char | Kind::Set(..) | Kind::Count => (),
+            _ => p.skip_while(util::is_whitespace),
+        }
+
+        if p.at_end() && !matches!(conv.kind, Kind::Count) {
+            underflow = true;
+            break;
+        }
+
+        // NEXT, read the field, limited to the width.
+        let rest = p.as_str();
+        let field_str = match conv.width {
+            Some(width) => match rest.char_indices().nth(width) {
+                Some((end, _)) => &rest[..end],
+                None => rest,
+            },
+            None => rest,
+        };
+
+        let (value, len) = match scan_field(&conv.kind, field_str, input, p.mark())? {
+            Some(result) => result,
+            None => break,
+        };
+
+        p.skip_over(len);
+
+        if let Some(slot) = conv.slot {
+            values[slot] = Some(value);
+            count += 1;
+        }
+    }
+
+    if underflow && count == 0 {
+        count = -1;
+    }
+
+    Ok(ScanResult { values, count })
+}
+
+/// Reads a single conversion from the head of the field.  Returns the value and the
+/// number of characters read, or None if the input doesn't match.
+fn scan_field(
+    kind: &Kind,
+    field: &str,
+    input: &str,
+    mark: usize,
+) -> Result<Option<(Value, usize)>, Exception> {
+    let mut p = Tokenizer::new(field);
+
+    let value = match kind {
+        Kind::Int(radix) => match read_radix(&mut p, *radix) {
+            Some((int_str, radix)) => {
+                let int = match MoltInt::from_str_radix(&int_str, radix) {
+                    Ok(int) => int,
+                    Err(_) => return molt_err!("expected integer but got \"{}\"", int_str),
+                };
+                Value::from(int)
+            }
+            None => return Ok(None),
+        },
+        #[cfg(feature = "float")]
+        Kind::Float => match util::read_float(&mut p) {
+            Some(flt_str) => Value::from(Value::get_float(&flt_str)?),
+            None => return Ok(None),
+        },
+        Kind::Char => match p.next() {
+            Some(ch) => Value::from(ch as MoltInt),
+            None => return Ok(None),
+        },
+        Kind::Str => {
+            let start = p.mark();
+            p.skip_while(|ch| !util::is_whitespace(ch));
+            Value::from(p.token(start).to_string())
+        }
+        Kind::Set(items, negated) => {
+            let start = p.mark();
+            p.skip_while(|ch| set_contains(items, ch) != *negated);
+
+            if p.mark() == start {
+                return Ok(None);
+            }
+            Value::from(p.token(start).to_string())
+        }
+        Kind::Count => {
+            return Ok(Some((Value::from(input[..mark].chars().count() as MoltInt), 0)));
+        }
+    };
+
+    Ok(Some((value, field[..p.mark()].chars().count())))
+}
+
+/// Reads an optionally signed integer in the given radix from the head of the input,
+/// leaving the pointer unchanged if there isn't one.  Returns the integer's digits and its
+/// radix, which the prefix gives if the radix is 0.  A hexadecimal integer may have a
+/// "0x" prefix, which is omitted from the result.
+fn read_radix(ptr: &mut Tokenizer, mut radix: u32) -> Option<(String, u32)> {
+    let mut p = ptr.clone();
+    let mut result = String::new();
+
+    if p.is('+') || p.is('-') {
+        result.push(p.next().unwrap());
+    }
+
+    // The prefix counts only if digits follow it; otherwise the "0" is the integer.
+    if (radix == 0 || radix == 16) && p.is('0') {
+        let mut q = p.clone();
+        q.skip();
+
+        let prefix_radix = match q.next() {
+            Some('x' | 'X') => 16,
+            Some('b' | 'B') if radix == 0 => 2,
+            _ => 0,
+        };
+
+        if prefix_radix != 0 && q.has(|ch| ch.is_digit(prefix_radix)) {
+            p = q;
+            radix = prefix_radix;
+        } else if radix == 0 {
+            radix = 8;
+        }
+    }
+
+    if radix == 0 {
+        radix = 10;
+    }
+
+    let start = p.mark();
+    p.skip_while(|ch| ch.is_digit(radix));
+
+    if p.mark() == start {
+        return None;
+    }
+
+    result.push_str(p.token(start));
+    *ptr = p;
+    Some((result, radix))
+}
+
+fn set_contains(items: &[SetItem], ch: char) -> bool {
+    items.iter().any(|item| match item {
+        SetItem::Char(c) => *c == ch,
+        SetItem::Range(lo, hi) => *lo <= ch && ch <= *hi,
+    })
+}
+
+/// Parses the format string into a list of fields, returning the fields and the number
+/// of result slots.  XPG positions must name one of the variables, if there are any, and
+/// can't exceed the number of conversions in any case.
+fn parse_format(fmt: &str, num_vars: usize) -> Result<(Vec<Field>, usize), Exception> {
+    let mut fields = Vec::new();
+    let mut p = Tokenizer::new(fmt);
+    let mut next_slot = 0;
+    let mut assigned: Vec<bool> = Vec::new();
+    let mut got_xpg = false;
+    let mut got_sequential = false;
+
+    while let Some(ch) = p.next() {
+        if util::is_whitespace(ch) {
+            p.skip_while(util::is_whitespace);
+            fields.push(Field::Space);
+            continue;
+        }
+
+        if ch != '%' {
+            fields.push(Field::Literal(ch));
+            continue;
+        }
+
+        if p.is('%') {
+            p.skip();
+            fields.push(Field::Literal('%'));
+            continue;
+        }
+
+        // FIRST, get the suppression flag, or the XPG position.
+        let mut suppress = false;
+        let mut number = read_number(&mut p);
+
+        if p.is('*') {
+            p.skip();
+            suppress = true;
+            number = read_number(&mut p);
+        } else if number.is_some() && p.is('$') {
+            p.skip();
+
+            if got_sequential {
+                return mix_error();
+            }
+            got_xpg = true;
+
+            next_slot = match number {
+                Some(n) if n > 0 && (num_vars == 0 || n <= num_vars) => n - 1,
+                _ => return molt_err!("\"%n$\" argument index out of range"),
+            };
+
+            // Each conversion begins with a "%", so a position past their number leaves
+            // some variable unassigned.
+            if next_slot >= fmt.matches('%').count() {
+                return unassigned_error();
+            }
+            number = read_number(&mut p);
+        } else {
+            if got_xpg {
+                return mix_error();
+            }
+            got_sequential = true;
+        }
+
+        // NEXT, skip any size modifiers.
+        while p.has(|ch| matches!(ch, 'h' | 'l' | 'L' | 'j' | 'q' | 't' | 'z')) {
+            p.skip();
+        }
+
+        // NEXT, get the conversion.
+        let kind = match p.next() {
+            Some('d' | 'u') => Kind::Int(10),
+            Some('i') => Kind::Int(0),
+            Some('x' | 'X') => Kind::Int(16),
+            Some('o') => Kind::Int(8),
+            Some('b') => Kind::Int(2),
+            #[cfg(feature = "float")]
+            Some('f' | 'e' | 'E' | 'g' | 'G') => Kind::Float,
+            Some('c') => {
+                if number.is_some() {
+                    return molt_err!("field width may not be specified in %c conversion");
+                }
+                Kind::Char
+            }
+            Some('s') => Kind::Str,
+            Some('[') => parse_set(&mut p)?,
+            Some('n') => Kind::Count,
+            Some(ch) => {
+                let mut buff = String::new();
+                buff.push(ch);
+                return molt_err!("bad scan conversion character \"{}\"", buff);
+            }
+            None => return molt_err!("unmatched % in format string"),
+        };
+
+        let slot = if suppress {
+            None
+        } else {
+            if assigned.len() <= next_slot {
+                assigned.resize(next_slot + 1, false);
+            }
+            if assigned[next_slot] {
+                return molt_err!("variable is assigned by multiple \"%n$\" conversion specifiers");
+            }
+            assigned[next_slot] = true;
+            next_slot += 1;
+            Some(next_slot - 1)
+        };
+
+        fields.push(Field::Conversion(Conversion {
+            slot,
+            width: number.filter(|w| *w > 0),
+            kind,
+        }));
+    }
+
+    if assigned.contains(&false) {
+        return unassigned_error();
+    }
+
+    Ok((fields, assigned.len()))
+}
+
+/// Parses a `%[...]` character set, just after the `[`.
+fn parse_set(p: &mut Tokenizer) -> Result<Kind, Exception> {
+    let mut items = Vec::new();
+    let negated = p.is('^');
+
+    if negated {
+        p.skip();
+    }
+
+    // A "]" at the beginning of the set is part of the set.
+    if p.is(']') {
+        p.skip();
+        items.push(SetItem::Char(']'));
+    }
+
+    loop {
+        match p.next() {
+            Some(']') => break,
+            Some(ch) => {
+                // A "-" at the end of the set is an ordinary character.
+                if p.is('-') {
+                    let mut q = p.clone();
+                    q.skip();
+
+                    match q.peek() {
+                        Some(hi) if hi != ']' => {
+                            q.skip();
+                            *p = q;
+                            items.push(SetItem::Range(ch.min(hi), ch.max(hi)));
+                            continue;
+                        }
+                        _ => (),
+                    }
+                }
+                items.push(SetItem::Char(ch));
+            }
+            None => return molt_err!("unmatched [ in format string"),
+        }
+    }
+
+    Ok(Kind::Set(items, negated))
+}
+
+fn read_number(p: &mut Tokenizer) -> Option<usize> {
+    let start = p.mark();
+    p.skip_while(|ch| ch.is_ascii_digit());
+    p.token(start).parse().ok()
+}
+
+fn mix_error<T>() -> Result<T, Exception> {
+    molt_err!("cannot mix \"%\" and \"%n$\" conversion specifiers")
+}
+
+fn unassigned_error<T>() -> Result<T, Exception> {
+    molt_err!("variable is not assigned by any conversion specifiers")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scan_list(input: &str, fmt: &str) -> Vec<String> {
+        scan(input, fmt, 0)
+            .unwrap()
+            .values
+            .iter()
+            .map(|v| v.as_ref().map(|v| v.to_string()).unwrap_or_default())
+            .collect()
+    }
+
+    #[test]
+    fn test_scan_numbers() {
+        assert_eq!(scan_list("12 -34", "%d %d"), ["12", "-34"]);
+        assert_eq!(scan_list("ff -7 101", "%x %o %b"), ["255", "-7", "5"]);
+        assert_eq!(scan_list("12345", "%2d%d"), ["12", "345"]);
+        assert_eq!(scan_list("0x1F", "%d"), ["0"]);
+        assert_eq!(scan_list("0x1F", "%i"), ["31"]);
+        assert_eq!(scan_list("012 -012 12 0b101 0", "%i %i %i %i %i"), ["10", "-10", "12", "5", "0"]);
+        assert_eq!(scan_list("09", "%i%s"), ["0", "9"]);
+        assert_eq!(scan_list("0x", "%i%s"), ["0", "x"]);
+        assert_eq!(scan_list("0x12 -0XfF", "%x %X"), ["18", "-255"]);
+        assert_eq!(scan_list("0x", "%d%s"), ["0", "x"]);
+        assert_eq!(scan_list("0xg", "%x%s"), ["0", "xg"]);
+        #[cfg(feature = "float")]
+        assert_eq!(scan_list("T=23.5 H=40", "T=%f H=%d"), ["23.5", "40"]);
+    }
+
+    #[test]
+    fn test_scan_strings() {
+        assert_eq!(scan_list("  abc def", "%s%s"), ["abc", "def"]);
+        assert_eq!(scan_list("abcdef", "%3s%s"), ["abc", "def"]);
+        assert_eq!(scan_list(" A", "%c%c"), ["32", "65"]);
+        assert_eq!(scan_list("abc123", "%[a-z]%[0-9]"), ["abc", "123"]);
+        assert_eq!(scan_list("key=value", "%[^=]=%s"), ["key", "value"]);
+        assert_eq!(scan_list("]]x", "%[]]"), ["]]"]);
+        assert_eq!(scan_list("a-b", "%[a-]"), ["a-"]);
+        assert_eq!(scan_list("αβ γ", "%s%n"), ["αβ", "2"]);
+    }
+
+    #[test]
+    fn test_scan_partial() {
+        let result = scan("12 abc", "%d %d", 0).unwrap();
+        assert_eq!(result.count, 1);
+        assert_eq!(result.values[1], None);
+
+        assert_eq!(scan("", "%d", 0).unwrap().count, -1);
+        assert_eq!(scan("  ", "%d", 0).unwrap().count, -1);
+        assert_eq!(scan("x", "%d", 0).unwrap().count, 0);
+        assert_eq!(scan("12", "%*d %d", 0).unwrap().count, -1);
+    }
+
+    #[test]
+    fn test_scan_xpg() {
+        assert_eq!(scan_list("1 2", "%2$d %1$d"), ["2", "1"]);
+        assert_eq!(scan_list("1 2", "%*d %d"), ["2"]);
+    }
+
+    #[test]
+    fn test_scan_errors() {
+        assert!(scan("", "%y", 0).is_err());
+        assert!(scan("", "%", 0).is_err());
+        assert!(scan("", "%[abc", 0).is_err());
+        assert!(scan("", "%2c", 0).is_err());
+        assert!(scan("", "%1$d %d", 0).is_err());
+        assert!(scan("", "%d %1$d", 0).is_err());
+        assert!(scan("", "%1$d %1$d", 0).is_err());
+        assert!(scan("", "%2$d", 0).is_err());
+        assert!(scan("", "%2$d %1$d", 1).is_err());
+        assert!(scan("abc", "%9223372036854775807$s", 0).is_err());
+        assert!(scan("abc", "%9223372036854775807$s", 1).is_err());
+    }
+}
@@ -9,20 +9,47 @@
 //! the current are said to be higher in the stack, following Standard TCL practice (e.g.,
 //! `upvar`, `uplevel`).
 //!
+//! The current scope is usually the top of the stack, but `uplevel` can temporarily make
+//! a higher scope current.  A scope pushed while a higher scope is current is that scope's
+//! callee: its level is one more than the level of its caller, rather than one more than
+//! the level of the scope beneath it in the stack.  Hence, each scope records its level
+//! and the index of its caller, and levels are resolved to stack indices by following the
+//! chain of callers from the current scope.
+//!
+//! With the `namespaces` feature, each scope also records its current namespace.  The
+//! variables of all namespaces live in the global scope, keyed by their fully qualified
+//! names (see the `namespace` module).  `::`-qualified variable names always refer to the
+//! global scope; in a namespace frame (the global scope itself, or a scope pushed by
+//! `namespace eval`) unqualified names refer to the current namespace's variables, while
+//! in a procedure's scope they refer to local variables as usual.
+//!
+//! With the `traces` feature, each scope also holds the traces on its variables, keyed by
+//! variable name.  Aliases have no traces of their own; the traces of the variable at the
+//! end of the alias chain apply.
+//!
 //! Molt clients do not interact with this mechanism directly, but via the
 //! `Interp` (or the Molt language itself).
 
+#[cfg(feature = "namespaces")]
+use crate::namespace;
+#[cfg(feature = "traces")]
+use crate::trace::VarTrace;
 use crate::types::Exception;
 use crate::types::{MoltList, MoltHasher};
 use crate::value::Value;
+use alloc::borrow::Cow;
+#[cfg(any(feature = "namespaces", feature = "traces"))]
+use alloc::rc::Rc;
 use alloc::string::String;
+#[cfg(feature = "namespaces")]
+use alloc::string::ToString as _;
 use alloc::vec::Vec;
 use indexmap::IndexMap;
 use core::fmt::Debug;
 
 /// A variable in a `Scope`.  If the variable is defined in the given `Scope`, it is a
 /// `Scalar` or an `Array`; if it is an alias to a variable in a higher scope (e.g., a global)
-/// then the `Upvar` gives the referenced scope.  The `New` variant is used transiently as
+/// then the `Upvar` gives the referenced scope and the variable's name in that scope.  The `New` variant is used transiently as
 /// part of setting a variable for the first time.
 #[derive(Eq, PartialEq)]
 enum Var {
@@ -32,9 +59,9 @@ enum Var {
     /// An array variable, with its hash table from names to values.
     Array(IndexMap<String, Value, MoltHasher>),
 
-    /// An alias to a variable at a higher stack level, with the referenced stack level.
-    /// Note that aliases can chain.
-    Upvar(usize),
+    /// An alias to a variable at a higher stack level, with the referenced stack level
+    /// and the name of the variable at that level.  Note that aliases can chain.
+    Upvar(usize, String),
 
     /// A variable that has just been created so that it can be set.
     New,
@@ -43,7 +70,7 @@ enum Var {
 impl Var {
     /// This is an upvar'd variable?
     fn is_upvar(&self) -> bool {
-        matches!(self, Var::Upvar(_))
+        matches!(self, Var::Upvar(_, _))
     }
 }
 
@@ -52,7 +79,7 @@ impl Debug for Var {
         match self {
             Var::Scalar(value) => write!(f, "Var::Scalar({})", value.as_str()),
             Var::Array(_) => write!(f, "Var::Array(TODO)"),
-            Var::Upvar(level) => write!(f, "Var::Upvar({})", level),
+            Var::Upvar(level, name) => write!(f, "Var::Upvar({}, {})", level, name),
             Var::New => write!(f, "Var::New"),
         }
     }
@@ -65,22 +92,92 @@ impl Debug for Var {
 struct Scope {
     /// Vars in this scope by name.
     map: IndexMap<String, Var, MoltHasher>,
+
+    /// The scope's level, counting from 0, the global scope.
+    level: usize,
+
+    /// The stack index of the scope that was current when this scope was pushed.
+    caller: usize,
+
+    /// The scope's current namespace, as a key; the global namespace is "".
+    #[cfg(feature = "namespaces")]
+    namespace: Rc<str>,
+
+    /// Whether unqualified variable names refer to the namespace's variables rather than
+    /// to local variables.
+    #[cfg(feature = "namespaces")]
+    ns_frame: bool,
+
+    /// The traces on the variables in this scope, by variable name, in order of creation.
+    #[cfg(feature = "traces")]
+    traces: IndexMap<String, Vec<Rc<VarTrace>>, MoltHasher>,
 }
 
 impl Scope {
-    /// Create a new empty scope.
-    pub fn new() -> Self {
+    /// Create a new empty scope at the given level.
+    pub fn new(level: usize, caller: usize) -> Self {
         Scope {
             map: IndexMap::default(),
+            level,
+            caller,
+            #[cfg(feature = "namespaces")]
+            namespace: Rc::from(""),
+            #[cfg(feature = "namespaces")]
+            ns_frame: false,
+            #[cfg(feature = "traces")]
+            traces: IndexMap::default(),
         }
     }
 }
 
+/// The variable scopes of a suspended coroutine, detached from the `ScopeStack`.  A new
+/// coroutine has none, and runs in the global scope.
+#[cfg(feature = "coroutines")]
+#[derive(Default, Debug)]
+pub(crate) struct DetachedScopes {
+    scopes: Vec<Scope>,
+
+    /// The stack index of the first scope when the scopes were detached.
+    base: usize,
+
+    /// The stack index of the scope that was current when the scopes were detached.
+    current: usize,
+}
+
+#[cfg(feature = "coroutines")]
+impl DetachedScopes {
+    /// Gets the stack index of the first scope when the scopes were detached.
+    pub fn base(&self) -> usize {
+        self.base
+    }
+}
+
+/// Relocates the stack index of a scope detached from `from` to its index once attached
+/// at `to`.  A coroutine's scopes are called from the global scope, so the global scope is
+/// the only other scope they can refer to.
+#[cfg(feature = "coroutines")]
+pub(crate) fn relocate(index: usize, from: usize, to: usize) -> usize {
+    if index == 0 {
+        0
+    } else {
+        debug_assert!(index >= from, "Detached scope refers to caller");
+        index - from + to
+    }
+}
+
 /// The scope stack: a stack of variable scopes corresponding to the Molt `proc`
 /// call stack.
 #[derive(Default, Debug)]
 pub(crate) struct ScopeStack {
     stack: Vec<Scope>,
+
+    /// The stack index of the current scope.
+    current: usize,
+
+    /// The number of variable traces in all scopes, so that untraced interpreters can
+    /// skip looking for them.
+    #[cfg(feature = "traces")]
+    num_traces: usize,
 }
 
 impl ScopeStack {
@@ -90,16 +187,28 @@ impl ScopeStack {
     /// Creates a scope stack containing only scope `0`, the global scope.  This is usually
     /// done once, as part of creating an `Interp`.
     pub fn new() -> Self {
-        let mut ss = Self { stack: Vec::new() };
-
-        ss.stack.push(Scope::new());
+        let mut ss = Self {
+            stack: Vec::new(),
+            current: 0,
+            #[cfg(feature = "traces")]
+            num_traces: 0,
+        };
+
+        #[allow(unused_mut)]
+        let mut global = Scope::new(0, 0);
+        #[cfg(feature = "namespaces")]
+        {
+            global.ns_frame = true;
+        }
+        ss.stack.push(global);
 
         ss
     }
 
     /// Requires the value of the named scalar variable in the current scope.
     pub fn get(&self, name: &str) -> Result<Value, Exception> {
-        match self.var(self.current(), name) {
+        let (at, key) = self.resolve(name);
+        match self.var(at, &key) {
             Some(Var::Scalar(value)) => Ok(value.clone()),
             Some(Var::Array(_)) => molt_err!("can't read \"{}\": variable is array", name),
             Some(_) => unreachable!(),
@@ -109,7 +218,8 @@ impl ScopeStack {
 
     /// Requires the value of an array element given its variable name and index.
     pub fn get_elem(&self, name: &str, index: &str) -> Result<Value, Exception> {
-        match self.var(self.current(), name) {
+        let (at, key) = self.resolve(name);
+        match self.var(at, &key) {
             Some(Var::Scalar(_)) => {
                 molt_err!("can't read \"{}({})\": variable isn't array", name, index)
             }
@@ -134,7 +244,7 @@ impl ScopeStack {
     /// variable.
     pub fn set_global(&mut self, name: &str, val: Value) -> Result<(), Exception> {
         match self.var_mut(0, name) {
-            Some(Var::Upvar(_)) => unreachable!(),
+            Some(Var::Upvar(_, _)) => unreachable!(),
             Some(Var::Array(_)) => molt_err!("can't set \"{}\": variable is array", name),
             Some(var) => {
                 // It was either Var::Scalar or Var::New; either way, replace it with a new
@@ -150,8 +260,9 @@ impl ScopeStack {
     /// if it doesn't already exist.  It's an error if the variable exists but is an array
     /// variable.
     pub fn set(&mut self, name: &str, val: Value) -> Result<(), Exception> {
-        match self.var_mut(self.current(), name) {
-            Some(Var::Upvar(_)) => unreachable!(),
+        let (at, key) = self.resolve(name);
+        match self.var_mut(at, &key) {
+            Some(Var::Upvar(_, _)) => unreachable!(),
             Some(Var::Array(_)) => molt_err!("can't set \"{}\": variable is array", name),
             Some(var) => {
                 // It was either Var::Scalar or Var::New; either way, replace it with a new
@@ -167,10 +278,10 @@ impl ScopeStack {
     /// and/or the element if they don't already exist. It's an error if the variable exists
     /// but is a scalar variable.
     pub fn set_elem(&mut self, name: &str, index: &str, val: Value) -> Result<(), Exception> {
-        let top = self.current();
+        let (at, key) = self.resolve(name);
 
-        match self.var_mut(top, name) {
-            Some(Var::Upvar(_)) => unreachable!(),
+        match self.var_mut(at, &key) {
+            Some(Var::Upvar(_, _)) => unreachable!(),
             Some(Var::Scalar(_)) => {
                 molt_err!("can't set \"{}({})\": variable isn't array", name, index)
             }
@@ -195,7 +306,8 @@ impl ScopeStack {
     /// Returns true if there's a variable with the given name, of whatever type, and
     /// false otherwise.
     pub fn exists(&self, name: &str) -> bool {
-        self.var(self.current(), name).is_some()
+        let (at, key) = self.resolve(name);
+        self.var(at, &key).is_some()
     }
 
     /// Returns true if there's a variable with the given name, of whatever type, and
@@ -210,18 +322,20 @@ impl ScopeStack {
     ///
     /// Note: it's irrelevant whether the variable is a scalar or array; it's going away.
     pub fn unset(&mut self, name: &str) {
-        self.unset_at(self.current(), name, false);
+        let (at, key) = self.resolve(name);
+        self.unset_at(at, &key, false);
     }
 
     /// Unset a variable at a given level in the stack.  If the variable at that level
     /// is linked to a higher level, follows the chain down, unsetting as it goes.
     fn unset_at(&mut self, level: usize, name: &str, array_only: bool) {
         // FIRST, if the variable at this level links to a lower level, follow the chain.
-        if let Some(Var::Upvar(at)) = self.stack[level].map.get(name) {
-            // NOTE: Using the variable true_level prevents a "doubly-borrowed" error.
+        if let Some(Var::Upvar(at, true_name)) = self.stack[level].map.get(name) {
+            // NOTE: Copying the level and name prevents a "doubly-borrowed" error.
             // Once Polonius is in use, this should no longer be necessary.
             let true_level = *at;
-            self.unset_at(true_level, name, array_only);
+            let true_name = true_name.clone();
+            self.unset_at(true_level, &true_name, array_only);
         }
 
         // NEXT, remove the variable at this level.
@@ -240,39 +354,263 @@ impl ScopeStack {
     /// **Note:** does not try to create the variable at the referenced scope level, if it
     /// does not exist; the variable will be created on the first `set`, if any.  This is
     /// consistent with standard TCL behavior.
+    ///
+    /// With the `namespaces` feature, the name may be qualified, in which case the local
+    /// variable is named by its tail.  Does nothing in a namespace frame, where global
+    /// variables are already accessible by their qualified names.
     pub fn upvar(&mut self, level: usize, name: &str) {
-        assert!(level < self.current(), "Can't upvar to current stack level");
+        assert!(level < self.level(), "Can't upvar to current stack level");
+        let at = self.index_of_level(level);
         let top = self.current();
-        self.stack[top].map.insert(name.into(), Var::Upvar(level));
+
+        #[cfg(feature = "namespaces")]
+        {
+            if self.stack[top].ns_frame {
+                return;
+            }
+
+            let local = namespace::tail(name);
+            let other = namespace::qualify("", name);
+            self.stack[top].map.insert(local.into(), Var::Upvar(at, other.into()));
+        }
+
+        #[cfg(not(feature = "namespaces"))]
+        self.stack[top].map.insert(name.into(), Var::Upvar(at, name.into()));
+    }
+
+    /// Links the variable `name` in the current scope to the variable `other` at the given
+    /// level, counting from `0`, the global scope.  The level may be the current level,
+    /// provided that the two names differ.
+    ///
+    /// If `other` is itself an alias, the new link refers directly to the variable at the
+    /// end of the chain.  It's an error if `name` already exists in the current scope
+    /// as something other than an alias, or if the link would refer to itself.
+    ///
+    /// **Note:** as with `upvar`, does not create the variable at the referenced scope level.
+    pub fn upvar_alias(&mut self, level: usize, other: &str, name: &str) -> Result<(), Exception> {
+        assert!(level <= self.level(), "Invalid scope level");
+
+        // FIRST, resolve the names; namespace variables live in the global scope.
+        let (mut true_level, other) = self.resolve_at(self.index_of_level(level), other);
+        let mut true_name: String = other.into();
+        let (top, key) = self.resolve(name);
+
+        // NEXT, follow the alias chain to the variable that will actually be referenced.
+        while let Some(Var::Upvar(at, at_name)) = self.stack[true_level].map.get(&true_name) {
+            true_level = *at;
+            true_name = at_name.clone();
+        }
+
+        if true_level == top && true_name == *key {
+            return molt_err!("can't upvar from variable to itself");
+        }
+
+        // NEXT, make sure we aren't replacing a local variable.
+        match self.stack[top].map.get(&*key) {
+            None | Some(Var::Upvar(_, _)) => (),
+            Some(_) => return molt_err!("variable \"{}\" already exists", name),
+        }
+
+        self.stack[top]
+            .map
+            .insert(key.into(), Var::Upvar(true_level, true_name));
+        Ok(())
     }
 
-    /// Returns the index of the current stack level, counting from 0, the global scope.
-    /// The current stack level has the highest index, but is said to be the lowest stack
-    /// level.
+    /// Returns the stack index of the current scope, counting from 0, the global scope.
+    /// The current scope is usually the top of the stack, which has the highest index but
+    /// is said to be the lowest stack level.
     pub fn current(&self) -> usize {
-        self.stack.len() - 1
+        self.current
     }
 
-    /// Pushes a new scope onto the stack.  The scope contains no variables by default, though
+    /// Returns the level of the current scope, counting from 0, the global scope.  This
+    /// is the same as the current stack index unless `uplevel` is in effect.
+    pub fn level(&self) -> usize {
+        self.stack[self.current].level
+    }
+
+    /// Returns the stack index of the scope at the given level, following the chain of
+    /// callers from the current scope.  Panics if the level is greater than the current
+    /// level; this implies a coding error at the Rust level.
+    pub fn index_of_level(&self, level: usize) -> usize {
+        assert!(level <= self.level(), "Invalid scope level");
+        let mut index = self.current;
+
+        while self.stack[index].level > level {
+            index = self.stack[index].caller;
+        }
+
+        index
+    }
+
+    /// Makes the scope at the given stack index the current scope, returning the index of the
+    /// previously current scope so that it can be restored.  This is used to implement
+    /// `uplevel`.
+    pub fn set_current(&mut self, index: usize) -> usize {
+        assert!(index < self.stack.len(), "Invalid scope index");
+        core::mem::replace(&mut self.current, index)
+    }
+
+    /// Pushes a new scope onto the stack and makes it current; it is called from the
+    /// previously current scope.  The scope contains no variables by default, though
     /// the procedure that is pushing it onto the stack will often add some.
+    ///
+    /// With the `namespaces` feature, the new scope is a procedure scope in the caller's
+    /// namespace.
     pub fn push(&mut self) {
-        self.stack.push(Scope::new());
+        #[allow(unused_mut)]
+        let mut scope = Scope::new(self.level() + 1, self.current);
+        #[cfg(feature = "namespaces")]
+        {
+            scope.namespace = Rc::clone(&self.stack[self.current].namespace);
+        }
+        self.stack.push(scope);
+        self.current = self.stack.len() - 1;
+    }
+
+    /// Pushes a new namespace frame onto the stack for the given namespace, as for
+    /// `namespace eval`, and makes it current.  Within it, unqualified variable names
+    /// refer to the namespace's variables.
+    #[cfg(feature = "namespaces")]
+    pub fn push_namespace(&mut self, ns: Rc<str>) {
+        self.push();
+        let top = self.current;
+        self.stack[top].namespace = ns;
+        self.stack[top].ns_frame = true;
+    }
+
+    /// Sets the current scope's namespace.  A procedure does this on entry, so that it
+    /// runs in the namespace in which it was defined.
+    #[cfg(feature = "namespaces")]
+    pub fn set_namespace(&mut self, ns: Rc<str>) {
+        let top = self.current;
+        self.stack[top].namespace = ns;
+    }
+
+    /// Returns the current scope's namespace, as a key.
+    #[cfg(feature = "namespaces")]
+    pub fn namespace(&self) -> &Rc<str> {
+        &self.stack[self.current].namespace
+    }
+
+    /// Returns true if the current scope is a namespace frame, i.e., is the global scope
+    /// or was pushed by `namespace eval`, rather than being a procedure's scope.
+    #[cfg(feature = "namespaces")]
+    pub fn is_ns_frame(&self) -> bool {
+        self.stack[self.current].ns_frame
+    }
+
+    /// Gets a list of the names of the variables in the given namespace, as a key.
+    #[cfg(feature = "namespaces")]
+    pub fn vars_in_namespace(&self, ns: &str) -> MoltList {
+        self.stack[0]
+            .map
+            .keys()
+            .filter(|key| namespace::qualifiers(key) == ns)
+            .map(|key| Value::from(namespace::tail(key).to_string()))
+            .collect()
+    }
+
+    /// Unsets all of the variables in the given namespace and its children, as part of
+    /// deleting the namespace.
+    #[cfg(feature = "namespaces")]
+    pub fn unset_namespace(&mut self, ns: &str) {
+        let within = |key: &str| key.starts_with(ns) && key[ns.len()..].starts_with(namespace::SEP);
+        self.stack[0].map.retain(|key, _| !within(key));
+
+        #[cfg(feature = "traces")]
+        {
+            let traces = &mut self.stack[0].traces;
+            let before: usize = traces.values().map(Vec::len).sum();
+            traces.retain(|key, _| !within(key));
+            let after: usize = traces.values().map(Vec::len).sum();
+            self.num_traces -= before - after;
+        }
     }
 
-    /// Pops the current scope from the stack. Panics if we're at the global scope; this implies an
-    /// coding error at the Rust level.
+    /// Pops the current scope from the stack, and makes its caller current again. Panics if
+    /// we're at the global scope, or if the current scope isn't on top of the stack; either
+    /// implies a coding error at the Rust level.
     pub fn pop(&mut self) {
-        self.stack.pop();
+        assert_eq!(self.current, self.stack.len() - 1, "Popped non-current scope!");
+        let scope = self.stack.pop().unwrap();
         assert!(!self.stack.is_empty(), "Popped global scope!");
+        self.current = scope.caller;
+
+        #[cfg(feature = "traces")]
+        {
+            self.num_traces -= scope.traces.values().map(Vec::len).sum::<usize>();
+        }
+    }
+
+    /// Detaches the scopes from the given stack index to the top of the stack, as when a
+    /// coroutine is suspended, and makes the scope at stack index `current` current again.
+    #[cfg(feature = "coroutines")]
+    pub fn detach(&mut self, base: usize, current: usize) -> DetachedScopes {
+        let scopes = self.stack.split_off(base);
+
+        #[cfg(feature = "traces")]
+        {
+            self.num_traces -= scopes
+                .iter()
+                .map(|scope| scope.traces.values().map(Vec::len).sum::<usize>())
+                .sum::<usize>();
+        }
+
+        let detached = DetachedScopes {
+            scopes,
+            base,
+            current: self.current,
+        };
+        self.current = current;
+        detached
+    }
+
+    /// Attaches detached scopes to the top of the stack, as when a coroutine is resumed, and
+    /// makes the scope that was current when they were detached current again.  Returns the
+    /// stack index of the first of them, for detaching them later.
+    ///
+    /// The scopes' references to each other are relocated to their new stack indices.
+    #[cfg(feature = "coroutines")]
+    pub fn attach(&mut self, detached: DetachedScopes) -> usize {
+        let base = self.stack.len();
+
+        for mut scope in detached.scopes {
+            scope.caller = relocate(scope.caller, detached.base, base);
+
+            for var in scope.map.values_mut() {
+                if let Var::Upvar(at, _) = var {
+                    *at = relocate(*at, detached.base, base);
+                }
+            }
+
+            #[cfg(feature = "traces")]
+            {
+                self.num_traces += scope.traces.values().map(Vec::len).sum::<usize>();
+            }
+
+            self.stack.push(scope);
+        }
+
+        self.current = relocate(detached.current, detached.base, base);
+        base
     }
 
     /// Gets a list of the names of the variables defined in the current scope.
+    ///
+    /// With the `namespaces` feature, in a namespace frame these are the variables in the
+    /// current namespace.
     pub fn vars_in_scope(&self) -> MoltList {
+        #[cfg(feature = "namespaces")]
+        if self.is_ns_frame() {
+            return self.vars_in_namespace(self.namespace());
+        }
+
         self.stack[self.current()]
             .map
             .keys()
-            .cloned()
-            .map(|x| Value::from(&x))
+            .map(Value::from)
             .collect()
     }
 
@@ -280,7 +618,13 @@ impl ScopeStack {
     /// are not local; and no variables are local in the global scope.
     pub fn vars_in_local_scope(&self) -> MoltList {
         // If we are at the global scope, there are no local variables.
-        if self.current() == 0 {
+        if self.level() == 0 {
+            return Vec::new();
+        }
+
+        // Nor are there any in a namespace frame.
+        #[cfg(feature = "namespaces")]
+        if self.is_ns_frame() {
             return Vec::new();
         }
 
@@ -292,25 +636,31 @@ impl ScopeStack {
             .collect()
     }
 
-    /// Gets a list of the variables defined in the global scope.
+    /// Gets a list of the variables defined in the global scope.  With the `namespaces`
+    /// feature, variables in other namespaces are excluded.
     pub fn vars_in_global_scope(&self) -> MoltList {
+        #[cfg(feature = "namespaces")]
+        return self.vars_in_namespace("");
+
+        #[cfg(not(feature = "namespaces"))]
         self.stack[0]
             .map
             .keys()
-            .cloned()
-            .map(|x| Value::from(&x))
+            .map(Value::from)
             .collect()
     }
 
     /// Determines whether the name names an array variable or not.
     pub fn array_exists(&self, name: &str) -> bool {
-        matches!(self.var(self.current(), name), Some(Var::Array(_)))
+        let (at, key) = self.resolve(name);
+        matches!(self.var(at, &key), Some(Var::Array(_)))
     }
 
     /// Gets a list of the array indices for the named array.  Returns the empty list
     /// if `name` doesn't name an array variable.
     pub fn array_indices(&self, name: &str) -> MoltList {
-        match self.var(self.current(), name) {
+        let (at, key) = self.resolve(name);
+        match self.var(at, &key) {
             Some(Var::Array(map)) => map.keys().cloned().map(Value::from).collect(),
             _ => Vec::new(),
         }
@@ -318,7 +668,8 @@ impl ScopeStack {
 
     /// Gets the size of the named array.  Returns 0 if `name` doesn't name an array variable.
     pub fn array_size(&self, name: &str) -> usize {
-        match self.var(self.current(), name) {
+        let (at, key) = self.resolve(name);
+        match self.var(at, &key) {
             Some(Var::Array(map)) => map.len(),
             _ => 0,
         }
@@ -327,7 +678,8 @@ impl ScopeStack {
     /// Gets the content of an array as a flat list of names and values.  If the named
     /// variable is not an array, returns the empty list.
     pub fn array_get(&self, name: &str) -> MoltList {
-        match self.var(self.current(), name) {
+        let (at, key) = self.resolve(name);
+        match self.var(at, &key) {
             Some(Var::Array(map)) => {
                 let mut list = Vec::new();
 
@@ -345,7 +697,8 @@ impl ScopeStack {
     /// Does nothing if the array element doesn't exist, or the variable isn't an array
     /// variable.
     pub fn unset_element(&mut self, name: &str, index: &str) {
-        if let Some(Var::Array(map)) = self.var_mut(self.current(), name) {
+        let (at, key) = self.resolve(name);
+        if let Some(Var::Array(map)) = self.var_mut(at, &key) {
             map.remove(index);
         }
     }
@@ -354,10 +707,11 @@ impl ScopeStack {
     /// if it doesn't exist. It's an error if the variable exists but is a scalar variable.
     pub fn array_set(&mut self, name: &str, kvlist: &[Value]) -> Result<(), Exception> {
         // List must be even.
-        assert!(kvlist.len() % 2 == 0);
+        assert!(kvlist.len().is_multiple_of(2));
 
-        match self.var_mut(self.current(), name) {
-            Some(Var::Upvar(_)) => unreachable!(),
+        let (at, key) = self.resolve(name);
+        match self.var_mut(at, &key) {
+            Some(Var::Upvar(_, _)) => unreachable!(),
             Some(Var::Scalar(_)) => molt_err!("can't array set \"{}\": variable isn't array", name),
             Some(Var::Array(map)) => {
                 // It was already an array; just add the new elements.
@@ -382,12 +736,137 @@ impl ScopeStack {
     ///
     /// Only affects array variables.
     pub fn array_unset(&mut self, name: &str) {
-        self.unset_at(self.current(), name, true);
+        let (at, key) = self.resolve(name);
+        self.unset_at(at, &key, true);
+    }
+
+    /// Returns true if any variable traces are defined.
+    #[cfg(feature = "traces")]
+    pub fn has_traces(&self) -> bool {
+        self.num_traces > 0
+    }
+
+    /// Adds a trace to the named variable, which need not exist.  If the name is an alias,
+    /// the trace is added to the variable it refers to.
+    #[cfg(feature = "traces")]
+    pub fn add_trace(&mut self, name: &str, trace: Rc<VarTrace>) {
+        let (at, key) = self.locate(name);
+        self.stack[at].traces.entry(key).or_default().push(trace);
+        self.num_traces += 1;
+    }
+
+    /// Removes the most recent of the named variable's traces that satisfies the
+    /// predicate, if any.
+    #[cfg(feature = "traces")]
+    pub fn remove_trace(&mut self, name: &str, pred: impl Fn(&VarTrace) -> bool) {
+        let (at, key) = self.locate(name);
+
+        if let Some(traces) = self.stack[at].traces.get_mut(&key) {
+            if let Some(i) = traces.iter().rposition(|trace| pred(trace)) {
+                traces.remove(i);
+                self.num_traces -= 1;
+
+                if traces.is_empty() {
+                    self.stack[at].traces.shift_remove(&key);
+                }
+            }
+        }
+    }
+
+    /// Gets the named variable's traces, most recent first.
+    #[cfg(feature = "traces")]
+    pub fn traces(&self, name: &str) -> Vec<Rc<VarTrace>> {
+        let (at, key) = self.locate(name);
+
+        match self.stack[at].traces.get(&key) {
+            Some(traces) => traces.iter().rev().cloned().collect(),
+            None => Vec::new(),
+        }
+    }
+
+    /// Removes and returns the named variable's traces, most recent first, as part of
+    /// unsetting the variable.
+    #[cfg(feature = "traces")]
+    pub fn take_traces(&mut self, name: &str) -> Vec<Rc<VarTrace>> {
+        let (at, key) = self.locate(name);
+
+        match self.stack[at].traces.shift_remove(&key) {
+            Some(mut traces) => {
+                self.num_traces -= traces.len();
+                traces.reverse();
+                traces
+            }
+            None => Vec::new(),
+        }
+    }
+
+    /// Removes and returns the traces on the current scope's variables, most recent first
+    /// for each variable, as part of popping the scope.  Traces on variables that don't
+    /// exist are discarded.
+    #[cfg(feature = "traces")]
+    pub fn take_scope_traces(&mut self) -> Vec<(String, Vec<Rc<VarTrace>>)> {
+        let scope = &mut self.stack[self.current];
+        let traces = core::mem::take(&mut scope.traces);
+        let mut result = Vec::new();
+
+        for (name, mut var_traces) in traces {
+            self.num_traces -= var_traces.len();
+
+            if matches!(scope.map.get(&name), Some(Var::Scalar(_) | Var::Array(_))) {
+                var_traces.reverse();
+                result.push((name, var_traces));
+            }
+        }
+
+        result
     }
 
     //--------------------------------------------------------------
     // Utilities
 
+    /// Finds the variable the name refers to in the current scope, following the alias
+    /// chain, and returns the stack index of its scope and its name in that scope.  The
+    /// variable need not exist.
+    #[cfg(feature = "traces")]
+    fn locate(&self, name: &str) -> (usize, String) {
+        let (mut at, key) = self.resolve(name);
+        let mut key: String = key.into();
+
+        while let Some(Var::Upvar(level, true_name)) = self.stack[at].map.get(&key) {
+            at = *level;
+            key = true_name.clone();
+        }
+
+        (at, key)
+    }
+
+    /// Resolves a variable name as used in the current scope to the stack index of the
+    /// scope that contains the variable, and the variable's name in that scope.
+    fn resolve<'a>(&self, name: &'a str) -> (usize, Cow<'a, str>) {
+        self.resolve_at(self.current, name)
+    }
+
+    /// Resolves a variable name as used in the scope with the given stack index.  Qualified
+    /// names, and unqualified names used in a namespace frame, refer to namespace variables,
+    /// which live in the global scope.
+    #[cfg(feature = "namespaces")]
+    fn resolve_at<'a>(&self, index: usize, name: &'a str) -> (usize, Cow<'a, str>) {
+        let scope = &self.stack[index];
+
+        if (scope.ns_frame && !scope.namespace.is_empty()) || namespace::is_qualified(name) {
+            (0, namespace::qualify(&scope.namespace, name))
+        } else {
+            (index, Cow::Borrowed(name))
+        }
+    }
+
+    /// Resolves a variable name as used in the scope with the given stack index.  Without
+    /// namespaces, this is simply the name in that scope.
+    #[cfg(not(feature = "namespaces"))]
+    fn resolve_at<'a>(&self, index: usize, name: &'a str) -> (usize, Cow<'a, str>) {
+        (index, Cow::Borrowed(name))
+    }
+
     /// Retrieves an immutable borrow of the variable of the given name, searching the
     /// the scope stack for the variable starting at the current level and following the
     /// alias chain as needed.
@@ -397,8 +876,8 @@ impl ScopeStack {
     /// TODO: Try using a loop rather than recursion, and see if that's any faster.
     fn var(&self, level: usize, name: &str) -> Option<&Var> {
         let var = self.stack[level].map.get(name);
-        if let Some(Var::Upvar(at)) = var {
-            self.var(*at, name)
+        if let Some(Var::Upvar(at, at_name)) = var {
+            self.var(*at, at_name)
         } else {
             var
         }
@@ -416,13 +895,14 @@ impl ScopeStack {
 
         // NOTE: 11/28/2019.  Without this transmutation, the borrow checker will not allow the
         // recursive call to var_mut, even though it can be seen that all we are using
-        // from the first borrow is the alias level. Under Polonius, a new borrow checker
+        // from the first borrow is the alias level and name. Under Polonius, a new borrow checker
         // currently under development, this pattern is allowed, and the unsafe code can
         // be deleted.
         let var: Option<&mut Var> = unsafe { ::core::mem::transmute(var) };
 
-        if let Some(Var::Upvar(at)) = var {
-            self.var_mut(*at, name)
+        if let Some(Var::Upvar(at, at_name)) = var {
+            let at_name = at_name.clone();
+            self.var_mut(*at, &at_name)
         } else {
             var
         }
@@ -559,6 +1039,82 @@ mod tests {
         assert_eq!(ss.current(), 0);
     }
 
+    #[test]
+    fn test_set_current() {
+        let mut ss = ScopeStack::new();
+        let _ = ss.set("a", Value::from("0"));
+        ss.push();
+        let _ = ss.set("a", Value::from("1"));
+        ss.push();
+        assert_eq!(ss.level(), 2);
+
+        // Make the global scope current, and push a scope as its callee.
+        let saved = ss.set_current(0);
+        assert_eq!(ss.get("a").unwrap().as_str(), "0");
+        ss.push();
+        assert_eq!(ss.current(), 3);
+        assert_eq!(ss.level(), 1);
+        assert_eq!(ss.index_of_level(0), 0);
+
+        // Upvar goes to the caller, not to the scope beneath it in the stack.
+        ss.upvar(0, "a");
+        assert_eq!(ss.get("a").unwrap().as_str(), "0");
+        ss.pop();
+        assert_eq!(ss.current(), 0);
+
+        ss.set_current(saved);
+        assert_eq!(ss.level(), 2);
+        assert_eq!(ss.index_of_level(1), 1);
+    }
+
+    #[cfg(feature = "coroutines")]
+    #[test]
+    fn test_detach_attach() {
+        let mut ss = ScopeStack::new();
+        let _ = ss.set("g", Value::from("global"));
+
+        // A coroutine pushes two scopes from the global scope, linking to the global
+        // variable and to its caller's variable.
+        let saved = ss.set_current(0);
+        let base = ss.attach(DetachedScopes::default());
+        assert_eq!(base, 1);
+        assert_eq!(ss.current(), 0);
+        ss.push();
+        let _ = ss.set("a", Value::from("1"));
+        ss.upvar(0, "g");
+        ss.push();
+        ss.upvar(1, "a");
+        assert_eq!(ss.level(), 2);
+
+        // Suspend it.
+        let detached = ss.detach(base, saved);
+        assert_eq!(ss.current(), 0);
+        assert_eq!(ss.level(), 0);
+
+        // Resume it from a procedure.
+        ss.push();
+        ss.push();
+        let saved = ss.set_current(0);
+        let base = ss.attach(detached);
+        assert_eq!(base, 3);
+        assert_eq!(ss.current(), 4);
+        assert_eq!(ss.level(), 2);
+        assert_eq!(ss.get("a").unwrap().as_str(), "1");
+        assert_eq!(ss.index_of_level(1), 3);
+        assert_eq!(ss.index_of_level(0), 0);
+
+        let _ = ss.set("a", Value::from("2"));
+        ss.pop();
+        assert_eq!(ss.get("a").unwrap().as_str(), "2");
+        assert_eq!(ss.get("g").unwrap().as_str(), "global");
+        ss.pop();
+        assert_eq!(ss.current(), 0);
+
+        let detached = ss.detach(base, saved);
+        assert!(detached.scopes.is_empty());
+        assert_eq!(ss.level(), 2);
+    }
+
     #[test]
     fn test_set_levels() {
         let mut ss = ScopeStack::new();
@@ -606,6 +1162,51 @@ mod tests {
         assert_eq!(ss.get("b").unwrap().as_str(), "2");
     }
 
+    #[test]
+    fn test_upvar_alias() {
+        let mut ss = ScopeStack::new();
+
+        let _ = ss.set("a", Value::from("1"));
+
+        // Link b@1 to a@0
+        ss.push();
+        assert!(ss.upvar_alias(0, "a", "b").is_ok());
+        assert_eq!(ss.get("b").unwrap().as_str(), "1");
+        assert!(ss.get("a").is_err());
+
+        let _ = ss.set("b", Value::from("2"));
+        let _ = ss.set_elem("c", "1", Value::from("3"));
+
+        // Link d@2 to b@1; it should refer to a@0.
+        ss.push();
+        assert!(ss.upvar_alias(1, "b", "d").is_ok());
+        assert!(ss.upvar_alias(1, "c", "e").is_ok());
+        assert_eq!(ss.get("d").unwrap().as_str(), "2");
+        assert_eq!(ss.get_elem("e", "1").unwrap().as_str(), "3");
+        let _ = ss.set("d", Value::from("4"));
+        ss.pop();
+
+        // Can alias within the current scope, but not to itself.
+        assert!(ss.upvar_alias(1, "c", "f").is_ok());
+        assert_eq!(ss.get_elem("f", "1").unwrap().as_str(), "3");
+        let _ = ss.set("h", Value::from("5"));
+        assert_eq!(
+            ss.upvar_alias(1, "c", "h"),
+            molt_err!("variable \"h\" already exists")
+        );
+        assert_eq!(
+            ss.upvar_alias(1, "g", "g"),
+            molt_err!("can't upvar from variable to itself")
+        );
+
+        // Unsetting the alias unsets the target.
+        ss.unset("f");
+        assert!(ss.get_elem("c", "1").is_err());
+        ss.pop();
+
+        assert_eq!(ss.get("a").unwrap().as_str(), "4");
+    }
+
     #[test]
     fn test_unset_levels() {
         let mut ss = ScopeStack::new();
@@ -878,4 +1479,101 @@ mod tests {
         assert!(ss.exists("b"));
         assert!(ss.elem_exists("b", "1"));
     }
+
+    #[cfg(feature = "namespaces")]
+    #[test]
+    fn test_namespace_frames() {
+        let mut ss = ScopeStack::new();
+        ss.set("a", "global".into()).expect("success");
+
+        // Unqualified names in a namespace frame are namespace variables.
+        ss.push_namespace(Rc::from("ns"));
+        ss.set("a", "ns".into()).expect("success");
+        assert_eq!(ss.get("a").unwrap().as_str(), "ns");
+        assert_eq!(ss.get("::a").unwrap().as_str(), "global");
+        assert_eq!(ss.vars_in_scope(), vec![Value::from("a")]);
+        assert!(ss.vars_in_local_scope().is_empty());
+
+        // Procedure scopes have local variables, and inherit the namespace.
+        ss.push();
+        assert_eq!(&**ss.namespace(), "ns");
+        assert!(!ss.exists("a"));
+        ss.set("a", "local".into()).expect("success");
+        assert_eq!(ss.get("::ns::a").unwrap().as_str(), "ns");
+        ss.pop();
+        ss.pop();
+
+        // Namespace variables live in the global scope under their qualified names.
+        assert_eq!(ss.get("ns::a").unwrap().as_str(), "ns");
+        assert_eq!(ss.vars_in_global_scope(), vec![Value::from("a")]);
+        assert_eq!(ss.vars_in_namespace("ns"), vec![Value::from("a")]);
+
+        ss.unset_namespace("ns");
+        assert!(!ss.exists("ns::a"));
+        assert!(ss.exists("a"));
+    }
+
+    #[cfg(feature = "namespaces")]
+    #[test]
+    fn test_upvar_qualified() {
+        let mut ss = ScopeStack::new();
+        ss.set("ns::x", "1".into()).expect("success");
+
+        ss.push();
+        ss.upvar(0, "::ns::x");
+        assert_eq!(ss.get("x").unwrap().as_str(), "1");
+
+        ss.upvar_alias(0, "ns::x", "y").expect("success");
+        ss.set("y", "2".into()).expect("success");
+        ss.pop();
+
+        assert_eq!(ss.get("ns::x").unwrap().as_str(), "2");
+    }
+
+    #[cfg(feature = "traces")]
+    fn trace(id: usize) -> Rc<VarTrace> {
+        use crate::trace::TraceCallback;
+        use crate::types::VarOps;
+        use core::cell::Cell;
+
+        Rc::new(VarTrace {
+            id,
+            ops: VarOps::ALL,
+            index: None,
+            callback: TraceCallback::Command(Value::from("cmd")),
+            active: Cell::new(false),
+        })
+    }
+
+    #[cfg(feature = "traces")]
+    #[test]
+    fn test_traces() {
+        let mut ss = ScopeStack::new();
+        assert!(!ss.has_traces());
+
+        // Traces on an alias go to the variable it refers to.
+        ss.push();
+        ss.upvar(0, "x");
+        ss.add_trace("x", trace(1));
+        ss.add_trace("x", trace(2));
+        ss.pop();
+
+        assert!(ss.has_traces());
+        let ids: Vec<usize> = ss.traces("x").iter().map(|t| t.id).collect();
+        assert_eq!(ids, vec![2, 1]);
+
+        ss.remove_trace("x", |t| t.id == 2);
+        assert_eq!(ss.traces("x").len(), 1);
+
+        assert_eq!(ss.take_traces("x").len(), 1);
+        assert!(ss.traces("x").is_empty());
+        assert!(!ss.has_traces());
+
+        // Traces in popped scopes are discarded.
+        ss.push();
+        ss.add_trace("y", trace(3));
+        assert!(ss.has_traces());
+        ss.pop();
+        assert!(!ss.has_traces());
+    }
 }
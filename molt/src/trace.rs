@@ -0,0 +1,109 @@
+//! Traces
+//!
+//! A variable trace calls a Molt command or a Rust closure when a variable is read,
+//! written, or unset.  Traces are attached to the variable itself, in the scope that
+//! contains it, so an access through an `upvar` alias or a `global` link fires the traces
+//! of the variable it refers to.  See `Interp::trace_var` and the `trace` command.
+//!
+//! An execution trace calls a Molt command or a Rust closure when a command is entered
+//! and when it returns.  Execution traces are kept by the interpreter, keyed by the
+//! command's name in the command table; they follow the command when it is renamed, and
+//! are discarded when it is deleted or redefined.  See `Interp::trace_execution`.
+
+use crate::interp::Interp;
+use crate::types::{ExecOps, Exception, MoltResult, VarOp, VarOps};
+use crate::value::Value;
+use alloc::boxed::Box;
+use alloc::string::String;
+use core::cell::Cell;
+use core::fmt::Debug;
+
+/// The type of a Rust closure called by a variable trace.  The arguments are the variable
+/// name as used in the access, the array index (or the empty string), and the operation.
+pub(crate) type VarTraceFunc = dyn Fn(&mut Interp, &str, &str, VarOp) -> Result<(), Exception>;
+
+/// The type of a Rust closure called by an execution trace.  The arguments are the
+/// command's words, and the command's result when it returns (or `None` when it is
+/// entered).
+pub(crate) type ExecTraceFunc =
+    dyn Fn(&mut Interp, &[Value], Option<&MoltResult>) -> Result<(), Exception>;
+
+/// What a trace calls when it fires.
+pub(crate) enum TraceCallback<F: ?Sized> {
+    /// A command prefix, to which the trace's arguments are appended.
+    Command(Value),
+
+    /// A Rust closure.
+    Closure(Box<F>),
+}
+
+/// A trace on a variable.
+pub(crate) struct VarTrace {
+    /// The trace's ID, for removal by `Interp::untrace_var`.
+    pub id: usize,
+
+    /// The operations that fire the trace.
+    pub ops: VarOps,
+
+    /// The array element being traced, if the trace is on a single element.
+    pub index: Option<String>,
+
+    /// What to call.
+    pub callback: TraceCallback<VarTraceFunc>,
+
+    /// Set while the trace is executing, so that the trace's own accesses to the
+    /// variable don't fire it again.
+    pub active: Cell<bool>,
+}
+
+impl VarTrace {
+    /// Determines whether the trace fires for the operation on the given element, if any.
+    pub fn fires_on(&self, index: Option<&str>, op: VarOp) -> bool {
+        self.ops.contains(op)
+            && !self.active.get()
+            && (self.index.is_none() || self.index.as_deref() == index)
+    }
+}
+
+/// A trace on a command's execution.
+pub(crate) struct ExecTrace {
+    /// The trace's ID, for removal by `Interp::untrace_execution`.
+    pub id: usize,
+
+    /// The operations that fire the trace.
+    pub ops: ExecOps,
+
+    /// What to call.
+    pub callback: TraceCallback<ExecTraceFunc>,
+
+    /// Set while the trace is executing, so that the trace's own calls to the command
+    /// don't fire it again.
+    pub active: Cell<bool>,
+}
+
+impl ExecTrace {
+    /// Determines whether the trace fires on entry to the command, if `leave` is false,
+    /// or on return from it, if `leave` is true.
+    pub fn fires_on(&self, leave: bool) -> bool {
+        let wanted = if leave { self.ops.leave } else { self.ops.enter };
+        wanted && !self.active.get()
+    }
+}
+
+impl Debug for ExecTrace {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match &self.callback {
+            TraceCallback::Command(cmd) => write!(f, "ExecTrace({}, {:?})", cmd.as_str(), self.ops),
+            TraceCallback::Closure(_) => write!(f, "ExecTrace(<closure>, {:?})", self.ops),
+        }
+    }
+}
+
+impl Debug for VarTrace {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match &self.callback {
+            TraceCallback::Command(cmd) => write!(f, "VarTrace({}, {:?})", cmd.as_str(), self.ops),
+            TraceCallback::Closure(_) => write!(f, "VarTrace(<closure>, {:?})", self.ops),
+        }
+    }
+}
@@ -780,31 +780,35 @@ impl Subcommand {
             }
         }
 
-        let mut msg = String::from("unknown or ambiguous subcommand \"");
-        msg.push_str(sub_name);
-        msg.push_str("\": must be ");
+        let names: Vec<&str> = ensemble.iter().map(|x| x.0).collect();
+        Err(unknown_subcommand(sub_name, &names))
+    }
+}
 
-        msg.push_str(ensemble[0].0);
-        let last = ensemble.len() - 1;
+/// Returns the usual error for an ensemble's unknown subcommand, given the names of its
+/// subcommands.
+pub(crate) fn unknown_subcommand(sub_name: &str, names: &[&str]) -> Exception {
+    let mut msg = String::from("unknown or ambiguous subcommand \"");
+    msg.push_str(sub_name);
+    msg.push_str("\": must be ");
 
-        if ensemble.len() > 1 {
-            msg.push_str(", ");
-        }
+    msg.push_str(names[0]);
+    let last = names.len() - 1;
 
-        if ensemble.len() > 2 {
-            let vec: Vec<&str> = ensemble[1..last].iter().map(|x| x.0).collect();
-            msg.push_str(&vec.join(", "));
-        }
+    if names.len() > 1 {
+        msg.push_str(", ");
+    }
 
-        if ensemble.len() > 1 {
-            msg.push_str(", or ");
-            msg.push_str(ensemble[last].0);
-        }
+    if names.len() > 2 {
+        msg.push_str(&names[1..last].join(", "));
+    }
 
-        molt_err!(
-            msg
-        )
+    if names.len() > 1 {
+        msg.push_str(", or ");
+        msg.push_str(names[last]);
     }
+
+    Exception::molt_err(Value::from(msg))
 }
 
 /// In TCL, variable references have two forms.  A string like "_some_var_(_some_index_)" is
@@ -849,6 +853,65 @@ impl VarName {
     }
 }
 
+/// An operation on a variable that can be traced: reading it, writing it, or unsetting it.
+/// See `Interp::trace_var`.
+#[cfg(feature = "traces")]
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum VarOp {
+    Read,
+    Write,
+    Unset,
+}
+
+#[cfg(feature = "traces")]
+impl VarOp {
+    /// Returns the operation's name, as passed to a `trace` command callback.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            VarOp::Read => "read",
+            VarOp::Write => "write",
+            VarOp::Unset => "unset",
+        }
+    }
+}
+
+/// The set of operations on a variable that a trace is interested in.
+#[cfg(feature = "traces")]
+#[derive(Debug, Default, Clone, Copy, Eq, PartialEq)]
+pub struct VarOps {
+    pub read: bool,
+    pub write: bool,
+    pub unset: bool,
+}
+
+#[cfg(feature = "traces")]
+impl VarOps {
+    /// All three operations.
+    pub const ALL: VarOps = VarOps {
+        read: true,
+        write: true,
+        unset: true,
+    };
+
+    /// Determines whether the set contains the operation.
+    pub fn contains(&self, op: VarOp) -> bool {
+        match op {
+            VarOp::Read => self.read,
+            VarOp::Write => self.write,
+            VarOp::Unset => self.unset,
+        }
+    }
+}
+
+/// The set of events in a command's execution that a trace is interested in.
+/// See `Interp::trace_execution`.
+#[cfg(feature = "traces")]
+#[derive(Debug, Default, Clone, Copy, Eq, PartialEq)]
+pub struct ExecOps {
+    pub enter: bool,
+    pub leave: bool,
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -6,6 +6,7 @@ use crate::tokenizer::Tokenizer;
 #[cfg(feature = "string-command")]
 use crate::types::*;
 use alloc::string::String;
+use core::cmp::Ordering;
 
 pub fn is_varname_char(ch: char) -> bool {
     is_alphanumeric(ch) || ch == '_'
@@ -62,6 +63,39 @@ pub fn to_uppercase(s: &str) -> String {
     }
 }
 
+#[cfg(any(feature = "string-command", feature = "regex"))]
+pub fn is_digit(ch: char) -> bool {
+    cfg_if::cfg_if! {
+        if #[cfg(feature = "unicode-alphanum")] {
+            ch.is_numeric()
+        } else {
+            ch.is_ascii_digit()
+        }
+    }
+}
+
+#[cfg(any(feature = "string-command", feature = "regex"))]
+pub fn is_uppercase(ch: char) -> bool {
+    cfg_if::cfg_if! {
+        if #[cfg(feature = "unicode-case")] {
+            ch.is_uppercase()
+        } else {
+            ch.is_ascii_uppercase()
+        }
+    }
+}
+
+#[cfg(any(feature = "string-command", feature = "regex"))]
+pub fn is_lowercase(ch: char) -> bool {
+    cfg_if::cfg_if! {
+        if #[cfg(feature = "unicode-case")] {
+            ch.is_lowercase()
+        } else {
+            ch.is_ascii_lowercase()
+        }
+    }
+}
+
 /// Reads the integer string from the head of the input.  If the function returns `Some`,
 /// the value is the integer string that was read, and the `ptr` points to the following
 /// character. Otherwise the `ptr` will be unchanged.
@@ -219,12 +253,197 @@ pub(crate) fn compare_len(str1: &str, str2: &str, length: Option<MoltInt>) -> Mo
     s1.cmp(s2) as MoltInt
 }
 
+/// Matches the text against a glob-style pattern, as used by `string match`, `switch -glob`,
+/// and the commands that filter names by pattern.  Returns true if the entire text matches.
+/// If `nocase` is true, letters are compared without regard to case.  The pattern may
+/// contain:
+///
+/// * `*`, which matches any sequence of characters, including the empty sequence.
+/// * `?`, which matches any single character.
+/// * `[chars]`, which matches any single character in the set.  The set may include
+///   ranges of the form `a-z`.
+/// * `\x`, which matches the character `x` literally.
+///
+/// Any other character matches itself.  Matching is character-based and allocation-free.
+pub(crate) fn glob_match(pattern: &str, text: &str, nocase: bool) -> bool {
+    let mut p = pattern;
+    let mut t = text;
+
+    // The pattern following the most recent `*`, and the text position it's currently
+    // being tried against.  On a mismatch we backtrack to here, letting the `*` consume
+    // one more character.
+    let mut star: Option<(&str, &str)> = None;
+
+    loop {
+        if let Some(rest) = p.strip_prefix('*') {
+            // FIRST, a run of stars is equivalent to a single star; and a star at the end
+            // of the pattern matches whatever text remains.
+            p = rest.trim_start_matches('*');
+            if p.is_empty() {
+                return true;
+            }
+            star = Some((p, t));
+            continue;
+        }
+
+        // NEXT, try to match a single character.
+        if let Some(ch) = t.chars().next() {
+            if let Some(rest) = glob_match_char(p, ch, nocase) {
+                p = rest;
+                t = &t[ch.len_utf8()..];
+                continue;
+            }
+        } else if p.is_empty() {
+            return true;
+        }
+
+        // NEXT, we have a mismatch.  Backtrack to the last star, if any, and let it
+        // consume one more character.
+        match star {
+            Some((star_p, star_t)) if !star_t.is_empty() => {
+                let skip = star_t.chars().next().unwrap().len_utf8();
+                p = star_p;
+                t = &star_t[skip..];
+                star = Some((p, t));
+            }
+            _ => return false,
+        }
+    }
+}
+
+/// Matches a single character against the head of a glob pattern, which must not begin
+/// with `*`.  Returns the remainder of the pattern on success.
+fn glob_match_char(pattern: &str, ch: char, nocase: bool) -> Option<&str> {
+    let fold = |c: char| if nocase { fold_case(c) } else { c };
+    let ch = fold(ch);
+    let mut chars = pattern.chars();
+
+    match chars.next()? {
+        '?' => Some(chars.as_str()),
+        '[' => {
+            let mut matched = false;
+
+            loop {
+                // An unterminated set never matches.
+                let first = fold(chars.next()?);
+
+                if first == ']' {
+                    break;
+                }
+
+                let rest = chars.as_str();
+                if let (Some('-'), Some(last)) = (rest.chars().next(), rest.chars().nth(1)) {
+                    let last = fold(last);
+                    if last != ']' {
+                        chars.next();
+                        chars.next();
+                        let (lo, hi) = if first <= last { (first, last) } else { (last, first) };
+                        matched |= lo <= ch && ch <= hi;
+                        continue;
+                    }
+                }
+
+                matched |= first == ch;
+            }
+
+            if matched {
+                Some(chars.as_str())
+            } else {
+                None
+            }
+        }
+        '\\' => {
+            let rest = chars.as_str();
+            match chars.next() {
+                Some(escaped) if fold(escaped) == ch => Some(chars.as_str()),
+                Some(_) => None,
+                None if ch == '\\' => Some(rest),
+                None => None,
+            }
+        }
+        pch if fold(pch) == ch => Some(chars.as_str()),
+        _ => None,
+    }
+}
+
+/// Folds the character to lower case for case-insensitive comparisons.
+pub(crate) fn fold_case(ch: char) -> char {
+    cfg_if::cfg_if! {
+        if #[cfg(feature = "unicode-case")] {
+            ch.to_lowercase().next().unwrap_or(ch)
+        } else {
+            ch.to_ascii_lowercase()
+        }
+    }
+}
+
+/// Compares two strings in "dictionary" order, as for `lsort -dictionary`: case is
+/// ignored except as a tie-breaker, and embedded runs of digits are compared as
+/// integers.
+pub(crate) fn dictionary_compare(str1: &str, str2: &str) -> Ordering {
+    let mut left = str1;
+    let mut right = str2;
+    let mut tie_breaker = Ordering::Equal;
+
+    loop {
+        let mut lchars = left.chars();
+        let mut rchars = right.chars();
+
+        let (lch, rch) = match (lchars.next(), rchars.next()) {
+            (None, None) => return tie_breaker,
+            (None, Some(_)) => return Ordering::Less,
+            (Some(_), None) => return Ordering::Greater,
+            (Some(lch), Some(rch)) => (lch, rch),
+        };
+
+        // FIRST, compare runs of digits numerically.  Leading zeros only break ties.
+        if lch.is_ascii_digit() && rch.is_ascii_digit() {
+            let lend = left.find(|ch: char| !ch.is_ascii_digit()).unwrap_or(left.len());
+            let rend = right.find(|ch: char| !ch.is_ascii_digit()).unwrap_or(right.len());
+            let ldigits = left[..lend].trim_start_matches('0');
+            let rdigits = right[..rend].trim_start_matches('0');
+
+            let order = ldigits.len().cmp(&rdigits.len()).then_with(|| ldigits.cmp(rdigits));
+
+            if order != Ordering::Equal {
+                return order;
+            }
+
+            if tie_breaker == Ordering::Equal {
+                tie_breaker = lend.cmp(&rend);
+            }
+
+            left = &left[lend..];
+            right = &right[rend..];
+            continue;
+        }
+
+        // NEXT, compare the characters without regard to case.  Upper case sorts first
+        // if the strings are otherwise equal.
+        if lch != rch {
+            let order = fold_case(lch).cmp(&fold_case(rch));
+
+            if order != Ordering::Equal {
+                return order;
+            }
+
+            if tie_breaker == Ordering::Equal {
+                tie_breaker = lch.cmp(&rch);
+            }
+        }
+
+        left = lchars.as_str();
+        right = rchars.as_str();
+    }
+}
+
 // From carlomilanesi, rust forums
 // https://users.rust-lang.org/t/how-to-get-a-substring-of-a-string/1351/11
 use core::ops::{Bound, RangeBounds};
 
 pub(crate) trait StringUtils {
     fn substring(&self, start: usize, len: usize) -> &str;
+    #[allow(dead_code)]
     fn slice(&self, range: impl RangeBounds<usize>) -> &str;
 }
 
@@ -309,6 +528,81 @@ mod tests {
         assert_eq!(Some('a'), p.peek());
     }
 
+    fn glob_match_cs(pattern: &str, text: &str) -> bool {
+        glob_match(pattern, text, false)
+    }
+
+    #[test]
+    fn test_util_glob_match() {
+        // Literals
+        assert!(glob_match_cs("", ""));
+        assert!(glob_match_cs("abc", "abc"));
+        assert!(!glob_match_cs("abc", "abcd"));
+        assert!(!glob_match_cs("abcd", "abc"));
+        assert!(!glob_match_cs("abc", "ABC"));
+
+        // Stars
+        assert!(glob_match_cs("*", ""));
+        assert!(glob_match_cs("*", "abc"));
+        assert!(glob_match_cs("a*", "abc"));
+        assert!(glob_match_cs("*c", "abc"));
+        assert!(glob_match_cs("a*c", "ac"));
+        assert!(glob_match_cs("a**c", "abbbc"));
+        assert!(glob_match_cs("*b*b*", "abcbd"));
+        assert!(!glob_match_cs("a*d", "abc"));
+        assert!(!glob_match_cs("*x*", "abc"));
+
+        // Question marks
+        assert!(glob_match_cs("?", "a"));
+        assert!(glob_match_cs("a?c", "abc"));
+        assert!(!glob_match_cs("?", ""));
+        assert!(!glob_match_cs("a?", "abc"));
+        assert!(glob_match_cs("?", "é"));
+
+        // Character sets
+        assert!(glob_match_cs("[abc]", "b"));
+        assert!(!glob_match_cs("[abc]", "d"));
+        assert!(glob_match_cs("[a-c]x", "bx"));
+        assert!(glob_match_cs("[c-a]", "b"));
+        assert!(glob_match_cs("[a-]", "-"));
+        assert!(glob_match_cs("x[0-9][0-9]", "x42"));
+        assert!(!glob_match_cs("x[0-9]", "xa"));
+        assert!(!glob_match_cs("[abc", "a"));
+
+        // Backslash escapes
+        assert!(glob_match_cs("a\\*", "a*"));
+        assert!(!glob_match_cs("a\\*", "ab"));
+        assert!(glob_match_cs("\\?\\[", "?["));
+        assert!(glob_match_cs("a\\", "a\\"));
+    }
+
+    #[test]
+    fn test_util_glob_match_nocase() {
+        assert!(glob_match("ABC", "abc", true));
+        assert!(glob_match("a*C", "AbC", true));
+        assert!(glob_match("[A-C]x", "bX", true));
+        assert!(glob_match("[a-c]", "B", true));
+        assert!(glob_match("\\A", "a", true));
+        assert!(!glob_match("abc", "abd", true));
+    }
+
+    #[test]
+    fn test_util_dictionary_compare() {
+        use core::cmp::Ordering::*;
+
+        assert_eq!(dictionary_compare("", ""), Equal);
+        assert_eq!(dictionary_compare("a", ""), Greater);
+        assert_eq!(dictionary_compare("abc", "abd"), Less);
+        assert_eq!(dictionary_compare("abc", "ABD"), Less);
+        assert_eq!(dictionary_compare("ABC", "abc"), Less);
+        assert_eq!(dictionary_compare("abc", "Abc"), Greater);
+        assert_eq!(dictionary_compare("x9", "x10"), Less);
+        assert_eq!(dictionary_compare("x10y", "x9z"), Greater);
+        assert_eq!(dictionary_compare("x01", "x1"), Greater);
+        assert_eq!(dictionary_compare("x1a", "x01b"), Less);
+        assert_eq!(dictionary_compare("a2b", "a2b"), Equal);
+    }
+
     #[test]
     #[allow(clippy::cognitive_complexity)]
     fn test_util_read_float() {
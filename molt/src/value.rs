@@ -172,8 +172,11 @@ use crate::dict::{dict_to_string, list_to_dict};
 use crate::expr::Datum;
 use crate::list::get_list;
 use crate::list::list_to_string;
+use crate::interp::Procedure;
 use crate::parser;
 use crate::parser::Script;
+use crate::parser::Subst;
+use crate::parser::SubstFlags;
 use crate::types::Exception;
 #[cfg(feature = "dict")]
 use crate::types::MoltDict;
@@ -746,6 +749,105 @@ impl Value {
         }
     }
 
+    /// Tries to return the `Value` as an index into a string or list of length `len`,
+    /// parsing the value's string representation if necessary.
+    ///
+    /// # Index Syntax
+    ///
+    /// An index is an integer, `end`, or either of these followed by `+` or `-` and
+    /// an integer offset; `end` is `len - 1`.  The returned index may be negative or
+    /// greater than `len - 1`; it's up to the caller to decide what that means.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use remolt::types::Value;
+    /// use remolt::types::MoltInt;
+    /// use remolt::types::Exception;
+    /// # fn dummy() -> Result<MoltInt,Exception> {
+    ///
+    /// let value = Value::from(3);
+    /// assert_eq!(value.as_index(10)?, 3);
+    ///
+    /// let value = Value::from("end-1");
+    /// assert_eq!(value.as_index(10)?, 8);
+    /// # Ok(1)
+    /// # }
+    /// ```
+    pub fn as_index(&self, len: usize) -> Result<MoltInt, Exception> {
+        // FIRST, if we have an integer then just return it.
+        if let DataRep::Int(int) = *self.inner.data_rep.borrow() {
+            return Ok(int);
+        }
+
+        // NEXT, if it's a plain integer, cache it; otherwise, parse the index.
+        let str = self.as_str();
+
+        if let Ok(int) = Value::get_int(str) {
+            *self.inner.data_rep.borrow_mut() = DataRep::Int(int);
+            return Ok(int);
+        }
+
+        Value::get_index(str, len)
+    }
+
+    /// Converts a string argument into an index into a string or list of length `len`,
+    /// returning an error on failure.  See [`as_index`](#method.as_index) for the
+    /// index syntax.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use remolt::types::*;
+    /// # fn dummy() -> Result<MoltInt,Exception> {
+    /// assert_eq!(Value::get_index("end", 5)?, 4);
+    /// assert_eq!(Value::get_index("1+2", 5)?, 3);
+    /// # Ok(1)
+    /// # }
+    /// ```
+    pub fn get_index(arg: &str, len: usize) -> Result<MoltInt, Exception> {
+        let trimmed = arg.trim_matches(util::is_whitespace);
+
+        // FIRST, split the index into its base and offset.  The operator can't be the
+        // first character, as that's the integer's sign.
+        let (base, offset) = if let Some(rest) = trimmed.strip_prefix("end") {
+            (None, rest)
+        } else {
+            match trimmed.char_indices().skip(1).find(|(_, ch)| *ch == '+' || *ch == '-') {
+                Some((i, _)) => (Some(&trimmed[..i]), &trimmed[i..]),
+                None => (Some(trimmed), ""),
+            }
+        };
+
+        // NEXT, get the base and offset values.
+        let base = match base {
+            None => len as MoltInt - 1,
+            Some(base) => match Value::get_int(base) {
+                Ok(int) => int,
+                Err(_) => return index_error(arg),
+            },
+        };
+
+        let offset = if offset.is_empty() {
+            0
+        } else if offset.starts_with('+') || offset.starts_with('-') {
+            // Don't allow a second sign, or white space, after the operator.
+            match offset[1..].chars().next() {
+                Some(ch) if ch.is_ascii_digit() => (),
+                _ => return index_error(arg),
+            }
+            match Value::get_int(offset) {
+                Ok(int) => int,
+                Err(_) => return index_error(arg),
+            }
+        } else {
+            return index_error(arg);
+        };
+
+        // An index that overflows is far beyond either end of any string or list.
+        Ok(base.saturating_add(offset))
+    }
+
     /// Tries to return the `Value` as a `MoltFloat`, parsing the
     /// value's string representation if necessary.
     ///
@@ -924,6 +1026,45 @@ impl Value {
         Ok(script)
     }
 
+    /// Returns the `Value` as an `Rc<Procedure>`, parsing the value's string representation
+    /// as an `apply` lambda expression if necessary.
+    ///
+    /// For internal use only.  The parsed lambda persists, so calling the same lambda
+    /// repeatedly doesn't re-parse its parameter list or body.
+    pub(crate) fn as_lambda(&self) -> Result<Rc<Procedure>, Exception> {
+        // FIRST, if we have the desired type, return it.
+        if let DataRep::Lambda(lambda) = &*self.inner.data_rep.borrow() {
+            return Ok(lambda.clone());
+        }
+
+        // NEXT, try to parse the string_rep as a lambda.
+        let lambda = Rc::new(Procedure::from_lambda(self)?);
+        *self.inner.data_rep.borrow_mut() = DataRep::Lambda(lambda.clone());
+
+        Ok(lambda)
+    }
+
+    /// Returns the `Value` as an `Rc<Subst>`, parsing the value's string representation
+    /// as a `subst` template with the given substitutions if necessary.
+    ///
+    /// For internal use only.  As with `as_script`, the parsed template persists, so
+    /// rendering the same template repeatedly doesn't re-parse it.
+    pub(crate) fn as_subst(&self, flags: SubstFlags) -> Result<Rc<Subst>, Exception> {
+        // FIRST, if we have the desired type, parsed with the same flags, return it.
+        if let DataRep::Subst(subst) = &*self.inner.data_rep.borrow() {
+            if subst.flags() == flags {
+                return Ok(subst.clone());
+            }
+        }
+
+        // NEXT, try to parse the string_rep as a template.
+        let str = self.as_str();
+        let subst = Rc::new(parser::parse_subst(str, flags)?);
+        *self.inner.data_rep.borrow_mut() = DataRep::Subst(subst.clone());
+
+        Ok(subst)
+    }
+
     /// Returns the `Value` as an `Rc<VarName>`, parsing the
     /// value's string representation if necessary.  This type is usually hidden by the
     /// `Interp`'s `var` and `set_var` methods, which use it implicitly; however it is
@@ -1132,7 +1273,9 @@ impl Value {
 /// string rep on demand.
 trait MoltAny: Any + Display + Debug {
     fn as_any(&self) -> &dyn Any;
+    #[allow(dead_code)]
     fn as_any_mut(&mut self) -> &mut dyn Any;
+    #[allow(dead_code)]
     fn into_any(self: Box<Self>) -> Box<dyn Any>;
 }
 
@@ -1180,6 +1323,12 @@ enum DataRep {
     /// A Script
     Script(Rc<Script>),
 
+    /// A `subst` template
+    Subst(Rc<Subst>),
+
+    /// An `apply` lambda expression
+    Lambda(Rc<Procedure>),
+
     /// A Variable Name
     VarName(Rc<VarName>),
 
@@ -1201,6 +1350,8 @@ impl Display for DataRep {
             DataRep::Flt(flt) => Value::fmt_float(f, *flt),
             DataRep::List(list) => write!(f, "{}", list_to_string(list)),
             DataRep::Script(_) => write!(f, "<script>"),
+            DataRep::Subst(_) => write!(f, "<subst>"),
+            DataRep::Lambda(_) => write!(f, "<lambda>"),
             DataRep::VarName(_) => write!(f, "<var-name>"),
             DataRep::Other(other) => write!(f, "{}", other),
             DataRep::None => write!(f, ""),
@@ -1208,6 +1359,13 @@ impl Display for DataRep {
     }
 }
 
+fn index_error(arg: &str) -> Result<MoltInt, Exception> {
+    molt_err!(
+        "bad index \"{}\": must be integer?[+-]integer? or end?[+-]integer?",
+        arg
+    )
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -1419,6 +1577,37 @@ mod tests {
         );
     }
 
+    #[test]
+    fn get_index() {
+        assert_eq!(Value::get_index("1", 5), Ok(1));
+        assert_eq!(Value::get_index("-1", 5), Ok(-1));
+        assert_eq!(Value::get_index("end", 5), Ok(4));
+        assert_eq!(Value::get_index("end-1", 5), Ok(3));
+        assert_eq!(Value::get_index("end+1", 5), Ok(5));
+        assert_eq!(Value::get_index("end", 0), Ok(-1));
+        assert_eq!(Value::get_index("1+2", 5), Ok(3));
+        assert_eq!(Value::get_index("-1+2", 5), Ok(1));
+        assert_eq!(Value::get_index("4-6", 5), Ok(-2));
+        assert_eq!(Value::get_index(" end-1 ", 5), Ok(3));
+
+        let err = molt_err!("bad index \"a\": must be integer?[+-]integer? or end?[+-]integer?");
+        assert_eq!(Value::get_index("a", 5), err);
+        assert!(Value::get_index("", 5).is_err());
+        assert!(Value::get_index("end-", 5).is_err());
+        assert!(Value::get_index("end--1", 5).is_err());
+        assert!(Value::get_index("endx", 5).is_err());
+        assert!(Value::get_index("1+x", 5).is_err());
+
+        let index = format!("end+{}", MoltInt::MAX);
+        assert_eq!(Value::get_index(&index, 5), Ok(MoltInt::MAX));
+        let index = format!("-2-{}", MoltInt::MAX);
+        assert_eq!(Value::get_index(&index, 5), Ok(MoltInt::MIN));
+
+        let value = Value::from("end-2");
+        assert_eq!(value.as_index(5), Ok(2));
+        assert_eq!(value.as_index(10), Ok(7));
+    }
+
     #[test]
     fn from_as_float() {
         let val = Value::from(12.5);
@@ -1505,6 +1694,44 @@ mod tests {
         assert_eq!(val.as_script(), molt_err!("missing close-brace"));
     }
 
+    #[test]
+    fn as_subst() {
+        let val = Value::from("a$b");
+        let subst = val.as_subst(SubstFlags::ALL).unwrap();
+        assert_eq!(subst.words().len(), 2);
+
+        // The parsed template is cached.
+        assert!(Rc::ptr_eq(&subst, &val.as_subst(SubstFlags::ALL).unwrap()));
+
+        // But not reused for different flags.
+        let flags = SubstFlags {
+            variables: false,
+            ..SubstFlags::ALL
+        };
+        assert_eq!(val.as_subst(flags).unwrap().words().len(), 1);
+
+        let val = Value::from("a[b");
+        assert_eq!(val.as_subst(SubstFlags::ALL), molt_err!("missing close-bracket"));
+    }
+
+    #[test]
+    fn as_lambda() {
+        let val = Value::from("{a {b 1}} {list $a $b}");
+        let lambda = val.as_lambda().unwrap();
+
+        // The parsed lambda is cached.
+        assert!(Rc::ptr_eq(&lambda, &val.as_lambda().unwrap()));
+
+        let val = Value::from("{a b}");
+        assert_eq!(
+            val.as_lambda().map(|_| ()),
+            molt_err!("can't interpret \"{a b}\" as a lambda expression")
+        );
+
+        let val = Value::from("{{}} {}");
+        assert_eq!(val.as_lambda().map(|_| ()), molt_err!("argument with no name"));
+    }
+
     #[test]
     fn as_var_name() {
         let val = Value::from("a");
@@ -3,33 +3,61 @@
 # If I add the "glob" command, I'll use that to pick up the tests.
 
 source append.tcl
+source apply.tcl
 source array.tcl
 source assert_eq.tcl
 source break.tcl
 source catch.tcl
+source concat.tcl
+source coroutine.tcl
 source continue.tcl
 source dict.tcl
 source error.tcl
+source eval.tcl
 source exit.tcl
 source expr.tcl
 source for.tcl
 source foreach.tcl
+source format.tcl
 source if.tcl
 source info.tcl
 source incr.tcl
 source interp.tcl
 source join.tcl
 source lappend.tcl
+source lassign.tcl
 source lindex.tcl
+source linsert.tcl
 source list.tcl
 source llength.tcl
+source lmap.tcl
+source lrange.tcl
+source lrepeat.tcl
+source lreplace.tcl
+source lreverse.tcl
+source lsearch.tcl
+source lset.tcl
+source lsort.tcl
+source namespace.tcl
 source parser.tcl
 source proc.tcl
+source regexp.tcl
+source regsub.tcl
 source rename.tcl
 source return.tcl
+source scan.tcl
 source set.tcl
+source split.tcl
 source string.tcl
+source subst.tcl
+source switch.tcl
+source tailcall.tcl
 source test.tcl
 source throw.tcl
+source trace.tcl
+source try.tcl
 source unset.tcl
+source uplevel.tcl
+source upvar.tcl
+source variable.tcl
 source while.tcl
@@ -0,0 +1,69 @@
+# Test Script: apply command
+
+test apply-1.1 {apply errors} {
+    apply
+} -error {wrong # args: should be "apply lambdaExpr ?arg ...?"}
+
+test apply-1.2 {bad lambda} {
+    apply {a b c d}
+} -error {can't interpret "a b c d" as a lambda expression}
+
+test apply-1.3 {bad parameter list} {
+    apply {{{a 1 2}} {}}
+} -error {too many fields in argument specifier "a 1 2"}
+
+test apply-1.4 {wrong # args} {
+    apply {{a {b 1} args} {}}
+} -error {wrong # args: should be "apply lambdaExpr a ?b? ?arg ...?"}
+
+test apply-1.5 {too many args} {
+    apply {{a} {}} 1 2
+} -error {wrong # args: should be "apply lambdaExpr a"}
+
+test apply-2.1 {simple lambda} {
+    apply {{a b} {expr {$a + $b}}} 1 2
+} -ok {3}
+
+test apply-2.2 {defaults and args} {
+    apply {{a {b 2} args} {list $a $b $args}} 1
+} -ok {1 2 {}}
+
+test apply-2.3 {args with spaces} {
+    apply {{args} {set args}} "a b" c
+} -ok {{a b} c}
+
+test apply-2.4 {no parameters} {
+    apply {{} {return hello}}
+} -ok {hello}
+
+test apply-2.5 {the lambda has its own scope} {
+    set x outer
+    apply {{} {set x inner}}
+    set x
+} -ok {outer}
+
+test apply-2.6 {namespace element is accepted} {
+    apply {{a} {expr {$a * 2}} ::} 21
+} -ok {42}
+
+test apply-3.1 {callback in a loop} {
+    set square {{x} {expr {$x * $x}}}
+    set result {}
+    foreach n {1 2 3} {
+        lappend result [apply $square $n]
+    }
+    set result
+} -ok {1 4 9}
+
+test apply-3.2 {callback as a command prefix} {
+    set cmp [list apply {{a b} {expr {[string length $a] - [string length $b]}}}]
+    lsort -command $cmp {ccc a bb}
+} -ok {a bb ccc}
+
+test apply-3.3 {errors propagate} {
+    apply {{} {error oops}}
+} -error {oops}
+
+test apply-3.4 {break outside of a loop} {
+    apply {{} {break}}
+} -error {invoked "break" outside of a loop}
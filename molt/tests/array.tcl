@@ -40,7 +40,7 @@ proc match_lists {expected got} {
 
 test array-1.1 {array names, no var} {
     array names
-} -error {wrong # args: should be "array names arrayName"}
+} -error {wrong # args: should be "array names arrayName ?mode? ?pattern?"}
 
 test array-1.2 {array names, unknown var} {
     array names unknown_variable
@@ -97,7 +97,7 @@ test array-3.4 {array exists, array var} {
 
 test array-4.1 {array get, no var} {
     array get
-} -error {wrong # args: should be "array get arrayName"}
+} -error {wrong # args: should be "array get arrayName ?pattern?"}
 
 test array-4.2 {array get, unknown var} {
     array get unknown_variable
@@ -175,6 +175,36 @@ test array-6.5 {array set on index} {
     list $result1 $result2
 } -ok {{can't set "a(1)": variable isn't array} {can't set "a": variable is array}}
 
+test array-7.1 {array names, glob pattern} {
+    array set a {apple 1 apricot 2 banana 3}
+    match_lists {apple apricot} [array names a ap*]
+} -ok {1}
+
+test array-7.2 {array names, -glob mode} {
+    array set a {apple 1 apricot 2 banana 3}
+    array names a -glob {*an*}
+} -ok {banana}
+
+test array-7.3 {array names, -exact mode} {
+    array set a {a* 1 ab 2}
+    array names a -exact a*
+} -ok {a*}
+
+test array-7.4 {array names, bad mode} {
+    array set a {a 1}
+    array names a -bogus a
+} -error {bad option "-bogus": must be -exact, -glob, or -regexp}
+
+test array-7.5 {array get, glob pattern} {
+    array set a {apple 1 apricot 2 banana 3}
+    match_dicts {apple 1 apricot 2} [array get a ap*]
+} -ok {1}
+
+test array-7.6 {array names, -regexp mode} {
+    array set a {apple 1 apricot 2 banana 3}
+    match_lists {apple banana} [array names a -regexp {^b|le$}]
+} -ok {1}
+
 #----------------------------------------------------------------------------
 # Cleanup
 
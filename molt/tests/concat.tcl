@@ -0,0 +1,25 @@
+# Test Script: concat command.
+
+test concat-1.1 {no arguments} {
+    concat
+} -ok {}
+
+test concat-1.2 {one argument} {
+    concat a
+} -ok {a}
+
+test concat-1.3 {lists are concatenated} {
+    concat {a b} {c d}
+} -ok {a b c d}
+
+test concat-1.4 {whitespace is trimmed} {
+    concat "  a b  " "\tc\n"
+} -ok {a b c}
+
+test concat-1.5 {empty arguments are ignored} {
+    concat a {} "  " b
+} -ok {a b}
+
+test concat-1.6 {nested lists are retained} {
+    concat {a {b c}} {{d e} f}
+} -ok {a {b c} {d e} f}
@@ -0,0 +1,394 @@
+# Test Script: coroutine, yield, and yieldto commands
+
+test coroutine-1.1 {coroutine errors} {
+    coroutine c
+} -error {wrong # args: should be "coroutine name cmd ?arg ...?"}
+
+test coroutine-1.2 {coroutine with an existing name} {
+    coroutine set list a
+} -error {command "set" already exists}
+
+test coroutine-1.3 {coroutine of an unknown command} -body {
+    list [catch {coroutine c nosuch} result] $result [info commands c]
+} -ok {1 {invalid command name "nosuch"} {}}
+
+test coroutine-1.4 {yield outside a coroutine} {
+    yield
+} -error {yield can only be called in a coroutine}
+
+test coroutine-1.5 {yieldto outside a coroutine} {
+    yieldto list
+} -error {yieldto can only be called in a coroutine}
+
+test coroutine-1.6 {yield errors} -setup {
+    proc myproc {} {yield a b}
+} -body {
+    coroutine c myproc
+} -cleanup {
+    rename myproc ""
+} -error {wrong # args: should be "yield ?returnValue?"}
+
+test coroutine-1.7 {too many arguments to resume} -setup {
+    proc myproc {} {yield; yield}
+    coroutine c myproc
+} -body {
+    c a b
+} -cleanup {
+    rename c ""
+    rename myproc ""
+} -error {wrong # args: should be "c ?arg?"}
+
+test coroutine-2.1 {yield and resume} -setup {
+    proc gen {n} {
+        for {set i 0} {$i < $n} {incr i} {
+            yield $i
+        }
+        return done
+    }
+} -body {
+    list [coroutine c gen 3] [c] [c] [c] [info commands c]
+} -cleanup {
+    rename gen ""
+} -ok {0 1 2 done {}}
+
+test coroutine-2.2 {yield returns the resume value} -setup {
+    proc summer {} {
+        set sum 0
+        while 1 {
+            incr sum [yield $sum]
+        }
+    }
+} -body {
+    list [coroutine acc summer] [acc 5] [acc 2] [acc 0]
+} -cleanup {
+    rename acc ""
+    rename summer ""
+} -ok {0 5 7 7}
+
+test coroutine-2.3 {command that doesn't yield} -body {
+    list [coroutine c list a b] [info commands c]
+} -ok {{a b} {}}
+
+test coroutine-2.4 {coroutine keeps its local variables} -setup {
+    proc myproc {a} {
+        set b [yield $a]
+        set c [yield $b]
+        list $a $b $c
+    }
+} -body {
+    list [coroutine c myproc 1] [c 2] [c 3]
+} -cleanup {
+    rename myproc ""
+} -ok {1 2 {1 2 3}}
+
+test coroutine-2.5 {coroutine runs at the global level} -setup {
+    proc myproc {} {
+        yield [info level]
+    }
+} -body {
+    set a [coroutine c1 myproc]
+    coroutine c2 eval {set x [yield]}
+    c2 5
+    list $a $::x [info exists x]
+} -cleanup {
+    rename c1 ""
+    rename myproc ""
+    unset ::x
+} -ok {1 5 0}
+
+test coroutine-2.6 {errors end the coroutine} -setup {
+    proc myproc {} {
+        yield
+        error oops
+    }
+    coroutine c myproc
+} -body {
+    list [catch {c} result] $result [info commands c]
+} -cleanup {
+    rename myproc ""
+} -ok {1 oops {}}
+
+test coroutine-2.7 {coroutine can't resume itself} -setup {
+    proc myproc {} {c}
+} -body {
+    coroutine c myproc
+} -cleanup {
+    rename myproc ""
+} -error {coroutine "c" is already running}
+
+test coroutine-2.8 {nested coroutines} -setup {
+    proc inner {} {
+        yield inner1
+        yield inner2
+    }
+    proc outer {} {
+        yield [coroutine i inner]
+        yield [i]
+    }
+} -body {
+    list [coroutine o outer] [o] [info commands i]
+} -cleanup {
+    rename i ""
+    rename o ""
+    rename inner ""
+    rename outer ""
+} -ok {inner1 inner2 i}
+
+test coroutine-2.9 {deleting a suspended coroutine} -setup {
+    proc myproc {} {yield}
+} -body {
+    coroutine c myproc
+    rename c ""
+    info commands c
+} -cleanup {
+    rename myproc ""
+} -ok {}
+
+test coroutine-3.1 {yield within control structures} -setup {
+    proc myproc {} {
+        if {1} {
+            foreach x {a b} {
+                lappend result [yield $x]
+            }
+        }
+        eval {lappend result [yield c]}
+        catch {lappend result [yield d]}
+        lappend result [apply {{y} {yield $y}} e]
+        return $result
+    }
+} -body {
+    list [coroutine c myproc] [c 1] [c 2] [c 3] [c 4] [c 5]
+} -cleanup {
+    rename myproc ""
+} -ok {a b c d e {1 2 3 4 5}}
+
+test coroutine-3.2 {break and continue within loops} -setup {
+    proc myproc {} {
+        for {set i 0} {$i < 10} {incr i} {
+            if {$i == 1} continue
+            if {$i == 3} break
+            yield $i
+        }
+        set i 0
+        while 1 {
+            if {[incr i] > 2} break
+            yield w$i
+        }
+        return end
+    }
+} -body {
+    list [coroutine c myproc] [c] [c] [c] [c]
+} -cleanup {
+    rename myproc ""
+} -ok {0 2 w1 w2 end}
+
+test coroutine-3.3 {catch within a coroutine} -setup {
+    proc myproc {} {
+        set code [catch {
+            yield
+            error oops
+        } result]
+        list $code $result
+    }
+} -body {
+    coroutine c myproc
+    c
+} -cleanup {
+    rename myproc ""
+} -ok {1 oops}
+
+test coroutine-3.4 {yield within a command substitution} -setup {
+    proc myproc {} {
+        list a [yield 1] {*}[yield 2] b
+    }
+} -body {
+    list [coroutine c myproc] [c x] [c {y z}]
+} -cleanup {
+    rename myproc ""
+} -ok {1 2 {a x y z b}}
+
+test coroutine-3.5 {yield within a native command} -setup {
+    proc myproc {} {
+        lsort -command {apply {{a b} {yield; string compare $a $b}}} {b a}
+    }
+} -body {
+    coroutine c myproc
+} -cleanup {
+    rename myproc ""
+} -error {cannot yield: C stack busy}
+
+test coroutine-3.6 {tailcall within a coroutine} -setup {
+    proc count {n} {
+        if {$n == 0} {
+            yield done
+            return finished
+        }
+        tailcall count [expr {$n - 1}]
+    }
+} -body {
+    list [coroutine c count 2000] [c]
+} -cleanup {
+    rename count ""
+} -ok {done finished}
+
+test coroutine-3.7 {yield within switch} -setup {
+    proc myproc {} {
+        foreach x {a b c} {
+            switch -glob -- $x {
+                a { lappend result [yield A] }
+                b -
+                c { lappend result [yield BC] }
+            }
+        }
+        return $result
+    }
+} -body {
+    list [coroutine c myproc] [c 1] [c 2] [c 3]
+} -cleanup {
+    rename myproc ""
+} -ok {A BC BC {1 2 3}}
+
+test coroutine-3.8 {yield within try body, handlers, and finally} -setup {
+    proc myproc {} {
+        try {
+            lappend ::log [yield body]
+            error oops
+        } on ok {} {
+            lappend ::log ok
+        } trap {} {msg opts} {
+            lappend ::log $msg [dict get $opts -code] [yield handler]
+        } finally {
+            lappend ::log [yield finally]
+        }
+    }
+} -body {
+    set ::log {}
+    list [coroutine c myproc] [c 1] [c 2] [c 3] $::log
+} -cleanup {
+    rename myproc ""
+    unset ::log
+} -ok {body handler finally {1 oops 1 2} {1 oops 1 2 3}}
+
+test coroutine-3.9 {try results within a coroutine} -setup {
+    proc myproc {} {
+        set a [try {yield; error oops} on error {msg} {set msg caught}]
+        set b [catch {try {yield; error first} finally {yield; error second}} msg]
+        set c [try {yield; set x body} finally {yield; set y finally}]
+        list $a $b $msg $c
+    }
+} -body {
+    coroutine c myproc
+    c; c; c; c
+    c
+} -cleanup {
+    rename myproc ""
+} -ok {caught 1 second body}
+
+test coroutine-3.10 {yield within dict for, uplevel, and expr} -setup {
+    proc myproc {} {
+        dict for {k v} {a 1 b 2} {lappend result [yield $k]}
+        lappend result [uplevel 1 {yield x}]
+        lappend result [expr {[yield y] + 1}]
+    }
+} -body {
+    list [coroutine c myproc] [c 1] [c 2] [c 3] [c 4]
+} -cleanup {
+    rename myproc ""
+} -ok {a b x y {1 2 3 5}}
+
+test coroutine-3.11 {yield within if and while conditions} -setup {
+    proc myproc {} {
+        set result {}
+        while {[set x [yield next]] ne ""} {
+            if {[yield ok] eq "quit"} {
+                break
+            }
+            lappend result $x
+        }
+        return $result
+    }
+} -body {
+    list [coroutine c myproc] [c a] [c go] [c b] [c quit]
+} -cleanup {
+    rename myproc ""
+} -ok {next ok next ok a}
+
+test coroutine-3.12 {yield within part of a word, and subst} -setup {
+    proc myproc {} {
+        set v 1
+        set a "$v [yield 1] [incr v]"
+        set b [subst {<[yield 2]>}]
+        list $a $b $v
+    }
+} -body {
+    list [coroutine c myproc] [c x] [c y]
+} -cleanup {
+    rename myproc ""
+} -ok {1 2 {{1 x 2} <y> 2}}
+
+test coroutine-3.13 {yield within lmap, dict map, dict update, and dict with} -setup {
+    proc myproc {} {
+        set l [lmap x {1 2} {expr {$x + [yield $x]}}]
+        set d [dict map {k v} {a 1} {yield $k}]
+        set e {a 1 b 2}
+        dict update e a x {set x [yield update]}
+        dict with e {set b [yield with]}
+        list $l $d $e
+    }
+} -body {
+    list [coroutine c myproc] [c 10] [c 20] [c A] [c 5] [c 6]
+} -cleanup {
+    rename myproc ""
+} -ok {1 2 a update with {{11 22} {a A} {a 5 b 6}}}
+
+test coroutine-3.14 {yield within for scripts and traced commands} -setup {
+    proc myproc {} {
+        for {set i [yield start]} {$i < 3} {incr i [yield next]} {
+            lappend result $i
+        }
+        lappend result [traced]
+    }
+    proc traced {} {
+        yield traced
+    }
+    trace add execution traced {enter leave} {apply {args {lappend ::log [lindex $args end]}}}
+    set ::log {}
+} -body {
+    list [coroutine c myproc] [c 0] [c 2] [c 2] [c done] $::log
+} -cleanup {
+    rename myproc ""
+    rename traced ""
+    unset ::log
+} -ok {start next next traced {0 2 done} {enter leave}}
+
+test coroutine-4.1 {yieldto} -setup {
+    proc myproc {} {
+        set args [yieldto list a b]
+        return $args
+    }
+} -body {
+    list [coroutine c myproc] [c 1 2 3]
+} -cleanup {
+    rename myproc ""
+} -ok {{a b} {1 2 3}}
+
+test coroutine-5.1 {info coroutine} -setup {
+    proc myproc {} {
+        yield [info coroutine]
+    }
+} -body {
+    list [info coroutine] [coroutine c myproc]
+} -cleanup {
+    rename c ""
+    rename myproc ""
+} -ok {{} ::c}
+
+test coroutine-5.2 {info cmdtype} -setup {
+    proc myproc {} {yield}
+    coroutine c myproc
+} -body {
+    list [info cmdtype c] [info cmdtype yield]
+} -cleanup {
+    rename c ""
+    rename myproc ""
+} -ok {coroutine native}
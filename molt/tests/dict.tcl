@@ -206,3 +206,249 @@ test dict-9.6 {dict unset: inner dict not a dict} {
     set var {a 1 b 2}
     dict unset var b z
 } -error {missing value to go with key}
+
+# dict append
+test dict-10.1 {dict append: signature} {
+    dict append
+} -error {wrong # args: should be "dict append dictVarName key ?value ...?"}
+
+test dict-10.2 {dict append: new variable} {
+    dict append var a x y
+} -ok {a xy}
+
+test dict-10.3 {dict append: existing key} {
+    set var {a 1 b 2}
+    dict append var b 3 4
+    set var
+} -ok {a 1 b 234}
+
+# dict incr
+test dict-11.1 {dict incr: signature} {
+    dict incr var
+} -error {wrong # args: should be "dict incr dictVarName key ?increment?"}
+
+test dict-11.2 {dict incr: missing key} {
+    set var {a 1}
+    dict incr var b
+} -ok {a 1 b 1}
+
+test dict-11.3 {dict incr: existing key, increment} {
+    set var {a 1 b 2}
+    dict incr var a 5
+} -ok {a 6 b 2}
+
+test dict-11.4 {dict incr: non-integer} {
+    set var {a x}
+    dict incr var a
+} -error {expected integer but got "x"}
+
+test dict-11.5 {dict incr: overflow wraps} {
+    set var {}
+    dict incr var a 9223372036854775807
+    dict incr var a 9223372036854775807
+} -ok {a -2}
+
+# dict lappend
+test dict-12.1 {dict lappend: signature} {
+    dict lappend var
+} -error {wrong # args: should be "dict lappend dictVarName key ?value ...?"}
+
+test dict-12.2 {dict lappend: missing and existing keys} {
+    set var {a {1 2}}
+    dict lappend var a 3 {4 5}
+    dict lappend var b x
+} -ok {a {1 2 3 {4 5}} b x}
+
+# dict for
+test dict-13.1 {dict for: signature} {
+    dict for {k v} {}
+} -error {wrong # args: should be "dict for {keyVarName valueVarName} dictionary script"}
+
+test dict-13.2 {dict for: bad variable list} {
+    dict for {k} {a 1} {}
+} -error {must have exactly two variable names}
+
+test dict-13.3 {dict for: iteration} {
+    set result {}
+    set r [dict for {k v} {a 1 b 2 c 3} {
+        lappend result $v $k
+    }]
+    list $r $result
+} -ok {{} {1 a 2 b 3 c}}
+
+test dict-13.4 {dict for: break and continue} {
+    set result {}
+    dict for {k v} {a 1 b 2 c 3 d 4} {
+        if {$k eq "b"} continue
+        if {$k eq "d"} break
+        lappend result $k
+    }
+    set result
+} -ok {a c}
+
+test dict-13.5 {dict for: errors} {
+    dict for {k v} {a 1} {error oops}
+} -error {oops}
+
+# dict map
+test dict-14.1 {dict map: signature} {
+    dict map {k v} {}
+} -error {wrong # args: should be "dict map {keyVarName valueVarName} dictionary script"}
+
+test dict-14.2 {dict map: mapping} {
+    dict map {k v} {a 1 b 2} {expr {$v * 10}}
+} -ok {a 10 b 20}
+
+test dict-14.3 {dict map: break and continue} {
+    dict map {k v} {a 1 b 2 c 3 d 4} {
+        if {$k eq "b"} continue
+        if {$k eq "d"} break
+        set v
+    }
+} -ok {a 1 c 3}
+
+# dict filter
+test dict-15.1 {dict filter: signature} {
+    dict filter {}
+} -error {wrong # args: should be "dict filter dictionary filterType ?arg ...?"}
+
+test dict-15.2 {dict filter: bad filter type} {
+    dict filter {a 1} nonesuch
+} -error {bad filterType "nonesuch": must be key, script, or value}
+
+test dict-15.3 {dict filter: key} {
+    list [dict filter {apple 1 banana 2 avocado 3} key a*] \
+         [dict filter {apple 1 banana 2 avocado 3} key b* *do] \
+         [dict filter {apple 1} key]
+} -ok {{apple 1 avocado 3} {banana 2 avocado 3} {}}
+
+test dict-15.4 {dict filter: value} {
+    dict filter {a x1 b y2 c x3} value x*
+} -ok {a x1 c x3}
+
+test dict-15.5 {dict filter: script} {
+    dict filter {a 1 b 2 c 3 d 4} script {k v} {expr {$v % 2 == 0}}
+} -ok {b 2 d 4}
+
+test dict-15.6 {dict filter: script, break and continue} {
+    dict filter {a 1 b 2 c 3 d 4} script {k v} {
+        if {$k eq "a"} continue
+        if {$k eq "d"} break
+        return -level 0 1
+    }
+} -ok {b 2 c 3}
+
+test dict-15.7 {dict filter: script signature} {
+    dict filter {a 1} script {k v}
+} -error {wrong # args: should be "dict filter dictionary script {keyVarName valueVarName} filterScript"}
+
+test dict-15.8 {dict filter: script must return a boolean} {
+    dict filter {a 1} script {k v} {return -level 0 maybe}
+} -error {expected boolean but got "maybe"}
+
+# dict merge
+test dict-16.1 {dict merge: no dictionaries} {
+    dict merge
+} -ok {}
+
+test dict-16.2 {dict merge: later values win} {
+    dict merge {a 1 b 2} {b 3 c 4} {c 5}
+} -ok {a 1 b 3 c 5}
+
+test dict-16.3 {dict merge: not a dictionary} {
+    dict merge {a 1} {b}
+} -error {missing value to go with key}
+
+# dict replace
+test dict-17.1 {dict replace: signature} {
+    dict replace {a 1} b
+} -error {wrong # args: should be "dict replace dictionary ?key value ...?"}
+
+test dict-17.2 {dict replace: replace and add} {
+    list [dict replace {a 1 b 2} b 3 c 4] [dict replace {a 1}]
+} -ok {{a 1 b 3 c 4} {a 1}}
+
+# dict getdef
+test dict-18.1 {dict getdef: signature} {
+    dict getdef {a 1} a
+} -error {wrong # args: should be "dict getdef dictionary ?key ...? key default"}
+
+test dict-18.2 {dict getdef: present and missing keys} {
+    list [dict getdef {a 1 b {x 2}} a 0] [dict getdef {a 1} c 0] \
+         [dict getdef {a 1 b {x 2}} b x 0] [dict getdef {a 1 b {x 2}} b y 0] \
+         [dict getdef {a 1} c d 0]
+} -ok {1 0 2 0 0}
+
+test dict-18.3 {dict getdef: not a dictionary} {
+    dict getdef {a 1 b 2} b c 0
+} -error {missing value to go with key}
+
+# dict update
+test dict-19.1 {dict update: signature} {
+    dict update var a
+} -error {wrong # args: should be "dict update dictVarName key varName ?key varName ...? script"}
+
+test dict-19.2 {dict update: odd key/varName pairs} {
+    dict update var a b c {}
+} -error {wrong # args: should be "dict update dictVarName key varName ?key varName ...? script"}
+
+test dict-19.3 {dict update: missing variable} {
+    dict update nonesuch a b {}
+} -error {can't read "nonesuch": no such variable}
+
+test dict-19.4 {dict update: update, add, and remove} {
+    set var {a 1 b 2 c 3}
+    set r [dict update var a x b y d z {
+        incr x
+        unset y
+        set z new
+        info exists ignored
+    }]
+    list $r $var
+} -ok {0 {a 2 c 3 d new}}
+
+test dict-19.5 {dict update: missing keys unset the variable} {
+    set var {a 1}
+    set y stale
+    dict update var b y {info exists y}
+} -ok {0}
+
+test dict-19.6 {dict update: updates even on error} {
+    set var {a 1}
+    catch {dict update var a x {set x 2; error oops}} result
+    list $result $var
+} -ok {oops {a 2}}
+
+# dict with
+test dict-20.1 {dict with: signature} {
+    dict with var
+} -error {wrong # args: should be "dict with dictVarName ?key ...? script"}
+
+test dict-20.2 {dict with: top level} {
+    set var {a 1 b 2}
+    set r [dict with var {
+        incr a
+        unset b
+        expr {$a * 10}
+    }]
+    list $r $var
+} -ok {20 {a 2}}
+
+test dict-20.3 {dict with: nested} {
+    set var {x {a 1 b 2} y 3}
+    dict with var x {
+        set a 10
+    }
+    set var
+} -ok {x {a 10 b 2} y 3}
+
+test dict-20.4 {dict with: missing key} {
+    set var {a 1}
+    dict with var b {}
+} -error {key "b" not known in dictionary}
+
+test dict-20.5 {dict with: dictionary variable unset in the body} {
+    set var {a 1}
+    dict with var {unset var}
+    info exists var
+} -ok {0}
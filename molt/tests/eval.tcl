@@ -0,0 +1,66 @@
+# Test Script: eval command.
+
+test eval-1.1 {eval errors} {
+    eval
+} -error {wrong # args: should be "eval arg ?arg ...?"}
+
+test eval-2.1 {single argument} {
+    eval {set a 1}
+} -ok {1}
+
+test eval-2.2 {arguments are concatenated} {
+    eval set a {{b c}}
+} -ok {b c}
+
+test eval-2.3 {command list} {
+    set cmd [list set a "x y"]
+    eval $cmd
+    set a
+} -ok {x y}
+
+test eval-2.4 {eval in the current scope} -setup {
+    proc doit {} {
+        set x local
+        eval {set x}
+    }
+} -body {
+    set x outer
+    doit
+} -cleanup {
+    rename doit ""
+} -ok {local}
+
+test eval-2.5 {repeated eval of the same value} {
+    set script {incr count}
+    set count 0
+    for {set i 0} {$i < 5} {incr i} {
+        eval $script
+    }
+    set count
+} -ok {5}
+
+test eval-3.1 {errors propagate} {
+    eval {error "oops"}
+} -error {oops}
+
+test eval-3.2 {break propagates} {
+    set result {}
+    foreach x {1 2 3} {
+        if {$x == 2} {
+            eval break
+        }
+        lappend result $x
+    }
+    set result
+} -ok {1}
+
+test eval-3.3 {return propagates} -setup {
+    proc doit {} {
+        eval {return early}
+        return late
+    }
+} -body {
+    doit
+} -cleanup {
+    rename doit ""
+} -ok {early}
@@ -2,7 +2,7 @@
 
 test foreach-1.1 {foreach argument error} {
     foreach
-} -error {wrong # args: should be "foreach varList list body"}
+} -error {wrong # args: should be "foreach varList list ?varList list ...? body"}
 
 test foreach-1.2 {error in body} {
     foreach x {1 2 3} {
@@ -54,3 +54,45 @@ test foreach-4.2 {continue in loop body} {
     }
     list $a $b
 } -ok {3 start}
+
+test foreach-1.3 {unpaired varList} {
+    foreach a {1 2} b {}
+} -error {wrong # args: should be "foreach varList list ?varList list ...? body"}
+
+test foreach-1.4 {empty varList} {
+    foreach {} {1 2} {}
+} -error {foreach varlist is empty}
+
+test foreach-5.1 {multiple lists} {
+    set result ""
+    foreach a {1 2 3} b {x y z} {
+        lappend result $a$b
+    }
+    set result
+} -ok {1x 2y 3z}
+
+test foreach-5.2 {multiple lists of different lengths} {
+    set result ""
+    foreach a {1 2 3} b {x} {
+        lappend result "$a:$b"
+    }
+    set result
+} -ok {1:x 2: 3:}
+
+test foreach-5.3 {multiple lists with different strides} {
+    set result ""
+    foreach {a b} {1 2 3 4} c {x y z} {
+        lappend result "$a$b$c"
+    }
+    set result
+} -ok {12x 34y z}
+
+test foreach-5.4 {body modifies the list variable} {
+    set list {1 2 3}
+    set result ""
+    foreach a $list {
+        lappend list $a
+        lappend result $a
+    }
+    list $result $list
+} -ok {{1 2 3} {1 2 3 1 2 3}}
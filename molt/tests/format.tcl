@@ -0,0 +1,114 @@
+# Test Script: format command
+
+test format-1.1 {format errors} {
+    format
+} -error {wrong # args: should be "format formatString ?arg ...?"}
+
+test format-1.2 {not enough arguments} {
+    format "%d %d" 1
+} -error {not enough arguments for all format specifiers}
+
+test format-1.3 {bad field specifier} {
+    format "%y" 1
+} -error {bad field specifier "y"}
+
+test format-1.4 {incomplete field specifier} {
+    format "abc %-5"
+} -error {format string ended in middle of field specifier}
+
+test format-1.5 {bad integer} {
+    format %d abc
+} -error {expected integer but got "abc"}
+
+test format-1.6 {bad float} {
+    format %f abc
+} -error {expected floating-point number but got "abc"}
+
+test format-1.7 {mixed XPG and sequential specifiers} {
+    format "%1\$s %s" a b
+} -error {cannot mix "%" and "%n$" conversion specifiers}
+
+test format-1.8 {XPG index out of range} {
+    format "%3\$s" a b
+} -error {"%n$" argument index out of range}
+
+test format-1.9 {field width too large} {
+    list [catch {format %99999999999999999999d 1} r1] $r1 \
+        [catch {format %*d 9223372036854775807 1} r2] $r2
+} -ok {1 {field width too large} 1 {field width too large}}
+
+test format-1.10 {precision too large} {
+    format %.70000f 1.0
+} -error {precision too large}
+
+test format-2.1 {no specifiers} {
+    list [format ""] [format "abc"] [format "100%%"]
+} -ok {{} abc 100%}
+
+test format-2.2 {extra arguments are ignored} {
+    format "%s" a b c
+} -ok {a}
+
+test format-3.1 {decimal integers} {
+    list [format %d 42] [format %i -42] [format %d 0x10] [format %ld 7]
+} -ok {42 -42 16 7}
+
+test format-3.2 {width and flags} {
+    list [format "%5d|%-5d|%05d|%+d|% d" 42 42 -42 42 42]
+} -ok {{   42|42   |-0042|+42| 42}}
+
+test format-3.3 {integer precision} {
+    list [format %.3d 7] [format %6.3d -7] [format %.0d 0]
+} -ok {007 {  -007} {}}
+
+test format-3.4 {hex, octal, and binary} {
+    list [format %x 255] [format %X 255] [format %08X 3054] [format %#x 255] \
+        [format %o 8] [format %#o 8] [format %b 10] [format %#b 10]
+} -ok {ff FF 00000BEE 0xff 10 010 1010 0b1010}
+
+test format-3.5 {unsigned} {
+    format %u 42
+} -ok {42}
+
+test format-3.6 {characters} {
+    list [format %c 65] [format %c 0x3b2] [format %-3c| 66]
+} -ok {A β {B  |}}
+
+test format-4.1 {strings} {
+    list [format %s abc] [format %6s abc] [format %-6s| abc] [format %.2s abc]
+} -ok {abc {   abc} {abc   |} ab}
+
+test format-4.2 {strings: width counts characters} {
+    format %5s|%.1s αβγ δε
+} -ok {  αβγ|δ}
+
+test format-5.1 {floats: %f} {
+    list [format %f 1.5] [format %.2f 3.14159] [format %8.3f -2.5] [format %-8.1f| 2] \
+        [format %+.1f 1] [format %08.2f -1.5] [format %.0f 7]
+} -ok {1.500000 3.14 {  -2.500} {2.0     |} +1.0 -0001.50 7}
+
+test format-5.2 {floats: %e} {
+    list [format %e 12345.678] [format %.2E 0.00012] [format %.0e 5] [format %e 0]
+} -ok {1.234568e+04 1.20E-04 5e+00 0.000000e+00}
+
+test format-5.3 {floats: %g} {
+    list [format %g 100000] [format %g 1000000] [format %g 0.0001] [format %g 0.00001] \
+        [format %g 1.5] [format %.3g 3.14159] [format %#g 1.5] [format %G 1e-10]
+} -ok {100000 1e+06 0.0001 1e-05 1.5 3.14 1.50000 1E-10}
+
+test format-5.4 {floats: integer arguments} {
+    format %.1f 3
+} -ok {3.0}
+
+test format-6.1 {star width and precision} {
+    list [format %*d 5 42] [format %-*d| 5 42] [format %*d| -5 42] [format %.*f 2 3.14159] \
+        [format %*.*s 6 2 abcdef]
+} -ok {{   42} {42   |} {42   |} 3.14 {    ab}}
+
+test format-7.1 {XPG positions} {
+    list [format {%2$s %1$s} a b] [format {%1$s-%1$s} x] [format {%1$*d} 4 7]
+} -ok {{b a} x-x {   7}}
+
+test format-8.1 {a device report} {
+    format "%08X %-10s %6.2f" 48879 probe 3.14159
+} -ok {0000BEEF probe        3.14}
@@ -7,7 +7,7 @@ test info-1.1 {info errors} {
 # TODO: Really need glob matching.
 test info-1.2 {info errors} {
     info nonesuch
-} -error {unknown or ambiguous subcommand "nonesuch": must be args, body, cmdtype, commands, complete, default, exists, globals, locals, procs, or vars}
+} -error {unknown or ambiguous subcommand "nonesuch": must be args, body, cmdcount, cmdtype, commands, complete, coroutine, default, exists, frame, globals, level, locals, procs, script, or vars}
 
 test info-2.1 {info complete errors} {
     info complete
@@ -242,3 +242,154 @@ test info-11.2 {info globals command: some defined; in proc} -setup {
     global a b
     unset a b
 } -ok {0 0 1 1 1}
+
+test info-12.1 {info commands: pattern} {
+    info commands upv*
+} -ok {upvar}
+
+test info-12.2 {info commands: no match} {
+    info commands nonesuch*
+} -ok {}
+
+test info-12.3 {info commands: errors} {
+    info commands a b
+} -error {wrong # args: should be "info commands ?pattern?"}
+
+test info-12.4 {info procs: pattern} -setup {
+    proc thisProc {} {}
+    proc thatProc {} {}
+} -body {
+    info procs thi*
+} -cleanup {
+    rename thisProc ""
+    rename thatProc ""
+} -ok {thisProc}
+
+test info-12.5 {info vars: pattern} -setup {
+    proc myproc {} {
+        set alpha 1
+        set beta 2
+        set alpine 3
+        info vars al*
+    }
+} -body {
+    myproc
+} -cleanup {
+    rename myproc ""
+} -ok {alpha alpine}
+
+test info-12.6 {info locals: pattern} -setup {
+    proc myproc {a b} {
+        info locals {[b]}
+    }
+} -body {
+    myproc 1 2
+} -cleanup {
+    rename myproc ""
+} -ok {b}
+
+test info-12.7 {info globals: pattern} -body {
+    global info_glob_var
+    set info_glob_var 1
+    info globals info_glob_*
+} -cleanup {
+    global info_glob_var
+    unset info_glob_var
+} -ok {info_glob_var}
+
+test info-13.1 {info level: errors} {
+    info level 1 2
+} -error {wrong # args: should be "info level ?number?"}
+
+test info-13.2 {info level: bad level} -setup {
+    proc myproc {} {info level 5}
+} -body {
+    myproc
+} -cleanup {
+    rename myproc ""
+} -error {bad level "5"}
+
+test info-13.3 {info level: current level} -setup {
+    proc myproc {} {info level}
+    proc outer {} {myproc}
+} -body {
+    list [expr {[outer] - [info level]}] [uplevel #0 {info level}]
+} -cleanup {
+    rename myproc ""
+    rename outer ""
+} -ok {2 0}
+
+test info-13.4 {info level: command words} -setup {
+    proc myproc {a args} {
+        list [info level 0] [info level -1] [info level [info level]]
+    }
+    proc outer {} {myproc x y z}
+} -body {
+    outer
+} -cleanup {
+    rename myproc ""
+    rename outer ""
+} -ok {{myproc x y z} outer {myproc x y z}}
+
+test info-13.5 {info level: uplevel} -setup {
+    proc myproc {} {uplevel 1 {info level 0}}
+    proc outer {a} {myproc}
+} -body {
+    outer 1
+} -cleanup {
+    rename myproc ""
+    rename outer ""
+} -ok {outer 1}
+
+test info-13.6 {info level: lambdas and namespace eval} -body {
+    list [apply {{x} {info level 0}} 5] \
+         [namespace eval ns1 {info level 0}]
+} -cleanup {
+    namespace delete ns1
+} -ok {{apply {{x} {info level 0}} 5} {namespace eval ns1 {info level 0}}}
+
+test info-14.1 {info frame: errors} {
+    info frame 0 1
+} -error {wrong # args: should be "info frame ?number?"}
+
+test info-14.2 {info frame: depth} -setup {
+    proc myproc {} {info frame}
+} -body {
+    expr {[myproc] - [info frame]}
+} -cleanup {
+    rename myproc ""
+} -ok {1}
+
+test info-14.3 {info frame: proc frame} -setup {
+    proc myproc {a} {info frame 0}
+} -body {
+    set frame [myproc 1]
+    list [dict get $frame type] [dict get $frame cmd] [dict get $frame proc] \
+        [expr {[dict get $frame level] - [info level]}]
+} -cleanup {
+    rename myproc ""
+} -ok {proc {myproc 1} myproc 1}
+
+test info-14.4 {info frame: lambda and source frames} -body {
+    set frame [apply {{} {info frame -1}}]
+    list [dict get [apply {{} {info frame 0}}] lambda] \
+         [dict get $frame type] [string match *info.tcl [dict get $frame file]]
+} -ok {{{} {info frame 0}} source 1}
+
+test info-14.5 {info frame: bad level} {
+    info frame 1000
+} -error {bad level "1000"}
+
+test info-15.1 {info script} {
+    string match *info.tcl [info script]
+} -ok {1}
+
+test info-15.2 {info script: errors} {
+    info script foo
+} -error {wrong # args: should be "info script "}
+
+test info-16.1 {info cmdcount} {
+    set a [info cmdcount]
+    set b [info cmdcount]
+    expr {$b - $a}
+} -ok {2}
@@ -0,0 +1,28 @@
+# Test Script: lassign command
+
+test lassign-1.1 {lassign errors} {
+    lassign
+} -error {wrong # args: should be "lassign list ?varName ...?"}
+
+test lassign-1.2 {no variables} {
+    lassign {a b c}
+} -ok {a b c}
+
+test lassign-2.1 {assign all elements} {
+    set result [lassign {a b c} x y z]
+    list $result $x $y $z
+} -ok {{} a b c}
+
+test lassign-2.2 {more variables than elements} {
+    set result [lassign {a b} x y z]
+    list $result $x $y $z
+} -ok {{} a b {}}
+
+test lassign-2.3 {more elements than variables} {
+    set result [lassign {a {b c} d e} x y]
+    list $result $x $y
+} -ok {{d e} a {b c}}
+
+test lassign-2.4 {bad list} {
+    lassign "a \{b" x
+} -error {unmatched open brace in list}
@@ -39,3 +39,21 @@ test list-2.2 {index list} {
 test list-3.1 {no arguments} {
     lindex
 } -error {wrong # args: should be "lindex list ?index ...?"}
+
+test lindex-4.1 {end indices} {
+    list [lindex {a b c} end] [lindex {a b c} end-1] [lindex {a b c} end-3] \
+        [lindex {a {b c d} e} 1 end]
+} -ok {c b {} d}
+
+test lindex-4.2 {index arithmetic} {
+    lindex {a b c d} 1+2
+} -ok {d}
+
+test lindex-4.3 {bad index} {
+    lindex {a b c} foo
+} -error {bad index "foo": must be integer?[+-]integer? or end?[+-]integer?}
+
+test lindex-4.4 {index shares the list's value} {
+    set x 0
+    lindex $x $x
+} -ok {0}
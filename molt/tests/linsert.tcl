@@ -0,0 +1,37 @@
+# Test Script: linsert command
+
+test linsert-1.1 {linsert errors} {
+    linsert {a b}
+} -error {wrong # args: should be "linsert list index ?element ...?"}
+
+test linsert-1.2 {bad index} {
+    linsert {a b} foo x
+} -error {bad index "foo": must be integer?[+-]integer? or end?[+-]integer?}
+
+test linsert-2.1 {insert at start} {
+    linsert {a b c} 0 x y
+} -ok {x y a b c}
+
+test linsert-2.2 {insert in the middle} {
+    linsert {a b c} 1 x {y z}
+} -ok {a x {y z} b c}
+
+test linsert-2.3 {insert at end} {
+    list [linsert {a b c} end x] [linsert {a b c} 3 x] [linsert {a b c} 99 x]
+} -ok {{a b c x} {a b c x} {a b c x}}
+
+test linsert-2.4 {insert before end} {
+    linsert {a b c} end-1 x
+} -ok {a b x c}
+
+test linsert-2.5 {negative index} {
+    linsert {a b c} -5 x
+} -ok {x a b c}
+
+test linsert-2.6 {no elements} {
+    linsert {a b c} 1
+} -ok {a b c}
+
+test linsert-2.7 {empty list} {
+    linsert {} 0 x
+} -ok {x}
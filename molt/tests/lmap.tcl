@@ -0,0 +1,54 @@
+# Test Script: lmap command
+
+test lmap-1.1 {lmap argument error} {
+    lmap
+} -error {wrong # args: should be "lmap varList list ?varList list ...? body"}
+
+test lmap-1.2 {unpaired varList} {
+    lmap a {1 2} b {}
+} -error {wrong # args: should be "lmap varList list ?varList list ...? body"}
+
+test lmap-1.3 {empty varList} {
+    lmap {} {1 2} {}
+} -error {lmap varlist is empty}
+
+test lmap-1.4 {error in body} {
+    lmap x {1 2 3} {
+        error "Simulated error"
+    }
+} -error {Simulated error}
+
+test lmap-2.1 {empty list} {
+    lmap a {} { set a }
+} -ok {}
+
+test lmap-2.2 {collect results} {
+    lmap a {1 2 3} { expr {$a * 2} }
+} -ok {2 4 6}
+
+test lmap-2.3 {stride > 1} {
+    lmap {a b} {1 2 3} { list $a $b }
+} -ok {{1 2} {3 {}}}
+
+test lmap-2.4 {multiple lists} {
+    lmap a {1 2 3} b {x y} { string cat $a $b }
+} -ok {1x 2y 3}
+
+test lmap-3.1 {break in loop body} {
+    lmap a {1 2 3 4} {
+        if {$a == 3} { break }
+        set a
+    }
+} -ok {1 2}
+
+test lmap-3.2 {continue in loop body} {
+    lmap a {1 2 3 4} {
+        if {$a % 2 == 0} { continue }
+        set a
+    }
+} -ok {1 3}
+
+test lmap-3.3 {loop variables remain set} {
+    lmap a {1 2 3} { set a }
+    set a
+} -ok {3}
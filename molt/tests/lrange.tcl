@@ -0,0 +1,29 @@
+# Test Script: lrange command
+
+test lrange-1.1 {lrange errors} {
+    lrange {a b c} 1
+} -error {wrong # args: should be "lrange list first last"}
+
+test lrange-1.2 {bad index} {
+    lrange {a b c} 0 foo
+} -error {bad index "foo": must be integer?[+-]integer? or end?[+-]integer?}
+
+test lrange-2.1 {basic range} {
+    lrange {a b c d e} 1 3
+} -ok {b c d}
+
+test lrange-2.2 {end indices} {
+    list [lrange {a b c d e} 2 end] [lrange {a b c d e} end-1 end] [lrange {a b c d e} 0 end-4]
+} -ok {{c d e} {d e} a}
+
+test lrange-2.3 {clamped range} {
+    lrange {a b c} -5 99
+} -ok {a b c}
+
+test lrange-2.4 {empty range} {
+    list [lrange {a b c} 2 1] [lrange {a b c} 5 9] [lrange {} 0 end]
+} -ok {{} {} {}}
+
+test lrange-2.5 {nested lists preserved} {
+    lrange {a {b c} {d e} f} 1 2
+} -ok {{b c} {d e}}
@@ -0,0 +1,32 @@
+# Test Script: lrepeat command
+
+test lrepeat-1.1 {lrepeat errors} {
+    lrepeat
+} -error {wrong # args: should be "lrepeat count ?value ...?"}
+
+test lrepeat-1.2 {negative count} {
+    lrepeat -1 a
+} -error {bad count "-1": must be integer >= 0}
+
+test lrepeat-1.3 {bad count} {
+    lrepeat x a
+} -error {expected integer but got "x"}
+
+test lrepeat-2.1 {repeat one element} {
+    lrepeat 3 a
+} -ok {a a a}
+
+test lrepeat-2.2 {repeat several elements} {
+    lrepeat 2 a {b c}
+} -ok {a {b c} a {b c}}
+
+test lrepeat-2.3 {zero count or no elements} {
+    list [lrepeat 0 a] [lrepeat 3]
+} -ok {{} {}}
+
+test lrepeat-2.4 {result too large} {
+    list \
+        [catch {lrepeat 9223372036854775807 a b} msg1] $msg1 \
+        [catch {lrepeat 4611686018427387904 a} msg2] $msg2 \
+        [lrepeat 9223372036854775807]
+} -ok {1 {result of lrepeat would be too large} 1 {result of lrepeat would be too large} {}}
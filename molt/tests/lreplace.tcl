@@ -0,0 +1,37 @@
+# Test Script: lreplace command
+
+test lreplace-1.1 {lreplace errors} {
+    lreplace {a b c} 1
+} -error {wrong # args: should be "lreplace list first last ?element ...?"}
+
+test lreplace-1.2 {bad index} {
+    lreplace {a b c} foo 1
+} -error {bad index "foo": must be integer?[+-]integer? or end?[+-]integer?}
+
+test lreplace-2.1 {delete elements} {
+    lreplace {a b c d e} 1 3
+} -ok {a e}
+
+test lreplace-2.2 {replace elements} {
+    lreplace {a b c d e} 1 3 x {y z}
+} -ok {a x {y z} e}
+
+test lreplace-2.3 {end indices} {
+    list [lreplace {a b c d} end end x] [lreplace {a b c d} end-1 end]
+} -ok {{a b c x} {a b}}
+
+test lreplace-2.4 {last < first inserts} {
+    lreplace {a b c} 1 0 x
+} -ok {a x b c}
+
+test lreplace-2.5 {clamped range} {
+    list [lreplace {a b c} -3 0 x] [lreplace {a b c} 1 99 x]
+} -ok {{x b c} {a x}}
+
+test lreplace-2.6 {first past end appends} {
+    lreplace {a b c} 5 6 x
+} -ok {a b c x}
+
+test lreplace-2.7 {empty list} {
+    lreplace {} 0 0 x
+} -ok {x}
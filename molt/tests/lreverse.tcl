@@ -0,0 +1,9 @@
+# Test Script: lreverse command
+
+test lreverse-1.1 {lreverse errors} {
+    lreverse
+} -error {wrong # args: should be "lreverse list"}
+
+test lreverse-2.1 {reverse a list} {
+    list [lreverse {a {b c} d}] [lreverse {}] [lreverse a]
+} -ok {{d {b c} a} {} a}
@@ -0,0 +1,121 @@
+# Test Script: lsearch command
+
+test lsearch-1.1 {lsearch errors} {
+    lsearch {a b}
+} -error {wrong # args: should be "lsearch ?-option value ...? list pattern"}
+
+test lsearch-1.2 {bad option} {
+    lsearch -bogus {a b} a
+} -error {bad option "-bogus": must be -all, -ascii, -decreasing, -dictionary, -exact, -glob, -increasing, -index, -inline, -integer, -nocase, -not, -real, -regexp, -sorted, or -start}
+
+test lsearch-1.3 {missing option values} {
+    list [catch {lsearch -start {a b} a} r1] $r1 [catch {lsearch -index {a b} a} r2] $r2
+} -ok {1 {missing starting index} 1 {"-index" option must be followed by list index}}
+
+test lsearch-1.4 {bad list} {
+    lsearch "a \{b" a
+} -error {unmatched open brace in list}
+
+test lsearch-2.1 {glob by default} {
+    list [lsearch {apple banana cherry} b*] [lsearch {apple banana cherry} *rr?] \
+        [lsearch {apple banana cherry} z*]
+} -ok {1 2 -1}
+
+test lsearch-2.2 {-exact} {
+    list [lsearch -exact {a* b c} a*] [lsearch -exact {abc b c} a*] [lsearch -glob {abc b c} a*]
+} -ok {0 -1 0}
+
+test lsearch-2.3 {-nocase} {
+    list [lsearch -nocase {Apple Banana} b*] [lsearch -exact -nocase {Apple Banana} APPLE] \
+        [lsearch {Apple Banana} b*]
+} -ok {1 0 -1}
+
+test lsearch-2.4 {-integer and -real with -exact} {
+    list [lsearch -exact -integer {1 0x10 3} 16] [lsearch -exact {1 0x10 3} 16] \
+        [lsearch -exact -real {1 2.0 3} 2]
+} -ok {1 -1 1}
+
+test lsearch-2.5 {-integer: bad pattern} {
+    lsearch -exact -integer {1 2 3} x
+} -error {expected integer but got "x"}
+
+test lsearch-3.1 {-all} {
+    list [lsearch -all {a b a c a} a] [lsearch -all {a b c} z]
+} -ok {{0 2 4} {}}
+
+test lsearch-3.2 {-inline} {
+    list [lsearch -inline {apple banana} b*] [lsearch -inline {apple banana} z*]
+} -ok {banana {}}
+
+test lsearch-3.3 {-all -inline} {
+    lsearch -all -inline {apple avocado banana} a*
+} -ok {apple avocado}
+
+test lsearch-3.4 {-not} {
+    list [lsearch -not {a a b a} a] [lsearch -not -all -inline {a1 b2 a3 c4} a*]
+} -ok {2 {b2 c4}}
+
+test lsearch-3.5 {-start} {
+    list [lsearch -start 1 {a b a} a] [lsearch -start end {a b a} a] \
+        [lsearch -start 5 {a b a} a] [lsearch -start -5 {a b a} a] \
+        [lsearch -all -start 1 {a b a a} a]
+} -ok {2 2 -1 0 {2 3}}
+
+test lsearch-3.6 {-index} {
+    list [lsearch -index 1 {{a x} {b y} {c z}} y] \
+        [lsearch -index end -inline {{a x} {b y} {c z}} z] \
+        [lsearch -index {1 0} {{a {x 1}} {b {y 2}}} y]
+} -ok {1 {c z} 1}
+
+test lsearch-3.7 {-index: missing element} {
+    lsearch -index 1 {{a x} b} y
+} -error {element 1 missing from sublist "b"}
+
+test lsearch-4.1 {-sorted} {
+    set list {a b c d e f g}
+    list [lsearch -sorted $list a] [lsearch -sorted $list d] [lsearch -sorted $list g] \
+        [lsearch -sorted $list dd] [lsearch -sorted $list z] [lsearch -sorted {} a]
+} -ok {0 3 6 -1 -1 -1}
+
+test lsearch-4.2 {-sorted finds the first of duplicates} {
+    lsearch -sorted {a b b b b c} b
+} -ok {1}
+
+test lsearch-4.3 {-sorted -integer} {
+    set list {1 2 5 9 10 100}
+    list [lsearch -sorted -integer $list 10] [lsearch -sorted $list 10] \
+        [lsearch -sorted -integer $list 0x64] [lsearch -sorted -integer $list 7]
+} -ok {4 -1 5 -1}
+
+test lsearch-4.4 {-sorted -real} {
+    lsearch -sorted -real {0.5 1.5 2.25 10.0} 2.25
+} -ok {2}
+
+test lsearch-4.5 {-sorted -decreasing} {
+    list [lsearch -sorted -decreasing -integer {100 10 9 5 1} 9] \
+        [lsearch -sorted -decreasing {e d c b a} a]
+} -ok {2 4}
+
+test lsearch-4.6 {-sorted -dictionary and -nocase} {
+    list [lsearch -sorted -dictionary {a1 a9 a10 a100} a10] \
+        [lsearch -sorted -nocase {Apple banana Cherry} cherry]
+} -ok {2 2}
+
+test lsearch-4.7 {-sorted -all, -not, -start, -inline} {
+    list [lsearch -sorted -all {a b b c} b] [lsearch -sorted -not {a b c} a] \
+        [lsearch -sorted -start 2 {a b b c} b] [lsearch -sorted -inline -index 0 {{a 1} {b 2}} b]
+} -ok {{1 2} 1 2 {b 2}}
+
+test lsearch-4.8 {-sorted with -glob or -regexp} {
+    list [lsearch -sorted -glob {a b c} b*] [lsearch -glob -sorted {a b c} b*] \
+        [lsearch -sorted -regexp {a bb c} {^b+$}] [lsearch -sorted -exact {a b c} b]
+} -ok {1 1 1 1}
+
+test lsearch-5.1 {-regexp} {
+    list [lsearch -regexp {apple b42 c} {\d+}] [lsearch -regexp -all {a1 b a2} {^a}] \
+        [lsearch -regexp -nocase -inline {x YES z} {^yes$}]
+} -ok {1 {0 2} YES}
+
+test lsearch-5.2 {-regexp, bad pattern} {
+    lsearch -regexp {a b} {[a}
+} -error {couldn't compile regular expression pattern: brackets [] not balanced}
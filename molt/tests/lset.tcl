@@ -0,0 +1,72 @@
+# Test Script: lset command
+
+test lset-1.1 {lset errors} {
+    lset x
+} -error {wrong # args: should be "lset listVar ?index? ?index ...? value"}
+
+test lset-1.2 {no such variable} {
+    lset nonesuch 0 x
+} -error {can't read "nonesuch": no such variable}
+
+test lset-1.3 {index out of range} {
+    set x {a b c}
+    list [catch {lset x 4 z} result] $result $x
+} -ok {1 {list index out of range} {a b c}}
+
+test lset-1.4 {negative index} {
+    set x {a b c}
+    lset x -1 z
+} -error {list index out of range}
+
+test lset-1.5 {bad index} {
+    set x {a b c}
+    lset x foo z
+} -error {bad index "foo": must be integer?[+-]integer? or end?[+-]integer?}
+
+test lset-2.1 {set an element} {
+    set x {a b c}
+    list [lset x 1 z] $x
+} -ok {{a z c} {a z c}}
+
+test lset-2.2 {end indices} {
+    set x {a b c}
+    lset x end z
+    lset x end-2 y
+} -ok {y b z}
+
+test lset-2.3 {append at end+1} {
+    set x {a b c}
+    list [lset x end+1 d] [lset x 4 e]
+} -ok {{a b c d} {a b c d e}}
+
+test lset-2.4 {no indices replaces the value} {
+    set x {a b c}
+    list [lset x z] [lset x {} y]
+} -ok {z y}
+
+test lset-3.1 {nested indices} {
+    set x {a {b c d} e}
+    lset x 1 2 z
+} -ok {a {b c z} e}
+
+test lset-3.2 {nested index list} {
+    set x {a {b {c d}} e}
+    lset x {1 1 0} z
+} -ok {a {b {z d}} e}
+
+test lset-3.3 {nested out of range} {
+    set x {a {b c} e}
+    lset x 1 5 z
+} -error {list index out of range}
+
+test lset-3.4 {nested append} {
+    set x {a {b c} e}
+    lset x 1 end+1 z
+} -ok {a {b c z} e}
+
+test lset-3.5 {value is not shared} {
+    set x {a b c}
+    set y $x
+    lset x 0 z
+    list $x $y
+} -ok {{z b c} {a b c}}
@@ -0,0 +1,179 @@
+# Test Script: lsort command
+
+test lsort-1.1 {lsort errors} {
+    lsort
+} -error {wrong # args: should be "lsort ?-option value ...? list"}
+
+test lsort-1.2 {bad option} {
+    lsort -bogus {a b}
+} -error {bad option "-bogus": must be -ascii, -command, -decreasing, -dictionary, -increasing, -index, -integer, -nocase, -real, -stride, or -unique}
+
+test lsort-1.3 {missing option values} {
+    list [catch {lsort -index {a b}} r1] $r1 \
+        [catch {lsort -command {a b}} r2] $r2 \
+        [catch {lsort -stride {a b}} r3] $r3
+} -ok {1 {"-index" option must be followed by list index} 1 {"-command" option must be followed by comparison command} 1 {"-stride" option must be followed by stride length}}
+
+test lsort-1.4 {bad list} {
+    lsort "a \{b"
+} -error {unmatched open brace in list}
+
+test lsort-2.1 {ascii sort} {
+    lsort {d b A c a B}
+} -ok {A B a b c d}
+
+test lsort-2.2 {ascii sort: -nocase is stable} {
+    lsort -nocase {d b A c a B}
+} -ok {A a b B c d}
+
+test lsort-2.3 {-decreasing} {
+    list [lsort -decreasing {b c a}] [lsort -increasing {b c a}]
+} -ok {{c b a} {a b c}}
+
+test lsort-2.4 {empty and single-element lists} {
+    list [lsort {}] [lsort a]
+} -ok {{} a}
+
+test lsort-2.5 {elements are preserved} {
+    lsort {{b c} {a b} {}}
+} -ok {{} {a b} {b c}}
+
+test lsort-3.1 {-integer} {
+    lsort -integer {10 9 -1 0x10 2}
+} -ok {-1 2 9 10 0x10}
+
+test lsort-3.2 {-integer: bad element} {
+    lsort -integer {1 x 2}
+} -error {expected integer but got "x"}
+
+test lsort-3.3 {-real} {
+    lsort -real {1.5 -2 10 1e1 0.25}
+} -ok {-2 0.25 1.5 10 1e1}
+
+test lsort-3.4 {-real: bad element} {
+    lsort -real {1.5 x}
+} -error {expected floating-point number but got "x"}
+
+test lsort-3.5 {-dictionary} {
+    lsort -dictionary {a10 a9 B2 b1 A2 x01 x1}
+} -ok {A2 a9 a10 b1 B2 x1 x01}
+
+test lsort-3.6 {-dictionary vs -ascii} {
+    lsort {a10 a9 B2}
+} -ok {B2 a10 a9}
+
+test lsort-4.1 {-unique} {
+    lsort -unique {c a b a c}
+} -ok {a b c}
+
+test lsort-4.2 {-unique retains the last duplicate} {
+    lsort -unique -index 0 {{a 1} {b 2} {a 3}}
+} -ok {{a 3} {b 2}}
+
+test lsort-4.3 {-unique -integer} {
+    lsort -unique -integer {1 01 0x1 2}
+} -ok {0x1 2}
+
+test lsort-5.1 {-index} {
+    lsort -index 1 {{a 3} {b 1} {c 2}}
+} -ok {{b 1} {c 2} {a 3}}
+
+test lsort-5.2 {-index end, -integer} {
+    lsort -integer -index end {{a 10} {b 9} {c 100}}
+} -ok {{b 9} {a 10} {c 100}}
+
+test lsort-5.3 {-index list} {
+    lsort -index {1 0} {{a {z 1}} {b {x 2}} {c {y 3}}}
+} -ok {{b {x 2}} {c {y 3}} {a {z 1}}}
+
+test lsort-5.4 {-index: missing element} {
+    lsort -index 1 {{a 1} b}
+} -error {element 1 missing from sublist "b"}
+
+test lsort-5.5 {-index is stable} {
+    lsort -index 0 {{b 1} {a 2} {b 3} {a 4}}
+} -ok {{a 2} {a 4} {b 1} {b 3}}
+
+test lsort-6.1 {-stride} {
+    lsort -stride 2 {c 1 a 2 b 3}
+} -ok {a 2 b 3 c 1}
+
+test lsort-6.2 {-stride with -index} {
+    lsort -stride 2 -index 1 -integer {c 3 a 2 b 1}
+} -ok {b 1 a 2 c 3}
+
+test lsort-6.3 {-stride: bad length} {
+    lsort -stride 2 {a b c}
+} -error {list size must be a multiple of the stride length}
+
+test lsort-6.4 {-stride: too short} {
+    lsort -stride 1 {a b c}
+} -error {stride length must be at least 2}
+
+test lsort-6.5 {-stride: index out of group} {
+    lsort -stride 2 -index 2 {a b c d}
+} -error {when used with "-stride", the leading "-index" value must be within the group}
+
+test lsort-7.1 {-command} -setup {
+    proc bylen {a b} {
+        expr {[string length $a] - [string length $b]}
+    }
+} -body {
+    lsort -command bylen {ccc a bb dd}
+} -cleanup {
+    rename bylen ""
+} -ok {a bb dd ccc}
+
+test lsort-7.2 {-command prefix} -setup {
+    proc cmp {sign a b} {
+        expr {$sign * ($a - $b)}
+    }
+} -body {
+    lsort -command {cmp -1} {1 3 2}
+} -cleanup {
+    rename cmp ""
+} -ok {3 2 1}
+
+test lsort-7.3 {-command: non-integer result} -setup {
+    proc bad {a b} { return x }
+} -body {
+    lsort -command bad {1 2}
+} -cleanup {
+    rename bad ""
+} -error {-compare command returned non-integer result}
+
+test lsort-7.4 {-command: error} -setup {
+    proc bad {a b} { error "oops" }
+} -body {
+    lsort -command bad {1 2}
+} -cleanup {
+    rename bad ""
+} -error {oops}
+
+test lsort-7.5 {-command: unknown command} {
+    lsort -command nonesuch {1 2}
+} -error {invalid command name "nonesuch"}
+
+test lsort-7.6 {-command: inconsistent comparisons} -setup {
+    proc flip {a b} {
+        global n
+        incr n
+        expr {($n % 2) ? -1 : 1}
+    }
+} -body {
+    set ::n 0
+    llength [lsort -command flip [lrepeat 3000 x]]
+} -cleanup {
+    rename flip ""
+    unset ::n
+} -ok {3000}
+
+test lsort-7.7 {-command: stable} -setup {
+    proc byfirst {a b} {
+        string compare [string index $a 0] [string index $b 0]
+    }
+} -body {
+    lsort -command byfirst {b1 a1 b2 a2 c1 a3 b3}
+} -cleanup {
+    rename byfirst ""
+} -ok {a1 a2 a3 b1 b2 b3 c1}
@@ -0,0 +1,347 @@
+# Test Script: namespace command, and namespace-qualified names
+
+test namespace-1.1 {namespace errors} {
+    namespace
+} -error {wrong # args: should be "namespace subcommand ?arg ...?"}
+
+test namespace-1.2 {namespace eval errors} {
+    namespace eval ns1
+} -error {wrong # args: should be "namespace eval name arg ?arg...?"}
+
+test namespace-1.3 {namespace delete unknown namespace} {
+    namespace delete ::nosuch
+} -error {unknown namespace "::nosuch" in namespace delete command}
+
+test namespace-1.4 {namespace delete global namespace} {
+    namespace delete ::
+} -error {can't delete the global namespace}
+
+test namespace-2.1 {namespace current} {
+    namespace current
+} -ok {::}
+
+test namespace-2.2 {namespace eval creates namespace} -body {
+    namespace eval ns1 {namespace current}
+} -cleanup {
+    namespace delete ns1
+} -ok {::ns1}
+
+test namespace-2.3 {nested namespaces} -body {
+    list [namespace eval ns1 {namespace eval ns2 {namespace current}}] \
+         [namespace eval ns1::ns2 {namespace current}] \
+         [namespace exists ::ns1] [namespace exists ns1::ns2]
+} -cleanup {
+    namespace delete ns1
+} -ok {::ns1::ns2 ::ns1::ns2 1 1}
+
+test namespace-2.4 {namespace eval concatenates arguments} -body {
+    namespace eval ns1 set x 5
+} -cleanup {
+    namespace delete ns1
+} -ok {5}
+
+test namespace-2.5 {namespace eval returns errors} -body {
+    namespace eval ns1 {error oops}
+} -cleanup {
+    namespace delete ns1
+} -error {oops}
+
+test namespace-2.6 {namespace eval adds a stack level} -body {
+    namespace eval ns1 {uplevel 1 {set y 2}}
+    set y
+} -cleanup {
+    namespace delete ns1
+} -ok {2}
+
+test namespace-3.1 {namespace exists} -body {
+    list [namespace exists ::] [namespace exists nosuch] \
+         [namespace eval ns1 {namespace exists ::}] [namespace exists ns1::]
+} -cleanup {
+    namespace delete ns1
+} -ok {1 0 1 1}
+
+test namespace-3.2 {namespace children} -setup {
+    namespace eval ns1 {
+        namespace eval a {}
+        namespace eval b {}
+        namespace eval a::c {}
+    }
+} -body {
+    list [lsort [namespace children ns1]] [namespace children ::ns1 a*] \
+         [namespace children ns1::a] [namespace eval ns1 {namespace children}]
+} -cleanup {
+    namespace delete ns1
+} -ok {{::ns1::a ::ns1::b} ::ns1::a ::ns1::a::c {::ns1::a ::ns1::b}}
+
+test namespace-3.3 {namespace children of unknown namespace} {
+    namespace children nosuch
+} -error {namespace "nosuch" not found in "::"}
+
+test namespace-3.4 {namespace qualifiers and tail} {
+    list [namespace qualifiers ::a::b::c] [namespace qualifiers a] \
+         [namespace tail ::a::b::c] [namespace tail a] [namespace tail ::]
+} -ok {::a::b {} c a {}}
+
+test namespace-4.1 {namespace procs} -setup {
+    namespace eval ns1 {
+        proc init {} {return ns1}
+    }
+    namespace eval ns2 {
+        proc init {} {return ns2}
+    }
+} -body {
+    list [ns1::init] [::ns2::init] [namespace eval ns1 init] [info commands init]
+} -cleanup {
+    namespace delete ns1 ns2
+} -ok {ns1 ns2 ns1 {}}
+
+test namespace-4.2 {procs run in their namespace} -setup {
+    namespace eval ns1 {
+        proc helper {} {return helped}
+        proc caller {} {list [namespace current] [helper]}
+    }
+} -body {
+    ns1::caller
+} -cleanup {
+    namespace delete ns1
+} -ok {::ns1 helped}
+
+test namespace-4.3 {namespaced commands fall back to global commands} -setup {
+    namespace eval ns1 {
+        proc f {} {list [llength {a b}] [::set x 1]}
+    }
+} -body {
+    ns1::f
+} -cleanup {
+    namespace delete ns1
+} -ok {2 1}
+
+test namespace-4.4 {proc in an unknown namespace} {
+    proc nosuch::foo {} {}
+} -error {can't create procedure "nosuch::foo": unknown namespace}
+
+test namespace-4.5 {qualified proc definition} -setup {
+    namespace eval ns1 {}
+    proc ::ns1::foo {} {namespace current}
+} -body {
+    ns1::foo
+} -cleanup {
+    namespace delete ns1
+} -ok {::ns1}
+
+test namespace-4.6 {info commands and info procs} -setup {
+    namespace eval ns1 {
+        proc foo {} {}
+        proc bar {} {}
+        namespace eval ns2 {proc baz {} {}}
+    }
+} -body {
+    list [lsort [info commands ::ns1::*]] [info procs ns1::b*] \
+         [namespace eval ns1 {info procs foo}] [info commands ::ns1::ns2::*]
+} -cleanup {
+    namespace delete ns1
+} -ok {{::ns1::bar ::ns1::foo} ::ns1::bar foo ::ns1::ns2::baz}
+
+test namespace-4.7 {info body and args with qualified names} -setup {
+    namespace eval ns1 {proc foo {a b} {return body}}
+} -body {
+    list [info args ::ns1::foo] [info body ns1::foo] [info cmdtype ns1::foo]
+} -cleanup {
+    namespace delete ns1
+} -ok {{a b} {return body} proc}
+
+test namespace-4.8 {rename into and out of namespaces} -setup {
+    namespace eval ns1 {}
+    proc foo {} {return foo}
+} -body {
+    rename foo ns1::bar
+    set a [ns1::bar]
+    rename ::ns1::bar ::foo
+    list $a [foo] [info commands ::ns1::*]
+} -cleanup {
+    rename foo ""
+    namespace delete ns1
+} -ok {foo foo {}}
+
+test namespace-4.9 {rename into unknown namespace} -setup {
+    proc foo {} {}
+} -body {
+    rename foo nosuch::foo
+} -cleanup {
+    rename foo ""
+} -error {can't rename to "nosuch::foo": unknown namespace}
+
+test namespace-5.1 {namespace variables} -body {
+    namespace eval ns1 {set x 1}
+    namespace eval ns2 {set x 2}
+    list [set ns1::x] $::ns2::x [namespace eval ns1 {set x}] [info exists ::x]
+} -cleanup {
+    namespace delete ns1 ns2
+} -ok {1 2 1 0}
+
+test namespace-5.2 {qualified variable names in procs} -setup {
+    namespace eval ns1 {set x 1}
+    proc getx {} {
+        incr ::ns1::x
+        set ns1::x
+    }
+} -body {
+    getx
+} -cleanup {
+    rename getx ""
+    namespace delete ns1
+} -ok {2}
+
+test namespace-5.3 {global variables with ::} -setup {
+    set ::gx 5
+} -body {
+    namespace eval ns1 {set ::gx}
+} -cleanup {
+    unset ::gx
+    namespace delete ns1
+} -ok {5}
+
+test namespace-5.4 {info vars} -setup {
+    namespace eval ns1 {
+        set a 1
+        set b 2
+        namespace eval ns2 {set c 3}
+    }
+} -body {
+    list [namespace eval ns1 {lsort [info vars]}] [lsort [info vars ::ns1::*]] \
+         [info vars ::ns1::ns2::*] [info globals a]
+} -cleanup {
+    namespace delete ns1
+} -ok {{a b} {::ns1::a ::ns1::b} ::ns1::ns2::c {}}
+
+test namespace-5.5 {namespace delete removes variables} -body {
+    namespace eval ns1 {set x 1}
+    namespace delete ns1
+    list [info exists ns1::x] [namespace exists ns1]
+} -ok {0 0}
+
+test namespace-5.6 {arrays in namespaces} -body {
+    namespace eval ns1 {set a(1) one}
+    list $ns1::a(1) [array names ::ns1::a]
+} -cleanup {
+    namespace delete ns1
+} -ok {one 1}
+
+test namespace-5.7 {global with qualified names} -setup {
+    namespace eval ns1 {set x 10}
+    proc getx {} {
+        global ns1::x
+        incr x
+    }
+} -body {
+    list [getx] $ns1::x
+} -cleanup {
+    rename getx ""
+    namespace delete ns1
+} -ok {11 11}
+
+test namespace-5.8 {upvar to namespace variables} -setup {
+    namespace eval ns1 {set x 10}
+} -body {
+    upvar #0 ns1::x y
+    set y 20
+    set ns1::x
+} -cleanup {
+    namespace delete ns1
+} -ok {20}
+
+test namespace-6.1 {namespace export} -body {
+    namespace eval ns1 {
+        namespace export a* b
+        namespace export b c
+        namespace export
+    }
+} -cleanup {
+    namespace delete ns1
+} -ok {a* b c}
+
+test namespace-6.2 {namespace export -clear} -body {
+    namespace eval ns1 {
+        namespace export a* b
+        namespace export -clear c
+        namespace export
+    }
+} -cleanup {
+    namespace delete ns1
+} -ok {c}
+
+test namespace-6.3 {qualified export pattern} {
+    namespace export ::ns1::a
+} -error {invalid export pattern "::ns1::a": pattern can't specify a namespace}
+
+test namespace-6.4 {namespace import} -setup {
+    namespace eval ns1 {
+        namespace export pub*
+        proc pub1 {} {return [priv]}
+        proc pub2 {} {return pub2}
+        proc priv {} {return priv}
+    }
+    namespace eval ns2 {}
+} -body {
+    namespace eval ns2 {
+        namespace import ::ns1::*
+        list [pub1] [pub2] [lsort [info commands ::ns2::*]]
+    }
+} -cleanup {
+    namespace delete ns1 ns2
+} -ok {priv pub2 {::ns2::pub1 ::ns2::pub2}}
+
+test namespace-6.5 {namespace import conflicts} -setup {
+    namespace eval ns1 {
+        namespace export foo
+        proc foo {} {return ns1}
+    }
+    namespace eval ns2 {
+        proc foo {} {return ns2}
+    }
+} -body {
+    set a [catch {namespace eval ns2 {namespace import ::ns1::foo}} result]
+    namespace eval ns2 {namespace import -force ::ns1::foo}
+    list $a $result [ns2::foo]
+} -cleanup {
+    namespace delete ns1 ns2
+} -ok {1 {can't import command "foo": already exists} ns1}
+
+test namespace-6.6 {namespace import errors} -body {
+    list [catch {namespace import ::nosuch::*} result] $result
+} -ok {1 {unknown namespace in import pattern "::nosuch::*"}}
+
+test namespace-6.7 {import into itself} -body {
+    namespace eval ns1 {namespace import ::ns1::*}
+} -cleanup {
+    namespace delete ns1
+} -error {import pattern "::ns1::*" tries to import from namespace "::ns1" into itself}
+
+test namespace-6.8 {export and import after the namespace deletes itself} -setup {
+    namespace eval ns1 {
+        namespace export foo
+        proc foo {} {}
+    }
+} -body {
+    namespace eval ns2 {
+        namespace delete ::ns2
+        list \
+            [catch {namespace export foo} msg1] $msg1 \
+            [catch {namespace export} msg2] $msg2 \
+            [catch {namespace import ::ns1::foo} msg3] $msg3
+    }
+} -cleanup {
+    namespace delete ns1
+} -ok {1 {namespace "::ns2" not found} 1 {namespace "::ns2" not found} 1 {namespace "::ns2" not found}}
+
+test namespace-7.1 {apply with a namespace} -setup {
+    namespace eval ns1 {proc helper {} {return helped}}
+} -body {
+    apply {{} {list [namespace current] [helper]} ns1}
+} -cleanup {
+    namespace delete ns1
+} -ok {::ns1 helped}
+
+test namespace-7.2 {apply with unknown namespace} {
+    apply {{} {} nosuch}
+} -error {namespace "::nosuch" not found}
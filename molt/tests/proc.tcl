@@ -118,3 +118,38 @@ test proc-4.4 {defined proc} -body {
 } -cleanup {
     rename myproc ""
 } -ok {args 1 b 2 a 3}
+
+test proc-5.1 {return -code break propagates to the caller} -setup {
+    proc brk {} {
+        return -code break
+    }
+} -body {
+    set count 0
+    while {1} {
+        incr count
+        if {$count == 2} { brk }
+    }
+    set count
+} -cleanup {
+    rename brk ""
+} -ok {2}
+
+test proc-5.2 {break in a proc body is an error} -setup {
+    proc brk {} {
+        break
+    }
+} -body {
+    brk
+} -cleanup {
+    rename brk ""
+} -error {invoked "break" outside of a loop}
+
+test proc-5.3 {wrong # args leaves the caller's scope current} -setup {
+    proc needs_arg {a} {}
+} -body {
+    set x 1
+    catch {needs_arg}
+    set x
+} -cleanup {
+    rename needs_arg ""
+} -ok {1}
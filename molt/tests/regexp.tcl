@@ -0,0 +1,101 @@
+# Test Script: regexp command
+
+test regexp-1.1 {regexp errors} {
+    regexp
+} -error {wrong # args: should be "regexp ?-switch ...? exp string ?matchVar? ?subMatchVar ...?"}
+
+test regexp-1.2 {missing string} {
+    regexp -nocase abc
+} -error {wrong # args: should be "regexp ?-switch ...? exp string ?matchVar? ?subMatchVar ...?"}
+
+test regexp-1.3 {bad switch} {
+    regexp -bogus a b
+} -error {bad switch "-bogus": must be -all, -indices, -inline, -nocase, -start, or --}
+
+test regexp-1.4 {variables with -inline} {
+    regexp -inline a b c
+} -error {regexp match variables not allowed when using -inline}
+
+test regexp-1.5 {bad patterns} {
+    list [catch {regexp {a(b} x} msg1] $msg1 [catch {regexp {a**} x} msg2] $msg2
+} -ok {1 {couldn't compile regular expression pattern: parentheses () not balanced} 1 {couldn't compile regular expression pattern: quantifier operand invalid}}
+
+test regexp-1.6 {backreferences} {
+    regexp {(a)\1} aa
+} -error {couldn't compile regular expression pattern: backreferences are not supported}
+
+test regexp-1.7 {deeply nested groups} {
+    regexp [string repeat ( 20000]a[string repeat ) 20000] a
+} -error {couldn't compile regular expression pattern: regular expression is too complex}
+
+test regexp-2.1 {simple matches} {
+    list [regexp {b+} abbbc] [regexp {^b} abc] [regexp {c$} abc]
+} -ok {1 0 1}
+
+test regexp-2.2 {match variables} {
+    set count [regexp {(\w+)@(\w+)\.com} "mail joe@example.com now" all user host]
+    list $count $all $user $host
+} -ok {1 joe@example.com joe example}
+
+test regexp-2.3 {unmatched groups and extra variables} {
+    regexp {(a)|(b)} b all first second extra
+    list $all $first $second $extra
+} -ok {b {} b {}}
+
+test regexp-2.4 {variables are untouched on failure} {
+    set all old
+    list [regexp {x} abc all] $all
+} -ok {0 old}
+
+test regexp-2.5 {-nocase} {
+    list [regexp -nocase {^HELLO} "hello world"] [regexp {^HELLO} "hello world"]
+} -ok {1 0}
+
+test regexp-2.6 {classes and escapes} {
+    regexp -inline {[[:alpha:]]+\s\d{2,3}} "id: abc 1234"
+} -ok {{abc 123}}
+
+test regexp-2.7 {non-greedy quantifiers} {
+    regexp -inline {<.+?>} "<a><b>"
+} -ok {<a>}
+
+test regexp-2.8 {-- ends switches} {
+    regexp -- -x a-xb
+} -ok {1}
+
+test regexp-3.1 {-indices} {
+    regexp -indices {b(c+)(x)?} abccd all sub missing
+    list $all $sub $missing
+} -ok {{1 3} {2 3} {-1 -1}}
+
+test regexp-3.2 {-inline} {
+    regexp -inline {(\d+)-(\d+)} "from 10-20"
+} -ok {10-20 10 20}
+
+test regexp-3.3 {-inline, no match} {
+    regexp -inline {\d} abc
+} -ok {}
+
+test regexp-3.4 {-all} {
+    list [regexp -all {\d+} "a1 b22 c333"] [regexp -all -inline {\d+} "a1 b22 c333"]
+} -ok {3 {1 22 333}}
+
+test regexp-3.5 {-all with variables gets the last match} {
+    regexp -all {(\d)(\w)} "1a 2b 3c" all digit letter
+    list $all $digit $letter
+} -ok {3c 3 c}
+
+test regexp-3.6 {-all with empty matches} {
+    regexp -all -inline {x*} ab
+} -ok {{} {} {}}
+
+test regexp-3.7 {-start} {
+    list [regexp -inline -start 2 {\d} "12345"] [regexp -indices -start end-1 {\d} "12345" idx] $idx \
+        [regexp -start 1 {^2} "12"]
+} -ok {3 1 {3 3} 0}
+
+test regexp-3.8 {log filter example} {
+    set line "2024-05-01 12:00:03 ERROR pump 3: pressure 4.2 bar"
+    regexp {(ERROR|WARN) (\w+) (\d+): (.*)$} $line -> level device unit message
+    list $level $device $unit $message
+} -ok {ERROR pump 3 {pressure 4.2 bar}}
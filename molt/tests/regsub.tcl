@@ -0,0 +1,62 @@
+# Test Script: regsub command
+
+test regsub-1.1 {regsub errors} {
+    regsub a b
+} -error {wrong # args: should be "regsub ?-switch ...? exp string subSpec ?varName?"}
+
+test regsub-1.2 {too many arguments} {
+    regsub -all a b c d e
+} -error {wrong # args: should be "regsub ?-switch ...? exp string subSpec ?varName?"}
+
+test regsub-1.3 {bad switch} {
+    regsub -bogus a b c
+} -error {bad switch "-bogus": must be -all, -nocase, -start, or --}
+
+test regsub-1.4 {bad pattern} {
+    regsub {[a} b c
+} -error {couldn't compile regular expression pattern: brackets [] not balanced}
+
+test regsub-2.1 {first match only} {
+    regsub {o} "foo boo" 0
+} -ok {f0o boo}
+
+test regsub-2.2 {-all} {
+    regsub -all {o} "foo boo" 0
+} -ok {f00 b00}
+
+test regsub-2.3 {no match} {
+    regsub {x} "abc" y
+} -ok {abc}
+
+test regsub-2.4 {-nocase} {
+    regsub -all -nocase {a} "AbA" x
+} -ok {xbx}
+
+test regsub-2.5 {-start} {
+    regsub -start 2 {a} "aaaa" x
+} -ok {aaxa}
+
+test regsub-2.6 {empty matches} {
+    regsub -all {x*} abc -
+} -ok {-a-b-c-}
+
+test regsub-3.1 {& and \0} {
+    regsub -all {\d+} "a1 b22" {<&|\0>}
+} -ok {a<1|1> b<22|22>}
+
+test regsub-3.2 {submatches} {
+    regsub {(\w+) (\w+)} "hello world" {\2 \1}
+} -ok {world hello}
+
+test regsub-3.3 {escaped & and backslash} {
+    regsub {b} abc {\&\\}
+} -ok {a&\c}
+
+test regsub-3.4 {unmatched submatch} {
+    regsub {(x)?b} abc {[\1]}
+} -ok {a[]c}
+
+test regsub-4.1 {variable} {
+    set count [regsub -all {\s+} "a  b   c" " " result]
+    list $count $result
+} -ok {2 {a b c}}
@@ -0,0 +1,128 @@
+# Test Script: scan command
+
+test scan-1.1 {scan errors} {
+    scan
+} -error {wrong # args: should be "scan string format ?varName ...?"}
+
+test scan-1.2 {bad conversion character} {
+    scan abc %y
+} -error {bad scan conversion character "y"}
+
+test scan-1.3 {unmatched %} {
+    scan abc "abc %"
+} -error {unmatched % in format string}
+
+test scan-1.4 {unmatched [} {
+    scan abc {%[abc}
+} -error {unmatched [ in format string}
+
+test scan-1.5 {width on %c} {
+    scan abc %2c
+} -error {field width may not be specified in %c conversion}
+
+test scan-1.6 {mixed XPG and sequential specifiers} {
+    scan "1 2" "%1\$d %d"
+} -error {cannot mix "%" and "%n$" conversion specifiers}
+
+test scan-1.7 {variable count mismatch} {
+    scan "1 2" "%d %d" a
+} -error {different numbers of variable names and field specifiers}
+
+test scan-1.8 {bad XPG index} {
+    scan "1" "%0\$d"
+} -error {"%n$" argument index out of range}
+
+test scan-1.9 {huge XPG index} {
+    list \
+        [catch {scan abc "%9223372036854775807\$s"} msg1] $msg1 \
+        [catch {scan abc "%9223372036854775807\$s" a} msg2] $msg2 \
+        [catch {scan "1 2" "%2\$d %1\$d" a} msg3] $msg3
+} -ok {1 {variable is not assigned by any conversion specifiers} 1 {"%n$" argument index out of range} 1 {"%n$" argument index out of range}}
+
+test scan-2.1 {inline integers} {
+    scan "12 -34 +5" "%d %d %d"
+} -ok {12 -34 5}
+
+test scan-2.2 {inline radixes} {
+    scan "ff 17 101" "%x %o %b"
+} -ok {255 15 5}
+
+test scan-2.3 {inline characters and strings} {
+    scan "A hello world" "%c %s %s"
+} -ok {65 hello world}
+
+test scan-2.4 {field widths} {
+    scan "12345abcdef" "%3d%2d%3s%s"
+} -ok {123 45 abc def}
+
+test scan-2.5 {literals} {
+    scan "T=23 H=40" "T=%d H=%d"
+} -ok {23 40}
+
+test scan-2.6 {character sets} {
+    scan "abc123def" {%[a-z]%[0-9]%[^0-9]}
+} -ok {abc 123 def}
+
+test scan-2.7 {right bracket in set} {
+    lindex [scan "]]x" {%[]]}] 0
+} -ok {]]}
+
+test scan-2.8 {suppressed conversions} {
+    scan "1 2 3" "%*d %d %*d"
+} -ok {2}
+
+test scan-2.9 {count of characters} {
+    scan "abc 12" "%s %n%d"
+} -ok {abc 4 12}
+
+test scan-2.10 {percent literal} {
+    scan "50%" "%d%%"
+} -ok {50}
+
+test scan-2.11 {XPG positions} {
+    scan "a b" {%2$s %1$s}
+} -ok {b a}
+
+test scan-2.12 {mismatch leaves empty values} {
+    scan "12 abc" "%d %d"
+} -ok {12 {}}
+
+test scan-2.13 {empty input} {
+    scan "" "%d"
+} -ok {}
+
+test scan-2.14 {radix prefixes} {
+    list [scan 0x1F %d] [scan 0x1F %i] [scan 0x12 %x] [scan 0x %d%s]
+} -ok {0 31 18 {0 x}}
+
+test scan-2.15 {%i radix prefixes} {
+    list [scan 012 %i] [scan 0x1f %i] [scan 0b101 %i] [scan -012 %i] [scan 12 %i] [scan 09 %i%s]
+} -ok {10 31 5 -10 12 {0 9}}
+
+test scan-3.1 {variables} {
+    set count [scan "T=23 H=40" "T=%d H=%d" t h]
+    list $count $t $h
+} -ok {2 23 40}
+
+test scan-3.2 {unassigned variables are left alone} {
+    set b old
+    set count [scan "12 abc" "%d %d" a b]
+    list $count $a $b
+} -ok {1 12 old}
+
+test scan-3.3 {underflow before first conversion} {
+    scan "   " "%d" a
+} -ok {-1}
+
+test scan-3.4 {no match} {
+    scan "abc" "%d" a
+} -ok {0}
+
+test scan-4.1 {floats} {
+    scan "T=23.5 H=40" "T=%f H=%d" t h
+    list $t $h
+} -ok {23.5 40}
+
+test scan-4.2 {float exponents} {
+    scan "1.5e3 -2" "%e %g"
+} -ok {1500 -2}
@@ -0,0 +1,53 @@
+# Test Script: split command
+
+test split-1.1 {split errors} {
+    split
+} -error {wrong # args: should be "split string ?splitChars?"}
+
+test split-1.2 {split errors} {
+    split a b c
+} -error {wrong # args: should be "split string ?splitChars?"}
+
+test split-2.1 {split on white space} {
+    split "a b\tc\nd"
+} -ok {a b c d}
+
+test split-2.2 {adjacent separators give empty elements} {
+    split " a  b "
+} -ok {{} a {} b {}}
+
+test split-2.3 {empty string} {
+    list [split ""] [split "" ,] [split "" {}]
+} -ok {{} {} {}}
+
+test split-3.1 {split on a character} {
+    split a,b,,c ,
+} -ok {a b {} c}
+
+test split-3.2 {split on a set of characters} {
+    split "a,b;c:d" ",;:"
+} -ok {a b c d}
+
+test split-3.3 {no separators found} {
+    split abc ,
+} -ok {abc}
+
+test split-3.4 {elements needing quoting} {
+    split "a b,c\{d" ,
+} -ok {{a b} c\{d}
+
+test split-3.5 {Unicode separators} {
+    split αβγβδ β
+} -ok {α γ δ}
+
+test split-4.1 {split into characters} {
+    split abc {}
+} -ok {a b c}
+
+test split-4.2 {split into characters: Unicode and spaces} {
+    split "カ タ" {}
+} -ok {カ { } タ}
+
+test split-5.1 {result is a list} {
+    llength [split a,b,c ,]
+} -ok {3}
@@ -143,6 +143,10 @@ test string-9.11 {string first: startIndex with Unicode} {
     string first б абв 1
 } -ok 1
 
+test string-9.12 {string first: character index, not byte index} {
+    list [string first c abc] [string first c abcdefc 1] [string first в абвгд]
+} -ok {2 2 2}
+
 # string trim
 test string-10.1 {string trim: empty} {
     string trim {}
@@ -357,3 +361,290 @@ test string-15.10 {string range: Unicode 1} {
 test string-15.11 {string range: Unicode 2} {
     string range カタカナ 2 3
 } -ok カナ
+
+test string-15.12 {string range: end indices} {
+    list [string range abcdef 2 end] [string range abcdef end-2 end-1] \
+        [string range abcdef 0 end+5] [string range abcdef 1+1 5-2]
+} -ok {cdef de abcdef cd}
+
+test string-15.13 {string range: first > last} {
+    string range abcdef 4 2
+} -ok {}
+
+test string-15.14 {string range: bad index} {
+    string range abcdef 0 bogus
+} -error {bad index "bogus": must be integer?[+-]integer? or end?[+-]integer?}
+
+test string-16.1 {string match: syntax} {
+    string match a
+} -error {wrong # args: should be "string match ?-nocase? pattern string"}
+
+test string-16.2 {string match: bad option} {
+    string match -bogus a a
+} -error {bad option "-bogus": must be -nocase}
+
+test string-16.3 {string match: literal} {
+    list [string match abc abc] [string match abc abd]
+} -ok {1 0}
+
+test string-16.4 {string match: star} {
+    list [string match a*c abbbc] [string match *c abc] [string match a* b]
+} -ok {1 1 0}
+
+test string-16.5 {string match: question mark} {
+    list [string match a?c abc] [string match a?c ac]
+} -ok {1 0}
+
+test string-16.6 {string match: character sets} {
+    list [string match {[a-c]x} bx] [string match {[abc]x} dx]
+} -ok {1 0}
+
+test string-16.7 {string match: escapes} {
+    list [string match {a\*} a*] [string match {a\*} ab]
+} -ok {1 0}
+
+test string-16.8 {string match: -nocase} {
+    list [string match -nocase A*C abc] [string match A*C abc]
+} -ok {1 0}
+
+test string-16.9 {string match: Unicode} {
+    string match カ?カ* カタカナ
+} -ok {1}
+
+test string-17.1 {string is: signature} {
+    string is alpha
+} -error {wrong # args: should be "string is class ?-strict? ?-failindex var? str"}
+
+test string-17.2 {string is: bad class} {
+    string is bogus abc
+} -error {bad class "bogus": must be alnum, alpha, ascii, boolean, control, dict, digit, double, entier, false, graph, integer, list, lower, print, punct, space, true, upper, wideinteger, wordchar, or xdigit}
+
+test string-17.3 {string is: bad option} {
+    string is alpha -bogus abc
+} -error {bad option "-bogus": must be -strict or -failindex}
+
+test string-17.4 {string is: missing failindex var} {
+    string is alpha -strict -failindex abc
+} -error {wrong # args: should be "string is class ?-strict? ?-failindex var? str"}
+
+test string-17.5 {string is: character classes} {
+    list \
+        [string is alnum abc123] [string is alnum abc-1] \
+        [string is alpha abc] [string is alpha ab1] \
+        [string is digit 123] [string is digit 12a] \
+        [string is space " \t\n"] [string is space " x"] \
+        [string is upper ABC] [string is upper AbC] \
+        [string is lower abc] [string is lower aBc] \
+        [string is xdigit 09afAF] [string is xdigit 0g] \
+        [string is wordchar ab_1] [string is wordchar a-b] \
+        [string is punct "!?."] [string is punct "!a"] \
+        [string is ascii abc] [string is ascii abcλ] \
+        [string is control "\n\t"] [string is control "\na"] \
+        [string is print "a b"] [string is print "a\n"] \
+        [string is graph "ab"] [string is graph "a b"]
+} -ok {1 0 1 0 1 0 1 0 1 0 1 0 1 0 1 0 1 0 1 0 1 0 1 0 1 0}
+
+test string-17.6 {string is: value classes} {
+    list \
+        [string is integer 123] [string is integer 1.5] \
+        [string is wideinteger -5] [string is entier 0x10] \
+        [string is double 1.5] [string is double abc] \
+        [string is boolean yes] [string is boolean maybe] \
+        [string is true on] [string is true off] \
+        [string is false 0] [string is false 1] \
+        [string is list {a {b c}}] [string is list "a \{b"] \
+        [string is dict {a 1 b 2}] [string is dict {a 1 b}]
+} -ok {1 0 1 1 1 0 1 0 1 0 1 0 1 0 1 0}
+
+test string-17.7 {string is: empty string} {
+    list [string is alpha ""] [string is integer ""] \
+        [string is alpha -strict ""] [string is integer -strict ""] \
+        [string is list -strict ""] [string is dict -strict ""]
+} -ok {1 1 0 0 1 1}
+
+test string-17.8 {string is: -failindex} {
+    set a [string is alpha -failindex idx abc1d]
+    list $a $idx
+} -ok {0 3}
+
+test string-17.9 {string is: -failindex, chars not bytes} {
+    string is alpha -failindex idx λμ1
+    set idx
+} -ok {2}
+
+test string-17.10 {string is: -failindex not set on success} {
+    set idx unset
+    string is alpha -failindex idx abc
+    set idx
+} -ok {unset}
+
+test string-17.11 {string is: -failindex, -strict empty} {
+    string is digit -strict -failindex idx ""
+    set idx
+} -ok {0}
+
+test string-17.12 {string is: -failindex, numbers} {
+    set result {}
+    foreach {class str} {
+        integer 12a integer 1.5 integer {12 a} integer abc integer 99999999999999999999
+        double 1.5x double x1.5 wideinteger -7z entier 0x1fg boolean yesno boolean maybe
+    } {
+        string is $class -failindex idx $str
+        lappend result $idx
+    }
+    set result
+} -ok {2 1 3 0 -1 3 0 2 4 3 0}
+
+test string-17.13 {string is: -failindex, lists} {
+    set result {}
+    foreach str [list "a b \{c" "\{a\}b c" "a \"b" "λ μ \{c" "a b"] {
+        lappend result [string is list -failindex idx $str] $idx
+    }
+    set result
+} -ok {0 4 0 0 0 2 0 4 1 4}
+
+# string index
+test string-18.1 {string index: syntax} {
+    string index abc
+} -error {wrong # args: should be "string index string charIndex"}
+
+test string-18.2 {string index: basic} {
+    list [string index abc 0] [string index abc 2] [string index abc 3] [string index abc -1]
+} -ok {a c {} {}}
+
+test string-18.3 {string index: end} {
+    list [string index abc end] [string index abc end-1] [string index abc end-3] \
+        [string index abc end+1] [string index {} end]
+} -ok {c b {} {} {}}
+
+test string-18.4 {string index: integer arithmetic} {
+    list [string index abcdef 1+2] [string index abcdef 5-1]
+} -ok {d e}
+
+test string-18.5 {string index: Unicode} {
+    list [string index カタカナ 1] [string index カタカナ end]
+} -ok {タ ナ}
+
+test string-18.6 {string index: bad index} {
+    string index abc end-x
+} -error {bad index "end-x": must be integer?[+-]integer? or end?[+-]integer?}
+
+test string-18.7 {string index: overflowing arithmetic} {
+    list [string index abc end+9223372036854775807] [string index abc -2-9223372036854775807] \
+        [lrange {a b c} 0 end+9223372036854775807]
+} -ok {{} {} {a b c}}
+
+# string replace
+test string-19.1 {string replace: syntax} {
+    string replace abc 1
+} -error {wrong # args: should be "string replace string first last ?newstring?"}
+
+test string-19.2 {string replace: delete} {
+    string replace abcdef 1 3
+} -ok {aef}
+
+test string-19.3 {string replace: replace} {
+    string replace abcdef 1 3 XY
+} -ok {aXYef}
+
+test string-19.4 {string replace: end indices} {
+    list [string replace abcdef end-1 end XY] [string replace abcdef 0 end]
+} -ok {abcdXY {}}
+
+test string-19.5 {string replace: clamped range} {
+    string replace abcdef -5 1 X
+} -ok {Xcdef}
+
+test string-19.6 {string replace: empty range} {
+    list [string replace abc 2 1 X] [string replace abc 3 5 X] [string replace abc -3 -1 X]
+} -ok {abc abc abc}
+
+test string-19.7 {string replace: Unicode} {
+    string replace カタカナ 1 2 ー
+} -ok {カーナ}
+
+# string repeat
+test string-20.1 {string repeat: syntax} {
+    string repeat abc
+} -error {wrong # args: should be "string repeat string count"}
+
+test string-20.2 {string repeat: basic} {
+    list [string repeat ab 3] [string repeat ab 1] [string repeat ab 0] [string repeat ab -1]
+} -ok {ababab ab {} {}}
+
+test string-20.3 {string repeat: bad count} {
+    string repeat ab x
+} -error {expected integer but got "x"}
+
+test string-20.4 {string repeat: result too large} {
+    list \
+        [catch {string repeat abc 9223372036854775807} msg1] $msg1 \
+        [catch {string repeat a 4611686018427387904} msg2] $msg2 \
+        [string repeat "" 9223372036854775807]
+} -ok {1 {result of string repeat would be too large} 1 {result of string repeat would be too large} {}}
+
+# string reverse
+test string-21.1 {string reverse: syntax} {
+    string reverse
+} -error {wrong # args: should be "string reverse string"}
+
+test string-21.2 {string reverse: basic} {
+    list [string reverse abc] [string reverse {}] [string reverse カタカナ]
+} -ok {cba {} ナカタカ}
+
+# string totitle
+test string-22.1 {string totitle: syntax} {
+    string totitle
+} -error {wrong # args: should be "string totitle string ?first? ?last?"}
+
+test string-22.2 {string totitle: basic} {
+    list [string totitle hELLO] [string totitle {}] [string totitle {hello world}]
+} -ok {Hello {} {Hello world}}
+
+test string-22.3 {string totitle: first} {
+    string totitle hello 2
+} -ok {heLlo}
+
+test string-22.4 {string totitle: first and last} {
+    list [string totitle HELLO 1 3] [string totitle hello end-1 end]
+} -ok {HEllO helLo}
+
+test string-22.5 {string totitle: empty range} {
+    string totitle hello 3 1
+} -ok {hello}
+
+# string wordend
+test string-23.1 {string wordend: syntax} {
+    string wordend abc
+} -error {wrong # args: should be "string wordend string index"}
+
+test string-23.2 {string wordend: basic} {
+    list [string wordend {hello world} 0] [string wordend {hello world} 2] \
+        [string wordend {hello world} 5] [string wordend {hello world} end]
+} -ok {5 5 6 11}
+
+test string-23.3 {string wordend: out of range} {
+    list [string wordend abc -1] [string wordend abc 10] [string wordend {} 0]
+} -ok {3 3 0}
+
+test string-23.4 {string wordend: underscores and Unicode} {
+    list [string wordend {a_b1 c} 1] [string wordend {αβ γ} 0]
+} -ok {4 2}
+
+# string wordstart
+test string-24.1 {string wordstart: syntax} {
+    string wordstart abc
+} -error {wrong # args: should be "string wordstart string index"}
+
+test string-24.2 {string wordstart: basic} {
+    list [string wordstart {hello world} 3] [string wordstart {hello world} 5] \
+        [string wordstart {hello world} 8] [string wordstart {hello world} end]
+} -ok {0 5 6 6}
+
+test string-24.3 {string wordstart: out of range} {
+    list [string wordstart abc -1] [string wordstart {ab cd} 10] [string wordstart {} 0]
+} -ok {0 3 0}
+
+test string-24.4 {string wordstart: Unicode} {
+    string wordstart {αβ γδ} 4
+} -ok {3}
@@ -0,0 +1,77 @@
+# Test Script: subst command
+
+test subst-1.1 {subst errors} {
+    subst
+} -error {wrong # args: should be "subst ?-nobackslashes? ?-nocommands? ?-novariables? string"}
+
+test subst-1.2 {bad switch} {
+    subst -bogus abc
+} -error {bad switch "-bogus": must be -nobackslashes, -nocommands, or -novariables}
+
+test subst-1.3 {missing close-bracket} {
+    subst {a [list b}
+} -error {missing close-bracket}
+
+test subst-1.4 {errors in command substitutions propagate} {
+    subst {a [error oops] b}
+} -error {oops}
+
+test subst-1.5 {missing close-paren} {
+    list [catch {subst {$x(}} r1] $r1 [catch {subst {a $x(1 b}} r2] $r2
+} -ok {1 {missing )} 1 {missing )}}
+
+test subst-2.1 {all substitutions} {
+    set name pump
+    set a(x) 3
+    subst {$name $a(x) [string toupper $name]\t"quoted" {braced}}
+} -ok "pump 3 PUMP\t\"quoted\" {braced}"
+
+test subst-2.2 {-nobackslashes} {
+    set x 1
+    subst -nobackslashes {$x\n[set x]}
+} -ok {1\n1}
+
+test subst-2.3 {-nocommands} {
+    set x 1
+    subst -nocommands {$x\t[set x]}
+} -ok "1\t\[set x\]"
+
+test subst-2.4 {-novariables} {
+    set x 1
+    subst -novariables {$x\t[set x]}
+} -ok "\$x\t1"
+
+test subst-2.5 {all disabled} {
+    subst -nobackslashes -nocommands -novariables {$x\t[set x]}
+} -ok {$x\t[set x]}
+
+test subst-2.6 {plain text and lone dollar} {
+    subst {costs $.50}
+} -ok {costs $.50}
+
+test subst-3.1 {break ends the substitution} {
+    subst {a[set x 1]b[break]c[set x 2]}
+} -ok {a1b}
+
+test subst-3.2 {continue substitutes the empty string} {
+    subst {a[continue]b}
+} -ok {ab}
+
+test subst-3.3 {return substitutes its value} {
+    subst {a[return x]b}
+} -ok {axb}
+
+test subst-4.1 {repeated rendering of a template} {
+    set template {Pump $unit: $pressure bar}
+    set result {}
+    foreach {unit pressure} {1 4.2 2 3.9} {
+        lappend result [subst $template]
+    }
+    set result
+} -ok {{Pump 1: 4.2 bar} {Pump 2: 3.9 bar}}
+
+test subst-4.2 {the same template with different switches} {
+    set template {$x [set x]}
+    set x 1
+    list [subst $template] [subst -nocommands $template] [subst $template]
+} -ok {{1 1} {1 [set x]} {1 1}}
@@ -0,0 +1,192 @@
+# Test Script: switch command.
+
+test switch-1.1 {switch errors} {
+    switch
+} -error {wrong # args: should be "switch ?-option ...? string ?pattern body ...? ?default body?"}
+
+test switch-1.2 {switch errors} {
+    switch a
+} -error {wrong # args: should be "switch ?-option ...? string ?pattern body ...? ?default body?"}
+
+test switch-1.3 {switch errors} {
+    switch -foo a b c
+} -error {bad option "-foo": must be -exact, -glob, -nocase, -regexp, or --}
+
+test switch-1.4 {switch errors} {
+    switch a b c d
+} -error {extra switch pattern with no body}
+
+test switch-1.5 {switch errors} {
+    switch a {b c d}
+} -error {extra switch pattern with no body}
+
+test switch-1.6 {switch errors} {
+    switch a b - c -
+} -error {no body specified for pattern "c"}
+
+test switch-1.7 {switch errors} {
+    switch a {}
+} -error {wrong # args: should be "switch ?-option ...? string {?pattern body ...? ?default body?}"}
+
+test switch-2.1 {exact match, separate arguments} {
+    switch b a {set x 1} b {set x 2} c {set x 3}
+} -ok {2}
+
+test switch-2.2 {exact match, braced list} {
+    switch c {
+        a {set x 1}
+        b {set x 2}
+        c {set x 3}
+    }
+} -ok {3}
+
+test switch-2.3 {no match} {
+    switch d {
+        a {set x 1}
+        b {set x 2}
+    }
+} -ok {}
+
+test switch-2.4 {default} {
+    switch d {
+        a {set x 1}
+        default {set x default}
+    }
+} -ok {default}
+
+test switch-2.5 {default only matches as the last pattern} {
+    switch default {
+        default {set x first}
+        b {set x 2}
+    }
+} -ok {first}
+
+test switch-2.6 {default is literal unless last} {
+    switch a {
+        default {set x first}
+        b {set x 2}
+    }
+} -ok {}
+
+test switch-2.7 {-exact} {
+    switch -exact a* {
+        a* {set x star}
+        default {set x default}
+    }
+} -ok {star}
+
+test switch-2.8 {-exact doesn't glob} {
+    switch -exact abc {
+        a* {set x star}
+        default {set x default}
+    }
+} -ok {default}
+
+test switch-2.9 {first match wins} {
+    switch a a {set x 1} a {set x 2}
+} -ok {1}
+
+test switch-3.1 {-glob} {
+    switch -glob abc {
+        x* {set x 1}
+        a* {set x 2}
+        default {set x 3}
+    }
+} -ok {2}
+
+test switch-3.2 {-glob with sets and ?} {
+    switch -glob cmd42 {
+        cmd[0-9] {set x 1}
+        cmd[0-9][0-9] {set x 2}
+        default {set x 3}
+    }
+} -ok {2}
+
+test switch-4.1 {-- ends options} {
+    switch -- -glob {
+        -glob {set x 1}
+        default {set x 2}
+    }
+} -ok {1}
+
+test switch-4.2 {options are only parsed if followed by two args} {
+    switch -glob {
+        -glob {set x 1}
+    }
+} -ok {1}
+
+test switch-5.1 {fallthrough} {
+    switch b {
+        a -
+        b -
+        c {set x abc}
+        default {set x other}
+    }
+} -ok {abc}
+
+test switch-5.2 {fallthrough to default} {
+    switch a {
+        a -
+        default {set x fell}
+    }
+} -ok {fell}
+
+test switch-6.1 {body is evaluated in the caller's scope} -setup {
+    proc doit {value} {
+        switch $value {
+            one {set result 1}
+            two {set result 2}
+        }
+        set result
+    }
+} -body {
+    doit two
+} -cleanup {
+    rename doit ""
+} -ok {2}
+
+test switch-6.2 {break propagates out of switch} {
+    set result {}
+    foreach x {a b c} {
+        switch $x {
+            b {break}
+            default {lappend result $x}
+        }
+    }
+    set result
+} -ok {a}
+
+test switch-7.1 {-nocase, exact} {
+    switch -nocase ABC {
+        abc {set x 1}
+        default {set x 2}
+    }
+} -ok {1}
+
+test switch-7.2 {-nocase, glob} {
+    switch -nocase -glob ABC {
+        a* {set x 1}
+        default {set x 2}
+    }
+} -ok {1}
+
+test switch-8.1 {-regexp} {
+    switch -regexp abc123 {
+        {^[a-z]+$} {set x letters}
+        {^[a-z]+\d+$} {set x mixed}
+        default {set x other}
+    }
+} -ok {mixed}
+
+test switch-8.2 {-regexp -nocase} {
+    switch -regexp -nocase ABC {
+        {^a} {set x 1}
+        default {set x 2}
+    }
+} -ok {1}
+
+test switch-8.3 {-regexp, bad pattern} {
+    switch -regexp abc {
+        {(a} {set x 1}
+    }
+} -error {couldn't compile regular expression pattern: parentheses () not balanced}
@@ -0,0 +1,191 @@
+# Test Script: tailcall command
+
+test tailcall-1.1 {tailcall errors} {
+    tailcall
+} -error {wrong # args: should be "tailcall command ?arg ...?"}
+
+test tailcall-1.2 {tailcall outside a proc} {
+    tailcall set x 1
+} -error {tailcall can only be called from a proc or lambda}
+
+test tailcall-1.3 {tailcall of an unknown command} -setup {
+    proc myproc {} {tailcall nosuch}
+} -body {
+    myproc
+} -cleanup {
+    rename myproc ""
+} -error {invalid command name "nosuch"}
+
+test tailcall-2.1 {tailcall replaces the proc's result} -setup {
+    proc myproc {} {
+        tailcall list a b
+        return notreached
+    }
+} -body {
+    myproc
+} -cleanup {
+    rename myproc ""
+} -ok {a b}
+
+test tailcall-2.2 {tailcall pops the proc's scope} -setup {
+    proc myproc {} {
+        set x local
+        tailcall set x caller
+    }
+} -body {
+    set x global
+    list [myproc] $x
+} -ok {caller caller}
+
+test tailcall-2.3 {tail recursion runs in constant stack space} -setup {
+    proc count {n acc} {
+        if {$n == 0} {
+            return $acc
+        }
+        tailcall count [expr {$n - 1}] [expr {$acc + 2}]
+    }
+} -body {
+    count 5000 0
+} -cleanup {
+    rename count ""
+} -ok {10000}
+
+test tailcall-2.4 {mutual tail recursion} -setup {
+    proc even {n} {
+        if {$n == 0} {return 1}
+        tailcall odd [expr {$n - 1}]
+    }
+    proc odd {n} {
+        if {$n == 0} {return 0}
+        tailcall even [expr {$n - 1}]
+    }
+} -body {
+    list [even 3000] [odd 3000] [even 3001]
+} -cleanup {
+    rename even ""
+    rename odd ""
+} -ok {1 0 0}
+
+test tailcall-2.5 {tail-called commands run in the caller's scope} {
+    apply {{x} {tailcall expr {$x * 2}}} 21
+} -error {can't read "x": no such variable}
+
+test tailcall-2.6 {tailcall from a lambda with arguments} {
+    apply {{x} {tailcall list $x [expr {$x * 2}]}} 21
+} -ok {21 42}
+
+test tailcall-2.7 {tailcall errors propagate} -setup {
+    proc myproc {} {tailcall error oops}
+} -body {
+    myproc
+} -cleanup {
+    rename myproc ""
+} -error {oops}
+
+test tailcall-2.8 {caught tailcall still runs when the proc returns} -setup {
+    proc myproc {} {
+        set code [catch {tailcall list tail}]
+        lappend ::log $code
+        return body
+    }
+} -body {
+    set ::log {}
+    list [myproc] $::log
+} -cleanup {
+    rename myproc ""
+    unset ::log
+} -ok {tail 2}
+
+test tailcall-2.9 {nested procs keep their own tail calls} -setup {
+    proc inner {} {return inner}
+    proc outer {} {
+        catch {tailcall list outer}
+        inner
+    }
+} -body {
+    outer
+} -cleanup {
+    rename inner ""
+    rename outer ""
+} -ok {outer}
+
+test tailcall-3.1 {tail-called procs replace the call frame} -setup {
+    proc first {} {tailcall second a b}
+    proc second {args} {list [info level] [info level 0]}
+} -body {
+    lassign [first] level argv
+    list [expr {$level - [info level]}] $argv
+} -cleanup {
+    rename first ""
+    rename second ""
+} -ok {1 {second a b}}
+
+test tailcall-3.2 {tailcall resolves the command in the proc's namespace} -setup {
+    namespace eval ns1 {
+        proc helper {} {return helped}
+        proc caller {} {tailcall helper}
+    }
+} -body {
+    ns1::caller
+} -cleanup {
+    namespace delete ns1
+} -ok {helped}
+
+test tailcall-3.3 {tail-called commands fire execution traces} -setup {
+    proc first {} {tailcall second}
+    proc second {} {return second}
+    proc logger {args} {lappend ::log [lindex $args end]}
+    trace add execution second {enter leave} logger
+} -body {
+    set ::log {}
+    list [first] $::log
+} -cleanup {
+    rename first ""
+    rename second ""
+    rename logger ""
+    unset ::log
+} -ok {second {enter leave}}
+
+test tailcall-4.1 {tail recursion through apply runs in constant stack space} -setup {
+    set ::f {{n} {
+        if {$n == 0} {return done}
+        tailcall apply $::f [expr {$n - 1}]
+    }}
+} -body {
+    apply $::f 100000
+} -cleanup {
+    unset ::f
+} -ok {done}
+
+test tailcall-4.2 {tail-called lambdas replace the call frame} -setup {
+    proc first {} {tailcall apply {{} {info level 0}}}
+} -body {
+    first
+} -cleanup {
+    rename first ""
+} -ok {apply {{} {info level 0}}}
+
+test tailcall-4.3 {traced tail recursion is limited by the recursion limit} -setup {
+    proc tr {n} {
+        if {$n == 0} {return done}
+        tailcall tr [expr {$n - 1}]
+    }
+    proc logger {args} {}
+    trace add execution tr enter logger
+} -body {
+    list [tr 100] [catch {tr 100000} msg] $msg
+} -cleanup {
+    rename tr ""
+    rename logger ""
+} -ok {done 1 {too many nested calls to Interp::eval (infinite loop?)}}
+
+test tailcall-4.4 {tail-called commands are limited by the recursion limit} -setup {
+    set ::f {{n} {
+        if {$n == 0} {return done}
+        tailcall eval [list apply $::f [expr {$n - 1}]]
+    }}
+} -body {
+    apply $::f 100000
+} -cleanup {
+    unset ::f
+} -error {too many nested calls to Interp::eval (infinite loop?)}
@@ -0,0 +1,426 @@
+# Test Script: trace command
+
+test trace-1.1 {trace errors} {
+    trace
+} -error {wrong # args: should be "trace subcommand ?arg ...?"}
+
+test trace-1.2 {trace add errors} {
+    trace add variable x
+} -error {wrong # args: should be "trace add type name opList command"}
+
+test trace-1.3 {bad trace type} {
+    trace add command x {read} foo
+} -error {bad option "command": must be execution or variable}
+
+test trace-1.4 {bad operation} {
+    trace add variable x {read append} foo
+} -error {bad operation "append": must be read, unset, or write}
+
+test trace-1.5 {empty operation list} {
+    trace add variable x {} foo
+} -error {bad operation list "": must be one or more of read, unset, or write}
+
+test trace-2.1 {write traces} -setup {
+    set ::log {}
+    proc logger {args} {lappend ::log $args}
+} -body {
+    trace add variable x write logger
+    set x 1
+    incr x
+    append x 0
+    set ::log
+} -cleanup {
+    unset x ::log
+    rename logger ""
+} -ok {{x {} write} {x {} write} {x {} write}}
+
+test trace-2.2 {read traces} -setup {
+    set ::log {}
+    proc logger {args} {lappend ::log $args}
+    set x 5
+} -body {
+    trace add variable x read logger
+    list $x [set x] $::log
+} -cleanup {
+    unset x ::log
+    rename logger ""
+} -ok {5 5 {{x {} read} {x {} read}}}
+
+test trace-2.3 {unset traces} -setup {
+    set ::log {}
+    proc logger {args} {lappend ::log $args}
+    set x 5
+} -body {
+    trace add variable x unset logger
+    unset x
+    list $::log [trace info variable x]
+} -cleanup {
+    unset ::log
+    rename logger ""
+} -ok {{{x {} unset}} {}}
+
+test trace-2.4 {unset traces don't fire for missing variables} -setup {
+    set ::log {}
+    proc logger {args} {lappend ::log $args}
+} -body {
+    trace add variable x unset logger
+    unset -nocomplain x
+    set ::log
+} -cleanup {
+    trace remove variable x unset logger
+    unset ::log
+    rename logger ""
+} -ok {}
+
+test trace-2.5 {read traces can set the value} -setup {
+    proc fetch {name index op} {
+        upvar 1 $name var
+        set var fetched
+    }
+} -body {
+    trace add variable x read fetch
+    set x
+} -cleanup {
+    unset x
+    rename fetch ""
+} -ok {fetched}
+
+test trace-2.6 {write traces can change the value} -setup {
+    proc clamp {name index op} {
+        upvar 1 $name var
+        if {$var > 10} {set var 10}
+    }
+} -body {
+    trace add variable x write clamp
+    list [set x 5] [set x 15] $x
+} -cleanup {
+    unset x
+    rename clamp ""
+} -ok {5 10 10}
+
+test trace-2.7 {trace errors} -setup {
+    proc readonly {args} {error "read-only"}
+    set x 1
+} -body {
+    trace add variable x write readonly
+    list [catch {set x 2} result] $result
+} -cleanup {
+    unset x
+    rename readonly ""
+} -ok {1 {can't set "x": read-only}}
+
+test trace-2.8 {read trace errors} -setup {
+    proc noread {args} {error "no access"}
+    set x 1
+} -body {
+    trace add variable x read noread
+    set x
+} -cleanup {
+    unset x
+    rename noread ""
+} -error {can't read "x": no access}
+
+test trace-2.9 {unset traces fire for locals when the proc returns} -setup {
+    set ::log {}
+    proc logger {args} {lappend ::log $args}
+    proc myproc {} {
+        set loc 1
+        trace add variable loc unset logger
+        trace add variable missing unset logger
+        return done
+    }
+} -body {
+    list [myproc] $::log
+} -cleanup {
+    unset ::log
+    rename logger ""
+    rename myproc ""
+} -ok {done {{loc {} unset}}}
+
+test trace-2.10 {local unset traces fire in the caller's scope} -setup {
+    proc logger {args} {uplevel 1 {set seen [info exists loc]}}
+    proc myproc {} {
+        set loc 1
+        trace add variable loc unset logger
+    }
+} -body {
+    set seen unset
+    myproc
+    set seen
+} -cleanup {
+    rename logger ""
+    rename myproc ""
+} -ok {0}
+
+test trace-3.1 {array traces} -setup {
+    set ::log {}
+    proc logger {args} {lappend ::log $args}
+} -body {
+    trace add variable a {write read} logger
+    set a(1) one
+    array set a {2 two}
+    set a(1)
+    set ::log
+} -cleanup {
+    unset a ::log
+    rename logger ""
+} -ok {{a 1 write} {a 2 write} {a 1 read}}
+
+test trace-3.2 {element traces} -setup {
+    set ::log {}
+    proc logger {args} {lappend ::log $args}
+} -body {
+    trace add variable a(1) {write unset} logger
+    set a(1) one
+    set a(2) two
+    unset a(2)
+    unset a(1)
+    set ::log
+} -cleanup {
+    unset a ::log
+    rename logger ""
+} -ok {{a 1 write} {a 1 unset}}
+
+test trace-3.3 {array unset traces} -setup {
+    set ::log {}
+    proc logger {args} {lappend ::log $args}
+    array set a {1 one 2 two}
+} -body {
+    trace add variable a unset logger
+    array unset a
+    set ::log
+} -cleanup {
+    unset ::log
+    rename logger ""
+} -ok {{a {} unset}}
+
+test trace-4.1 {traces fire through upvar} -setup {
+    set ::log {}
+    proc logger {args} {lappend ::log $args}
+    proc setit {} {
+        upvar 1 x y
+        set y 2
+    }
+} -body {
+    trace add variable x write logger
+    setit
+    set ::log
+} -cleanup {
+    unset x ::log
+    rename logger ""
+    rename setit ""
+} -ok {{y {} write}}
+
+test trace-4.2 {traces fire through global} -setup {
+    set ::log {}
+    proc logger {args} {lappend ::log $args}
+    proc getit {} {
+        global gx
+        set gx
+    }
+    set ::gx 1
+} -body {
+    trace add variable ::gx read logger
+    getit
+    set ::log
+} -cleanup {
+    unset ::gx ::log
+    rename logger ""
+    rename getit ""
+} -ok {{gx {} read}}
+
+test trace-4.3 {traces on upvar aliases apply to the target} -setup {
+    set ::log {}
+    proc logger {args} {lappend ::log $args}
+    proc watch {} {
+        upvar 1 x y
+        trace add variable y write logger
+    }
+} -body {
+    watch
+    set x 1
+    set ::log
+} -cleanup {
+    unset x ::log
+    rename logger ""
+    rename watch ""
+} -ok {{x {} write}}
+
+test trace-5.1 {trace info} -body {
+    trace add variable x {write unset} {foo 1}
+    trace add variable x read bar
+    trace add variable a(1) write baz
+    list [trace info variable x] [trace info variable a(1)] [trace info variable a]
+} -cleanup {
+    trace remove variable x {write unset} {foo 1}
+    trace remove variable x read bar
+    trace remove variable a(1) write baz
+} -ok {{{read bar} {{write unset} {foo 1}}} {{write baz}} {}}
+
+test trace-5.2 {trace remove} -setup {
+    set ::log {}
+    proc logger {args} {lappend ::log $args}
+} -body {
+    trace add variable x write logger
+    trace remove variable x read logger
+    set x 1
+    trace remove variable x write logger
+    set x 2
+    list $::log [trace info variable x]
+} -cleanup {
+    unset x ::log
+    rename logger ""
+} -ok {{{x {} write}} {}}
+
+test trace-5.3 {traces don't fire recursively} -setup {
+    proc double {name index op} {
+        upvar 1 $name var
+        set var [expr {$var * 2}]
+    }
+} -body {
+    trace add variable x write double
+    set x 3
+} -cleanup {
+    unset x
+    rename double ""
+} -ok {6}
+
+test trace-6.1 {execution trace errors} {
+    trace add execution nosuch enter foo
+} -error {unknown command "nosuch"}
+
+test trace-6.2 {bad execution operation} {
+    trace add execution set {enter enterstep} foo
+} -error {bad operation "enterstep": must be enter or leave}
+
+test trace-6.3 {empty execution operation list} {
+    trace add execution set {} foo
+} -error {bad operation list "": must be one or more of enter or leave}
+
+test trace-7.1 {enter and leave traces} -setup {
+    set ::log {}
+    proc logger {args} {lappend ::log $args}
+    proc double {x} {expr {2 * $x}}
+} -body {
+    trace add execution double {enter leave} logger
+    double [double 2]
+    set ::log
+} -cleanup {
+    unset ::log
+    rename logger ""
+    rename double ""
+} -ok {{{double 2} enter} {{double 2} 0 4 leave} {{double 4} enter} {{double 4} 0 8 leave}}
+
+test trace-7.2 {leave traces see errors} -setup {
+    set ::log {}
+    proc logger {args} {lappend ::log $args}
+    proc fail {} {error oops}
+} -body {
+    trace add execution fail leave logger
+    list [catch {fail} result] $result $::log
+} -cleanup {
+    unset ::log
+    rename logger ""
+    rename fail ""
+} -ok {1 oops {{fail 1 oops leave}}}
+
+test trace-7.3 {enter trace errors prevent execution} -setup {
+    set ::ran 0
+    proc deny {args} {error "denied"}
+    proc run {} {set ::ran 1}
+} -body {
+    trace add execution run enter deny
+    list [catch {run} result] $result $::ran
+} -cleanup {
+    unset ::ran
+    rename deny ""
+    rename run ""
+} -ok {1 denied 0}
+
+test trace-7.4 {leave trace errors replace the result} -setup {
+    proc deny {args} {error "denied"}
+    proc run {} {return ok}
+} -body {
+    trace add execution run leave deny
+    run
+} -cleanup {
+    rename deny ""
+    rename run ""
+} -error {denied}
+
+test trace-7.5 {traces on native commands and command prefixes} -setup {
+    set ::log {}
+    proc logger {args} {lappend ::log $args}
+    proc noop {} {}
+} -body {
+    trace add execution noop enter logger
+    noop
+    eval noop
+    apply {{} noop}
+    set ::log
+} -cleanup {
+    unset ::log
+    rename logger ""
+    rename noop ""
+} -ok {{noop enter} {noop enter} {noop enter}}
+
+test trace-7.6 {traces don't fire recursively} -setup {
+    set ::count 0
+    proc counter {args} {incr ::count; noop}
+    proc noop {} {}
+} -body {
+    trace add execution noop enter counter
+    noop
+    set ::count
+} -cleanup {
+    unset ::count
+    rename counter ""
+    rename noop ""
+} -ok {1}
+
+test trace-7.7 {traces follow renames} -setup {
+    set ::log {}
+    proc logger {args} {lappend ::log $args}
+    proc old {} {}
+} -body {
+    trace add execution old enter logger
+    rename old new
+    new
+    list $::log [trace info execution new]
+} -cleanup {
+    unset ::log
+    rename logger ""
+    rename new ""
+} -ok {{{new enter}} {{enter logger}}}
+
+test trace-7.8 {traces are removed when the command is redefined} -setup {
+    set ::log {}
+    proc logger {args} {lappend ::log $args}
+    proc cmd {} {}
+} -body {
+    trace add execution cmd enter logger
+    proc cmd {} {}
+    cmd
+    list $::log [trace info execution cmd]
+} -cleanup {
+    unset ::log
+    rename logger ""
+    rename cmd ""
+} -ok {{} {}}
+
+test trace-7.9 {trace info and remove execution} -setup {
+    proc cmd {} {}
+} -body {
+    trace add execution cmd enter foo
+    trace add execution cmd {enter leave} {bar 1}
+    set a [trace info execution cmd]
+    trace remove execution cmd enter foo
+    trace remove execution cmd leave {bar 1}
+    list $a [trace info execution cmd]
+} -cleanup {
+    rename cmd ""
+} -ok {{{{enter leave} {bar 1}} {enter foo}} {{{enter leave} {bar 1}}}}
+
+test trace-7.10 {trace info on unknown command} {
+    trace info execution nosuch
+} -error {unknown command "nosuch"}
@@ -0,0 +1,149 @@
+# Test Script: try command
+
+test try-1.1 {try errors} {
+    try
+} -error {wrong # args: should be "try body ?handler ...? ?finally script?"}
+
+test try-1.2 {bad handler} {
+    try {set x 1} catch {} {}
+} -error {bad handler "catch": must be finally, on, or trap}
+
+test try-1.3 {incomplete on clause} {
+    try {set x 1} on error {}
+} -error {wrong # args to on clause: must be "... on code variableList script"}
+
+test try-1.4 {incomplete trap clause} {
+    try {set x 1} trap {} {}
+} -error {wrong # args to trap clause: must be "... trap pattern variableList script"}
+
+test try-1.5 {finally must be last} {
+    try {set x 1} finally {} on ok {} {}
+} -error {wrong # args to finally clause: must be "... finally script"}
+
+test try-1.6 {bad completion code} {
+    try {set x 1} on oops {} {}
+} -error {bad completion code "oops": must be ok, error, return, break, continue, or an integer}
+
+test try-1.7 {too many variables} {
+    try {set x 1} on ok {a b c} {}
+} -error {bad variable list "a b c": must name at most two variables}
+
+test try-1.8 {last handler can't fall through} {
+    try {set x 1} on ok {} -
+} -error {last non-finally clause must not have a body of "-"}
+
+test try-1.9 {handlers are checked before the body is evaluated} -body {
+    set x 0
+    catch {try {set x 1} on oops {} {}}
+    set x
+} -ok {0}
+
+test try-2.1 {no handlers} {
+    try {set x 5}
+} -ok {5}
+
+test try-2.2 {unhandled errors propagate} {
+    try {error oops} on ok {} {}
+} -error {oops}
+
+test try-2.3 {on ok} {
+    try {set x 5} on ok {result} {list ok $result}
+} -ok {ok 5}
+
+test try-2.4 {on error} {
+    try {error oops} on ok {} {list ok} on error {msg} {list error $msg}
+} -ok {error oops}
+
+test try-2.5 {on with integer codes} -setup {
+    proc seven {} {return -code 7 seven}
+} -body {
+    list [try {set x 1} on 0 {} {set x ok}] \
+         [try {error oops} on 1 {} {set x error}] \
+         [try {seven} on 7 {r} {set r}]
+} -cleanup {
+    rename seven ""
+} -ok {ok error seven}
+
+test try-2.6 {on break and continue} {
+    set a {}
+    foreach i {1 2 3} {
+        lappend a [try {continue} on continue {} {set x c}]
+        lappend a [try {break} on break {} {set x b}]
+    }
+    set a
+} -ok {c b c b c b}
+
+test try-2.7 {on return} -setup {
+    proc myproc {} {
+        try {return 5} on return {value} {list returned $value}
+    }
+} -body {
+    myproc
+} -cleanup {
+    rename myproc ""
+} -ok {returned 5}
+
+test try-2.8 {options variable} {
+    try {error oops} on error {msg opts} {
+        list $msg [dict get $opts -code] [dict get $opts -level] [dict get $opts -errorcode]
+    }
+} -ok {oops 1 0 NONE}
+
+test try-2.9 {handler errors replace the result} {
+    try {error oops} on error {msg} {error "handled $msg"}
+} -error {handled oops}
+
+test try-2.10 {fall through} {
+    list [try {break} on break {} - on continue {} {set x loop}] \
+         [try {continue} on break {} - on continue {} {set x loop}]
+} -ok {loop loop}
+
+test try-2.11 {first matching handler wins} {
+    try {error oops} on error {} {set x first} on error {} {set x second}
+} -ok {first}
+
+test try-3.1 {trap matches error code prefix} {
+    try {throw {A B C} oops} trap {A X} {} {set x AX} trap {A B} {msg} {list AB $msg}
+} -ok {AB oops}
+
+test try-3.2 {trap matches whole error code} {
+    try {throw {A B} oops} trap {A B} {msg opts} {dict get $opts -errorcode}
+} -ok {A B}
+
+test try-3.3 {empty trap pattern matches any error} {
+    try {error oops} trap {} {msg} {set msg}
+} -ok {oops}
+
+test try-3.4 {trap doesn't match longer patterns} {
+    try {throw {A} oops} trap {A B} {} {set x matched}
+} -error {oops}
+
+test try-3.5 {trap doesn't match non-errors} {
+    try {set x 1} trap {} {} {set x matched}
+} -ok {1}
+
+test try-4.1 {finally after success} -body {
+    set ::log {}
+    list [try {set x 1} finally {lappend ::log finally}] $::log
+} -cleanup {
+    unset ::log
+} -ok {1 finally}
+
+test try-4.2 {finally after an unhandled error} -body {
+    set ::log {}
+    list [catch {try {error oops} finally {lappend ::log finally}} msg] $msg $::log
+} -cleanup {
+    unset ::log
+} -ok {1 oops finally}
+
+test try-4.3 {finally after a handler} -body {
+    set ::log {}
+    list [try {error oops} on error {} {lappend ::log handler; set x handled} \
+              finally {lappend ::log finally}] $::log
+} -cleanup {
+    unset ::log
+} -ok {handled {handler finally}}
+
+test try-4.4 {finally errors replace the result} {
+    try {error oops} on error {} {set x handled} finally {error "finally failed"}
+} -error {finally failed}
@@ -0,0 +1,176 @@
+# Test Script: uplevel command
+
+test uplevel-1.1 {uplevel errors} {
+    uplevel
+} -error {wrong # args: should be "uplevel ?level? command ?arg ...?"}
+
+test uplevel-1.2 {uplevel errors} {
+    uplevel 5 {set a 1}
+} -error {bad level "5"}
+
+test uplevel-1.3 {uplevel errors} {
+    uplevel #-1 {set a 1}
+} -error {bad level "#-1"}
+
+test uplevel-2.1 {uplevel to caller} -setup {
+    proc setit {} {
+        uplevel 1 {set x 5}
+    }
+} -body {
+    setit
+    set x
+} -cleanup {
+    rename setit ""
+} -ok {5}
+
+test uplevel-2.2 {uplevel with default level} -setup {
+    proc getit {} {
+        set x local
+        uplevel {set x}
+    }
+} -body {
+    set x caller
+    getit
+} -cleanup {
+    rename getit ""
+} -ok {caller}
+
+test uplevel-2.3 {uplevel to global} -setup {
+    proc setglobal {} {
+        uplevel #0 {set uplevel_global hello}
+    }
+} -body {
+    setglobal
+    global uplevel_global
+    set uplevel_global
+} -cleanup {
+    global uplevel_global
+    unset uplevel_global
+    rename setglobal ""
+} -ok {hello}
+
+test uplevel-2.4 {uplevel concatenates its arguments} -setup {
+    proc setit {} {
+        uplevel 1 set x {{a b}}
+    }
+} -body {
+    setit
+    set x
+} -cleanup {
+    rename setit ""
+} -ok {a b}
+
+test uplevel-2.5 {uplevel 0 is the current level} {
+    uplevel 0 {set y 1}
+    set y
+} -ok {1}
+
+test uplevel-2.6 {uplevel returns the script's value} -setup {
+    proc run {} {
+        uplevel 1 {expr {$a + 1}}
+    }
+} -body {
+    set a 41
+    run
+} -cleanup {
+    rename run ""
+} -ok {42}
+
+test uplevel-3.1 {procs called from uplevel see their real caller} -setup {
+    proc with_lock {body} {
+        set lockvar inner
+        uplevel 1 $body
+    }
+    proc setcaller {} {
+        upvar 1 lockvar v
+        set v changed
+    }
+} -body {
+    set lockvar outer
+    with_lock {setcaller}
+    set lockvar
+} -cleanup {
+    rename with_lock ""
+    rename setcaller ""
+} -ok {changed}
+
+test uplevel-3.2 {nested uplevel} -setup {
+    proc outer {} {
+        set v outer
+        inner
+    }
+    proc inner {} {
+        set v inner
+        uplevel 1 {uplevel 1 {set v}}
+    }
+} -body {
+    set v caller
+    outer
+} -cleanup {
+    rename outer ""
+    rename inner ""
+} -ok {caller}
+
+test uplevel-3.3 {uplevel sees the caller's variables} -setup {
+    proc check {} {
+        uplevel 1 {lappend result [info exists marker]}
+    }
+} -body {
+    set marker 1
+    set result {}
+    check
+    set result
+} -cleanup {
+    rename check ""
+} -ok {1}
+
+test uplevel-4.1 {return propagates from uplevel} -setup {
+    proc early {} {
+        uplevel 1 {return done}
+        return notreached
+    }
+} -body {
+    early
+} -cleanup {
+    rename early ""
+} -ok {done}
+
+test uplevel-4.2 {break propagates from uplevel} -setup {
+    proc retry {body} {
+        set code [catch {uplevel 1 $body} result]
+        return -code $code $result
+    }
+} -body {
+    set count 0
+    while {1} {
+        incr count
+        retry {
+            if {$count == 3} { break }
+        }
+    }
+    set count
+} -cleanup {
+    rename retry ""
+} -ok {3}
+
+test uplevel-4.3 {errors propagate from uplevel} -setup {
+    proc fail {} {
+        uplevel 1 {error "oops"}
+    }
+} -body {
+    fail
+} -cleanup {
+    rename fail ""
+} -error {oops}
+
+test uplevel-4.4 {scope is restored after an error} -setup {
+    proc fail {} {
+        set x local
+        catch {uplevel 1 {error "oops"}}
+        set x
+    }
+} -body {
+    fail
+} -cleanup {
+    rename fail ""
+} -ok {local}
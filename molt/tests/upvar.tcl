@@ -0,0 +1,154 @@
+# Test Script: upvar command
+
+test upvar-1.1 {upvar errors} {
+    upvar
+} -error {wrong # args: should be "upvar ?level? otherVar localVar ?otherVar localVar ...?"}
+
+test upvar-1.2 {upvar errors} {
+    upvar 1 a
+} -error {wrong # args: should be "upvar ?level? otherVar localVar ?otherVar localVar ...?"}
+
+test upvar-1.3 {upvar errors} {
+    upvar 1 a b c
+} -error {wrong # args: should be "upvar ?level? otherVar localVar ?otherVar localVar ...?"}
+
+test upvar-1.4 {upvar errors} {
+    upvar 5 a b
+} -error {bad level "5"}
+
+test upvar-1.5 {upvar errors} {
+    upvar #-1 a b
+} -error {bad level "#-1"}
+
+test upvar-1.6 {upvar errors} {
+    upvar 0 a a
+} -error {can't upvar from variable to itself}
+
+test upvar-1.7 {upvar errors} {
+    set b 1
+    upvar 0 a b
+} -error {variable "b" already exists}
+
+test upvar-1.8 {upvar errors} {
+    upvar 0 a b(1)
+} -error {bad variable name "b(1)": can't create a scalar variable that looks like an array element}
+
+test upvar-2.1 {upvar to caller, different name} -setup {
+    proc setit {varName value} {
+        upvar 1 $varName local
+        set local $value
+    }
+} -body {
+    setit x 5
+    set x
+} -cleanup {
+    rename setit ""
+} -ok {5}
+
+test upvar-2.2 {upvar with default level} -setup {
+    proc incrit {varName} {
+        upvar $varName local
+        incr local
+    }
+} -body {
+    set x 5
+    incrit x
+    incrit x
+    set x
+} -cleanup {
+    rename incrit ""
+} -ok {7}
+
+test upvar-2.3 {upvar to global} -setup {
+    proc setglobal {} {
+        upvar #0 upvar_global g
+        set g "hello"
+    }
+} -body {
+    setglobal
+    global upvar_global
+    set upvar_global
+} -cleanup {
+    global upvar_global
+    unset upvar_global
+    rename setglobal ""
+} -ok {hello}
+
+test upvar-2.4 {upvar multiple pairs} -setup {
+    proc swap {aName bName} {
+        upvar 1 $aName a $bName b
+        set tmp $a
+        set a $b
+        set b $tmp
+        return
+    }
+} -body {
+    set x 1
+    set y 2
+    swap x y
+    list $x $y
+} -cleanup {
+    rename swap ""
+} -ok {2 1}
+
+test upvar-2.5 {upvar array variable} -setup {
+    proc fill {arrName} {
+        upvar 1 $arrName arr
+        set arr(a) 1
+        set arr(b) 2
+        array size arr
+    }
+} -body {
+    set count [fill data]
+    list $count $data(a) $data(b)
+} -cleanup {
+    rename fill ""
+} -ok {2 1 2}
+
+test upvar-2.6 {upvar chains through several levels} -setup {
+    proc outer {varName} {
+        upvar 1 $varName x
+        inner x
+    }
+    proc inner {varName} {
+        upvar 1 $varName y
+        set y "inner"
+    }
+} -body {
+    outer z
+    set z
+} -cleanup {
+    rename outer ""
+    rename inner ""
+} -ok {inner}
+
+test upvar-2.7 {unset through upvar} -setup {
+    proc clear {varName} {
+        upvar 1 $varName v
+        unset v
+    }
+} -body {
+    set x 1
+    clear x
+    info exists x
+} -cleanup {
+    rename clear ""
+} -ok {0}
+
+test upvar-2.8 {upvar within the current scope} {
+    set a 1
+    upvar 0 a b
+    set b 2
+    set a
+} -ok {2}
+
+test upvar-2.9 {upvar to a variable that doesn't exist yet} -setup {
+    proc create {varName} {
+        upvar 1 $varName v
+        info exists v
+    }
+} -body {
+    list [create newvar] [info exists newvar]
+} -cleanup {
+    rename create ""
+} -ok {0 0}
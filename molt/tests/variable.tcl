@@ -0,0 +1,85 @@
+# Test Script: variable command
+
+test variable-1.1 {variable errors} {
+    variable
+} -error {wrong # args: should be "variable ?name value...? name ?value?"}
+
+test variable-1.2 {array element} {
+    variable a(1) 1
+} -error {can't define "a(1)": name refers to an element in an array}
+
+test variable-2.1 {variable in namespace eval} -body {
+    namespace eval ns1 {
+        variable x 1 y 2
+        variable z
+    }
+    list $ns1::x $ns1::y [info exists ns1::z]
+} -cleanup {
+    namespace delete ns1
+} -ok {1 2 0}
+
+test variable-2.2 {variable in a proc} -setup {
+    namespace eval ns1 {
+        variable count 0
+        proc next {} {
+            variable count
+            incr count
+        }
+    }
+} -body {
+    ns1::next
+    ns1::next
+    list [ns1::next] $ns1::count
+} -cleanup {
+    namespace delete ns1
+} -ok {3 3}
+
+test variable-2.3 {variable sets value in a proc} -setup {
+    namespace eval ns1 {
+        proc init {} {
+            variable x 5
+            set x
+        }
+    }
+} -body {
+    list [ns1::init] $ns1::x [info exists x]
+} -cleanup {
+    namespace delete ns1
+} -ok {5 5 0}
+
+test variable-2.4 {qualified variable names} -setup {
+    namespace eval ns1 {namespace eval ns2 {}}
+    proc getit {} {
+        variable ns1::ns2::y 7
+        set y
+    }
+} -body {
+    list [getit] $ns1::ns2::y
+} -cleanup {
+    rename getit ""
+    namespace delete ns1
+} -ok {7 7}
+
+test variable-2.5 {variable in the global namespace} -body {
+    proc setg {} {
+        variable gv 3
+    }
+    setg
+    set ::gv
+} -cleanup {
+    rename setg ""
+    unset ::gv
+} -ok {3}
+
+test variable-2.6 {variable conflicts with local} -setup {
+    namespace eval ns1 {
+        proc f {} {
+            set x 1
+            variable x
+        }
+    }
+} -body {
+    ns1::f
+} -cleanup {
+    namespace delete ns1
+} -error {variable "x" already exists}